@@ -500,4 +500,37 @@ object CoreModel {
         suggestedDocsParser.tryParse(
             app.lockbook.core.suggestedDocs()
         )
+
+    private val pinFileParser = Json {
+        serializersModule = SerializersModule {
+            createPolyRelation(Unit.serializer(), PinFileError.serializer())
+        }
+    }
+
+    fun pinFile(id: String): Result<Unit, CoreError<PinFileError>> =
+        pinFileParser.tryParse(
+            app.lockbook.core.pinFile(id)
+        )
+
+    private val unpinFileParser = Json {
+        serializersModule = SerializersModule {
+            createPolyRelation(Unit.serializer(), Empty.serializer())
+        }
+    }
+
+    fun unpinFile(id: String): Result<Unit, CoreError<Empty>> =
+        unpinFileParser.tryParse(
+            app.lockbook.core.unpinFile(id)
+        )
+
+    private val listPinnedParser = Json {
+        serializersModule = SerializersModule {
+            createPolyRelation(ListSerializer(File.serializer()), Empty.serializer())
+        }
+    }
+
+    fun listPinned(): Result<List<File>, CoreError<Empty>> =
+        listPinnedParser.tryParse(
+            app.lockbook.core.listPinned()
+        )
 }
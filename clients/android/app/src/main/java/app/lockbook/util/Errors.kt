@@ -462,6 +462,15 @@ enum class SaveDrawingError : UiCoreError {
     }
 }
 
+@Serializable
+enum class PinFileError : UiCoreError {
+    FileDoesNotExist;
+
+    override fun toLbError(res: Resources): LbError = when (this) {
+        FileDoesNotExist -> LbError.newUserError(getString(res, R.string.file_does_not_exist))
+    }
+}
+
 val <T> T.exhaustive: T
     get() = this
 
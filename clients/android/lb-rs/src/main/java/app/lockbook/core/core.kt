@@ -33,6 +33,9 @@ external fun getDrawing(id: String): String
 external fun saveDrawing(id: String, drawing: String): String
 
 external fun suggestedDocs(): String
+external fun pinFile(id: String): String
+external fun unpinFile(id: String): String
+external fun listPinned(): String
 
 // sync
 external fun syncAll(syncModel: Any): String
@@ -184,6 +184,18 @@ public struct CoreApi: LockbookApi {
         fromPrimitiveResult(result: suggested_docs())
     }
     
+    public func pinFile(id: UUID) -> FfiResult<Empty, PinFileError> {
+        fromPrimitiveResult(result: pin_file(id.uuidString))
+    }
+    
+    public func unpinFile(id: UUID) -> FfiResult<Empty, UnpinFileError> {
+        fromPrimitiveResult(result: unpin_file(id.uuidString))
+    }
+    
+    public func listPinned() -> FfiResult<[File], ListPinnedError> {
+        fromPrimitiveResult(result: list_pinned())
+    }
+    
     public func getPathById(id: UUID) -> FfiResult<String, GetPathByIdError> {
         fromPrimitiveResult(result: get_path_by_id(id.uuidString))
     }
@@ -176,6 +176,18 @@ Vestibulum ante ipsum primis in vel.
         .failure(.init(unexpected: "LAZY"))
     }
     
+    public func pinFile(id: UUID) -> FfiResult<Empty, PinFileError> {
+        .failure(.init(unexpected: "LAZY"))
+    }
+    
+    public func unpinFile(id: UUID) -> FfiResult<Empty, UnpinFileError> {
+        .failure(.init(unexpected: "LAZY"))
+    }
+    
+    public func listPinned() -> FfiResult<[File], ListPinnedError> {
+        .failure(.init(unexpected: "LAZY"))
+    }
+    
     public func getPathById(id: UUID) -> FfiResult<String, GetPathByIdError> {
         .failure(.init(unexpected: "LAZY"))
     }
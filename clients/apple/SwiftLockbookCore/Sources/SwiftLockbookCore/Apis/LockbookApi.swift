@@ -45,6 +45,9 @@ public protocol LockbookApi {
     func getFileById(id: UUID) -> FfiResult<File, GetFileByIdError>
     func getFileByPath(path: String) -> FfiResult<File, GetFileByPathError>
     func suggestedDocs() -> FfiResult<[UUID], SuggestedDocsError>
+    func pinFile(id: UUID) -> FfiResult<Empty, PinFileError>
+    func unpinFile(id: UUID) -> FfiResult<Empty, UnpinFileError>
+    func listPinned() -> FfiResult<[File], ListPinnedError>
     func getPathById(id: UUID) -> FfiResult<String, GetPathByIdError>
     
     func timeAgo(timeStamp: Int64) -> String
@@ -295,3 +295,15 @@ public enum GeneralSearchError: String, UiError {
 public enum SuggestedDocsError: String, UiError {
     case Stub
 }
+
+public enum PinFileError: String, UiError {
+    case FileDoesNotExist
+}
+
+public enum UnpinFileError: String, UiError {
+    case Stub
+}
+
+public enum ListPinnedError: String, UiError {
+    case Stub
+}
@@ -1,21 +1,23 @@
 use std::{io, str::FromStr};
 
 use cli_rs::cli_error::{CliError, CliResult};
-use lb::{Core, WorkUnit};
+use lb::{AccountManager, Core, CoreError, WorkUnit};
 
 use is_terminal::IsTerminal;
 
 use crate::{ensure_account, input};
 
-pub fn new(core: &Core, username: String, api_url: ApiUrl) -> CliResult<()> {
+pub fn new(accounts: &AccountManager, username: String, api_url: ApiUrl) -> CliResult<()> {
+    let core = accounts.new_account_core()?;
     println!("generating keys and checking for username availability...");
     core.create_account(&username, &api_url.0, true)?;
+    accounts.switch(&username)?;
     println!("account created!");
 
     Ok(())
 }
 
-pub fn import(core: &Core) -> CliResult<()> {
+pub fn import(accounts: &AccountManager) -> CliResult<()> {
     if io::stdin().is_terminal() {
         return Err(CliError::from("to import an existing lockbook account, pipe your account string into this command, e.g.:\npbpaste | lockbook account import".to_string()));
     }
@@ -26,14 +28,47 @@ pub fn import(core: &Core) -> CliResult<()> {
         .expect("failed to read from stdin");
     account_string.retain(|c| !c.is_whitespace());
 
+    let core = accounts.new_account_core()?;
     println!("importing account...");
-    core.import_account(&account_string)?;
+    let account = core.import_account(&account_string)?;
+    accounts.switch(&account.username)?;
 
     println!("account imported! next, try to sync by running: lockbook sync");
 
     Ok(())
 }
 
+pub fn list(accounts: &AccountManager, core: &Core) -> CliResult<()> {
+    let current = core.get_account().ok().map(|account| account.username);
+    for username in accounts.list_accounts()? {
+        let marker = if Some(&username) == current.as_ref() { "*" } else { " " };
+        println!("{marker} {username}");
+    }
+
+    Ok(())
+}
+
+pub fn switch(accounts: &AccountManager, username: String) -> CliResult<()> {
+    match accounts.switch(&username) {
+        Ok(_) => {
+            println!("switched to {username}");
+            Ok(())
+        }
+        Err(err) if err.kind == CoreError::AccountNonexistent => {
+            Err(CliError::from(format!("no account {username} found, run lockbook account list")))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn account_completor(accounts: &AccountManager, prompt: &str) -> CliResult<Vec<String>> {
+    Ok(accounts
+        .list_accounts()?
+        .into_iter()
+        .filter(|username| username.starts_with(prompt))
+        .collect())
+}
+
 pub fn export(core: &Core, skip_check: bool) -> CliResult<()> {
     ensure_account(core)?;
 
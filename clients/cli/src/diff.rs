@@ -0,0 +1,93 @@
+use std::io;
+
+use cli_rs::cli_error::CliResult;
+use is_terminal::IsTerminal;
+use lb::{Core, DiffAgainst, DiffLine, DiffLineKind, DocumentDiff};
+
+use crate::{ensure_account_and_root, input::FileInput};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const REVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+pub fn diff(core: &Core, target: FileInput, against: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let file = target.find(core)?;
+    let against = if against.is_empty() {
+        DiffAgainst::Base
+    } else {
+        let against: FileInput = against.parse().unwrap(); // infallible
+        DiffAgainst::Document(against.find(core)?.id)
+    };
+
+    let diff = core.diff_document(file.id, against)?;
+    if diff.is_empty() {
+        println!("no changes.");
+        return Ok(());
+    }
+
+    let color = io::stdout().is_terminal();
+    match diff {
+        DocumentDiff::Text { hunks } => {
+            for hunk in hunks {
+                let header = format!(
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+                );
+                if color {
+                    println!("{CYAN}{header}{RESET}");
+                } else {
+                    println!("{header}");
+                }
+                for line in hunk.lines {
+                    println!("{}", format_line(&line, color));
+                }
+            }
+        }
+        DocumentDiff::Drawing(d) => {
+            println!(
+                "{} strokes added, {} strokes removed, {} strokes unchanged",
+                d.strokes_added, d.strokes_removed, d.strokes_unchanged
+            );
+        }
+        DocumentDiff::Other { old_size, new_size, .. } => {
+            println!(
+                "binary content changed ({} -> {})",
+                lb::bytes_to_human(old_size as u64),
+                lb::bytes_to_human(new_size as u64)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn format_line(line: &DiffLine, color: bool) -> String {
+    let (prefix, line_color) = match line.kind {
+        DiffLineKind::Context => (' ', ""),
+        DiffLineKind::Deleted => ('-', RED),
+        DiffLineKind::Inserted => ('+', GREEN),
+    };
+    if !color || line.kind == DiffLineKind::Context {
+        return format!("{prefix}{}", line.text);
+    }
+
+    let mut result = format!("{line_color}{prefix}");
+    if line.words.is_empty() {
+        result.push_str(&line.text);
+    } else {
+        // highlight the words that changed within the line
+        for word in &line.words {
+            if word.changed {
+                result.push_str(&format!("{REVERSE}{}{RESET}{line_color}", word.text));
+            } else {
+                result.push_str(&word.text);
+            }
+        }
+    }
+    result.push_str(RESET);
+    result
+}
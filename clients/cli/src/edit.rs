@@ -13,7 +13,7 @@ use cli_rs::{
 use hotwatch::{Event, EventKind, Hotwatch};
 use lb::{Core, Uuid};
 
-use crate::{ensure_account_and_root, input::FileInput};
+use crate::{ensure_account_and_root, find_template, input::FileInput};
 
 pub fn edit(core: &Core, editor: Editor, target: FileInput) -> CliResult<()> {
     ensure_account_and_root(core)?;
@@ -99,6 +99,16 @@ impl Editor {
     }
 }
 
+pub fn today(core: &Core, editor: Editor, template: String, pattern: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let template = if template.is_empty() { None } else { Some(find_template(core, template)?.id) };
+    let pattern = if pattern.is_empty() { lb::DEFAULT_DAILY_NOTE_PATTERN } else { &pattern };
+
+    let note = core.get_or_create_daily_note(lb::today(), pattern, template)?;
+    edit(core, editor, FileInput::Id(note.id))
+}
+
 pub fn editor_flag() -> Flag<'static, Editor> {
     Flag::new("editor")
         .description("optional editor flag, if not present falls back to LOCKBOOK_EDITOR, if not present falls back to a platform default")
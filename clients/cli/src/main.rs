@@ -1,11 +1,15 @@
 mod account;
 mod debug;
+mod diff;
 mod edit;
 mod imex;
 mod input;
 mod list;
 mod share;
+mod status;
 mod stream;
+mod tasks;
+mod team;
 
 use std::env;
 use std::path::PathBuf;
@@ -20,12 +24,21 @@ use cli_rs::{
 };
 
 use input::FileInput;
-use lb::{Core, CoreError, Filter, Uuid};
+use lb::{AccountManager, Core, CoreError, Filter, Uuid};
+
+/// The account commands run in, if it isn't the current one. The `--account` flag overrides it.
+const ACCOUNT_VAR: &str = "LOCKBOOK_ACCOUNT";
 
 fn run() -> CliResult<()> {
-    let core = &core()?;
+    let account_flag = account_flag();
+    let account = match &account_flag {
+        Some((account, _)) => Some(account.clone()),
+        None => env::var(ACCOUNT_VAR).ok(),
+    };
+    let accounts = &accounts()?;
+    let core = &core(accounts, account)?;
 
-    Command::name("lockbook")
+    let mut command = Command::name("lockbook")
         .description("The private, polished note-taking platform.") 
         .version(env!("CARGO_PKG_VERSION"))
         .subcommand(
@@ -37,12 +50,22 @@ fn run() -> CliResult<()> {
                         .input(Flag::<ApiUrl>::new("api_url")
                             .description("location of the lockbook server you're trying to use. If not provided will check the API_URL env var, and then fall back to https://api.prod.lockbook.net"))
                         .handler(|username, api_url| {
-                            account::new(core, username.get(), api_url.get())
+                            account::new(accounts, username.get(), api_url.get())
                         })
                 )
                 .subcommand(
                     Command::name("import").description("import an existing account by piping in the account string")
-                        .handler(|| account::import(core))
+                        .handler(|| account::import(accounts))
+                )
+                .subcommand(
+                    Command::name("list").description("list the accounts on this device. the current one is marked with *")
+                        .handler(|| account::list(accounts, core))
+                )
+                .subcommand(
+                    Command::name("switch").description("switch the account commands run in. to run one command in another account, use lockbook --account=<username> <command>")
+                        .input(Arg::str("username").description("the username of the account to switch to.")
+                            .completor(|prompt| account::account_completor(accounts, prompt)))
+                        .handler(|username| account::switch(accounts, username.get()))
                 )
                 .subcommand(
                     Command::name("export").description("reveal your account's private key")
@@ -98,6 +121,14 @@ fn run() -> CliResult<()> {
                             .completor(|prompt| input::file_completor(core, prompt, None)))
                 .handler(|force, target| delete(core, force.get(), target.get()))
         )
+        .subcommand(
+            Command::name("diff").description("show the changes to a document since it was last synced")
+                .input(Flag::<String>::new("against")
+                    .description("path or id of another document to compare with, instead of the last synced version"))
+                .input(Arg::<FileInput>::name("target").description("path or id of the document to diff")
+                            .completor(|prompt| input::file_completor(core, prompt, None)))
+                .handler(|against, target| diff::diff(core, target.get(), against.get()))
+        )
         .subcommand(
             Command::name("edit").description("edit a document")
                 .input(edit::editor_flag())
@@ -132,9 +163,11 @@ fn run() -> CliResult<()> {
         )
         .subcommand(
             Command::name("new").description("create a new file at the given path or do nothing if it exists")
+                .input(Flag::<String>::new("template")
+                    .description("path or id of a template to fill in the new document from. paths without a leading '/' are within /templates/"))
                 .input(Arg::<FileInput>::name("path").description("create a new file at the given path or do nothing if it exists")
                             .completor(|prompt| input::file_completor(core, prompt, Some(Filter::FoldersOnly))))
-                .handler(|target| create_file(core, target.get()))
+                .handler(|template, target| create_file(core, target.get(), template.get()))
         )
         .subcommand(
             Command::name("stream")
@@ -161,6 +194,14 @@ fn run() -> CliResult<()> {
                 .input(Arg::str("new_name"))
                 .handler(|target, new_name| rename(core, target.get(), new_name.get()))
         )
+        .subcommand(
+            Command::name("revert").description("discard local changes to a file that haven't been synced yet")
+                .input(Flag::bool("all").description("discard all local changes"))
+                .input(Arg::str("target").description("lockbook file path or ID of file to revert")
+                            .completor(|prompt| input::file_completor(core, prompt, None))
+                            .default(String::new()))
+                .handler(|all, target| status::revert(core, all.get(), target.get()))
+        )
         .subcommand(
             Command::name("share").description("sharing related commands")
                 .subcommand(
@@ -171,6 +212,19 @@ fn run() -> CliResult<()> {
                         .input(Flag::bool("read-only"))
                         .handler(|target, username, ro| share::new(core, target.get(), username.get(), ro.get()))
                 )
+                .subcommand(
+                    Command::name("transfer").description("offer a file and everything in it to another user, who becomes its owner if they accept")
+                        .input(Arg::<FileInput>::name("target").description("lockbook file path or ID of file to transfer")
+                            .completor(|prompt| input::file_completor(core, prompt, None)))
+                        .input(Arg::str("username"))
+                        .handler(|target, username| share::transfer(core, target.get(), username.get()))
+                )
+                .subcommand(
+                    Command::name("who").description("list everyone who can access a file and where their access comes from")
+                        .input(Arg::<FileInput>::name("target").description("lockbook file path or ID of file to inspect")
+                            .completor(|prompt| input::file_completor(core, prompt, None)))
+                        .handler(|target| share::who(core, target.get()))
+                )
                 .subcommand(
                     Command::name("pending").description("list pending shares")
                         .handler(|| share::pending(core))
@@ -189,13 +243,91 @@ fn run() -> CliResult<()> {
                                .completor(|prompt| share::pending_share_completor(core, prompt)))
                         .handler(|target| share::delete(core, target.get()))
                 )
+                .subcommand(
+                    Command::name("pending-transfers").description("list files offered to you by other users")
+                        .handler(|| share::pending_transfers(core))
+                )
+                .subcommand(
+                    Command::name("accept-transfer").description("become the owner of a file offered to you, moving it into your file tree")
+                        .input(Arg::<Uuid>::name("pending-transfer-id").description("ID of pending transfer")
+                                    .completor(|prompt| share::pending_transfer_completor(core, prompt)))
+                        .input(Arg::<FileInput>::name("target").description("lockbook file path or ID of the folder you want to place this file")
+                            .completor(|prompt| input::file_completor(core, prompt, Some(Filter::FoldersOnly))))
+                        .handler(|id, dest| share::accept_transfer(core, id.get(), dest.get()))
+                )
+                .subcommand(
+                    Command::name("reject-transfer").description("decline a file offered to you")
+                        .input(Arg::<Uuid>::name("pending-transfer-id").description("ID of pending transfer to decline")
+                               .completor(|prompt| share::pending_transfer_completor(core, prompt)))
+                        .handler(|target| share::reject_transfer(core, target.get()))
+                )
+        )
+        .subcommand(
+            Command::name("team").description("share files with groups of users")
+                .subcommand(
+                    Command::name("list").description("list the teams you administer or are a member of")
+                        .handler(|| team::list(core))
+                )
+                .subcommand(
+                    Command::name("create").description("create a team that you administer")
+                        .input(Arg::str("name"))
+                        .handler(|name| team::create(core, name.get()))
+                )
+                .subcommand(
+                    Command::name("add").description("add a user to a team, sharing everything shared with the team with them")
+                        .input(Arg::str("team"))
+                        .input(Arg::str("username"))
+                        .handler(|team, username| team::add(core, team.get(), username.get()))
+                )
+                .subcommand(
+                    Command::name("remove").description("remove a user from a team, unsharing everything shared with the team with them")
+                        .input(Arg::str("team"))
+                        .input(Arg::str("username"))
+                        .handler(|team, username| team::remove(core, team.get(), username.get()))
+                )
+                .subcommand(
+                    Command::name("share").description("share a file with every member of a team, including future members")
+                        .input(Arg::str("team"))
+                        .input(Arg::<FileInput>::name("target").description("lockbook file path or ID of file to share")
+                            .completor(|prompt| input::file_completor(core, prompt, None)))
+                        .input(Flag::bool("read-only"))
+                        .handler(|team, target, ro| team::share(core, team.get(), target.get(), ro.get()))
+                )
+        )
+        .subcommand(
+            Command::name("status").description("list local changes that haven't been synced yet")
+                .handler(|| status::status(core))
+        )
+        .subcommand(
+            Command::name("today").description("edit today's daily note, creating it if it doesn't exist")
+                .input(edit::editor_flag())
+                .input(Flag::<String>::new("template")
+                    .description("path or id of a template to fill in the note from if it's created. paths without a leading '/' are within /templates/"))
+                .input(Flag::<String>::new("pattern")
+                    .description("where daily notes are kept, using {{year}}, {{month}}, {{day}} and {{date}} (YYYY-MM-DD). defaults to /journal/{{year}}/{{month}}/{{date}}.md"))
+                .handler(|editor, template, pattern| edit::today(core, editor.get(), template.get(), pattern.get()))
+        )
+        .subcommand(
+            Command::name("tasks").description("list the open tasks in your markdown documents")
+                .input(Flag::bool("all").description("include completed tasks"))
+                .input(Flag::<String>::new("due").description("only tasks due on or before this date (YYYY-MM-DD)"))
+                .input(Arg::<FileInput>::name("target").description("path or id of the folder or document to list tasks from")
+                            .completor(|prompt| input::file_completor(core, prompt, None))
+                            .default(FileInput::Path("/".to_string())))
+                .handler(|all, due, target| tasks::tasks(core, all.get(), due.get(), target.get()))
         )
         .subcommand(
             Command::name("sync").description("sync your local changes back to lockbook servers") // todo also back
-                .handler(|| sync(core))
+                .input(Flag::bool("dry-run").description("list what would be synced without changing anything"))
+                .handler(|dry_run| sync(core, dry_run.get()))
         )
-        .with_completions()
-        .parse();
+        .with_completions();
+
+    match account_flag {
+        // the parser doesn't know the flag, so it parses the arguments after it
+        Some((_, args)) => command.parse_args(&args)?,
+        None => command.parse(),
+    }
 
     Ok(())
 }
@@ -204,7 +336,20 @@ fn main() {
     run().exit();
 }
 
-fn core() -> CliResult<Core> {
+/// Takes `--account=<username>` or `--account <username>` from before the command, returning the
+/// username and the rest of the arguments.
+fn account_flag() -> Option<(String, Vec<String>)> {
+    let mut args = env::args().skip(1);
+    let first = args.next()?;
+    let account = match first.strip_prefix("--account") {
+        Some("") => args.next()?,
+        Some(value) => value.strip_prefix('=')?.to_string(),
+        None => return None,
+    };
+    Some((account, args.collect()))
+}
+
+fn accounts() -> CliResult<AccountManager> {
     let specified_path = env::var("LOCKBOOK_PATH");
 
     let default_path = env::var("HOME") // unix
@@ -215,17 +360,51 @@ fn core() -> CliResult<Core> {
         .or(default_path)
         .map_err(|_| "no cli location")?;
 
-    Core::init(&lb::Config { writeable_path, logs: true, colored_logs: true })
+    AccountManager::init(&lb::Config { writeable_path, logs: true, colored_logs: true })
         .map_err(|err| CliError::from(err.msg))
 }
 
-fn sync(core: &Core) -> CliResult<()> {
+fn core(accounts: &AccountManager, account: Option<String>) -> CliResult<Core> {
+    match account {
+        Some(username) => accounts.get(&username).map_err(|err| match err.kind {
+            CoreError::AccountNonexistent => CliError::from(format!("no account {username} found")),
+            _ => CliError::from(err),
+        }),
+        None => accounts.current().map_err(|err| CliError::from(err.msg)),
+    }
+}
+
+fn sync(core: &Core, dry_run: bool) -> CliResult<()> {
     ensure_account(core)?;
 
+    if dry_run {
+        let plan = core.plan_sync()?;
+        if plan.is_empty() {
+            println!("nothing to sync");
+            return Ok(());
+        }
+        if !plan.local_changes.is_empty() {
+            println!("local changes:");
+            plan.local_changes.iter().for_each(|c| println!("  {c}"));
+        }
+        if !plan.server_changes.is_empty() {
+            println!("server changes:");
+            plan.server_changes.iter().for_each(|c| println!("  {c}"));
+        }
+        if !plan.conflicts.is_empty() {
+            println!("conflicts:");
+            plan.conflicts.iter().for_each(|c| println!("  {c}"));
+        }
+        return Ok(());
+    }
+
     println!("syncing...");
-    core.sync(Some(Box::new(|sp: lb::SyncProgress| {
+    let status = core.sync(Some(Box::new(|sp: lb::SyncProgress| {
         println!("{sp}");
     })))?;
+    for id in status.conflicts {
+        println!("merged with conflicts: {}", core.get_path_by_id(id)?);
+    }
     Ok(())
 }
 
@@ -270,13 +449,17 @@ fn move_file(core: &Core, src: FileInput, dest: FileInput) -> CliResult<()> {
     Ok(())
 }
 
-fn create_file(core: &Core, path: FileInput) -> CliResult<()> {
+fn create_file(core: &Core, path: FileInput, template: String) -> CliResult<()> {
     ensure_account_and_root(core)?;
 
     let FileInput::Path(path) = path else {
         return Err(CliError::from("cannot create a file using ids"));
     };
 
+    if !template.is_empty() {
+        return create_from_template(core, &path, template);
+    }
+
     match core.get_by_path(&path) {
         Ok(_f) => Ok(()),
         Err(err) => match err.kind {
@@ -289,6 +472,31 @@ fn create_file(core: &Core, path: FileInput) -> CliResult<()> {
     }
 }
 
+fn create_from_template(core: &Core, path: &str, template: String) -> CliResult<()> {
+    let template = find_template(core, template)?;
+
+    let (parent, name) = match path.rsplit_once('/') {
+        Some((parent, name)) => (format!("{parent}/"), name),
+        None => ("/".to_string(), path),
+    };
+    let parent = core.get_by_path(&parent)?;
+
+    core.create_from_template(template.id, parent.id, name)?;
+    Ok(())
+}
+
+/// Finds a template by id or path; paths without a leading '/' are within the templates folder
+fn find_template(core: &Core, template: String) -> CliResult<lb::File> {
+    let template = match template.parse().unwrap() {
+        // infallible
+        FileInput::Path(template) if !template.starts_with('/') => {
+            FileInput::Path(format!("{}{template}", lb::TEMPLATES_FOLDER_PATH))
+        }
+        template => template,
+    };
+    template.find(core)
+}
+
 fn rename(core: &Core, target: FileInput, new_name: String) -> Result<(), CliError> {
     ensure_account_and_root(core)?;
 
@@ -13,6 +13,73 @@ pub fn new(core: &Core, target: FileInput, username: String, read_only: bool) ->
     Ok(())
 }
 
+pub fn transfer(core: &Core, target: FileInput, username: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let id = target.find(core)?.id;
+    core.transfer_ownership(id, &username)?;
+    println!(
+        "done!\nfile '{}' will belong to {} once they accept it, after you next sync.",
+        id, username
+    );
+    Ok(())
+}
+
+pub fn pending_transfers(core: &Core) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let pending_transfers = to_share_infos(core.get_pending_transfers()?);
+    if pending_transfers.is_empty() {
+        println!("no pending transfers.");
+        return Ok(());
+    }
+    print_share_infos(&pending_transfers);
+    Ok(())
+}
+
+pub fn accept_transfer(core: &Core, target: Uuid, dest: FileInput) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let parent = dest.find(core)?;
+    core.accept_transfer(target, parent.id)?;
+    println!("done!\nfile '{}' now belongs to you.", target);
+    Ok(())
+}
+
+pub fn reject_transfer(core: &Core, target: Uuid) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    core.reject_transfer(target)?;
+    Ok(())
+}
+
+pub fn who(core: &Core, target: FileInput) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let id = target.find(core)?.id;
+    let permissions = core.effective_permissions(id)?;
+    let w_user = permissions
+        .iter()
+        .map(|p| p.username.len())
+        .max()
+        .unwrap_or_default();
+    for permission in permissions {
+        let (mode, source) = match permission.granted_by {
+            None => ("owner".to_string(), String::new()),
+            Some(granted_by) if granted_by == id => {
+                (permission.mode.to_string().to_lowercase(), String::new())
+            }
+            Some(granted_by) => (
+                permission.mode.to_string().to_lowercase(),
+                format!("shared via {}", core.get_path_by_id(granted_by)?),
+            ),
+        };
+        let line = format!("{:<w_user$}  {:<5}  {}", permission.username, mode, source);
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
 pub fn pending(core: &Core) -> CliResult<()> {
     ensure_account_and_root(core)?;
 
@@ -142,6 +209,17 @@ fn share_infos_table(infos: &[ShareInfo]) -> String {
     ret
 }
 
+pub fn pending_transfer_completor(
+    core: &lb::CoreLib<lb::service::api_service::Network, lb::OnDiskDocuments>, prompt: &str,
+) -> Result<Vec<String>, CliError> {
+    Ok(core
+        .get_pending_transfers()?
+        .into_iter()
+        .map(|transfer| transfer.id.to_string())
+        .filter(|id| id.starts_with(prompt))
+        .collect())
+}
+
 pub fn pending_share_completor(
     core: &lb::CoreLib<lb::service::api_service::Network, lb::OnDiskDocuments>, prompt: &str,
 ) -> Result<Vec<String>, CliError> {
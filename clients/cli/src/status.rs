@@ -0,0 +1,49 @@
+use cli_rs::cli_error::{CliError, CliResult};
+use lb::{Core, CoreError, PlannedOp};
+
+use crate::{ensure_account_and_root, input::FileInput};
+
+pub fn status(core: &Core) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let changes = core.describe_local_changes()?;
+    if changes.is_empty() {
+        println!("no local changes.");
+        return Ok(());
+    }
+    for change in changes {
+        println!("{change}");
+    }
+    Ok(())
+}
+
+pub fn revert(core: &Core, all: bool, target: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    if all {
+        core.revert_all_local_changes()?;
+        return Ok(());
+    }
+
+    if target.is_empty() {
+        return Err(CliError::from("specify a file to revert, or --all"));
+    }
+    let target: FileInput = target.parse().unwrap(); // infallible
+    let id = match &target {
+        FileInput::Id(id) => *id,
+        FileInput::Path(path) => match core.get_by_path(path) {
+            Ok(f) => f.id,
+            // locally deleted files can only be found among the local changes
+            Err(err) if err.kind == CoreError::FileNonexistent => core
+                .describe_local_changes()?
+                .into_iter()
+                .find(|c| c.op == PlannedOp::Delete && &c.path == path)
+                .map(|c| c.id)
+                .ok_or_else(|| CliError::from(format!("no local changes to '{target}'")))?,
+            Err(err) => return Err(err.into()),
+        },
+    };
+
+    core.revert_local_change(id)?;
+    Ok(())
+}
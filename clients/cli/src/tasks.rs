@@ -0,0 +1,48 @@
+use std::io;
+
+use cli_rs::cli_error::{CliError, CliResult};
+use is_terminal::IsTerminal;
+use lb::{Core, TaskFilter};
+
+use crate::{ensure_account_and_root, input::FileInput};
+
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+pub fn tasks(core: &Core, all: bool, due: String, target: FileInput) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let due_by = if due.is_empty() { None } else { Some(due) };
+    if let Some(due_by) = &due_by {
+        if due_by.len() != 10 || due_by.as_bytes()[4] != b'-' || due_by.as_bytes()[7] != b'-' {
+            return Err(CliError::from("due dates are written YYYY-MM-DD"));
+        }
+    }
+
+    let parent = target.find(core)?.id;
+    let filter =
+        TaskFilter { checked: if all { None } else { Some(false) }, due_by, parent: Some(parent) };
+    let tasks = core.list_tasks(filter)?;
+    if tasks.is_empty() {
+        println!("no tasks.");
+        return Ok(());
+    }
+
+    let color = io::stdout().is_terminal();
+    for task in tasks {
+        let path = core.get_path_by_id(task.id)?;
+        let check = if task.checked { "[x]" } else { "[ ]" };
+        let location = format!("{path}:{}", task.line);
+        let due = task
+            .due
+            .map(|due| format!(" @due({due})"))
+            .unwrap_or_default();
+        if color {
+            println!("{DIM}{location}{RESET} {check} {}{RED}{due}{RESET}", task.text);
+        } else {
+            println!("{location} {check} {}{due}", task.text);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use cli_rs::cli_error::{CliError, CliResult};
+use lb::{Core, CoreError};
+
+use crate::{ensure_account_and_root, input::FileInput};
+
+pub fn list(core: &Core) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let teams = core.get_teams()?;
+    if teams.is_empty() {
+        println!("no teams.");
+        return Ok(());
+    }
+    for team in teams {
+        println!("{} (admin: {})", team.name, team.admin);
+        println!("  members: {}", team.members.join(", "));
+        for share in team.shares {
+            let path = core
+                .get_path_by_id(share.id)
+                .unwrap_or_else(|_| share.id.to_string());
+            println!("  shared: {} ({})", path, share.mode.to_string().to_lowercase());
+        }
+    }
+    Ok(())
+}
+
+pub fn create(core: &Core, name: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    core.create_team(&name)?;
+    println!("created team '{}'.", name);
+    Ok(())
+}
+
+pub fn add(core: &Core, team: String, username: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    core.add_team_member(&team, &username)
+        .map_err(|err| match err.kind {
+            CoreError::AccountNonexistent => CliError::from(format!("no account {username} found")),
+            _ => CliError::from(err),
+        })?;
+    println!(
+        "done!\n{} will have access to the files shared with '{}' next time you sync.",
+        username, team
+    );
+    Ok(())
+}
+
+pub fn remove(core: &Core, team: String, username: String) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    core.remove_team_member(&team, &username)
+        .map_err(|err| match err.kind {
+            CoreError::AccountNonexistent => CliError::from(format!("no account {username} found")),
+            _ => CliError::from(err),
+        })?;
+    println!(
+        "done!\n{} will lose access to the files shared with '{}' next time you sync.",
+        username, team
+    );
+    Ok(())
+}
+
+pub fn share(core: &Core, team: String, target: FileInput, read_only: bool) -> CliResult<()> {
+    ensure_account_and_root(core)?;
+
+    let id = target.find(core)?.id;
+    let mode = if read_only { lb::ShareMode::Read } else { lb::ShareMode::Write };
+    core.share_file_with_team(id, &team, mode)?;
+    println!("done!\nfile '{}' will be shared with '{}' next time you sync.", id, team);
+    Ok(())
+}
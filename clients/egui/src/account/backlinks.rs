@@ -0,0 +1,141 @@
+use std::{sync::mpsc, thread};
+
+use eframe::egui;
+
+use crate::model::DocType;
+
+enum BacklinksUpdate {
+    Error(lb::Uuid, String),
+    Done(lb::Uuid, Vec<Backlink>),
+}
+
+/// The documents that link to the open document
+pub struct Backlinks {
+    update_tx: mpsc::Sender<BacklinksUpdate>,
+    update_rx: mpsc::Receiver<BacklinksUpdate>,
+    id: Option<lb::Uuid>,
+    links: Vec<Backlink>,
+    err_msg: Option<String>,
+}
+
+struct Backlink {
+    name: String,
+    path: String,
+    id: lb::Uuid,
+}
+
+impl Backlinks {
+    pub fn new() -> Self {
+        let (update_tx, update_rx) = mpsc::channel();
+        Self { update_tx, update_rx, id: None, links: vec![], err_msg: None }
+    }
+
+    /// Shows the backlinks of `id` (or none), calculating them if they aren't already shown.
+    pub fn set_file(&mut self, ctx: &egui::Context, core: &lb::Core, id: Option<lb::Uuid>) {
+        if self.id != id {
+            self.id = id;
+            self.links.clear();
+            self.err_msg = None;
+            self.recalc_and_redraw(ctx, core);
+        }
+    }
+
+    pub fn recalc_and_redraw(&mut self, ctx: &egui::Context, core: &lb::Core) {
+        if let Some(id) = self.id {
+            Self::calc(ctx, core, id, &self.update_tx);
+        }
+    }
+
+    fn calc(
+        ctx: &egui::Context, core: &lb::Core, id: lb::Uuid,
+        update_tx: &mpsc::Sender<BacklinksUpdate>,
+    ) {
+        let ctx = ctx.clone();
+        let core = core.clone();
+        let update_tx = update_tx.clone();
+
+        thread::spawn(move || {
+            let update = match core.get_backlinks(id) {
+                Ok(ids) => BacklinksUpdate::Done(
+                    id,
+                    ids.into_iter()
+                        .filter_map(|link_id| {
+                            let file = core.get_file_by_id(link_id).ok()?;
+                            let path = core.get_path_by_id(link_id).unwrap_or_default();
+                            Some(Backlink { name: file.name, path, id: link_id })
+                        })
+                        .collect(),
+                ),
+                Err(err) => BacklinksUpdate::Error(id, format!("{:?}", err.kind)),
+            };
+            update_tx.send(update).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn process_updates(&mut self) {
+        while let Ok(update) = self.update_rx.try_recv() {
+            match update {
+                // updates for files that are no longer open are stale
+                BacklinksUpdate::Error(id, err) if Some(id) == self.id => self.err_msg = Some(err),
+                BacklinksUpdate::Done(id, links) if Some(id) == self.id => {
+                    self.links = links;
+                    self.err_msg = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether there's nothing to show, in which case the panel is hidden.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty() && self.err_msg.is_none()
+    }
+
+    /// Returns the id of a document that was clicked, to be opened
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<lb::Uuid> {
+        ui.add_space(15.0);
+        ui.heading("Backlinks");
+        ui.add_space(10.0);
+
+        if let Some(err_msg) = &self.err_msg {
+            ui.label(egui::RichText::new(err_msg).color(ui.visuals().error_fg_color));
+            return None;
+        }
+
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_source("backlinks")
+            .show(ui, |ui| {
+                for link in &self.links {
+                    let response = ui
+                        .horizontal(|ui| {
+                            DocType::from_name(&link.name).to_icon().show(ui);
+                            ui.vertical(|ui| {
+                                ui.label(&link.name);
+                                ui.label(
+                                    egui::RichText::new(&link.path)
+                                        .size(13.0)
+                                        .color(egui::Color32::GRAY),
+                                );
+                            });
+                        })
+                        .response;
+
+                    let response = ui.interact(
+                        response.rect,
+                        egui::Id::new("backlink").with(link.id),
+                        egui::Sense::click(),
+                    );
+                    if response.hovered() {
+                        ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::PointingHand);
+                    }
+                    if response.clicked() {
+                        clicked = Some(link.id);
+                    }
+                    ui.add_space(10.0);
+                }
+            });
+        clicked
+    }
+}
@@ -1,9 +1,13 @@
 mod background;
+mod backlinks;
 mod full_doc_search;
 mod modals;
+mod pinned;
+mod sharing;
 mod suggested_docs;
 mod syncing;
 mod tabs;
+mod tasks;
 mod tree;
 mod workspace;
 
@@ -17,27 +21,40 @@ use eframe::egui;
 use lb::{FileType, NameComponents};
 
 use crate::model::{AccountScreenInitData, Usage};
-use crate::settings::Settings;
+use crate::settings::{Settings, SyncedSettings, SETTINGS_NAMESPACE};
+use crate::splash::LoadAccount;
+use crate::theme;
 use crate::theme::Icon;
 use crate::util::NUM_KEYS;
 use crate::widgets::{separator, Button};
 use crate::UpdateOutput;
 
 use self::background::*;
+use self::backlinks::Backlinks;
 use self::full_doc_search::FullDocSearch;
 use self::modals::*;
-
+use self::pinned::Pinned;
+use self::sharing::Sharing;
 use self::suggested_docs::SuggestedDocs;
 use self::syncing::{SyncPanel, SyncUpdate};
 use self::tabs::{
-    Drawing, ImageViewer, Markdown, PdfViewer, PlainText, SVGEditor, Tab, TabContent, TabFailure,
+    DiffViewer, Drawing, ImageViewer, Markdown, PdfViewer, PlainText, SVGEditor, Tab, TabContent,
+    TabFailure,
 };
+use self::tasks::Tasks;
 use self::tree::{FileTree, TreeNode};
 use self::workspace::Workspace;
 
+/// the name of the template in the templates folder that new daily notes are created from
+const DAILY_NOTE_TEMPLATE: &str = "daily.md";
+
 pub struct AccountScreen {
     ctx: egui::Context,
     settings: Arc<RwLock<Settings>>,
+    accounts: lb::AccountManager,
+    usernames: Vec<String>,
+    username: String,
+    switch_to: Option<LoadAccount>,
     core: lb::Core,
     toasts: egui_notify::Toasts,
 
@@ -50,23 +67,35 @@ pub struct AccountScreen {
     has_pending_shares: bool,
     is_new_user: bool,
     suggested: SuggestedDocs,
+    backlinks: Backlinks,
+    sharing: Sharing,
+    pinned: Pinned,
+    tasks: Tasks,
     full_search_doc: FullDocSearch,
     sync: SyncPanel,
     usage: Result<Usage, String>,
     workspace: Workspace,
     modals: Modals,
     shutdown: Option<AccountShutdownProgress>,
+
+    /// heading to select in a document opened from a link, once the document is loaded
+    link_heading: Option<(lb::Uuid, String)>,
 }
 
 impl AccountScreen {
     pub fn new(
-        settings: Arc<RwLock<Settings>>, core: lb::Core, acct_data: AccountScreenInitData,
-        ctx: &egui::Context, is_new_user: bool,
+        settings: Arc<RwLock<Settings>>, accounts: lb::AccountManager, core: lb::Core,
+        acct_data: AccountScreenInitData, ctx: &egui::Context, is_new_user: bool,
     ) -> Self {
         let (update_tx, update_rx) = mpsc::channel();
 
         let AccountScreenInitData { sync_status, files, usage, has_pending_shares } = acct_data;
         let core_clone = core.clone();
+        let usernames = accounts.list_accounts().unwrap_or_default();
+        let username = core
+            .get_account()
+            .map(|account| account.username)
+            .unwrap_or_default();
 
         let background = BackgroundWorker::new(ctx, &update_tx);
         let background_tx = background.spawn_worker();
@@ -75,8 +104,12 @@ impl AccountScreen {
             .with_margin(egui::vec2(40.0, 30.0))
             .with_padding(egui::vec2(20.0, 20.0));
 
-        Self {
+        let this = Self {
             settings,
+            accounts,
+            usernames,
+            username,
+            switch_to: None,
             core,
             toasts,
             update_tx,
@@ -86,14 +119,21 @@ impl AccountScreen {
             is_new_user,
             tree: FileTree::new(files, &core_clone),
             suggested: SuggestedDocs::new(&core_clone),
+            backlinks: Backlinks::new(),
+            sharing: Sharing::new(),
+            pinned: Pinned::new(&core_clone),
+            tasks: Tasks::new(&core_clone),
             full_search_doc: FullDocSearch::new(&core_clone),
             sync: SyncPanel::new(sync_status),
             usage,
             workspace: Workspace::new(),
             modals: Modals::default(),
             shutdown: None,
+            link_heading: None,
             ctx: ctx.clone(),
-        }
+        };
+        this.load_synced_settings();
+        this
     }
 
     pub fn begin_shutdown(&mut self) {
@@ -107,6 +147,21 @@ impl AccountScreen {
         self.background_tx.send(BackgroundEvent::Shutdown).unwrap();
     }
 
+    /// Saves and syncs like when the app closes, then hands off to load another account.
+    pub fn begin_switch(&mut self, load: LoadAccount) {
+        self.switch_to = Some(load);
+        self.begin_shutdown();
+    }
+
+    /// Once the screen has shut down for the user to switch accounts, what's needed to load the
+    /// next one.
+    pub fn take_switch(
+        &mut self,
+    ) -> Option<(Arc<RwLock<Settings>>, lb::AccountManager, LoadAccount)> {
+        let load = self.switch_to.take()?;
+        Some((self.settings.clone(), self.accounts.clone(), load))
+    }
+
     pub fn is_shutdown(&self) -> bool {
         match &self.shutdown {
             Some(s) => s.done_saving && s.done_syncing,
@@ -117,12 +172,13 @@ impl AccountScreen {
     pub fn update(&mut self, ctx: &egui::Context, output: &mut UpdateOutput) {
         self.process_updates(ctx, output);
         self.process_keys(ctx, output);
-        self.process_dropped_files(ctx);
         self.toasts.show(ctx);
 
         if self.shutdown.is_some() {
+            let msg =
+                if self.switch_to.is_some() { "Switching accounts..." } else { "Shutting down..." };
             egui::CentralPanel::default()
-                .show(ctx, |ui| ui.centered_and_justified(|ui| ui.label("Shutting down...")));
+                .show(ctx, |ui| ui.centered_and_justified(|ui| ui.label(msg)));
             return Default::default();
         }
 
@@ -155,20 +211,95 @@ impl AccountScreen {
                         ui.add_space(15.0);
 
                         if self.full_search_doc.results.is_empty() {
+                            if let Some(file) = self.pinned.show(ui, ctx, &self.core) {
+                                if file.is_folder() {
+                                    self.tree.reveal_file(file.id, &self.core);
+                                } else {
+                                    self.open_file(file.id, ctx, false);
+                                }
+                            }
+                            ui.add_space(15.0);
                             if let Some(file) = self.suggested.show(ui) {
                                 self.open_file(file, ctx, false);
                             }
                             ui.add_space(15.0);
+                            if let Some(file) = self.tasks.show(ui, ctx, &self.core) {
+                                self.open_file(file, ctx, false);
+                            }
+                            ui.add_space(15.0);
                             self.show_tree(ui);
                         }
                     });
                 });
             });
 
+        let open_markdown = self
+            .workspace
+            .current_tab()
+            .filter(|tab| matches!(tab.content, Some(TabContent::Markdown(_))))
+            .map(|tab| tab.id);
+        self.backlinks.set_file(ctx, &self.core, open_markdown);
+        self.backlinks.process_updates();
+        let open_file = self.workspace.current_tab().map(|tab| tab.id);
+        self.sharing.set_file(ctx, &self.core, open_file);
+        self.sharing.process_updates();
+
+        // open documents whose tasks were checked from the tasks panel are reloaded
+        let toggled_ids = self
+            .tasks
+            .process_updates()
+            .into_iter()
+            .filter(|&id| {
+                self.workspace
+                    .tabs
+                    .iter()
+                    .any(|tab| tab.id == id && !tab.is_dirty() && !tab.is_diff())
+            })
+            .collect::<Vec<_>>();
+        if !toggled_ids.is_empty() {
+            self.refresh_tree_and_tabs(ctx, toggled_ids);
+        }
+
+        egui::SidePanel::right("backlinks_panel")
+            .frame(
+                egui::Frame::none()
+                    .fill(ctx.style().visuals.panel_fill)
+                    .inner_margin(egui::Margin::symmetric(10.0, 0.0)),
+            )
+            .default_width(250.0)
+            .show_animated(
+                ctx,
+                is_expanded && !(self.backlinks.is_empty() && self.sharing.is_empty()),
+                |ui| {
+                    ui.set_enabled(!self.is_any_modal_open());
+
+                    if !self.sharing.is_empty() {
+                        self.sharing.show(ui);
+                    }
+                    if !self.backlinks.is_empty() {
+                        if let Some(file) = self.backlinks.show(ui) {
+                            self.open_file(file, ctx, false);
+                        }
+                    }
+                },
+            );
+
         egui::CentralPanel::default()
             .frame(egui::Frame::default().fill(ctx.style().visuals.widgets.noninteractive.bg_fill))
             .show(ctx, |ui| self.show_workspace(output, ui));
 
+        // after the workspace, where images dropped onto a document are attached to it
+        self.process_dropped_files(ctx);
+
+        // links to lockbook files open in a tab rather than the browser
+        let lb_url = ctx.output_mut(|o| match &o.open_url {
+            Some(url) if url.url.starts_with("lb://") => o.open_url.take(),
+            _ => None,
+        });
+        if let Some(lb_url) = lb_url {
+            self.open_link(&lb_url.url, ctx);
+        }
+
         if self.is_new_user {
             self.modals.account_backup = Some(AccountBackup);
             self.is_new_user = false;
@@ -187,7 +318,11 @@ impl AccountScreen {
                 AccountUpdate::SaveResult(id, result) => {
                     if let Some(tab) = self.workspace.get_mut_tab_by_id(id) {
                         match result {
-                            Ok(time_saved) => tab.last_saved = time_saved,
+                            Ok(time_saved) => {
+                                tab.last_saved = time_saved;
+                                self.backlinks.recalc_and_redraw(ctx, &self.core);
+                                self.tasks.recalc_and_redraw(ctx, &self.core);
+                            }
                             Err(err) => {
                                 tab.failure = Some(TabFailure::Unexpected(format!("{:?}", err)))
                             }
@@ -215,6 +350,9 @@ impl AccountScreen {
                     }
                     OpenModal::InitiateShare(target) => self.open_share_modal(target),
                     OpenModal::NewFolder(maybe_parent) => self.open_new_folder_modal(maybe_parent),
+                    OpenModal::NewFromTemplate(maybe_parent) => {
+                        self.open_new_from_template_modal(maybe_parent)
+                    }
                     OpenModal::Settings => {
                         self.modals.settings = Some(SettingsModal::new(&self.core, &self.settings));
                     }
@@ -234,21 +372,34 @@ impl AccountScreen {
                     }
                     Err(msg) => self.modals.error = Some(ErrorModal::new(msg)),
                 },
+                AccountUpdate::DailyNoteOpened(result) => match result {
+                    Ok((id, root)) => {
+                        self.tree.root = root;
+                        self.tree.reveal_file(id, &self.core);
+                        self.open_file(id, ctx, false);
+                    }
+                    Err(msg) => self.modals.error = Some(ErrorModal::new(msg)),
+                },
                 AccountUpdate::FileCreated(result) => match result {
                     Ok(f) => {
                         let (id, is_doc) = (f.id, f.is_document());
                         self.tree.root.insert(f);
                         self.tree.reveal_file(id, &self.core);
                         if is_doc {
-                            self.open_file(id, ctx, true);
+                            // documents created from a template were named by the user
+                            let needs_name = self.modals.new_from_template.is_none();
+                            self.open_file(id, ctx, needs_name);
                         }
                         // Close whichever new file modal was open.
                         self.modals.new_folder = None;
+                        self.modals.new_from_template = None;
                         ctx.request_repaint();
                     }
                     Err(msg) => {
                         if let Some(m) = &mut self.modals.new_folder {
                             m.err_msg = Some(msg)
+                        } else if let Some(m) = &mut self.modals.new_from_template {
+                            m.err_msg = Some(msg)
                         }
                     }
                 },
@@ -261,6 +412,15 @@ impl AccountScreen {
                             Ok(content) => tab.content = Some(content),
                             Err(fail) => tab.failure = Some(fail),
                         }
+
+                        if let Some((link_id, heading)) = self.link_heading.take() {
+                            match &mut tab.content {
+                                Some(TabContent::Markdown(md)) if link_id == id => {
+                                    md.editor.select_heading(&heading)
+                                }
+                                _ => self.link_heading = Some((link_id, heading)),
+                            }
+                        }
                     }
                 }
                 AccountUpdate::FileRenamed { id, new_name, new_child_paths } => {
@@ -284,10 +444,12 @@ impl AccountScreen {
                             tab.path = new_path.clone();
                         }
                     }
+                    self.reload_tabs_linked_with(ctx, new_child_paths.into_keys().collect());
                 }
                 AccountUpdate::FileDeleted(f) => {
                     self.tree.remove(&f);
                     self.suggested.recalc_and_redraw(ctx, &self.core);
+                    self.pinned.recalc_and_redraw(ctx, &self.core);
                 }
                 AccountUpdate::SyncUpdate(update) => {
                     self.process_sync_update(ctx, update);
@@ -299,7 +461,7 @@ impl AccountScreen {
                     for i in 0..self.workspace.tabs.len() {
                         let tab_id = self.workspace.tabs[i].id;
 
-                        if tab_id == id {
+                        if tab_id == id && !self.workspace.tabs[i].is_diff() {
                             match res {
                                 Ok(new_tab) => {
                                     self.workspace.tabs[i] = new_tab;
@@ -333,6 +495,7 @@ impl AccountScreen {
                 AccountUpdate::FileShared(result) => match result {
                     Ok(_) => {
                         self.modals.create_share = None;
+                        self.sharing.recalc_and_redraw(ctx, &self.core);
                         self.perform_sync(ctx);
                     }
                     Err(msg) => {
@@ -350,6 +513,13 @@ impl AccountScreen {
                 AccountUpdate::FoundPendingShares(has_pending_shares) => {
                     self.has_pending_shares = has_pending_shares
                 }
+                AccountUpdate::SyncedSettingsLoaded(result) => match result {
+                    Ok(Some(synced)) => self.apply_synced_settings(ctx, synced),
+                    // uploaded once they're changed, so that a new device's settings don't replace
+                    // those synced from other devices before its first sync
+                    Ok(None) => {}
+                    Err(msg) => self.modals.error = Some(ErrorModal::new(msg)),
+                },
                 AccountUpdate::EditorRenameSignal(new_name) => {
                     if let Some(tab) = &self.workspace.tabs.get(self.workspace.active_tab) {
                         let core = self.core.clone();
@@ -392,6 +562,11 @@ impl AccountScreen {
             self.create_file(false);
         }
 
+        // Ctrl-D to open today's daily note.
+        if ctx.input_mut(|i| i.consume_key(CTRL, egui::Key::D)) {
+            self.open_daily_note(ctx);
+        }
+
         // Ctrl-S to save current tab.
         if ctx.input_mut(|i| i.consume_key(CTRL, egui::Key::S)) {
             self.save_tab(ctx, self.workspace.active_tab);
@@ -481,6 +656,7 @@ impl AccountScreen {
     }
 
     fn show_tree(&mut self, ui: &mut egui::Ui) {
+        self.tree.state.pinned = self.pinned.ids();
         let resp = egui::ScrollArea::both()
             .show(ui, |ui| self.tree.show(ui))
             .inner;
@@ -500,6 +676,13 @@ impl AccountScreen {
             ui.ctx().request_repaint();
         }
 
+        if let Some(file) = resp.new_from_template_modal {
+            self.update_tx
+                .send(OpenModal::NewFromTemplate(Some(file)).into())
+                .unwrap();
+            ui.ctx().request_repaint();
+        }
+
         if let Some(file) = resp.create_share_modal {
             self.update_tx
                 .send(OpenModal::InitiateShare(file).into())
@@ -524,6 +707,21 @@ impl AccountScreen {
             }
         }
 
+        if resp.revert_request {
+            let selected_files = self.tree.get_selected_files();
+            if !selected_files.is_empty() {
+                self.revert_files(ui.ctx(), selected_files);
+            }
+        }
+
+        if let Some(id) = resp.diff_request {
+            self.open_diff(id, ui.ctx());
+        }
+
+        if let Some((id, pinned)) = resp.pin_request {
+            self.pinned.set_pinned(ui.ctx(), &self.core, id, pinned);
+        }
+
         if let Some(id) = resp.dropped_on {
             self.move_selected_files_to(ui.ctx(), id);
         }
@@ -575,6 +773,10 @@ impl AccountScreen {
                 };
                 incoming_shares_btn.on_hover_text("Incoming shares");
 
+                ui.add_space(5.0);
+
+                self.show_account_switcher(ui);
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.add_space(10.0);
                     let zen_mode_btn = Button::default().icon(&Icon::HIDE_SIDEBAR).show(ui);
@@ -592,13 +794,104 @@ impl AccountScreen {
         );
     }
 
+    fn show_account_switcher(&mut self, ui: &mut egui::Ui) {
+        let accounts_btn = Button::default().icon(&Icon::ACCOUNT).show(ui);
+        let popup_id = ui.make_persistent_id("account_switcher");
+        if accounts_btn.clicked() {
+            ui.memory_mut(|m| m.toggle_popup(popup_id));
+        }
+
+        let mut load = None;
+        egui::popup::popup_below_widget(ui, popup_id, &accounts_btn, |ui| {
+            ui.set_min_width(200.0);
+            for username in &self.usernames {
+                if ui
+                    .selectable_label(username == &self.username, username)
+                    .clicked()
+                    && username != &self.username
+                {
+                    load = Some(LoadAccount::Switch(username.clone()));
+                }
+            }
+            ui.separator();
+            if ui.button("Add Account").clicked() {
+                load = Some(LoadAccount::New);
+            }
+        });
+        accounts_btn.on_hover_text(format!("Signed in as {}", self.username));
+
+        if let Some(load) = load {
+            self.begin_switch(load);
+            ui.ctx().request_repaint();
+        }
+    }
+
     fn save_settings(&mut self) {
         if let Err(err) = self.settings.read().unwrap().to_file() {
             self.modals.error = Some(ErrorModal::new(err));
         }
+        self.upload_synced_settings();
+    }
+
+    /// Loads the settings synced with the account, which replace this device's copy of them.
+    pub fn load_synced_settings(&self) {
+        let core = self.core.clone();
+        let update_tx = self.update_tx.clone();
+        let ctx = self.ctx.clone();
+
+        thread::spawn(move || {
+            let result = core
+                .get_settings::<SyncedSettings>(SETTINGS_NAMESPACE)
+                .map_err(|err| format!("{:?}", err));
+            update_tx
+                .send(AccountUpdate::SyncedSettingsLoaded(result))
+                .unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    fn apply_synced_settings(&mut self, ctx: &egui::Context, synced: SyncedSettings) {
+        // changes being made in the settings modal are saved when it closes
+        if self.modals.settings.is_some() {
+            return;
+        }
+
+        let mut settings = self.settings.write().unwrap();
+        if settings.synced() == synced {
+            return;
+        }
+        let toolbar_changed = settings.toolbar_visibility != synced.toolbar_visibility;
+        settings.apply_synced(synced);
+        theme::apply_settings(&settings, ctx);
+        let result = settings.to_file();
+        let toolbar_visibility = settings.toolbar_visibility;
+        drop(settings);
+
+        if toolbar_changed {
+            self.refresh_toolbar_visibilities(toolbar_visibility);
+        }
+        if let Err(err) = result {
+            self.modals.error = Some(ErrorModal::new(err));
+        }
+    }
+
+    fn upload_synced_settings(&self) {
+        let core = self.core.clone();
+        let synced = self.settings.read().unwrap().synced();
+
+        thread::spawn(move || {
+            if let Err(err) = core.set_settings(SETTINGS_NAMESPACE, &synced) {
+                eprintln!("couldn't save synced settings: {:?}", err);
+            }
+        });
     }
 
     pub fn refresh_tree_and_workspace(&self, ctx: &egui::Context, work: lb::SyncStatus) {
+        self.refresh_tree_and_tabs(ctx, ids_changed_on_server(&work));
+    }
+
+    /// Reloads the file tree and any open tabs for the given files.
+    fn refresh_tree_and_tabs(&self, ctx: &egui::Context, changed_ids: Vec<lb::Uuid>) {
         let opened_ids = self
             .workspace
             .tabs
@@ -619,8 +912,7 @@ impl AccountScreen {
             update_tx.send(AccountUpdate::ReloadTree(root)).unwrap();
             ctx.request_repaint();
 
-            let server_ids = ids_changed_on_server(&work);
-            let stale_tab_ids = server_ids.iter().filter(|id| opened_ids.contains(id));
+            let stale_tab_ids = changed_ids.iter().filter(|id| opened_ids.contains(id));
 
             for &id in stale_tab_ids {
                 let name = match core.get_file_by_id(id) {
@@ -654,6 +946,7 @@ impl AccountScreen {
                             if ext == "md" {
                                 TabContent::Markdown(Markdown::boxed(
                                     core.clone(),
+                                    id,
                                     &bytes,
                                     &toolbar_visibility,
                                     update_tx.clone(),
@@ -705,6 +998,36 @@ impl AccountScreen {
         self.modals.new_folder = Some(NewFolderModal::new(parent_path));
     }
 
+    fn open_new_from_template_modal(&mut self, maybe_parent: Option<lb::File>) {
+        let parent_id = match maybe_parent {
+            Some(f) => {
+                if f.is_folder() {
+                    f.id
+                } else {
+                    f.parent
+                }
+            }
+            None => self.core.get_root().unwrap().id,
+        };
+
+        let parent_path = self.core.get_path_by_id(parent_id).unwrap();
+        let templates = self
+            .core
+            .list_templates()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|template| {
+                let path = self.core.get_path_by_id(template.id).unwrap_or_default();
+                let path = path
+                    .strip_prefix(lb::TEMPLATES_FOLDER_PATH)
+                    .unwrap_or(&path)
+                    .to_string();
+                (path, template.id)
+            })
+            .collect();
+        self.modals.new_from_template = Some(NewFromTemplateModal::new(parent_path, templates));
+    }
+
     fn open_share_modal(&mut self, target: lb::File) {
         self.modals.create_share = Some(CreateShareModal::new(target));
     }
@@ -722,6 +1045,46 @@ impl AccountScreen {
         });
     }
 
+    fn create_from_template(&mut self, params: NewFromTemplateParams) {
+        let parent = self.core.get_by_path(&params.parent_path).unwrap();
+
+        let core = self.core.clone();
+        let update_tx = self.update_tx.clone();
+        thread::spawn(move || {
+            let result = core
+                .create_from_template(params.template, parent.id, &params.name)
+                .map_err(|err| format!("{:?}", err));
+            update_tx.send(AccountUpdate::FileCreated(result)).unwrap();
+        });
+    }
+
+    /// Opens today's note, creating it from /templates/daily.md if that exists.
+    fn open_daily_note(&mut self, ctx: &egui::Context) {
+        let core = self.core.clone();
+        let ctx = ctx.clone();
+        let update_tx = self.update_tx.clone();
+
+        thread::spawn(move || {
+            let template = core
+                .get_by_path(&format!("{}{}", lb::TEMPLATES_FOLDER_PATH, DAILY_NOTE_TEMPLATE))
+                .ok()
+                .map(|f| f.id);
+            let result = core
+                .get_or_create_daily_note(lb::today(), lb::DEFAULT_DAILY_NOTE_PATTERN, template)
+                .map(|note| {
+                    // folders may have been created for the note
+                    let all_metas = core.list_metadatas().unwrap();
+                    (note.id, tree::create_root_node(all_metas))
+                })
+                .map_err(|err| format!("{:?}", err));
+
+            update_tx
+                .send(AccountUpdate::DailyNoteOpened(result))
+                .unwrap();
+            ctx.request_repaint();
+        });
+    }
+
     fn create_file(&mut self, is_drawing: bool) {
         let mut focused_parent = self.tree.root.file.id;
         for id in self.tree.state.selected.drain() {
@@ -798,6 +1161,7 @@ impl AccountScreen {
                         if ext == "md" {
                             TabContent::Markdown(Markdown::boxed(
                                 core.clone(),
+                                id,
                                 &bytes,
                                 &toolbar_visibility,
                                 update_tx.clone(),
@@ -821,8 +1185,77 @@ impl AccountScreen {
         });
     }
 
+    /// Opens the file an `lb://<id>` url links to, selecting the heading after its `#` if any.
+    fn open_link(&mut self, url: &str, ctx: &egui::Context) {
+        let link = url.trim_start_matches("lb://");
+        let (id, heading) = match link.split_once('#') {
+            Some((id, heading)) => (id, Some(heading)),
+            None => (link, None),
+        };
+        let id = match lb::Uuid::parse_str(id) {
+            Ok(id) if self.core.get_file_by_id(id).is_ok() => id,
+            _ => {
+                self.toasts
+                    .error("The linked file doesn't exist")
+                    .set_closable(false)
+                    .set_show_progress_bar(false)
+                    .set_duration(Some(Duration::from_secs(7)));
+                return;
+            }
+        };
+
+        self.open_file(id, ctx, false);
+
+        if let Some(heading) = heading {
+            match self
+                .workspace
+                .get_mut_tab_by_id(id)
+                .and_then(|tab| tab.content.as_mut())
+            {
+                Some(TabContent::Markdown(md)) => md.editor.select_heading(heading),
+                Some(_) => {}
+                None => self.link_heading = Some((id, heading.to_string())),
+            }
+        }
+    }
+
+    fn open_diff(&mut self, id: lb::Uuid, ctx: &egui::Context) {
+        let diff = match self.core.diff_document(id, lb::DiffAgainst::Base) {
+            Ok(diff) => diff,
+            Err(err) => {
+                self.toasts
+                    .error(format!("Couldn't show changes: {}", err))
+                    .set_closable(false)
+                    .set_show_progress_bar(false)
+                    .set_duration(Some(Duration::from_secs(7)));
+                return;
+            }
+        };
+
+        let content = TabContent::Diff(DiffViewer::boxed(diff));
+        if let Some(i) = self
+            .workspace
+            .tabs
+            .iter()
+            .position(|t| t.id == id && t.is_diff())
+        {
+            self.workspace.tabs[i].content = Some(content);
+            self.workspace.active_tab = i;
+        } else {
+            let fname = match self.core.get_file_by_id(id) {
+                Ok(file) => format!("{} (changes)", file.name),
+                Err(_) => return,
+            };
+            let fpath = self.core.get_path_by_id(id).unwrap_or_default();
+            self.workspace.open_tab(id, &fname, &fpath, false);
+            self.workspace.tabs[self.workspace.active_tab].content = Some(content);
+        }
+        ctx.request_repaint();
+    }
+
     fn move_selected_files_to(&mut self, ctx: &egui::Context, target: lb::Uuid) {
         let files = self.tree.get_selected_files();
+        let mut moved_ids = Vec::new();
 
         for f in files {
             if f.parent == target {
@@ -839,13 +1272,49 @@ impl AccountScreen {
                 if let Some(tab) = self.workspace.get_mut_tab_by_id(f.id) {
                     tab.path = self.core.get_path_by_id(f.id).unwrap();
                 }
+                moved_ids.extend(
+                    self.core
+                        .get_and_get_children_recursively(f.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|file| file.id),
+                );
                 ctx.request_repaint();
             }
         }
+        self.reload_tabs_linked_with(ctx, moved_ids);
 
         ctx.request_repaint();
     }
 
+    /// Reloads the open documents that link to or from renamed or moved files, since lb rewrites
+    /// links that the rename or move broke. Tabs with unsaved changes are left alone.
+    fn reload_tabs_linked_with(&self, ctx: &egui::Context, ids: Vec<lb::Uuid>) {
+        let links = match self.core.link_graph() {
+            Ok(graph) => graph.links,
+            Err(err) => {
+                eprintln!("couldn't get link graph: {:?}", err);
+                return;
+            }
+        };
+
+        let linked_ids = links
+            .into_iter()
+            .filter(|(_, to)| ids.contains(to))
+            .map(|(from, _)| from)
+            .chain(ids.iter().copied())
+            .filter(|&id| {
+                self.workspace
+                    .tabs
+                    .iter()
+                    .any(|tab| tab.id == id && !tab.is_dirty() && !tab.is_diff())
+            })
+            .collect::<Vec<_>>();
+        if !linked_ids.is_empty() {
+            self.refresh_tree_and_tabs(ctx, linked_ids);
+        }
+    }
+
     fn rename_file(&mut self, req: (lb::Uuid, String), ctx: &egui::Context) {
         if let Some(tab) = self.workspace.tabs.iter_mut().find(|t| t.id.eq(&req.0)) {
             if let Some(TabContent::Markdown(md)) = &mut tab.content {
@@ -920,6 +1389,27 @@ impl AccountScreen {
         });
     }
 
+    fn revert_files(&mut self, ctx: &egui::Context, files: Vec<lb::File>) {
+        let mut reverted = Vec::new();
+        for f in files {
+            reverted.extend(
+                self.core
+                    .get_and_get_children_recursively(f.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|f| f.id),
+            );
+            if let Err(err) = self.core.revert_local_change(f.id) {
+                self.toasts
+                    .error(format!("Couldn't revert \"{}\": {}", f.name, err))
+                    .set_closable(false)
+                    .set_show_progress_bar(false)
+                    .set_duration(Some(Duration::from_secs(7)));
+            }
+        }
+        self.refresh_tree_and_tabs(ctx, reverted);
+    }
+
     fn delete_files(&mut self, ctx: &egui::Context, files: Vec<lb::File>) {
         let core = self.core.clone();
         let update_tx = self.update_tx.clone();
@@ -960,6 +1450,7 @@ pub enum AccountUpdate {
     OpenModal(OpenModal),
 
     FileCreated(Result<lb::File, String>),
+    DailyNoteOpened(Result<(lb::Uuid, TreeNode), String>),
     FileShared(Result<(), String>),
     FileLoaded(lb::Uuid, Result<TabContent, TabFailure>),
     FileRenamed {
@@ -979,6 +1470,7 @@ pub enum AccountUpdate {
 
     ShareAccepted(Result<lb::File, String>),
     FoundPendingShares(bool),
+    SyncedSettingsLoaded(Result<Option<SyncedSettings>, String>),
 
     DoneDeleting,
 
@@ -991,6 +1483,7 @@ pub enum AccountUpdate {
 
 pub enum OpenModal {
     NewFolder(Option<lb::File>),
+    NewFromTemplate(Option<lb::File>),
     InitiateShare(lb::File),
     Settings,
     AcceptShare,
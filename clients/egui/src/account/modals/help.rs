@@ -22,6 +22,7 @@ impl HelpModal {
             .body(|mut body| {
                 for (k, v) in [
                     ("Ctrl-N", "Open the New File prompt"),
+                    ("Ctrl-D", "Open today's daily note"),
                     ("Ctrl-Space, Ctrl-L", "Open the search prompt"),
                     ("Ctrl-S", "Save the active document"),
                     ("Ctrl-W", "Close an opened document"),
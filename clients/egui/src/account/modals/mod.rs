@@ -17,7 +17,7 @@ pub use error::ErrorModal;
 pub use file_picker::FilePicker;
 pub use file_picker::FilePickerAction;
 pub use help::HelpModal;
-pub use new_file::{NewFileParams, NewFolderModal};
+pub use new_file::{NewFileParams, NewFolderModal, NewFromTemplateModal, NewFromTemplateParams};
 pub use search::SearchModal;
 pub use settings::{SettingsModal, SettingsResponse};
 
@@ -36,6 +36,7 @@ pub struct Modals {
     pub file_picker: Option<FilePicker>,
     pub help: Option<HelpModal>,
     pub new_folder: Option<NewFolderModal>,
+    pub new_from_template: Option<NewFromTemplateModal>,
     pub search: Option<SearchModal>,
     pub settings: Option<SettingsModal>,
 }
@@ -108,6 +109,12 @@ impl super::AccountScreen {
             }
         }
 
+        if let Some(response) = show(ctx, x_offset, &mut self.modals.new_from_template) {
+            if let Some(submission) = response.inner {
+                self.create_from_template(submission);
+            }
+        }
+
         if let Some(response) = show(ctx, x_offset, &mut self.modals.create_share) {
             if let Some(submission) = response.inner {
                 self.create_share(submission)
@@ -138,7 +145,7 @@ impl super::AccountScreen {
         }
     }
 
-    fn refresh_toolbar_visibilities(&mut self, visibility: ToolBarVisibility) {
+    pub fn refresh_toolbar_visibilities(&mut self, visibility: ToolBarVisibility) {
         self.workspace.tabs.iter_mut().for_each(|t| {
             if let Some(crate::account::tabs::TabContent::Markdown(ref mut md)) = &mut t.content {
                 md.toolbar.visibility = visibility;
@@ -152,6 +159,7 @@ impl super::AccountScreen {
             || m.accept_share.is_some()
             || m.account_backup.is_some()
             || m.new_folder.is_some()
+            || m.new_from_template.is_some()
             || m.create_share.is_some()
             || m.file_picker.is_some()
             || m.search.is_some()
@@ -170,6 +178,10 @@ impl super::AccountScreen {
             m.new_folder = None;
             return true;
         }
+        if m.new_from_template.is_some() {
+            m.new_from_template = None;
+            return true;
+        }
         if m.create_share.is_some() {
             m.create_share = None;
             return true;
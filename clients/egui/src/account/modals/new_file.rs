@@ -90,3 +90,134 @@ impl super::Modal for NewFolderModal {
         maybe_submission
     }
 }
+
+pub struct NewFromTemplateParams {
+    pub template: lb::Uuid,
+    pub parent_path: String,
+    pub name: String,
+}
+
+pub struct NewFromTemplateModal {
+    parent_path: String,
+    /// The templates' paths within the templates folder, and their ids.
+    templates: Vec<(String, lb::Uuid)>,
+    selected: usize,
+    new_name: String,
+    name_field_needs_focus: bool,
+    pub err_msg: Option<String>,
+}
+
+impl NewFromTemplateModal {
+    pub fn new(parent_path: String, templates: Vec<(String, lb::Uuid)>) -> Self {
+        Self {
+            parent_path,
+            templates,
+            selected: 0,
+            new_name: "".to_string(),
+            name_field_needs_focus: true,
+            err_msg: None,
+        }
+    }
+}
+
+impl super::Modal for NewFromTemplateModal {
+    type Response = Option<NewFromTemplateParams>;
+
+    fn title(&self) -> &str {
+        "New from Template"
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) -> Self::Response {
+        let mut maybe_submission = None;
+
+        ui.add_space(10.0);
+
+        if self.templates.is_empty() {
+            ui.label(format!(
+                "You don't have any templates yet. Documents in {} are offered here.",
+                lb::TEMPLATES_FOLDER_PATH
+            ));
+            ui.add_space(10.0);
+            return None;
+        }
+
+        egui::Grid::new("new_from_template_modal_content")
+            .spacing(egui::vec2(10.0, 10.0))
+            .show(ui, |ui| {
+                ui.label("Parent:");
+
+                // The path of the parent folder.
+                ui.add_sized(
+                    ui.available_size_before_wrap(),
+                    egui::TextEdit::singleline(&mut self.parent_path)
+                        .margin(egui::vec2(8.0, 8.0))
+                        .hint_text("Parent...")
+                        .interactive(false),
+                );
+
+                ui.end_row();
+
+                ui.label("Template:");
+
+                egui::ComboBox::from_id_source("new_from_template_template")
+                    .width(300.0)
+                    .selected_text(&self.templates[self.selected].0)
+                    .show_ui(ui, |ui| {
+                        for (i, (path, _)) in self.templates.iter().enumerate() {
+                            if ui.selectable_value(&mut self.selected, i, path).clicked() {
+                                self.name_field_needs_focus = true;
+                            }
+                        }
+                    });
+
+                ui.end_row();
+
+                ui.label("Name:");
+
+                // The new file's name. It gets the template's extension if it doesn't have one.
+                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                    ui.set_max_width(300.0);
+
+                    let out = egui::TextEdit::singleline(&mut self.new_name)
+                        .margin(egui::vec2(8.0, 8.0))
+                        .hint_text("Name...")
+                        .show(ui);
+
+                    if out.response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        if self.new_name.is_empty() {
+                            self.err_msg = Some("File names cannot be empty!".to_string());
+                        } else {
+                            let (template_path, template) = &self.templates[self.selected];
+                            let mut name = self.new_name.clone();
+                            if !name.contains('.') {
+                                if let Some((_, ext)) = template_path.rsplit_once('.') {
+                                    name = format!("{name}.{ext}");
+                                }
+                            }
+                            maybe_submission = Some(NewFromTemplateParams {
+                                template: *template,
+                                parent_path: self.parent_path.clone(),
+                                name,
+                            });
+                        }
+                    }
+
+                    if self.name_field_needs_focus {
+                        out.response.request_focus();
+                        self.name_field_needs_focus = false;
+                    }
+                });
+
+                ui.end_row();
+            });
+
+        ui.add_space(10.0);
+
+        if let Some(msg) = &self.err_msg {
+            ui.label(egui::RichText::new(msg).color(egui::Color32::RED));
+            ui.add_space(10.0);
+        }
+
+        maybe_submission
+    }
+}
@@ -0,0 +1,132 @@
+use std::{collections::HashSet, sync::mpsc, thread};
+
+use eframe::egui;
+
+use crate::{model::DocType, theme::Icon};
+
+enum PinnedUpdate {
+    Error(String),
+    Done(Vec<lb::File>),
+}
+
+/// The files the user pinned, which are synced across their devices
+pub struct Pinned {
+    update_tx: mpsc::Sender<PinnedUpdate>,
+    update_rx: mpsc::Receiver<PinnedUpdate>,
+    files: Vec<lb::File>,
+    err_msg: Option<String>,
+}
+
+impl Pinned {
+    pub fn new(core: &lb::Core) -> Self {
+        let (update_tx, update_rx) = mpsc::channel();
+        Self::calc(core, &update_tx);
+        Self { update_tx, update_rx, files: vec![], err_msg: None }
+    }
+
+    pub fn recalc_and_redraw(&mut self, ctx: &egui::Context, core: &lb::Core) {
+        Self::calc(core, &self.update_tx);
+        ctx.request_repaint();
+    }
+
+    fn calc(core: &lb::Core, update_tx: &mpsc::Sender<PinnedUpdate>) {
+        let core = core.clone();
+        let update_tx = update_tx.clone();
+
+        thread::spawn(move || {
+            let update = match core.list_pinned() {
+                Ok(files) => PinnedUpdate::Done(files),
+                Err(err) => PinnedUpdate::Error(format!("{:?}", err)),
+            };
+            update_tx.send(update).unwrap();
+        });
+    }
+
+    /// Pins or unpins a file in the background, then refreshes the list
+    pub fn set_pinned(&self, ctx: &egui::Context, core: &lb::Core, id: lb::Uuid, pinned: bool) {
+        let ctx = ctx.clone();
+        let core = core.clone();
+        let update_tx = self.update_tx.clone();
+
+        thread::spawn(move || {
+            let result = if pinned {
+                core.pin_file(id).map_err(|err| format!("{:?}", err.kind))
+            } else {
+                core.unpin_file(id).map_err(|err| format!("{:?}", err))
+            };
+            match result {
+                Ok(()) => Self::calc(&core, &update_tx),
+                Err(err) => update_tx.send(PinnedUpdate::Error(err)).unwrap(),
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn ids(&self) -> HashSet<lb::Uuid> {
+        self.files.iter().map(|file| file.id).collect()
+    }
+
+    /// Returns a pinned file that was clicked, to be opened or revealed
+    pub fn show(
+        &mut self, ui: &mut egui::Ui, ctx: &egui::Context, core: &lb::Core,
+    ) -> Option<lb::File> {
+        while let Ok(update) = self.update_rx.try_recv() {
+            match update {
+                PinnedUpdate::Error(err) => self.err_msg = Some(err),
+                PinnedUpdate::Done(files) => {
+                    self.files = files;
+                    self.err_msg = None;
+                }
+            }
+        }
+
+        if self.files.is_empty() && self.err_msg.is_none() {
+            return None;
+        }
+
+        egui::CollapsingHeader::new("Pinned")
+            .default_open(true)
+            .show(ui, |ui| {
+                if let Some(err_msg) = &self.err_msg {
+                    ui.label(egui::RichText::new(err_msg).color(ui.visuals().error_fg_color));
+                    return None;
+                }
+
+                let mut clicked = None;
+                for file in &self.files {
+                    let response = ui
+                        .horizontal(|ui| {
+                            let icon = match file.file_type {
+                                lb::FileType::Folder => Icon::FOLDER,
+                                _ => DocType::from_name(&file.name).to_icon(),
+                            };
+                            icon.show(ui);
+                            ui.label(&file.name);
+                        })
+                        .response;
+
+                    let response = ui.interact(
+                        response.rect,
+                        egui::Id::new("pinned").with(file.id),
+                        egui::Sense::click(),
+                    );
+                    if response.hovered() {
+                        ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::PointingHand);
+                    }
+                    if response.clicked() {
+                        clicked = Some(file.clone());
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Unpin").clicked() {
+                            self.set_pinned(ctx, core, file.id, false);
+                            ui.close_menu();
+                        }
+                    });
+                    ui.add_space(5.0);
+                }
+                clicked
+            })
+            .body_returned
+            .flatten()
+    }
+}
@@ -0,0 +1,132 @@
+use std::{sync::mpsc, thread};
+
+use eframe::egui;
+
+enum SharingUpdate {
+    Error(lb::Uuid, String),
+    Done(lb::Uuid, Vec<Access>),
+}
+
+/// Who can access the open file and which share grants them that access
+pub struct Sharing {
+    update_tx: mpsc::Sender<SharingUpdate>,
+    update_rx: mpsc::Receiver<SharingUpdate>,
+    id: Option<lb::Uuid>,
+    access: Vec<Access>,
+    err_msg: Option<String>,
+}
+
+struct Access {
+    username: String,
+    mode: String,
+    /// the path of the ancestor whose share grants the access, if it isn't the file itself
+    source: Option<String>,
+}
+
+impl Sharing {
+    pub fn new() -> Self {
+        let (update_tx, update_rx) = mpsc::channel();
+        Self { update_tx, update_rx, id: None, access: vec![], err_msg: None }
+    }
+
+    /// Shows who can access `id` (or no one), calculating it if it isn't already shown.
+    pub fn set_file(&mut self, ctx: &egui::Context, core: &lb::Core, id: Option<lb::Uuid>) {
+        if self.id != id {
+            self.id = id;
+            self.access.clear();
+            self.err_msg = None;
+            self.recalc_and_redraw(ctx, core);
+        }
+    }
+
+    pub fn recalc_and_redraw(&mut self, ctx: &egui::Context, core: &lb::Core) {
+        if let Some(id) = self.id {
+            Self::calc(ctx, core, id, &self.update_tx);
+        }
+    }
+
+    fn calc(
+        ctx: &egui::Context, core: &lb::Core, id: lb::Uuid, update_tx: &mpsc::Sender<SharingUpdate>,
+    ) {
+        let ctx = ctx.clone();
+        let core = core.clone();
+        let update_tx = update_tx.clone();
+
+        thread::spawn(move || {
+            let update = match core.effective_permissions(id) {
+                Ok(permissions) => SharingUpdate::Done(
+                    id,
+                    permissions
+                        .into_iter()
+                        .map(|permission| {
+                            let mode = match permission.granted_by {
+                                None => "Owner".to_string(),
+                                Some(_) => permission.mode.to_string(),
+                            };
+                            let source = permission
+                                .granted_by
+                                .filter(|&granted_by| granted_by != id)
+                                .map(|granted_by| {
+                                    core.get_path_by_id(granted_by)
+                                        .unwrap_or_else(|_| granted_by.to_string())
+                                });
+                            Access { username: permission.username, mode, source }
+                        })
+                        .collect(),
+                ),
+                Err(err) => SharingUpdate::Error(id, format!("{:?}", err.kind)),
+            };
+            update_tx.send(update).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn process_updates(&mut self) {
+        while let Ok(update) = self.update_rx.try_recv() {
+            match update {
+                // updates for files that are no longer open are stale
+                SharingUpdate::Error(id, err) if Some(id) == self.id => self.err_msg = Some(err),
+                SharingUpdate::Done(id, access) if Some(id) == self.id => {
+                    self.access = access;
+                    self.err_msg = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the open file isn't shared with anyone, in which case the inspector is hidden.
+    pub fn is_empty(&self) -> bool {
+        self.access.len() <= 1 && self.err_msg.is_none()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(15.0);
+        ui.heading("Sharing");
+        ui.add_space(10.0);
+
+        if let Some(err_msg) = &self.err_msg {
+            ui.label(egui::RichText::new(err_msg).color(ui.visuals().error_fg_color));
+            return;
+        }
+
+        for access in &self.access {
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    ui.label(&access.username);
+                    if let Some(source) = &access.source {
+                        ui.label(
+                            egui::RichText::new(format!("shared via {}", source))
+                                .size(13.0)
+                                .color(egui::Color32::GRAY),
+                        );
+                    }
+                });
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                    ui.label(egui::RichText::new(&access.mode).color(egui::Color32::GRAY));
+                });
+            });
+            ui.add_space(10.0);
+        }
+    }
+}
@@ -47,8 +47,28 @@ impl super::AccountScreen {
                     self.sync.status = Ok("just now".to_owned());
                     self.sync.phase = SyncPhase::IdleGood;
                     if let Ok(work) = result {
+                        if !work.conflicts.is_empty() {
+                            self.toasts
+                                .warning(match work.conflicts.len() {
+                                    1 => {
+                                        "A document was merged with conflicting changes".to_string()
+                                    }
+                                    n => format!(
+                                        "{} documents were merged with conflicting changes",
+                                        n
+                                    ),
+                                })
+                                .set_closable(false)
+                                .set_show_progress_bar(false)
+                                .set_duration(Some(std::time::Duration::from_secs(7)));
+                        }
                         self.refresh_tree_and_workspace(ctx, work);
                         self.suggested.recalc_and_redraw(ctx, &self.core);
+                        self.backlinks.recalc_and_redraw(ctx, &self.core);
+                        self.sharing.recalc_and_redraw(ctx, &self.core);
+                        self.tasks.recalc_and_redraw(ctx, &self.core);
+                        self.pinned.recalc_and_redraw(ctx, &self.core);
+                        self.load_synced_settings();
                     }
                     self.refresh_sync_status(ctx);
 
@@ -0,0 +1,186 @@
+use eframe::egui;
+use egui::text::LayoutJob;
+use lb::{DiffLine, DiffLineKind, DocumentDiff};
+
+/// Shows the changes to a document since it was last synced, with the synced version on the left
+/// and the current version on the right.
+pub struct DiffViewer {
+    diff: DocumentDiff,
+    rows: Vec<DiffRow>,
+}
+
+enum DiffRow {
+    /// separates hunks
+    Gap,
+    Lines {
+        old: Option<(usize, DiffLine)>,
+        new: Option<(usize, DiffLine)>,
+    },
+}
+
+impl DiffViewer {
+    pub fn boxed(diff: DocumentDiff) -> Box<Self> {
+        let rows = match &diff {
+            DocumentDiff::Text { hunks } => side_by_side(hunks),
+            _ => Vec::new(),
+        };
+        Box::new(Self { diff, rows })
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if self.diff.is_empty() {
+            ui.label("No changes since the last sync.");
+            return;
+        }
+
+        match &self.diff {
+            DocumentDiff::Text { .. } => self.show_text(ui),
+            DocumentDiff::Drawing(d) => {
+                ui.label(format!(
+                    "{} strokes added, {} strokes removed, {} strokes unchanged",
+                    d.strokes_added, d.strokes_removed, d.strokes_unchanged
+                ));
+            }
+            DocumentDiff::Other { old_size, new_size, .. } => {
+                ui.label(format!(
+                    "Content changed ({} → {})",
+                    lb::bytes_to_human(*old_size as u64),
+                    lb::bytes_to_human(*new_size as u64)
+                ));
+            }
+        }
+    }
+
+    fn show_text(&self, ui: &mut egui::Ui) {
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        let text_color = ui.visuals().text_color();
+        let weak_color = ui.visuals().weak_text_color();
+        let (deleted_bg, inserted_bg) = if ui.visuals().dark_mode {
+            (egui::Color32::from_rgb(70, 30, 30), egui::Color32::from_rgb(30, 60, 35))
+        } else {
+            (egui::Color32::from_rgb(255, 225, 225), egui::Color32::from_rgb(220, 250, 225))
+        };
+        let (deleted_word_bg, inserted_word_bg) = if ui.visuals().dark_mode {
+            (egui::Color32::from_rgb(130, 45, 45), egui::Color32::from_rgb(40, 110, 55))
+        } else {
+            (egui::Color32::from_rgb(255, 175, 175), egui::Color32::from_rgb(165, 235, 180))
+        };
+
+        let cell = |line: &Option<(usize, DiffLine)>| -> (String, LayoutJob) {
+            let mut job = LayoutJob::default();
+            let (line_number, line) = match line {
+                Some((n, line)) => (n.to_string(), line),
+                None => return (String::new(), job),
+            };
+            let (bg, word_bg) = match line.kind {
+                DiffLineKind::Context => (egui::Color32::TRANSPARENT, egui::Color32::TRANSPARENT),
+                DiffLineKind::Deleted => (deleted_bg, deleted_word_bg),
+                DiffLineKind::Inserted => (inserted_bg, inserted_word_bg),
+            };
+            let format = |background| egui::TextFormat {
+                font_id: font.clone(),
+                color: text_color,
+                background,
+                ..Default::default()
+            };
+            if line.words.is_empty() {
+                job.append(&line.text, 0.0, format(bg));
+            } else {
+                for word in &line.words {
+                    job.append(&word.text, 0.0, format(if word.changed { word_bg } else { bg }));
+                }
+            }
+            (line_number, job)
+        };
+
+        egui::ScrollArea::both().id_source("diff").show(ui, |ui| {
+            egui::Frame::none()
+                .inner_margin(egui::vec2(7.0, 7.0))
+                .show(ui, |ui| {
+                    egui::Grid::new("diff_grid")
+                        .num_columns(4)
+                        .spacing(egui::vec2(10.0, 2.0))
+                        .show(ui, |ui| {
+                            for row in &self.rows {
+                                match row {
+                                    DiffRow::Gap => {
+                                        for _ in 0..4 {
+                                            ui.label(
+                                                egui::RichText::new("⋯")
+                                                    .font(font.clone())
+                                                    .color(weak_color),
+                                            );
+                                        }
+                                    }
+                                    DiffRow::Lines { old, new } => {
+                                        for side in [old, new] {
+                                            let (line_number, job) = cell(side);
+                                            ui.label(
+                                                egui::RichText::new(line_number)
+                                                    .font(font.clone())
+                                                    .color(weak_color),
+                                            );
+                                            ui.label(job);
+                                        }
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+    }
+}
+
+/// Lays out hunks in two columns, aligning each deleted line with the inserted line that replaces
+/// it.
+fn side_by_side(hunks: &[lb::DiffHunk]) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    for (i, hunk) in hunks.iter().enumerate() {
+        if i > 0 {
+            rows.push(DiffRow::Gap);
+        }
+
+        let (mut old_n, mut new_n) = (hunk.old_start, hunk.new_start);
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        for line in &hunk.lines {
+            match line.kind {
+                DiffLineKind::Deleted => {
+                    deleted.push((old_n, line.clone()));
+                    old_n += 1;
+                }
+                DiffLineKind::Inserted => {
+                    inserted.push((new_n, line.clone()));
+                    new_n += 1;
+                }
+                DiffLineKind::Context => {
+                    flush(&mut rows, &mut deleted, &mut inserted);
+                    rows.push(DiffRow::Lines {
+                        old: Some((old_n, line.clone())),
+                        new: Some((new_n, line.clone())),
+                    });
+                    old_n += 1;
+                    new_n += 1;
+                }
+            }
+        }
+        flush(&mut rows, &mut deleted, &mut inserted);
+    }
+    rows
+}
+
+fn flush(
+    rows: &mut Vec<DiffRow>, deleted: &mut Vec<(usize, DiffLine)>,
+    inserted: &mut Vec<(usize, DiffLine)>,
+) {
+    let mut deleted = deleted.drain(..);
+    let mut inserted = inserted.drain(..);
+    loop {
+        let (old, new) = (deleted.next(), inserted.next());
+        if old.is_none() && new.is_none() {
+            break;
+        }
+        rows.push(DiffRow::Lines { old, new });
+    }
+}
@@ -17,11 +17,19 @@ pub struct Markdown {
 
 impl Markdown {
     pub fn boxed(
-        core: lb::Core, bytes: &[u8], toolbar_visibility: &ToolBarVisibility,
+        core: lb::Core, id: lb::Uuid, bytes: &[u8], toolbar_visibility: &ToolBarVisibility,
         update_tx: Sender<AccountUpdate>, needs_name: bool,
     ) -> Box<Self> {
         let content = String::from_utf8_lossy(bytes).to_string();
+        let collaborative = is_shared(&core, id);
+        let conflicted = core
+            .get_conflicts()
+            .map(|conflicts| conflicts.iter().any(|conflict| conflict.id == id))
+            .unwrap_or_default();
         let mut editor = Editor::new(core);
+        editor.file_id = Some(id);
+        editor.collaborative = collaborative;
+        editor.conflicted = conflicted;
         editor.set_text(content);
 
         let toolbar = ToolBar::new(toolbar_visibility);
@@ -49,3 +57,18 @@ impl Markdown {
         .inner
     }
 }
+
+/// whether a file or one of its ancestors is shared, in which case others may be editing it too
+fn is_shared(core: &lb::Core, id: lb::Uuid) -> bool {
+    let mut id = id;
+    while let Ok(file) = core.get_file_by_id(id) {
+        if !file.shares.is_empty() {
+            return true;
+        }
+        if file.parent == file.id {
+            break;
+        }
+        id = file.parent;
+    }
+    false
+}
@@ -1,3 +1,4 @@
+mod diff_viewer;
 mod drawing;
 mod image_viewer;
 mod markdown;
@@ -5,6 +6,7 @@ mod pdf_viewer;
 mod plain_text;
 mod svg_editor;
 
+pub use diff_viewer::DiffViewer;
 pub use drawing::Drawing;
 pub use image_viewer::ImageViewer;
 pub use markdown::Markdown;
@@ -58,9 +60,15 @@ impl Tab {
     pub fn is_dirty(&self) -> bool {
         self.last_changed > self.last_saved
     }
+
+    /// Diff tabs share their file's id but are read-only views next to the file's own tab.
+    pub fn is_diff(&self) -> bool {
+        matches!(self.content, Some(TabContent::Diff(_)))
+    }
 }
 
 pub enum TabContent {
+    Diff(Box<DiffViewer>),
     Drawing(Box<Drawing>),
     Image(Box<ImageViewer>),
     Markdown(Box<Markdown>),
@@ -0,0 +1,161 @@
+use std::{sync::mpsc, thread};
+
+use eframe::egui;
+
+enum TasksUpdate {
+    Error(String),
+    Done(Vec<TaskItem>),
+    Toggled(lb::Uuid),
+}
+
+/// The open tasks in all markdown documents
+pub struct Tasks {
+    update_tx: mpsc::Sender<TasksUpdate>,
+    update_rx: mpsc::Receiver<TasksUpdate>,
+    tasks: Vec<TaskItem>,
+    err_msg: Option<String>,
+}
+
+struct TaskItem {
+    task: lb::Task,
+    name: String,
+}
+
+impl Tasks {
+    pub fn new(core: &lb::Core) -> Self {
+        let (update_tx, update_rx) = mpsc::channel();
+        Self::calc(core, &update_tx);
+        Self { update_tx, update_rx, tasks: vec![], err_msg: None }
+    }
+
+    pub fn recalc_and_redraw(&mut self, ctx: &egui::Context, core: &lb::Core) {
+        Self::calc(core, &self.update_tx);
+        ctx.request_repaint();
+    }
+
+    fn calc(core: &lb::Core, update_tx: &mpsc::Sender<TasksUpdate>) {
+        let core = core.clone();
+        let update_tx = update_tx.clone();
+
+        thread::spawn(move || {
+            let filter = lb::TaskFilter { checked: Some(false), ..Default::default() };
+            let update = match core.list_tasks(filter) {
+                Ok(tasks) => TasksUpdate::Done(
+                    tasks
+                        .into_iter()
+                        .filter_map(|task| {
+                            let name = core.get_file_by_id(task.id).ok()?.name;
+                            Some(TaskItem { task, name })
+                        })
+                        .collect(),
+                ),
+                Err(err) => TasksUpdate::Error(format!("{:?}", err)),
+            };
+            update_tx.send(update).unwrap();
+        });
+    }
+
+    /// Checks or unchecks a task in the background, according to `task.checked`. Checked tasks stay
+    /// listed until the next recalc, so they can be unchecked.
+    fn set_checked(
+        ctx: &egui::Context, core: &lb::Core, task: &lb::Task, tx: &mpsc::Sender<TasksUpdate>,
+    ) {
+        let ctx = ctx.clone();
+        let core = core.clone();
+        let task = task.clone();
+        let update_tx = tx.clone();
+
+        thread::spawn(move || {
+            let update = match core.set_task_state(&task, task.checked) {
+                Ok(()) => TasksUpdate::Toggled(task.id),
+                Err(err) => TasksUpdate::Error(format!("{:?}", err.kind)),
+            };
+            update_tx.send(update).unwrap();
+            ctx.request_repaint();
+        });
+    }
+
+    /// Returns the ids of documents whose tasks were checked or unchecked, whose open tabs should be
+    /// reloaded
+    pub fn process_updates(&mut self) -> Vec<lb::Uuid> {
+        let mut toggled = Vec::new();
+        while let Ok(update) = self.update_rx.try_recv() {
+            match update {
+                TasksUpdate::Error(err) => self.err_msg = Some(err),
+                TasksUpdate::Done(tasks) => {
+                    self.tasks = tasks;
+                    self.err_msg = None;
+                }
+                TasksUpdate::Toggled(id) => toggled.push(id),
+            }
+        }
+        toggled
+    }
+
+    /// Returns the id of a document whose task was clicked, to be opened
+    pub fn show(
+        &mut self, ui: &mut egui::Ui, ctx: &egui::Context, core: &lb::Core,
+    ) -> Option<lb::Uuid> {
+        if self.tasks.is_empty() && self.err_msg.is_none() {
+            return None;
+        }
+
+        egui::CollapsingHeader::new(format!("Tasks ({})", self.tasks.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                if let Some(err_msg) = &self.err_msg {
+                    ui.label(egui::RichText::new(err_msg).color(ui.visuals().error_fg_color));
+                    return None;
+                }
+
+                let mut clicked = None;
+                egui::ScrollArea::vertical()
+                    .id_source("tasks")
+                    .max_height(250.0)
+                    .show(ui, |ui| {
+                        for item in &mut self.tasks {
+                            ui.horizontal(|ui| {
+                                if ui.checkbox(&mut item.task.checked, "").changed() {
+                                    Self::set_checked(ctx, core, &item.task, &self.update_tx);
+                                }
+
+                                let response = ui
+                                    .vertical(|ui| {
+                                        ui.label(&item.task.text);
+                                        let mut details = item.name.clone();
+                                        if let Some(due) = &item.task.due {
+                                            details = format!("{details} · due {due}");
+                                        }
+                                        ui.label(
+                                            egui::RichText::new(details)
+                                                .size(13.0)
+                                                .color(egui::Color32::GRAY),
+                                        );
+                                    })
+                                    .response;
+
+                                let response = ui.interact(
+                                    response.rect,
+                                    egui::Id::new("task")
+                                        .with(item.task.id)
+                                        .with(item.task.line),
+                                    egui::Sense::click(),
+                                );
+                                if response.hovered() {
+                                    ui.output_mut(|o| {
+                                        o.cursor_icon = egui::CursorIcon::PointingHand
+                                    });
+                                }
+                                if response.clicked() {
+                                    clicked = Some(item.task.id);
+                                }
+                            });
+                            ui.add_space(5.0);
+                        }
+                    });
+                clicked
+            })
+            .body_returned
+            .flatten()
+    }
+}
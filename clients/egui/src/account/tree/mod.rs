@@ -77,6 +77,10 @@ impl FileTree {
                 r.inner.new_folder_modal = Some(self.root.file.clone());
                 ui.close_menu();
             }
+            if ui.button("New from Template").clicked() {
+                r.inner.new_from_template_modal = Some(self.root.file.clone());
+                ui.close_menu();
+            }
         });
 
         if self.state.is_dragging() {
@@ -282,6 +282,11 @@ impl TreeNode {
             ui.close_menu();
         }
 
+        if ui.button("New from Template").clicked() {
+            node_resp.new_from_template_modal = Some(self.file.clone());
+            ui.close_menu();
+        }
+
         ui.separator();
 
         if ui.button("Rename").clicked() {
@@ -305,6 +310,22 @@ impl TreeNode {
             ui.close_menu();
         }
 
+        if ui.button("Revert Changes").clicked() {
+            node_resp.revert_request = true;
+            ui.close_menu();
+        }
+
+        if self.file.is_document() && ui.button("Show Changes").clicked() {
+            node_resp.diff_request = Some(self.file.id);
+            ui.close_menu();
+        }
+
+        let is_pinned = state.pinned.contains(&self.file.id);
+        if ui.button(if is_pinned { "Unpin" } else { "Pin" }).clicked() {
+            node_resp.pin_request = Some((self.file.id, !is_pinned));
+            ui.close_menu();
+        }
+
         ui.separator();
 
         if ui.button("Export").clicked() {
@@ -9,9 +9,13 @@ pub struct NodeResponse {
     pub new_drawing: Option<bool>,
     pub export_file: Option<Result<(lb::File, PathBuf), LbError>>,
     pub new_folder_modal: Option<lb::File>,
+    pub new_from_template_modal: Option<lb::File>,
     pub create_share_modal: Option<lb::File>,
     pub rename_request: Option<(lb::Uuid, String)>,
     pub delete_request: bool,
+    pub revert_request: bool,
+    pub diff_request: Option<lb::Uuid>,
+    pub pin_request: Option<(lb::Uuid, bool)>,
     pub dropped_on: Option<lb::Uuid>,
 }
 
@@ -21,11 +25,17 @@ impl NodeResponse {
         this.new_file = this.new_file.or(other.new_file);
         this.new_drawing = this.new_drawing.or(other.new_drawing);
         this.new_folder_modal = this.new_folder_modal.or(other.new_folder_modal);
+        this.new_from_template_modal = this
+            .new_from_template_modal
+            .or(other.new_from_template_modal);
         this.create_share_modal = this.create_share_modal.or(other.create_share_modal);
         this.export_file = this.export_file.or(other.export_file);
         this.open_requests.extend(other.open_requests);
         this.rename_request = this.rename_request.or(other.rename_request);
         this.delete_request = this.delete_request || other.delete_request;
+        this.revert_request = this.revert_request || other.revert_request;
+        this.diff_request = this.diff_request.or(other.diff_request);
+        this.pin_request = this.pin_request.or(other.pin_request);
         this.dropped_on = this.dropped_on.or(other.dropped_on);
         this
     }
@@ -11,6 +11,7 @@ pub struct TreeState {
     pub max_node_width: f32,
     pub selected: HashSet<lb::Uuid>,
     pub expanded: HashSet<lb::Uuid>,
+    pub pinned: HashSet<lb::Uuid>,
     pub renaming: NodeRenamingState,
     pub request_scroll: bool,
     pub dnd: TreeDragAndDropState,
@@ -26,6 +27,7 @@ impl Default for TreeState {
             max_node_width: 0.0,
             selected: HashSet::new(),
             expanded: HashSet::new(),
+            pinned: HashSet::new(),
             dnd: TreeDragAndDropState::default(),
             renaming: NodeRenamingState::default(),
             request_scroll: false,
@@ -43,7 +43,9 @@ impl Workspace {
     }
 
     pub fn get_mut_tab_by_id(&mut self, id: lb::Uuid) -> Option<&mut Tab> {
-        self.tabs.iter_mut().find(|tab| tab.id == id)
+        self.tabs
+            .iter_mut()
+            .find(|tab| tab.id == id && !tab.is_diff())
     }
 
     pub fn is_empty(&self) -> bool {
@@ -56,7 +58,7 @@ impl Workspace {
 
     pub fn goto_tab_id(&mut self, id: lb::Uuid) -> bool {
         for (i, tab) in self.tabs.iter().enumerate() {
-            if tab.id == id {
+            if tab.id == id && !tab.is_diff() {
                 self.active_tab = i;
                 return true;
             }
@@ -305,6 +307,10 @@ impl super::AccountScreen {
                             match resp {
                                 TabLabelResponse::Clicked => {
                                     if self.workspace.active_tab == i {
+                                        if self.workspace.tabs[i].is_diff() {
+                                            continue;
+                                        }
+
                                         // we should rename the file.
 
                                         let active_name = self.workspace.tabs[i].name.clone();
@@ -388,6 +394,7 @@ impl super::AccountScreen {
                         };
                     } else if let Some(content) = &mut tab.content {
                         match content {
+                            TabContent::Diff(diff) => diff.show(ui),
                             TabContent::Drawing(draw) => draw.show(ui),
                             TabContent::Markdown(md) => {
                                 let resp = md.show(ui);
@@ -53,7 +53,7 @@ impl Lockbook {
 
         theme::init(&settings, ctx);
 
-        let splash = SplashScreen::new(settings, maybe_settings_err);
+        let mut splash = SplashScreen::new(settings, maybe_settings_err);
         splash.start_loading_core(ctx);
         Lockbook::Splash(splash)
     }
@@ -66,16 +66,22 @@ impl Lockbook {
             // request a new frame.
             Self::Splash(screen) => {
                 if let Some(handoff) = screen.update(ctx) {
-                    let SplashHandOff { settings, core, maybe_acct_data } = handoff;
+                    let SplashHandOff { settings, accounts, core, maybe_acct_data } = handoff;
 
                     *self = match maybe_acct_data {
                         Some(acct_data) => {
                             let is_new_user = false;
-                            let acct_scr =
-                                AccountScreen::new(settings, core, acct_data, ctx, is_new_user);
+                            let acct_scr = AccountScreen::new(
+                                settings,
+                                accounts,
+                                core,
+                                acct_data,
+                                ctx,
+                                is_new_user,
+                            );
                             Self::Account(acct_scr)
                         }
-                        None => Self::Onboard(OnboardScreen::new(settings, core)),
+                        None => Self::Onboard(OnboardScreen::new(settings, accounts, core)),
                     };
 
                     ctx.request_repaint();
@@ -85,20 +91,30 @@ impl Lockbook {
             // Account screen.
             Self::Onboard(screen) => {
                 if let Some(handoff) = screen.update(ctx) {
-                    let OnboardHandOff { settings, core, acct_data } = handoff;
+                    let OnboardHandOff { settings, accounts, core, acct_data } = handoff;
 
                     let is_new_user = true;
-                    let acct_scr = AccountScreen::new(settings, core, acct_data, ctx, is_new_user);
+                    let acct_scr =
+                        AccountScreen::new(settings, accounts, core, acct_data, ctx, is_new_user);
                     *self = Self::Account(acct_scr);
 
                     ctx.request_repaint();
                 }
             }
-            // On the account screen, we're just waiting for it to gracefully shutdown.
+            // On the account screen, we're just waiting for it to gracefully shutdown, either to
+            // close or to switch to another account.
             Self::Account(screen) => {
                 screen.update(ctx, &mut output);
                 if screen.is_shutdown() {
-                    output.close = true;
+                    match screen.take_switch() {
+                        Some((settings, accounts, load)) => {
+                            let mut splash = SplashScreen::switching(settings, accounts, load);
+                            splash.start_loading_core(ctx);
+                            *self = Self::Splash(splash);
+                            ctx.request_repaint();
+                        }
+                        None => output.close = true,
+                    }
                 }
             }
         }
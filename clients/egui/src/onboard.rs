@@ -10,6 +10,7 @@ use crate::widgets::ButtonGroup;
 
 pub struct OnboardHandOff {
     pub settings: Arc<RwLock<Settings>>,
+    pub accounts: lb::AccountManager,
     pub core: lb::Core,
     pub acct_data: AccountScreenInitData,
 }
@@ -29,6 +30,7 @@ enum State {
 
 pub struct OnboardScreen {
     settings: Arc<RwLock<Settings>>,
+    accounts: lb::AccountManager,
     core: lb::Core,
 
     update_tx: mpsc::Sender<Update>,
@@ -47,11 +49,14 @@ pub struct OnboardScreen {
 }
 
 impl OnboardScreen {
-    pub fn new(settings: Arc<RwLock<Settings>>, core: lb::Core) -> Self {
+    pub fn new(
+        settings: Arc<RwLock<Settings>>, accounts: lb::AccountManager, core: lb::Core,
+    ) -> Self {
         let (update_tx, update_rx) = mpsc::channel();
 
         Self {
             settings,
+            accounts,
             core,
             update_tx,
             update_rx,
@@ -75,6 +80,7 @@ impl OnboardScreen {
                     Ok(acct_data) => {
                         resp = Some(OnboardHandOff {
                             settings: self.settings.clone(),
+                            accounts: self.accounts.clone(),
                             core: self.core.clone(),
                             acct_data,
                         });
@@ -107,6 +113,7 @@ impl OnboardScreen {
                     Ok(acct_data) => {
                         resp = Some(OnboardHandOff {
                             settings: self.settings.clone(),
+                            accounts: self.accounts.clone(),
                             core: self.core.clone(),
                             acct_data,
                         });
@@ -218,6 +225,7 @@ impl OnboardScreen {
     fn create_account(&mut self, ctx: &egui::Context) {
         self.state = State::Busy(Route::Create);
 
+        let accounts = self.accounts.clone();
         let core = self.core.clone();
         let uname = self.uname.clone();
         let update_tx = self.update_tx.clone();
@@ -229,6 +237,7 @@ impl OnboardScreen {
 
             let result = core
                 .create_account(&uname, &api_url, true)
+                .and_then(|account| accounts.switch(&account.username))
                 .map_err(|err| format!("{:?}", err))
                 .and_then(|_| load_account_data(&core));
 
@@ -240,6 +249,7 @@ impl OnboardScreen {
     fn import_account(&mut self, ctx: &egui::Context) {
         self.state = State::Busy(Route::Import);
 
+        let accounts = self.accounts.clone();
         let core = self.core.clone();
         let key = self.acct_str.clone();
         let tx = self.update_tx.clone();
@@ -248,6 +258,7 @@ impl OnboardScreen {
         thread::spawn(move || {
             if let Err(err) = core
                 .import_account(&key)
+                .and_then(|account| accounts.switch(&account.username))
                 .map_err(|err| format!("{:?}", err))
             {
                 tx.send(Update::AccountImported(Some(err))).unwrap();
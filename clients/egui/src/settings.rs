@@ -4,6 +4,9 @@ use std::io;
 use crate::util::data_dir;
 use crate::widgets::ToolBarVisibility;
 
+/// The namespace of the settings synced with the user's account; see `SyncedSettings`.
+pub const SETTINGS_NAMESPACE: &str = "egui";
+
 #[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 pub struct Settings {
@@ -41,6 +44,49 @@ impl Settings {
         let content = serde_json::to_string(&self).ok().unwrap();
         fs::write(&self.path, content)
     }
+
+    pub fn synced(&self) -> SyncedSettings {
+        SyncedSettings {
+            theme_mode: self.theme_mode.clone(),
+            theme_color: self.theme_color,
+            toolbar_visibility: self.toolbar_visibility,
+            open_new_files: self.open_new_files,
+            auto_save: self.auto_save,
+            auto_sync: self.auto_sync,
+            sidebar_usage: self.sidebar_usage,
+        }
+    }
+
+    pub fn apply_synced(&mut self, synced: SyncedSettings) {
+        self.theme_mode = synced.theme_mode;
+        self.theme_color = synced.theme_color;
+        self.toolbar_visibility = synced.toolbar_visibility;
+        self.open_new_files = synced.open_new_files;
+        self.auto_save = synced.auto_save;
+        self.auto_sync = synced.auto_sync;
+        self.sidebar_usage = synced.sidebar_usage;
+    }
+}
+
+/// The settings that follow the user's account to their other devices. The rest, like whether
+/// the window is maximized or the side panel is hidden, belong to this device and are only saved
+/// in the local settings file, which also keeps the synced settings for before the account loads.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SyncedSettings {
+    pub theme_mode: ThemeMode,
+    pub theme_color: lb::ColorAlias,
+    pub toolbar_visibility: ToolBarVisibility,
+    pub open_new_files: bool,
+    pub auto_save: bool,
+    pub auto_sync: bool,
+    pub sidebar_usage: bool,
+}
+
+impl Default for SyncedSettings {
+    fn default() -> Self {
+        Settings::default().synced()
+    }
 }
 
 impl Default for Settings {
@@ -8,18 +8,29 @@ use crate::util::data_dir;
 
 pub struct SplashHandOff {
     pub settings: Arc<RwLock<Settings>>,
+    pub accounts: lb::AccountManager,
     pub core: lb::Core,
     pub maybe_acct_data: Option<AccountScreenInitData>,
 }
 
+/// Which of the accounts on this device to load
+pub enum LoadAccount {
+    Current,
+    Switch(String),
+    /// a new account, which the user creates or imports next
+    New,
+}
+
 enum SplashUpdate {
     Status(String),
     Error(String),
-    Done((lb::Core, Option<AccountScreenInitData>)),
+    Done((lb::AccountManager, lb::Core, Option<AccountScreenInitData>)),
 }
 
 pub struct SplashScreen {
     settings: Arc<RwLock<Settings>>,
+    accounts: Option<lb::AccountManager>,
+    load: Option<LoadAccount>,
 
     update_tx: mpsc::Sender<SplashUpdate>,
     update_rx: mpsc::Receiver<SplashUpdate>,
@@ -32,35 +43,70 @@ impl SplashScreen {
     pub fn new(settings: Arc<RwLock<Settings>>, maybe_error: Option<String>) -> Self {
         let (update_tx, update_rx) = mpsc::channel();
 
-        Self { settings, update_tx, update_rx, maybe_error, status: None }
+        Self {
+            settings,
+            accounts: None,
+            load: Some(LoadAccount::Current),
+            update_tx,
+            update_rx,
+            maybe_error,
+            status: None,
+        }
     }
 
-    pub fn start_loading_core(&self, ctx: &egui::Context) {
+    /// Loads another account after the user switched from the one that was open
+    pub fn switching(
+        settings: Arc<RwLock<Settings>>, accounts: lb::AccountManager, load: LoadAccount,
+    ) -> Self {
+        let (update_tx, update_rx) = mpsc::channel();
+
+        Self {
+            settings,
+            accounts: Some(accounts),
+            load: Some(load),
+            update_tx,
+            update_rx,
+            maybe_error: None,
+            status: None,
+        }
+    }
+
+    pub fn start_loading_core(&mut self, ctx: &egui::Context) {
         if self.maybe_error.is_some() {
             return;
         }
 
         let ctx = ctx.clone();
         let tx = self.update_tx.clone();
+        let accounts = self.accounts.take();
+        let load = self.load.take().unwrap_or(LoadAccount::Current);
 
         std::thread::spawn(move || {
-            let writeable_path = match data_dir() {
-                Ok(dir) => format!("{}/egui", dir),
+            tx.send(SplashUpdate::Status("Loading core...".to_string()))
+                .unwrap();
+
+            let accounts = match accounts.map(Ok).unwrap_or_else(init_accounts) {
+                Ok(accounts) => accounts,
                 Err(err) => {
                     tx.send(SplashUpdate::Error(err)).unwrap();
+                    ctx.request_repaint();
                     return;
                 }
             };
 
-            let cfg = lb::Config { logs: true, colored_logs: true, writeable_path };
-
-            tx.send(SplashUpdate::Status("Loading core...".to_string()))
-                .unwrap();
-
-            let core = match lb::Core::init(&cfg) {
+            let core = match load {
+                LoadAccount::Current => accounts.current().map_err(|err| format!("{:?}", err)),
+                LoadAccount::Switch(username) => accounts
+                    .switch(&username)
+                    .map_err(|err| format!("{:?}", err.kind)),
+                LoadAccount::New => accounts
+                    .new_account_core()
+                    .map_err(|err| format!("{:?}", err)),
+            };
+            let core = match core {
                 Ok(core) => core,
                 Err(err) => {
-                    tx.send(SplashUpdate::Error(format!("{:?}", err))).unwrap();
+                    tx.send(SplashUpdate::Error(err)).unwrap();
                     ctx.request_repaint();
                     return;
                 }
@@ -120,10 +166,10 @@ impl SplashScreen {
                 let acct_data =
                     AccountScreenInitData { sync_status, files, usage, has_pending_shares };
 
-                tx.send(SplashUpdate::Done((core, Some(acct_data))))
+                tx.send(SplashUpdate::Done((accounts, core, Some(acct_data))))
                     .unwrap();
             } else {
-                tx.send(SplashUpdate::Done((core, None))).unwrap();
+                tx.send(SplashUpdate::Done((accounts, core, None))).unwrap();
             }
 
             ctx.request_repaint();
@@ -138,10 +184,11 @@ impl SplashScreen {
             match update {
                 SplashUpdate::Status(msg) => self.status = Some(msg),
                 SplashUpdate::Error(msg) => self.maybe_error = Some(msg),
-                SplashUpdate::Done((core, maybe_acct_data)) => {
+                SplashUpdate::Done((accounts, core, maybe_acct_data)) => {
                     self.status = Some("Done.".to_string());
                     resp = Some(SplashHandOff {
                         settings: self.settings.clone(),
+                        accounts,
                         core,
                         maybe_acct_data,
                     });
@@ -165,6 +212,12 @@ impl SplashScreen {
     }
 }
 
+fn init_accounts() -> Result<lb::AccountManager, String> {
+    let writeable_path = format!("{}/egui", data_dir()?);
+    let cfg = lb::Config { logs: true, colored_logs: true, writeable_path };
+    lb::AccountManager::init(&cfg).map_err(|err| format!("{:?}", err))
+}
+
 fn is_signed_in(core: &lb::Core) -> Result<bool, String> {
     match core.get_account() {
         Ok(_acct) => Ok(true),
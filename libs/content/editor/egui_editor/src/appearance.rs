@@ -4,6 +4,7 @@ use egui::ecolor::Hsva;
 use egui::{Color32, Visuals};
 use pulldown_cmark::HeadingLevel;
 
+use crate::highlighting::TokenType;
 use crate::style::{BlockNodeType, InlineNodeType, MarkdownNodeType};
 
 // Apple colors: https://developer.apple.com/design/human-interface-guidelines/foundations/color/
@@ -63,12 +64,22 @@ pub struct Appearance {
     pub checkbox_bg: Option<ThemedColor>,
     pub heading: Option<ThemedColor>,
     pub heading_line: Option<ThemedColor>,
+    pub table_border: Option<ThemedColor>,
     pub code: Option<ThemedColor>,
+    pub code_keyword: Option<ThemedColor>,
+    pub code_type: Option<ThemedColor>,
+    pub code_function: Option<ThemedColor>,
+    pub code_string: Option<ThemedColor>,
+    pub code_number: Option<ThemedColor>,
+    pub code_comment: Option<ThemedColor>,
     pub bold: Option<ThemedColor>,
     pub italics: Option<ThemedColor>,
     pub strikethrough: Option<ThemedColor>,
     pub link: Option<ThemedColor>,
     pub syntax: Option<ThemedColor>,
+    pub conflict_bg: Option<ThemedColor>,
+    pub misspelling: Option<ThemedColor>,
+    pub find_match_bg: Option<ThemedColor>,
 
     // sizes
     pub bullet_radius: Option<f32>,
@@ -121,6 +132,60 @@ impl Appearance {
         self.selection_bg.unwrap_or(color).get(self.current_theme)
     }
 
+    pub fn conflict_bg(&self) -> Color32 {
+        let mut color = ORANGE;
+
+        color.light = {
+            let mut color_hsva = Hsva::from(color.light);
+            color_hsva.s /= 4.0;
+            Color32::from(color_hsva)
+        };
+        color.dark = {
+            let mut color_hsva = Hsva::from(color.dark);
+            color_hsva.a /= 5.0;
+            Color32::from(color_hsva)
+        };
+
+        self.conflict_bg.unwrap_or(color).get(self.current_theme)
+    }
+
+    /// background of text matching the find bar's query
+    pub fn find_match_bg(&self) -> Color32 {
+        let mut color = YELLOW;
+
+        color.light = {
+            let mut color_hsva = Hsva::from(color.light);
+            color_hsva.s /= 2.0;
+            Color32::from(color_hsva)
+        };
+        color.dark = {
+            let mut color_hsva = Hsva::from(color.dark);
+            color_hsva.a /= 4.0;
+            Color32::from(color_hsva)
+        };
+
+        self.find_match_bg.unwrap_or(color).get(self.current_theme)
+    }
+
+    /// color of another editor's cursor and name, which is the same for them in everyone's editor
+    pub fn collaborator(&self, username: &str) -> Color32 {
+        let colors = [RED, ORANGE, GREEN, TEAL, INDIGO, PURPLE, PINK, BROWN];
+        let hash = username
+            .bytes()
+            .fold(0usize, |hash, b| hash.wrapping_mul(31).wrapping_add(b as usize));
+        colors[hash % colors.len()].get(self.current_theme)
+    }
+
+    /// background of text selected by another editor
+    pub fn collaborator_selection_bg(&self, username: &str) -> Color32 {
+        self.collaborator(username).gamma_multiply(0.25)
+    }
+
+    /// color of the squiggly underline below misspelled words
+    pub fn misspelling(&self) -> Color32 {
+        self.misspelling.unwrap_or(RED).get(self.current_theme)
+    }
+
     pub fn checkbox_bg(&self) -> Color32 {
         self.checkbox_bg.unwrap_or(GRAY_4).get(self.current_theme)
     }
@@ -133,10 +198,27 @@ impl Appearance {
         self.heading_line.unwrap_or(GRAY).get(self.current_theme)
     }
 
+    pub fn table_border(&self) -> Color32 {
+        self.table_border.unwrap_or(GRAY_3).get(self.current_theme)
+    }
+
     pub fn code(&self) -> Color32 {
         self.code.unwrap_or(PINK).get(self.current_theme)
     }
 
+    /// color of a highlighted token in a code block
+    pub fn code_token(&self, token_type: TokenType) -> Color32 {
+        match token_type {
+            TokenType::Keyword => self.code_keyword.unwrap_or(PURPLE),
+            TokenType::Type => self.code_type.unwrap_or(TEAL),
+            TokenType::Function => self.code_function.unwrap_or(BLUE),
+            TokenType::String => self.code_string.unwrap_or(ORANGE),
+            TokenType::Number => self.code_number.unwrap_or(INDIGO),
+            TokenType::Comment => self.code_comment.unwrap_or(GRAY),
+        }
+        .get(self.current_theme)
+    }
+
     pub fn bold(&self) -> Color32 {
         self.bold.unwrap_or(BLACK).get(self.current_theme)
     }
@@ -187,6 +269,7 @@ impl Appearance {
             MarkdownNodeType::Block(BlockNodeType::ListItem(_)) => CaptureCondition::Always,
             MarkdownNodeType::Block(BlockNodeType::Heading(_))
             | MarkdownNodeType::Block(BlockNodeType::Quote)
+            | MarkdownNodeType::Block(BlockNodeType::Table(_))
             | MarkdownNodeType::Block(BlockNodeType::Code)
             | MarkdownNodeType::Block(BlockNodeType::Rule)
             | MarkdownNodeType::Inline(InlineNodeType::Code)
@@ -194,8 +277,15 @@ impl Appearance {
             | MarkdownNodeType::Inline(InlineNodeType::Italic)
             | MarkdownNodeType::Inline(InlineNodeType::Strikethrough)
             | MarkdownNodeType::Inline(InlineNodeType::Link)
-            | MarkdownNodeType::Inline(InlineNodeType::Image) => CaptureCondition::NoCursor,
-            MarkdownNodeType::Document | MarkdownNodeType::Paragraph => CaptureCondition::Never,
+            | MarkdownNodeType::Inline(InlineNodeType::WikiLink)
+            | MarkdownNodeType::Inline(InlineNodeType::Image)
+            | MarkdownNodeType::Inline(InlineNodeType::Math)
+            | MarkdownNodeType::Inline(InlineNodeType::FootnoteReference)
+            | MarkdownNodeType::Block(BlockNodeType::Math) => CaptureCondition::NoCursor,
+            // a definition's label is always shown so it can be matched to its references
+            MarkdownNodeType::Block(BlockNodeType::FootnoteDefinition)
+            | MarkdownNodeType::Document
+            | MarkdownNodeType::Paragraph => CaptureCondition::Never,
         }
     }
 
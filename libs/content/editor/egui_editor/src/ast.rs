@@ -4,10 +4,11 @@ use crate::input::cursor::Cursor;
 use crate::layouts::Annotation;
 use crate::offset_types::{DocCharOffset, RangeExt, RangeIterExt, RelCharOffset};
 use crate::style::{
-    BlockNode, BlockNodeType, InlineNode, ListItem, MarkdownNode, MarkdownNodeType,
+    BlockNode, BlockNodeType, ColumnAlignment, InlineNode, ListItem, MarkdownNode,
+    MarkdownNodeType, TableItem,
 };
 use crate::Editor;
-use pulldown_cmark::{Event, HeadingLevel, LinkType, OffsetIter, Options, Parser, Tag};
+use pulldown_cmark::{Alignment, Event, HeadingLevel, LinkType, OffsetIter, Options, Parser, Tag};
 
 #[derive(Default, Debug, PartialEq)]
 pub struct Ast {
@@ -43,6 +44,9 @@ impl AstNode {
 pub fn calc(buffer: &SubBuffer) -> Ast {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_TASKLISTS);
     let parser = Parser::new_ext(&buffer.text, options);
     let mut result = Ast {
         nodes: vec![AstNode::new(
@@ -52,7 +56,7 @@ pub fn calc(buffer: &SubBuffer) -> Ast {
         )],
         root: 0,
     };
-    result.push_children(result.root, &mut parser.into_offset_iter(), buffer);
+    result.push_children(result.root, &mut parser.into_offset_iter(), buffer, &[]);
     result
 }
 
@@ -81,7 +85,27 @@ impl Ast {
             .map(|(idx, _)| idx)
     }
 
-    fn push_children(&mut self, current_idx: usize, iter: &mut OffsetIter, buffer: &SubBuffer) {
+    /// Returns the index of the definition of the footnote with label `label`, if any. Like
+    /// references to link definitions, footnote references match labels case-insensitively.
+    pub fn footnote_definition(&self, buffer: &SubBuffer, label: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| {
+            node.node_type == MarkdownNode::Block(BlockNode::FootnoteDefinition)
+                && buffer[node.head_range()]
+                    .trim_start_matches("[^")
+                    .split("]:")
+                    .next()
+                    .is_some_and(|definition_label| {
+                        definition_label.to_lowercase() == label.to_lowercase()
+                    })
+        })
+    }
+
+    /// Pushes the nodes for `iter`'s events up to the end of the current node. `alignments` are the
+    /// column alignments of the table containing the current node, if any.
+    fn push_children(
+        &mut self, current_idx: usize, iter: &mut OffsetIter, buffer: &SubBuffer,
+        alignments: &[Alignment],
+    ) {
         let mut skipped = 0;
         while let Some((event, range)) = iter.next() {
             let range = buffer
@@ -89,7 +113,11 @@ impl Ast {
                 .range_to_char((range.start.into(), range.end.into()));
             match event {
                 Event::Start(child_tag) => {
+                    let mut child_alignments = None;
                     let new_child_node = match child_tag {
+                        Tag::Paragraph if Self::is_math_block(&buffer[range]) => {
+                            MarkdownNode::Block(BlockNode::Math)
+                        }
                         Tag::Paragraph => MarkdownNode::Paragraph,
                         Tag::Heading(level, _, _) => MarkdownNode::Block(BlockNode::Heading(level)),
                         Tag::BlockQuote => MarkdownNode::Block(BlockNode::Quote),
@@ -126,15 +154,40 @@ impl Ast {
                         Tag::Image(l, u, t) => {
                             MarkdownNode::Inline(InlineNode::Image(l, u.to_string(), t.to_string()))
                         }
+                        Tag::Table(table_alignments) => {
+                            child_alignments = Some(table_alignments);
+                            MarkdownNode::Block(BlockNode::Table(TableItem::Table))
+                        }
+                        Tag::TableHead => {
+                            MarkdownNode::Block(BlockNode::Table(TableItem::HeaderRow))
+                        }
+                        Tag::TableRow => MarkdownNode::Block(BlockNode::Table(TableItem::Row)),
+                        Tag::FootnoteDefinition(_) => {
+                            MarkdownNode::Block(BlockNode::FootnoteDefinition)
+                        }
+                        Tag::TableCell => {
+                            // cells are pushed in column order
+                            let column = self.nodes[current_idx].children.len();
+                            let alignment = match alignments.get(column) {
+                                Some(Alignment::Center) => ColumnAlignment::Center,
+                                Some(Alignment::Right) => ColumnAlignment::Right,
+                                _ => ColumnAlignment::Left,
+                            };
+                            MarkdownNode::Block(BlockNode::Table(TableItem::Cell(alignment)))
+                        }
                         _ => {
                             skipped += 1;
                             continue;
                         }
                     };
-                    if let Some(new_child_idx) =
-                        self.push_child(current_idx, new_child_node, range, buffer)
-                    {
-                        self.push_children(new_child_idx, iter, buffer);
+                    let is_math = new_child_node == MarkdownNode::Block(BlockNode::Math);
+                    let maybe_new_child_idx =
+                        self.push_child(current_idx, new_child_node, range, buffer);
+                    if let (Some(new_child_idx), false) = (maybe_new_child_idx, is_math) {
+                        let alignments = child_alignments.as_deref().unwrap_or(alignments);
+                        self.push_children(new_child_idx, iter, buffer, alignments);
+                    } else {
+                        Self::skip_children(iter);
                     }
                 }
                 Event::Code(_) => {
@@ -153,6 +206,17 @@ impl Ast {
                         buffer,
                     );
                 }
+                Event::FootnoteReference(label) => {
+                    self.push_child(
+                        current_idx,
+                        MarkdownNode::Inline(InlineNode::FootnoteReference(label.to_string())),
+                        range,
+                        buffer,
+                    );
+                }
+                Event::TaskListMarker(checked) => {
+                    self.push_task_marker(current_idx, checked, buffer);
+                }
                 Event::End(_) => {
                     if skipped == 0 {
                         break;
@@ -160,21 +224,237 @@ impl Ast {
                         skipped -= 1;
                     }
                 }
-                _ => {} // todo: there are some interesting events ignored (rules, tables, etc)
+                _ => {}
+            }
+        }
+        self.push_math(current_idx, buffer);
+        self.push_wiki_links(current_idx, buffer);
+    }
+
+    /// Pushes inline math in the text of the current node that isn't captured by its children.
+    /// pulldown-cmark doesn't parse math, so it's found after the node's other children.
+    /// $e^{i\pi} + 1 = 0$
+    fn push_math(&mut self, current_idx: usize, buffer: &SubBuffer) {
+        if !Self::has_inline_text(&self.nodes[current_idx].node_type) {
+            return;
+        }
+
+        let mut pushed = false;
+        for range in self.uncaptured(current_idx) {
+            let range_start = buffer.segs.offset_to_byte(range.0);
+            for (start, end) in Self::find_math(&buffer[range]) {
+                let math_range = buffer
+                    .segs
+                    .range_to_char((range_start + start, range_start + end));
+                let node = MarkdownNode::Inline(InlineNode::Math);
+                pushed |= self
+                    .push_child(current_idx, node, math_range, buffer)
+                    .is_some();
+            }
+        }
+
+        if pushed {
+            self.sort_children(current_idx);
+        }
+    }
+
+    /// Returns the byte range of each inline math span in `text`. Like in pandoc, the opening `$`
+    /// must be followed by a non-space and the closing `$` must be preceded by a non-space and not
+    /// followed by a digit, so that prices like $5 and $10 aren't math.
+    fn find_math(text: &str) -> Vec<(usize, usize)> {
+        let bytes = text.as_bytes();
+        let is_delimiter = |i: usize| {
+            bytes[i] == b'$'
+                && (i == 0 || bytes[i - 1] != b'\\')
+                && bytes.get(i + 1) != Some(&b'$')
+                && (i == 0 || bytes[i - 1] != b'$')
+        };
+
+        let mut result = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if !is_delimiter(i) || bytes.get(i + 1).is_none_or(u8::is_ascii_whitespace) {
+                i += 1;
+                continue;
+            }
+            let close = (i + 2..bytes.len()).find(|&j| {
+                is_delimiter(j)
+                    && !bytes[j - 1].is_ascii_whitespace()
+                    && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit)
+            });
+            match close {
+                Some(close) if !text[i..close].contains('\n') => {
+                    result.push((i, close + 1));
+                    i = close + 1;
+                }
+                _ => i += 1,
+            }
+        }
+        result
+    }
+
+    /// Returns whether a paragraph is block math, delimited by `$$`
+    /// $$
+    /// \int_0^1 x^2 dx
+    /// $$
+    fn is_math_block(text: &str) -> bool {
+        let text = text.trim();
+        text.len() >= 4
+            && text.starts_with("$$")
+            && text.ends_with("$$")
+            && !text[2..text.len() - 2].contains("$$")
+    }
+
+    /// Returns whether inline nodes pulldown-cmark doesn't parse (inline math and wiki links) are
+    /// found in the text of nodes of this type
+    fn has_inline_text(node_type: &MarkdownNode) -> bool {
+        matches!(
+            node_type,
+            MarkdownNode::Paragraph
+                | MarkdownNode::Block(
+                    BlockNode::Heading(..)
+                        | BlockNode::ListItem(..)
+                        | BlockNode::Table(TableItem::Cell(..))
+                )
+                | MarkdownNode::Inline(
+                    InlineNode::Bold | InlineNode::Italic | InlineNode::Strikethrough
+                )
+        )
+    }
+
+    /// Returns the ranges of the text of a node that aren't captured by its children, in order
+    fn uncaptured(&self, node_idx: usize) -> Vec<(DocCharOffset, DocCharOffset)> {
+        let node = &self.nodes[node_idx];
+        let mut result = Vec::new();
+        let mut start = node.text_range.0;
+        for &child_idx in &node.children {
+            let child_range = self.nodes[child_idx].range;
+            result.push((start, child_range.0.max(start)));
+            start = child_range.1.max(start);
+        }
+        result.push((start, node.text_range.1.max(start)));
+        result
+    }
+
+    /// Sorts a node's children into document order after children are pushed out of order
+    fn sort_children(&mut self, node_idx: usize) {
+        let mut children = std::mem::take(&mut self.nodes[node_idx].children);
+        children.sort_by_key(|&child_idx| self.nodes[child_idx].range.0);
+        self.nodes[node_idx].children = children;
+    }
+
+    /// Pushes wiki links in the text of the current node that isn't captured by its children.
+    /// pulldown-cmark doesn't parse wiki links, so they're found after the node's other children.
+    /// [[target]]
+    /// [[path/to/target#heading|label]]
+    fn push_wiki_links(&mut self, current_idx: usize, buffer: &SubBuffer) {
+        if !Self::has_inline_text(&self.nodes[current_idx].node_type) {
+            return;
+        }
+
+        let mut pushed = false;
+        for range in self.uncaptured(current_idx) {
+            let range_start = buffer.segs.offset_to_byte(range.0);
+            for (start, end, target) in Self::find_wiki_links(&buffer[range]) {
+                let link_range = buffer
+                    .segs
+                    .range_to_char((range_start + start, range_start + end));
+                let node = MarkdownNode::Inline(InlineNode::WikiLink(target));
+                pushed |= self
+                    .push_child(current_idx, node, link_range, buffer)
+                    .is_some();
+            }
+        }
+
+        if pushed {
+            self.sort_children(current_idx);
+        }
+    }
+
+    /// Returns the byte range and target of each wiki link in `text`
+    fn find_wiki_links(text: &str) -> Vec<(usize, usize, String)> {
+        let mut result = Vec::new();
+        let mut search_start = 0;
+        while let Some(start) = text[search_start..].find("[[") {
+            let start = search_start + start;
+            let content_start = start + 2;
+            let Some(content_len) = text[content_start..].find("]]") else {
+                break;
+            };
+            let content = &text[content_start..content_start + content_len];
+            if content.contains(['[', ']', '\n']) {
+                // the link starts at a later pair of brackets, if any
+                search_start = start + 1;
+                continue;
+            }
+
+            let end = content_start + content_len + 2;
+            let target = content.split('|').next().unwrap_or_default().trim();
+            if !target.is_empty() {
+                result.push((start, end, target.to_string()));
+            }
+            search_start = end;
+        }
+        result
+    }
+
+    /// Consumes the events of a node that wasn't pushed, through the node's end
+    fn skip_children(iter: &mut OffsetIter) {
+        let mut depth = 0;
+        for (event, _) in iter {
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) if depth == 0 => break,
+                Event::End(_) => depth -= 1,
+                _ => {}
             }
         }
     }
 
+    /// Makes the bulleted list item with a task list marker a todo item. pulldown-cmark reports the
+    /// marker at the start of the item's text, after the item (and for loose lists, the item's
+    /// paragraph) is pushed.
+    /// - [ ] todo
+    /// - [x] done
+    fn push_task_marker(&mut self, current_idx: usize, checked: bool, buffer: &SubBuffer) {
+        let item_idx = match self.nodes[current_idx].node_type {
+            MarkdownNode::Block(BlockNode::ListItem(..)) => current_idx,
+            _ => match self.parent(current_idx) {
+                Some(parent_idx) => parent_idx,
+                None => return,
+            },
+        };
+        let indent_level = match self.nodes[item_idx].node_type {
+            MarkdownNode::Block(BlockNode::ListItem(ListItem::Bulleted, indent_level)) => {
+                indent_level
+            }
+            _ => return,
+        };
+
+        // the marker and up to one space after it join the item's head
+        let item = &mut self.nodes[item_idx];
+        let text = &buffer[item.text_range];
+        if !(text.starts_with("[ ]") || text.starts_with("[x]") || text.starts_with("[X]")) {
+            return;
+        }
+        let mut text_start = item.text_range.0 + 3;
+        if buffer[(text_start, item.text_range.1)].starts_with(' ') {
+            text_start += 1;
+        }
+        item.node_type =
+            MarkdownNode::Block(BlockNode::ListItem(ListItem::Todo(checked), indent_level));
+        item.text_range.0 = text_start;
+
+        if current_idx != item_idx {
+            let paragraph = &mut self.nodes[current_idx];
+            paragraph.range.0 = paragraph.range.0.max(text_start).min(paragraph.range.1);
+            paragraph.text_range.0 = paragraph.range.0;
+        }
+    }
+
     fn item_type(text: &str) -> ListItem {
         let text = text.trim_start();
-        if text.starts_with("+ [ ]") || text.starts_with("* [ ]") || text.starts_with("- [ ]") {
-            ListItem::Todo(false)
-        } else if text.starts_with("+ [x]")
-            || text.starts_with("* [x]")
-            || text.starts_with("- [x]")
-        {
-            ListItem::Todo(true)
-        } else if let Some(prefix) = text.split('.').next() {
+        if let Some(prefix) = text.split('.').next() {
             if let Ok(num) = prefix.parse::<usize>() {
                 ListItem::Numbered(num)
             } else {
@@ -189,17 +469,38 @@ impl Ast {
         &mut self, parent_idx: usize, mut markdown_node: MarkdownNode,
         cmark_range: (DocCharOffset, DocCharOffset), buffer: &SubBuffer,
     ) -> Option<usize> {
+        let is_cell =
+            matches!(markdown_node, MarkdownNode::Block(BlockNode::Table(TableItem::Cell(..))));
+
         // assumption: whitespace-only nodes have no children
-        if buffer[cmark_range].trim().is_empty() {
+        // empty table cells are kept so the cursor can be moved into them
+        if buffer[cmark_range].trim().is_empty() && !is_cell {
             return None;
         }
 
         let range = {
             let mut range = cmark_range;
 
-            // trim trailing whitespace from range
-            // operations that adjust styles will not add or remove trailing whitespace
-            range.1 -= buffer[cmark_range].len() - buffer[cmark_range].trim_end().len();
+            if is_cell {
+                // capture the pipe before a cell; keep trailing whitespace, which pads the cell
+                if range.0 > 0 && &buffer[(range.0 - 1, range.0)] == "|" {
+                    range.0 -= 1;
+                }
+            } else {
+                // trim trailing whitespace from range
+                // operations that adjust styles will not add or remove trailing whitespace
+                range.1 -= buffer[cmark_range].len() - buffer[cmark_range].trim_end().len();
+            }
+
+            // capture the delimiter row with the header row
+            // | header |
+            // | ------ |
+            if markdown_node == MarkdownNode::Block(BlockNode::Table(TableItem::HeaderRow)) {
+                let rest = &buffer[(cmark_range.1, buffer.segs.last_cursor_position())];
+                let delimiter_row = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                // assumption: delimiter rows are made of single-byte unicode sequences
+                range.1 = cmark_range.1 + delimiter_row.trim_end().len();
+            }
 
             // capture leading whitespace for list items and code blocks (affects non-fenced code blocks only)
             if matches!(
@@ -278,8 +579,9 @@ impl Ast {
                     }
                 }
                 MarkdownNode::Block(BlockNode::Code) => {
-                    if (buffer[range].starts_with("```\n") && buffer[range].ends_with("\n```"))
-                        || (buffer[range].starts_with("~~~\n") && buffer[range].ends_with("\n~~~"))
+                    let first_line = buffer[range].split('\n').next().unwrap_or_default();
+                    if (first_line.starts_with("```") && buffer[range].ends_with("\n```"))
+                        || (first_line.starts_with("~~~") && buffer[range].ends_with("\n~~~"))
                     {
                         /*
                         ```
@@ -288,8 +590,13 @@ impl Ast {
                         ~~~
                         code block
                         ~~~
+                        ```rust
+                        code block with info string
+                        ```
                          */
-                        text_range.0 += 3;
+                        text_range.0 = buffer
+                            .segs
+                            .offset_to_char(buffer.segs.offset_to_byte(range.0) + first_line.len());
                         text_range.1 -= 3;
                     } else {
                         /*
@@ -315,9 +622,8 @@ impl Ast {
 
                     text_range.0 += buffer[range].len() - buffer[range].trim_start().len();
                     text_range.0 += match item_type {
-                        ListItem::Bulleted => 1,
+                        ListItem::Bulleted | ListItem::Todo(_) => 1,
                         ListItem::Numbered(n) => 1 + n.to_string().len(),
-                        ListItem::Todo(_) => 5,
                     };
 
                     // correct cmark behavior with no space in syntax chars
@@ -353,6 +659,33 @@ impl Ast {
                         text_range.0 = text_range.1 - 1;
                     }
                 }
+                MarkdownNode::Block(BlockNode::Table(TableItem::HeaderRow | TableItem::Row)) => {
+                    // | cell | cell |
+                    // | ---- | ---- |
+                    let row = buffer[cmark_range].trim_end();
+                    text_range.1 = cmark_range.1 - (buffer[cmark_range].len() - row.len());
+                    if row.ends_with('|') && !row.ends_with("\\|") {
+                        text_range.1 -= 1;
+                    }
+                    text_range.1 = text_range.1.max(text_range.0).min(range.1);
+                }
+                MarkdownNode::Block(BlockNode::Table(TableItem::Cell(..))) => {
+                    // | cell
+                    // |cell
+                    // cell
+                    if buffer[range].starts_with('|') {
+                        text_range.0 += 1;
+                    }
+                    let content = &buffer[text_range];
+                    if content.trim().is_empty() {
+                        // empty cells keep up to one space of padding in the head
+                        text_range.0 += content.len().min(1);
+                        text_range.1 = text_range.0;
+                    } else {
+                        text_range.0 += content.len() - content.trim_start().len();
+                        text_range.1 -= content.len() - content.trim_end().len();
+                    }
+                }
                 MarkdownNode::Inline(InlineNode::Code) => {
                     // `code`
                     text_range.0 += 1;
@@ -387,6 +720,49 @@ impl Ast {
                         }
                     }
                 }
+                MarkdownNode::Inline(InlineNode::WikiLink(..)) => {
+                    // [[target]]
+                    // [[target|label]]
+                    text_range.0 += 2;
+                    text_range.1 -= 2;
+                    if let Some(pipe) = buffer[text_range].find('|') {
+                        let label_start = buffer.segs.offset_to_byte(text_range.0) + pipe + 1;
+                        let label_start = buffer.segs.offset_to_char(label_start);
+                        if !buffer[(label_start, text_range.1)].trim().is_empty() {
+                            text_range.0 = label_start;
+                        }
+                    }
+                }
+                MarkdownNode::Block(BlockNode::FootnoteDefinition) => {
+                    // [^label]: definition
+                    let definition = &buffer[range];
+                    if let Some(label_end) = definition.find("]:") {
+                        let rest = &definition[label_end + 2..];
+                        let text_start =
+                            definition.len() - rest.trim_start_matches([' ', '\t']).len();
+                        text_range.0 = buffer
+                            .segs
+                            .offset_to_char(buffer.segs.offset_to_byte(range.0) + text_start);
+                    }
+                }
+                MarkdownNode::Inline(InlineNode::FootnoteReference(..)) => {
+                    // [^label]
+                    text_range.0 += 2;
+                    text_range.1 -= 1;
+                }
+                MarkdownNode::Inline(InlineNode::Math) => {
+                    // $math$
+                    text_range.0 += 1;
+                    text_range.1 -= 1;
+                }
+                MarkdownNode::Block(BlockNode::Math) => {
+                    // $$math$$
+                    // $$
+                    // math
+                    // $$
+                    text_range.0 += 2;
+                    text_range.1 -= 2;
+                }
                 MarkdownNode::Inline(InlineNode::Image(LinkType::Inline, url, title)) => {
                     // ![title](http://url.com)
                     text_range.0 += 2;
@@ -697,6 +1073,7 @@ impl Editor {
 
 #[cfg(test)]
 mod test {
+    use crate::style::{BlockNode, ColumnAlignment, InlineNode, ListItem, MarkdownNode, TableItem};
     use crate::test_input;
 
     #[test]
@@ -709,6 +1086,212 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_table() {
+        let buffer = "| a | bb |\n|---|:-:|\n| ccc | |\n|e|\n\nafter".into();
+        let ast = super::calc(&buffer);
+        let table_nodes = ast
+            .nodes
+            .iter()
+            .filter_map(|node| match node.node_type {
+                MarkdownNode::Block(BlockNode::Table(item)) => Some((
+                    item,
+                    &buffer[node.range],
+                    &buffer[node.head_range()],
+                    &buffer[node.text_range],
+                    &buffer[node.tail_range()],
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let cell = |alignment| TableItem::Cell(alignment);
+        let left = ColumnAlignment::Left;
+        let center = ColumnAlignment::Center;
+        assert_eq!(
+            table_nodes,
+            vec![
+                (
+                    TableItem::Table,
+                    "| a | bb |\n|---|:-:|\n| ccc | |\n|e|",
+                    "",
+                    "| a | bb |\n|---|:-:|\n| ccc | |\n|e|",
+                    ""
+                ),
+                (TableItem::HeaderRow, "| a | bb |\n|---|:-:|", "", "| a | bb ", "|\n|---|:-:|"),
+                (cell(left), "| a ", "| ", "a", " "),
+                (cell(center), "| bb ", "| ", "bb", " "),
+                (TableItem::Row, "| ccc | |", "", "| ccc | ", "|"),
+                (cell(left), "| ccc ", "| ", "ccc", " "),
+                (cell(center), "| ", "| ", "", ""),
+                (TableItem::Row, "|e|", "", "|e", "|"),
+                (cell(left), "|e", "|", "e", ""),
+            ]
+        );
+
+        // the paragraph after the table is still parsed
+        assert_eq!(ast.nodes[ast.nodes[ast.root].children[1]].node_type, MarkdownNode::Paragraph);
+    }
+
+    #[test]
+    fn test_code_block_info_string() {
+        let buffer = "```rust\nfn a() {}\n```\n\n~~~\nb\n~~~".into();
+        let ast = super::calc(&buffer);
+        let code_blocks = ast
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == MarkdownNode::Block(BlockNode::Code))
+            .map(|node| {
+                (&buffer[node.head_range()], &buffer[node.text_range], &buffer[node.tail_range()])
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(code_blocks, vec![("```rust", "\nfn a() {}\n", "```"), ("~~~", "\nb\n", "~~~")]);
+    }
+
+    #[test]
+    fn test_wiki_link() {
+        let buffer =
+            "see [[Note]] and **[[a/b#Heading|the heading]]**\n\n`[[code]]` [[]] [[[x]]] [[y\n]]"
+                .into();
+        let ast = super::calc(&buffer);
+        let mut nodes = ast.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.range.0);
+        let wiki_links = nodes
+            .into_iter()
+            .filter_map(|node| match &node.node_type {
+                MarkdownNode::Inline(InlineNode::WikiLink(target)) => Some((
+                    target.as_str(),
+                    &buffer[node.range],
+                    &buffer[node.head_range()],
+                    &buffer[node.text_range],
+                    &buffer[node.tail_range()],
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            wiki_links,
+            vec![
+                ("Note", "[[Note]]", "[[", "Note", "]]"),
+                (
+                    "a/b#Heading",
+                    "[[a/b#Heading|the heading]]",
+                    "[[a/b#Heading|",
+                    "the heading",
+                    "]]"
+                ),
+                ("x", "[[x]]", "[[", "x", "]]"),
+            ]
+        );
+
+        // children stay in document order
+        let paragraph = &ast.nodes[ast.nodes[ast.root].children[0]];
+        let child_starts = paragraph
+            .children
+            .iter()
+            .map(|&child_idx| ast.nodes[child_idx].range.0)
+            .collect::<Vec<_>>();
+        let mut sorted_child_starts = child_starts.clone();
+        sorted_child_starts.sort();
+        assert_eq!(child_starts, sorted_child_starts);
+    }
+
+    #[test]
+    fn test_math() {
+        let buffer = "costs $5 and $10\n\nbut $x^2$ is math\n\n$$\n\\frac{a}{b}\n$$".into();
+        let ast = super::calc(&buffer);
+        let math = ast
+            .nodes
+            .iter()
+            .filter(|node| {
+                matches!(
+                    node.node_type,
+                    MarkdownNode::Inline(InlineNode::Math) | MarkdownNode::Block(BlockNode::Math)
+                )
+            })
+            .map(|node| {
+                (&buffer[node.head_range()], &buffer[node.text_range], &buffer[node.tail_range()])
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(math, vec![("$", "x^2", "$"), ("$$", "\n\\frac{a}{b}\n", "$$")]);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let buffer = "a claim[^1] and another[^Note]\n\n[^1]: a source\n\n[^note]:\tanother".into();
+        let ast = super::calc(&buffer);
+        let footnotes = ast
+            .nodes
+            .iter()
+            .filter(|node| {
+                matches!(
+                    node.node_type,
+                    MarkdownNode::Inline(InlineNode::FootnoteReference(..))
+                        | MarkdownNode::Block(BlockNode::FootnoteDefinition)
+                )
+            })
+            .map(|node| {
+                (&buffer[node.head_range()], &buffer[node.text_range], &buffer[node.tail_range()])
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            footnotes,
+            vec![
+                ("[^", "1", "]"),
+                ("[^", "Note", "]"),
+                ("[^1]: ", "a source", ""),
+                ("[^note]:\t", "another", "")
+            ]
+        );
+
+        // labels match case-insensitively
+        let definition_idx = ast.footnote_definition(&buffer, "Note").unwrap();
+        assert_eq!(&buffer[ast.nodes[definition_idx].text_range], "another");
+        assert_eq!(ast.footnote_definition(&buffer, "2"), None);
+    }
+
+    #[test]
+    fn test_task_list() {
+        let buffer =
+            "- [ ] todo\n+ [X] done\n* [x]\n\n1. [ ] numbered\n\n- [ ] loose\n\n- [ ] list".into();
+        let ast = super::calc(&buffer);
+        let items = ast
+            .nodes
+            .iter()
+            .filter_map(|node| match node.node_type {
+                MarkdownNode::Block(BlockNode::ListItem(item, _)) => {
+                    Some((item, &buffer[node.head_range()], &buffer[node.text_range]))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            items,
+            vec![
+                (ListItem::Todo(false), "- [ ] ", "todo"),
+                (ListItem::Todo(true), "+ [X] ", "done"),
+                (ListItem::Bulleted, "* ", "[x]"),
+                (ListItem::Numbered(1), "1. ", "[ ] numbered"),
+                (ListItem::Todo(false), "- [ ] ", "loose"),
+                (ListItem::Todo(false), "- [ ] ", "list"),
+            ]
+        );
+
+        // the paragraphs of loose items don't include the marker
+        let paragraphs = ast
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == MarkdownNode::Paragraph)
+            .map(|node| &buffer[node.range])
+            .collect::<Vec<_>>();
+        assert!(paragraphs.contains(&"loose"));
+    }
 }
 
 // grievances with pullmark:
@@ -0,0 +1,241 @@
+use crate::input::canonical::{Modification, Region};
+use egui::{Context, DroppedFile, Event, Rect, Ui};
+use lb::{FileType, Uuid};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Extensions of files that are imported as attachments rather than pasted as text
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Images being imported as attachments in the background; the link to each attachment is
+/// inserted once it's created
+#[derive(Clone, Default)]
+pub struct Attachments {
+    inserted: Arc<Mutex<Vec<Modification>>>,
+}
+
+impl Attachments {
+    /// Imports image files that are pasted (as a `file://` url, which is how file managers copy
+    /// files) or dropped onto the editor as attachments: files next to the document being edited.
+    /// Returns the rest of the events.
+    pub fn handle_events(
+        &self, ui: &Ui, rect: Rect, events: &[Event], core: &lb::Core, file_id: Option<Uuid>,
+    ) -> Vec<Event> {
+        let Some(file_id) = file_id else {
+            return events.to_vec();
+        };
+
+        let mut result = Vec::new();
+        for event in events {
+            match event {
+                Event::Paste(text) => match image_path(text) {
+                    Some(path) => {
+                        let name = path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        self.import(ui.ctx(), core, file_id, name, None, Some(path));
+                    }
+                    None => result.push(event.clone()),
+                },
+                _ => result.push(event.clone()),
+            }
+        }
+
+        // files dropped onto the editor are attachments; files dropped elsewhere are left to the app
+        let hovering = ui
+            .input(|i| i.pointer.hover_pos())
+            .map(|pos| rect.contains(pos))
+            .unwrap_or_default();
+        if hovering {
+            let dropped = ui.input_mut(|i| {
+                let (images, others): (Vec<DroppedFile>, Vec<DroppedFile>) = i
+                    .raw
+                    .dropped_files
+                    .drain(..)
+                    .partition(|file| is_image(Path::new(&dropped_name(file))));
+                i.raw.dropped_files = others;
+                images
+            });
+            for file in dropped {
+                let bytes = file.bytes.as_ref().map(|bytes| bytes.to_vec());
+                self.import(ui.ctx(), core, file_id, dropped_name(&file), bytes, file.path);
+            }
+        }
+
+        result
+    }
+
+    /// The insertions of links to attachments created since the last call
+    pub fn take_inserted(&self) -> Vec<Modification> {
+        std::mem::take(&mut *self.inserted.lock().unwrap())
+    }
+
+    /// Creates an attachment next to the document `file_id` from `bytes` or, if there are none,
+    /// from the file at `path`, then queues the insertion of a link to it. The attachment is named
+    /// `name` or, if that's taken, `name` with a number added.
+    fn import(
+        &self, ctx: &Context, core: &lb::Core, file_id: Uuid, name: String, bytes: Option<Vec<u8>>,
+        path: Option<PathBuf>,
+    ) {
+        let inserted = self.inserted.clone();
+        let core = core.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let bytes = match (bytes, &path) {
+                (Some(bytes), _) => bytes,
+                (None, Some(path)) => match fs::read(path) {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        tracing::error!("failed to read attachment {:?}: {:?}", path, err);
+                        return;
+                    }
+                },
+                (None, None) => return,
+            };
+
+            let attachment = core.get_file_by_id(file_id).and_then(|file| {
+                let siblings = core.get_children(file.parent).unwrap_or_default();
+                let name = available_name(&name, &siblings);
+                let attachment = core.create_file(&name, file.parent, FileType::Document)?;
+                core.write_document(attachment.id, &bytes)?;
+                Ok(attachment)
+            });
+            let attachment = match attachment {
+                Ok(attachment) => attachment,
+                Err(err) => {
+                    tracing::error!("failed to create attachment {}: {:?}", name, err);
+                    return;
+                }
+            };
+
+            inserted.lock().unwrap().push(Modification::Replace {
+                region: Region::Selection,
+                text: image_link(&attachment.name),
+            });
+            ctx.request_repaint();
+        });
+    }
+}
+
+/// A markdown image linking to the attachment named `name` next to the document
+fn image_link(name: &str) -> String {
+    let alt = name
+        .rsplit_once('.')
+        .map(|(stem, _)| stem)
+        .unwrap_or(name)
+        .replace(['[', ']'], "");
+    format!("![{}]({})", alt, name.replace('%', "%25").replace(' ', "%20"))
+}
+
+/// `name`, or `name` with the lowest number added that no file in `siblings` is named
+fn available_name(name: &str, siblings: &[lb::File]) -> String {
+    let taken = |candidate: &str| siblings.iter().any(|sibling| sibling.name == candidate);
+    if !taken(name) {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (name, String::new()),
+    };
+    let mut i = 1;
+    loop {
+        let candidate = format!("{}-{}{}", stem, i, extension);
+        if !taken(&candidate) {
+            return candidate;
+        }
+        i += 1;
+    }
+}
+
+/// The path of the image file on disk that pasted text refers to, if the text is a `file://` url
+/// of an image file. Other text is pasted as it is, even if it happens to be a path.
+fn image_path(text: &str) -> Option<PathBuf> {
+    let path = image_url_path(text)?;
+    if path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// The path in pasted text that's a `file://` url with an image extension
+fn image_url_path(text: &str) -> Option<PathBuf> {
+    let text = text.trim();
+    if text.contains('\n') {
+        return None;
+    }
+    let path = PathBuf::from(lb::percent_decode(text.strip_prefix("file://")?));
+    if is_image(&path) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image_extension| extension.eq_ignore_ascii_case(image_extension))
+        })
+        .unwrap_or_default()
+}
+
+/// The name of a dropped file, from its path if it has one
+fn dropped_name(file: &DroppedFile) -> String {
+    match &file.path {
+        Some(path) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        None => file.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{available_name, image_link, image_url_path};
+    use std::path::PathBuf;
+
+    fn file(name: &str) -> lb::File {
+        lb::File {
+            id: lb::Uuid::new_v4(),
+            parent: lb::Uuid::nil(),
+            name: name.to_string(),
+            file_type: lb::FileType::Document,
+            last_modified: 0,
+            last_modified_by: String::new(),
+            shares: vec![],
+        }
+    }
+
+    #[test]
+    fn test_available_name() {
+        let siblings = vec![file("pic.png"), file("pic-1.png"), file("notes")];
+
+        assert_eq!(available_name("other.png", &siblings), "other.png");
+        assert_eq!(available_name("pic.png", &siblings), "pic-2.png");
+        assert_eq!(available_name("notes", &siblings), "notes-1");
+    }
+
+    #[test]
+    fn test_image_url_path() {
+        assert_eq!(
+            image_url_path("file:///home/me/my%20pic%231.PNG\n"),
+            Some(PathBuf::from("/home/me/my pic#1.PNG"))
+        );
+        assert_eq!(image_url_path("/home/me/pic.png"), None);
+        assert_eq!(image_url_path("file:///home/me/notes.txt"), None);
+        assert_eq!(image_url_path("file:///a.png\nfile:///b.png"), None);
+    }
+
+    #[test]
+    fn test_image_link() {
+        assert_eq!(image_link("my [pic] 100%.png"), "![my pic 100%](my%20[pic]%20100%25.png)");
+    }
+}
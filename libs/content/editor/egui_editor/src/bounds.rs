@@ -6,7 +6,9 @@ use crate::galleys::Galleys;
 use crate::input::canonical::Bound;
 use crate::input::cursor::Cursor;
 use crate::offset_types::{DocByteOffset, DocCharOffset, RangeExt, RelByteOffset};
-use crate::style::{BlockNodeType, MarkdownNodeType};
+use crate::style::{
+    BlockNode, BlockNodeType, InlineNode, MarkdownNode, MarkdownNodeType, TableItem,
+};
 use crate::unicode_segs::UnicodeSegs;
 use crate::Editor;
 use egui::epaint::text::cursor::RCursor;
@@ -24,6 +26,8 @@ pub type Lines = Vec<(DocCharOffset, DocCharOffset)>;
 pub type Paragraphs = Vec<(DocCharOffset, DocCharOffset)>;
 pub type Text = Vec<(DocCharOffset, DocCharOffset)>;
 pub type PlainTextLinks = Vec<(DocCharOffset, DocCharOffset)>;
+pub type Tables = Vec<(DocCharOffset, DocCharOffset)>;
+pub type Cells = Vec<(DocCharOffset, DocCharOffset)>;
 
 /// Represents bounds of various text regions in the buffer. Region bounds are inclusive on both sides. Regions do not
 /// overlap, have region.0 <= region.1, and are sorted. Character and doc regions are not stored explicitly but can be
@@ -41,6 +45,10 @@ pub struct Bounds {
 
     /// Plain text links are styled and clickable but aren't markdown links.
     pub links: PlainTextLinks,
+
+    /// Tables and the text of their cells, which tab/shift+tab move between.
+    pub tables: Tables,
+    pub cells: Cells,
 }
 
 pub fn calc_ast(ast: &Ast) -> AstTextRanges {
@@ -203,13 +211,19 @@ pub fn calc_text(
             cursor_paragraphs,
         );
 
-        let this_range_pushed = if text_range.range_type == AstTextRangeType::Text || !captured {
-            // text range or uncaptured syntax range
-            result.push(text_range.range);
-            true
-        } else {
-            false
-        };
+        // captured math is rendered in place of its text
+        let is_math = matches!(
+            text_range.node(ast),
+            MarkdownNode::Inline(InlineNode::Math) | MarkdownNode::Block(BlockNode::Math)
+        );
+        let this_range_pushed =
+            if (text_range.range_type == AstTextRangeType::Text && !is_math) || !captured {
+                // text range or uncaptured syntax range
+                result.push(text_range.range);
+                true
+            } else {
+                false
+            };
 
         if !this_range_pushed && !last_range_pushed {
             // empty range between captured ranges
@@ -263,12 +277,38 @@ pub fn calc_links(buffer: &SubBuffer, text: &Text, ast: &Ast) -> PlainTextLinks
     result
 }
 
+pub fn calc_tables(ast: &Ast) -> Tables {
+    ast.nodes
+        .iter()
+        .filter(|node| node.node_type == MarkdownNode::Block(BlockNode::Table(TableItem::Table)))
+        .map(|node| node.range)
+        .collect()
+}
+
+pub fn calc_cells(ast: &Ast) -> Cells {
+    ast.nodes
+        .iter()
+        .filter(|node| {
+            matches!(node.node_type, MarkdownNode::Block(BlockNode::Table(TableItem::Cell(..))))
+        })
+        .map(|node| node.text_range)
+        .collect()
+}
+
 pub fn captured(
     cursor: Cursor, paragraphs: &Paragraphs, ast: &Ast, text_range: &AstTextRange,
     hover_syntax_reveal_debounce_state: HoverSyntaxRevealDebounceState, appearance: &Appearance,
     cursor_paragraphs: (usize, usize),
 ) -> bool {
-    let ast_node_range = ast.nodes[*text_range.ancestors.last().unwrap()].range;
+    let ast_node_idx = *text_range.ancestors.last().unwrap();
+    let ast_node_range = if let MarkdownNode::Block(BlockNode::Table(TableItem::Cell(..))) =
+        ast.nodes[ast_node_idx].node_type
+    {
+        // table cells reveal their syntax together with the rest of their row
+        ast.nodes[text_range.ancestors[text_range.ancestors.len() - 2]].range
+    } else {
+        ast.nodes[ast_node_idx].range
+    };
     let intersects_selection = ast_node_range.intersects_allow_empty(&cursor.selection);
 
     let debounce_satisfied = hover_syntax_reveal_debounce_state.pointer_offset_updated_at
@@ -822,6 +862,8 @@ impl Editor {
         println!("paragraphs: {:?}", self.ranges_text(&self.bounds.paragraphs));
         println!("text: {:?}", self.ranges_text(&self.bounds.text));
         println!("links: {:?}", self.ranges_text(&self.bounds.links));
+        println!("tables: {:?}", self.ranges_text(&self.bounds.tables));
+        println!("cells: {:?}", self.ranges_text(&self.bounds.cells));
     }
 
     fn ranges_text(&self, ranges: &[(DocCharOffset, DocCharOffset)]) -> Vec<String> {
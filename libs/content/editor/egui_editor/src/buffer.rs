@@ -21,6 +21,7 @@ pub type Mutation = Vec<SubMutation>; // todo: tinyvec candidate
 #[derive(Clone, Debug)]
 pub enum EditorMutation {
     Buffer(Mutation), // todo: tinyvec candidate
+    Batch(Mutation),  // applied as its own undo step e.g. replacing every match of a search
     Undo,
     Redo,
     // todo: redefine
@@ -107,15 +108,8 @@ impl Buffer {
                         .any(|m| matches!(m, SubMutation::Insert { .. } | SubMutation::Delete(..)))
                 }) || now - self.last_apply > UNDO_DEBOUNCE_PERIOD
                 {
-                    self.undo_queue.push_back(current_text_mods.clone());
-                    if self.undo_queue.len() > MAX_UNDOS {
-                        // when modifications overflow the queue, apply them to undo_base
-                        if let Some(undo_mods) = self.undo_queue.pop_front() {
-                            for m in undo_mods {
-                                self.undo_base.apply_modification(m, debug, appearance);
-                            }
-                        }
-                    }
+                    let current_text_mods = current_text_mods.clone();
+                    self.push_undo(current_text_mods, debug, appearance);
                     self.current_text_mods = Some(vec![modification.clone()]);
                 } else {
                     current_text_mods.push(modification.clone());
@@ -135,6 +129,42 @@ impl Buffer {
             .apply_modification(modification, debug, appearance)
     }
 
+    /// applies `modification` as its own undo step, so that one undo reverts all of it and nothing else
+    pub fn apply_batch(
+        &mut self, modification: Mutation, debug: &mut DebugInfo, appearance: &mut Appearance,
+    ) -> (bool, Option<String>, Option<String>) {
+        if let Some(current_text_mods) = self.current_text_mods.take() {
+            self.push_undo(current_text_mods, debug, appearance);
+        }
+
+        self.last_apply = Instant::now();
+        self.redo_stack = Vec::new();
+        let result = self
+            .current
+            .apply_modification(modification.clone(), debug, appearance);
+
+        // a cursor-only modification after the batch keeps subsequent text updates out of its undo step
+        let cursor = vec![SubMutation::Cursor { cursor: self.current.cursor }];
+        self.current_text_mods = Some(vec![modification, cursor]);
+
+        result
+    }
+
+    /// adds a modification to the undo queue
+    fn push_undo(
+        &mut self, mods: Vec<Mutation>, debug: &mut DebugInfo, appearance: &mut Appearance,
+    ) {
+        self.undo_queue.push_back(mods);
+        if self.undo_queue.len() > MAX_UNDOS {
+            // when modifications overflow the queue, apply them to undo_base
+            if let Some(undo_mods) = self.undo_queue.pop_front() {
+                for m in undo_mods {
+                    self.undo_base.apply_modification(m, debug, appearance);
+                }
+            }
+        }
+    }
+
     /// undoes one modification, if able
     pub fn undo(&mut self, debug: &mut DebugInfo, appearance: &mut Appearance) {
         if let Some(current_text_mods) = &self.current_text_mods {
@@ -191,6 +221,46 @@ impl Buffer {
             }
         }
     }
+
+    /// applies an edit made by another editor of the document, replacing the text in the byte range `range` with
+    /// `text` while the cursor keeps its place; clears undo history, whose modifications may no longer apply
+    pub fn apply_remote(
+        &mut self, range: (DocByteOffset, DocByteOffset), text: &str, debug: &mut DebugInfo,
+        appearance: &mut Appearance,
+    ) {
+        // edits can split graphemes, so the graphemes they touch are replaced whole
+        let graphemes = &self.current.segs.grapheme_indexes;
+        let start = match graphemes.binary_search(&range.0) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let end = match graphemes.binary_search(&range.1) {
+            Ok(i) | Err(i) => i,
+        };
+        let replacement = [
+            &self.current[(graphemes[start], range.0)],
+            text,
+            &self.current[(range.1, graphemes[end])],
+        ]
+        .concat();
+
+        let cursor = self.current.cursor;
+        self.current.apply_modification(
+            vec![
+                SubMutation::Cursor { cursor: (start, end).into() },
+                SubMutation::Insert { text: replacement, advance_cursor: false },
+                SubMutation::Cursor { cursor },
+            ],
+            debug,
+            appearance,
+        );
+
+        self.undo_base = self.current.clone();
+        self.undo_queue.clear();
+        self.current_text_mods = None;
+        self.redo_stack.clear();
+        self.last_apply = Instant::now();
+    }
 }
 
 impl From<&str> for SubBuffer {
@@ -469,7 +539,7 @@ impl Index<(DocCharOffset, DocCharOffset)> for SubBuffer {
 
 #[cfg(test)]
 mod test {
-    use crate::buffer::{SubBuffer, SubMutation};
+    use crate::buffer::{Buffer, SubBuffer, SubMutation};
     use crate::input::cursor::Cursor;
 
     #[test]
@@ -622,4 +692,77 @@ mod test {
             assert!(text_updated);
         }
     }
+
+    #[test]
+    fn undo_batch() {
+        let mut buffer: Buffer = "cat cat".into();
+        let mut debug = Default::default();
+        let mut appearance = Default::default();
+
+        // typing just before and just after the batch isn't part of its undo step
+        buffer.apply(
+            vec![
+                SubMutation::Cursor { cursor: 7.into() },
+                SubMutation::Insert { text: "!".to_string(), advance_cursor: true },
+            ],
+            &mut debug,
+            &mut appearance,
+        );
+        buffer.apply_batch(
+            vec![
+                SubMutation::Cursor { cursor: (0, 3).into() },
+                SubMutation::Insert { text: "dog".to_string(), advance_cursor: true },
+                SubMutation::Cursor { cursor: (4, 7).into() },
+                SubMutation::Insert { text: "dog".to_string(), advance_cursor: true },
+            ],
+            &mut debug,
+            &mut appearance,
+        );
+        buffer.apply(
+            vec![SubMutation::Insert { text: "?".to_string(), advance_cursor: true }],
+            &mut debug,
+            &mut appearance,
+        );
+        assert_eq!(buffer.current.text, "dog dog?!");
+
+        buffer.undo(&mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "dog dog!");
+        buffer.undo(&mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "cat cat!");
+        buffer.redo(&mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "dog dog!");
+    }
+
+    #[test]
+    fn apply_remote() {
+        let mut buffer: Buffer = "hello world".into();
+        let mut debug = Default::default();
+        let mut appearance = Default::default();
+
+        buffer.apply(
+            vec![
+                SubMutation::Cursor { cursor: 11.into() },
+                SubMutation::Insert { text: "!".to_string(), advance_cursor: true },
+            ],
+            &mut debug,
+            &mut appearance,
+        );
+        buffer.apply_remote((0.into(), 5.into()), "goodbye", &mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "goodbye world!");
+        assert_eq!(buffer.current.cursor.selection, (14.into(), 14.into()));
+
+        // local modifications can't be undone past a remote edit
+        buffer.undo(&mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "goodbye world!");
+    }
+
+    #[test]
+    fn apply_remote_within_grapheme() {
+        let mut buffer: Buffer = "cafe\u{301}".into();
+        let mut debug = Default::default();
+        let mut appearance = Default::default();
+
+        buffer.apply_remote((4.into(), 4.into()), "x", &mut debug, &mut appearance);
+        assert_eq!(buffer.current.text, "cafex\u{301}");
+    }
 }
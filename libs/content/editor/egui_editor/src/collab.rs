@@ -0,0 +1,148 @@
+use crate::buffer::SubBuffer;
+use crate::offset_types::{DocByteOffset, DocCharOffset};
+use lb::{CollabSession, CoreError, TextEdit, Uuid};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// how often edits are exchanged with the document's other editors
+const SYNC_INTERVAL: Duration = Duration::from_millis(500);
+
+/// how long to wait before trying again when edits can't be exchanged e.g. while offline
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Edits a shared document together with its other editors as they type. Edits are exchanged
+/// with the server on a background thread, which stops when this is dropped.
+pub struct Collab {
+    session: Arc<Mutex<Option<CollabSession>>>,
+
+    /// the editor's text when the session was started, which edits made before it was joined are
+    /// relative to
+    base: String,
+
+    /// whether the editor's edits made before the session was joined have been merged into it
+    joined: bool,
+
+    stop: Arc<AtomicBool>,
+}
+
+/// Another editor's selection, drawn with their username
+pub struct RemoteCursor {
+    pub username: String,
+    pub selection: (DocCharOffset, DocCharOffset),
+}
+
+impl Collab {
+    pub fn start(core: lb::Core, id: Uuid, text: &str, ctx: egui::Context) -> Self {
+        let session: Arc<Mutex<Option<CollabSession>>> = Default::default();
+        let stop: Arc<AtomicBool> = Default::default();
+        let result = Self {
+            session: session.clone(),
+            base: text.to_string(),
+            joined: false,
+            stop: stop.clone(),
+        };
+
+        thread::spawn(move || {
+            let joined = loop {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                match core.start_collab(id) {
+                    Ok(joined) => break joined,
+                    Err(err) if matches!(err.kind, CoreError::FileNotDocument) => return,
+                    // e.g. offline, or a reader waiting for a writer to start a session
+                    Err(_) => thread::sleep(RETRY_INTERVAL),
+                }
+            };
+            *session.lock().unwrap() = Some(joined.clone());
+            ctx.request_repaint();
+
+            while !stop.load(Ordering::Relaxed) {
+                match core.sync_collab(&joined) {
+                    Ok(received) => {
+                        if received {
+                            ctx.request_repaint();
+                        }
+                        thread::sleep(SYNC_INTERVAL);
+                    }
+                    Err(_) => thread::sleep(RETRY_INTERVAL),
+                }
+            }
+
+            // others stop seeing this editor's cursor now rather than when it times out
+            let _ = core.leave_collab(&joined);
+        });
+
+        result
+    }
+
+    /// Records the editor's edits to `text` if it was updated, then returns the edits other editors
+    /// made, in order, as byte ranges of the text as of each edit. When the session is first joined,
+    /// which can be long after it was started e.g. while offline, the edits made to the text since
+    /// it was started are merged with the session's text.
+    pub fn update(&mut self, text: &str, text_updated: bool) -> Vec<TextEdit> {
+        let session = self.session.lock().unwrap();
+        let Some(session) = session.as_ref() else {
+            return Vec::new();
+        };
+
+        let edits = if !self.joined {
+            self.joined = true;
+            session.receive();
+            session.merge(&self.base, text);
+            vec![TextEdit::between(text, &session.text())]
+        } else {
+            if text_updated {
+                let edit = TextEdit::between(&session.text(), text);
+                session.replace(edit.range, &edit.text);
+            }
+            session.receive()
+        };
+
+        edits
+            .into_iter()
+            .filter(|edit| edit.range.0 != edit.range.1 || !edit.text.is_empty())
+            .collect()
+    }
+
+    /// Shows the other editors the buffer's selection; the buffer is expected to match the session
+    pub fn set_selection(&self, buffer: &SubBuffer) {
+        if let Some(session) = self.session.lock().unwrap().as_ref() {
+            let selection = buffer.segs.range_to_byte(buffer.cursor.selection);
+            session.set_selection((selection.0 .0, selection.1 .0));
+        }
+    }
+
+    /// The other editors' selections in the buffer, which is expected to match the session
+    pub fn cursors(&self, buffer: &SubBuffer) -> Vec<RemoteCursor> {
+        let session = self.session.lock().unwrap();
+        let Some(session) = session.as_ref() else {
+            return Vec::new();
+        };
+
+        // graphemes can be split by other editors' edits, so offsets are rounded to a grapheme
+        let to_char = |offset: usize| {
+            let graphemes = &buffer.segs.grapheme_indexes;
+            match graphemes.binary_search(&DocByteOffset(offset)) {
+                Ok(i) => DocCharOffset(i),
+                Err(i) => DocCharOffset(i.saturating_sub(1)),
+            }
+        };
+        session
+            .cursors()
+            .into_iter()
+            .map(|cursor| RemoteCursor {
+                username: cursor.username,
+                selection: (to_char(cursor.selection.0), to_char(cursor.selection.1)),
+            })
+            .collect()
+    }
+}
+
+impl Drop for Collab {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::ast::Ast;
+use crate::buffer::SubBuffer;
+use crate::offset_types::{DocByteOffset, RangeExt};
+use crate::style::{BlockNode, MarkdownNode};
+use lb::ConflictHunk;
+
+/// The hunks left in the document by a conflicting merge, leaving out marker-like lines in code
+/// blocks, which are more likely to be an example of a conflict than a conflict
+pub fn calc(ast: &Ast, buffer: &SubBuffer) -> Vec<ConflictHunk> {
+    let code_blocks = ast
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == MarkdownNode::Block(BlockNode::Code))
+        .map(|node| node.range)
+        .collect::<Vec<_>>();
+
+    lb::find_conflict_hunks(&buffer.text)
+        .into_iter()
+        .filter(|hunk| {
+            let start = buffer.segs.offset_to_char(DocByteOffset(hunk.range.start));
+            !code_blocks
+                .iter()
+                .any(|code_block| code_block.contains(start, true, false))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::buffer::SubBuffer;
+
+    #[test]
+    fn test_conflicts() {
+        let buffer: SubBuffer = "<<<<<<< mine\na\n=======\nb\n>>>>>>> theirs\n".into();
+        let ast = crate::ast::calc(&buffer);
+        let conflicts = super::calc(&ast, &buffer);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].mine, "a\n");
+        assert_eq!(conflicts[0].theirs, "b\n");
+    }
+
+    #[test]
+    fn test_conflicts_in_code_block() {
+        let buffer: SubBuffer =
+            "how a conflict looks:\n\n```\n<<<<<<< mine\na\n=======\nb\n>>>>>>> theirs\n```\n"
+                .into();
+        let ast = crate::ast::calc(&buffer);
+
+        assert!(super::calc(&ast, &buffer).is_empty());
+    }
+}
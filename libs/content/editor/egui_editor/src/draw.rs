@@ -2,12 +2,14 @@ use crate::appearance::{GRAY, YELLOW};
 use crate::bounds::RangesExt;
 use crate::images::ImageState;
 use crate::input::canonical::{Location, Modification, Region};
+use crate::input::cursor::Cursor;
 use crate::layouts::Annotation;
-use crate::offset_types::RangeExt;
+use crate::offset_types::{DocByteOffset, RangeExt};
 use crate::style::{BlockNode, InlineNode, ListItem, MarkdownNode, RenderStyle};
 use crate::Editor;
 use egui::text::LayoutJob;
-use egui::{Align2, Color32, FontId, Pos2, Rect, Rounding, Sense, Stroke, Ui, Vec2};
+use egui::{Align2, Color32, FontId, Layout, Pos2, Rect, Rounding, Sense, Shape, Stroke, Ui, Vec2};
+use lb::ConflictChoice;
 use pulldown_cmark::HeadingLevel;
 
 impl Editor {
@@ -91,6 +93,22 @@ impl Editor {
                 }
             }
 
+            // draw table borders
+            if let Some(table_row) = &galley.table_row {
+                let stroke = Stroke::new(1.0, self.appearance.table_border());
+                let (top, bottom) = (galley.galley_location.min.y, galley.galley_location.max.y);
+                for separator in &table_row.separators {
+                    let x = galley.text_location.x + separator;
+                    ui.painter()
+                        .line_segment([Pos2 { x, y: top }, Pos2 { x, y: bottom }], stroke);
+                }
+                if table_row.header {
+                    let min = Pos2 { x: galley.text_location.x, y: bottom };
+                    let max = Pos2 { x: galley.text_location.x + table_row.width, y: bottom };
+                    ui.painter().line_segment([min, max], stroke);
+                }
+            }
+
             // draw images
             if let Some(image) = &galley.image {
                 match &image.image_state {
@@ -116,6 +134,13 @@ impl Editor {
                 }
             }
 
+            // draw math, tinted the color of text
+            for math in &galley.math {
+                let uv = Rect { min: Pos2 { x: 0.0, y: 0.0 }, max: Pos2 { x: 1.0, y: 1.0 } };
+                ui.painter()
+                    .image(math.texture, math.location, uv, self.appearance.text());
+            }
+
             // draw text
             ui.painter()
                 .galley(galley.text_location, galley.galley.clone());
@@ -130,6 +155,176 @@ impl Editor {
         ui.allocate_exact_size(ui_size, Sense::hover());
     }
 
+    /// Highlights the hunks left in the document by a conflicting merge and shows buttons on each
+    /// hunk's first line that replace the hunk with one or both versions.
+    pub fn draw_conflicts(&mut self, ui: &mut Ui) {
+        let mut resolutions = Vec::new();
+        for hunk in &self.conflicts {
+            let range = self
+                .buffer
+                .current
+                .segs
+                .range_to_char((DocByteOffset(hunk.range.start), DocByteOffset(hunk.range.end)));
+            let start_line = Cursor::from(range.start()).start_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            let end_line = Cursor::from(range.end() - 1).start_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            let rect = Rect {
+                min: Pos2 { x: ui.max_rect().min.x, y: start_line[0].y },
+                max: Pos2 { x: ui.max_rect().max.x, y: end_line[1].y },
+            };
+            ui.painter()
+                .rect_filled(rect, Rounding::same(3.0), self.appearance.conflict_bg());
+
+            let buttons_rect = Rect {
+                min: Pos2 { x: rect.min.x, y: start_line[0].y },
+                max: Pos2 { x: rect.max.x, y: start_line[1].y },
+            };
+            let mut buttons_ui =
+                ui.child_ui(buttons_rect, Layout::right_to_left(egui::Align::Center));
+            for (label, choice) in [
+                ("Keep Both", ConflictChoice::KeepBoth),
+                ("Keep Theirs", ConflictChoice::KeepTheirs),
+                ("Keep Mine", ConflictChoice::KeepMine),
+            ] {
+                if buttons_ui.small_button(label).clicked() {
+                    resolutions.push((range, hunk.resolve(choice)));
+                }
+            }
+        }
+
+        for (range, text) in resolutions {
+            self.custom_events.push(Modification::Replace {
+                region: Region::BetweenLocations {
+                    start: Location::DocCharOffset(range.start()),
+                    end: Location::DocCharOffset(range.end()),
+                },
+                text,
+            });
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Highlights the text matching the find bar's query, line by line
+    pub fn draw_find_matches(&self, ui: &mut Ui) {
+        for &range in &self.find.matches {
+            let (first_line, last_line) = self.bounds.lines.find_intersecting(range, false);
+            for &line in &self.bounds.lines[first_line..last_line] {
+                let start = Cursor::from(range.start().max(line.start())).start_line(
+                    &self.galleys,
+                    &self.bounds.text,
+                    &self.appearance,
+                );
+                let end = Cursor::from(range.end().min(line.end())).start_line(
+                    &self.galleys,
+                    &self.bounds.text,
+                    &self.appearance,
+                );
+                let rect = Rect { min: start[0], max: end[1] };
+                ui.painter().rect_filled(
+                    rect,
+                    Rounding::same(2.0),
+                    self.appearance.find_match_bg(),
+                );
+            }
+        }
+    }
+
+    /// Draws the cursors and selections of the document's other editors, labeled with their names
+    pub fn draw_remote_cursors(&self, ui: &mut Ui) {
+        let Some(collab) = &self.collab else {
+            return;
+        };
+        for cursor in collab.cursors(&self.buffer.current) {
+            let color = self.appearance.collaborator(&cursor.username);
+
+            let range = (cursor.selection.start(), cursor.selection.end());
+            if !range.is_empty() {
+                let (first_line, last_line) = self.bounds.lines.find_intersecting(range, false);
+                for &line in &self.bounds.lines[first_line..last_line] {
+                    let start = Cursor::from(range.start().max(line.start())).start_line(
+                        &self.galleys,
+                        &self.bounds.text,
+                        &self.appearance,
+                    );
+                    let end = Cursor::from(range.end().min(line.end())).start_line(
+                        &self.galleys,
+                        &self.bounds.text,
+                        &self.appearance,
+                    );
+                    ui.painter().rect_filled(
+                        Rect { min: start[0], max: end[1] },
+                        Rounding::same(2.0),
+                        self.appearance.collaborator_selection_bg(&cursor.username),
+                    );
+                }
+            }
+
+            let caret = Cursor::from(cursor.selection.1).start_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            ui.painter()
+                .line_segment(caret, Stroke { width: 2.0, color });
+
+            let label = ui.painter().layout_no_wrap(
+                cursor.username,
+                FontId::proportional(10.0),
+                Color32::WHITE,
+            );
+            let label_rect = Align2::LEFT_BOTTOM
+                .anchor_rect(Rect::from_min_size(caret[0], label.size() + Vec2::new(4.0, 0.0)));
+            ui.painter()
+                .rect_filled(label_rect, Rounding::same(2.0), color);
+            ui.painter()
+                .galley(label_rect.min + Vec2::new(2.0, 0.0), label);
+        }
+    }
+
+    /// Underlines misspelled words with a squiggle, except the word being typed
+    pub fn draw_misspellings(&self, ui: &mut Ui) {
+        let cursor = self.buffer.current.cursor.selection;
+        let stroke = Stroke::new(1.0, self.appearance.misspelling());
+        for &range in &self.spellcheck.misspellings {
+            if cursor.is_empty() && cursor.1 == range.1 {
+                continue;
+            }
+
+            let start_line = Cursor::from(range.start()).start_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            let end_line = Cursor::from(range.end()).start_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            if start_line[1].y != end_line[1].y {
+                continue; // words split across lines aren't underlined
+            }
+
+            let (amplitude, period) = (1.5, 4.0);
+            let y = start_line[1].y - amplitude;
+            let mut points = Vec::new();
+            let mut x = start_line[1].x;
+            while x < end_line[1].x {
+                let phase = ((x - start_line[1].x) / (period / 2.0)) as usize % 2;
+                points.push(Pos2 { x, y: if phase == 0 { y - amplitude } else { y + amplitude } });
+                x += period / 2.0;
+            }
+            points.push(Pos2 { x: end_line[1].x, y });
+            ui.painter().add(Shape::line(points, stroke));
+        }
+    }
+
     pub fn draw_image_placeholder(
         &self, ui: &mut Ui, location: Rect, icon: &'static str, caption: &'static str,
     ) {
@@ -187,6 +382,7 @@ impl Editor {
                         stroke.color = self.appearance.code();
                     }
                     MarkdownNode::Inline(InlineNode::Link(..))
+                    | MarkdownNode::Inline(InlineNode::WikiLink(..))
                     | MarkdownNode::Inline(InlineNode::Image(..)) => {
                         stroke.color = self.appearance.link();
                     }
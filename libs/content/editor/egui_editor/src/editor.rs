@@ -4,26 +4,40 @@ use serde::Serialize;
 use std::ffi::{c_char, CString};
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 use std::ptr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::{cmp, mem};
 
 use egui::os::OperatingSystem;
 use egui::{Color32, Context, Event, FontDefinitions, Frame, Pos2, Rect, Sense, Ui, Vec2};
+use lb::ConflictHunk;
 
 use crate::appearance::Appearance;
 use crate::ast::Ast;
+use crate::attachments::Attachments;
 use crate::bounds::{BoundCase, Bounds};
 use crate::buffer::Buffer;
+use crate::collab::Collab;
 use crate::debug::DebugInfo;
+use crate::find::Find;
 use crate::galleys::Galleys;
+use crate::highlighting::{HighlightCache, Highlighter, SyntectHighlighter};
 use crate::images::ImageCache;
-use crate::input::canonical::{Bound, Modification, Offset, Region};
+use crate::input::canonical::{Bound, Location, Modification, Offset, Region};
 use crate::input::click_checker::{ClickChecker, EditorClickChecker};
 use crate::input::cursor::{Cursor, PointerState};
 use crate::input::events;
+use crate::math::MathCache;
 use crate::offset_types::{DocCharOffset, RangeExt};
+use crate::spellcheck::{
+    CustomWords, Dictionary, SpellcheckAction, SpellcheckCache, SpellcheckMenu,
+};
 use crate::style::{BlockNode, InlineNode, ListItem, MarkdownNode};
-use crate::{ast, bounds, galleys, images, register_fonts};
+use crate::wiki_links::{LinkAutocomplete, WikiLinkCache};
+use crate::{
+    ast, bounds, conflicts, galleys, highlighting, images, math, register_fonts, spellcheck,
+    wiki_links,
+};
 
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 #[repr(C)]
@@ -127,18 +141,35 @@ pub struct Editor {
 
     // config
     pub appearance: Appearance,
+    pub file_id: Option<lb::Uuid>, // the document being edited, which wiki links are relative to
+    pub highlighter: Box<dyn Highlighter>, // highlights code in fenced code blocks
+    pub dictionary: Option<Arc<Dictionary>>, // checks spelling; `None` disables spellcheck
+    pub collaborative: bool, // edits the document together with its other editors as they type
+    pub conflicted: bool, // sync merged the document with conflicting changes that aren't resolved
 
     // state
     pub buffer: Buffer,
     pub pointer_state: PointerState, // state of cursor not subject to undo history
     pub debug: DebugInfo,
     pub images: ImageCache,
+    pub math: MathCache,
     pub has_focus: bool,
+    pub link_autocomplete: LinkAutocomplete,
+    pub spellcheck_menu: SpellcheckMenu,
+    pub find: Find,
+    pub attachments: Attachments,
+    pub custom_words: CustomWords, // words the user added to their dictionary
+    pub pending_heading: Option<String>, // heading to select once the document is loaded
+    pub collab: Option<Collab>,
 
     // cached intermediate state
     pub ast: Ast,
     pub bounds: Bounds,
     pub galleys: Galleys,
+    pub conflicts: Vec<ConflictHunk>,
+    pub wiki_links: WikiLinkCache,
+    pub highlights: HighlightCache,
+    pub spellcheck: SpellcheckCache,
 
     // computed state from last frame
     pub ui_rect: Rect,
@@ -172,16 +203,33 @@ impl Editor {
             client: Default::default(),
 
             appearance: Default::default(),
+            file_id: None,
+            highlighter: Box::<SyntectHighlighter>::default(),
+            dictionary: spellcheck::system_dictionary(),
+            collaborative: false,
+            conflicted: false,
 
             buffer: "".into(),
             pointer_state: Default::default(),
             debug: Default::default(),
             images: Default::default(),
+            math: Default::default(),
             has_focus: true,
+            link_autocomplete: Default::default(),
+            spellcheck_menu: Default::default(),
+            find: Default::default(),
+            attachments: Default::default(),
+            custom_words: Default::default(),
+            pending_heading: None,
+            collab: None,
 
             ast: Default::default(),
             bounds: Default::default(),
             galleys: Default::default(),
+            conflicts: Default::default(),
+            wiki_links: Default::default(),
+            highlights: Default::default(),
+            spellcheck: Default::default(),
 
             ui_rect: Rect { min: Default::default(), max: Default::default() },
 
@@ -226,7 +274,16 @@ impl Editor {
         let mut surrender_focus = false;
         for event in &events {
             if let Event::PointerButton { pos, pressed: true, .. } = event {
-                if ui.is_enabled() && self.scroll_area_rect.contains(*pos) && self.has_focus {
+                let in_find = self
+                    .find
+                    .rect
+                    .map(|rect| rect.contains(*pos))
+                    .unwrap_or_default();
+                if ui.is_enabled()
+                    && self.scroll_area_rect.contains(*pos)
+                    && self.has_focus
+                    && !in_find
+                {
                     request_focus = true;
                 } else {
                     surrender_focus = true;
@@ -269,7 +326,8 @@ impl Editor {
         self.ui_rect = sao.inner_rect;
 
         // set focus again because egui clears it for our widget for some reason
-        if focus {
+        // (unless the find bar took it)
+        if focus && !self.find.has_focus {
             ui.memory_mut(|m| {
                 m.request_focus(id);
                 m.lock_focus(id, true);
@@ -300,8 +358,39 @@ impl Editor {
         // process events
         let (text_updated, selection_updated, pointer_offset_updated) = if self.initialized {
             if ui.memory(|m| m.has_focus(id)) {
-                let custom_events = mem::take(&mut self.custom_events);
-                self.process_events(events, &custom_events, touch_mode);
+                let mut custom_events = mem::take(&mut self.custom_events);
+                let events = self
+                    .link_autocomplete
+                    .handle_events(events, &mut custom_events);
+                let events = self.spellcheck_menu.handle_events(
+                    &events,
+                    &EditorClickChecker {
+                        ui_rect: self.ui_rect,
+                        galleys: &self.galleys,
+                        buffer: &self.buffer,
+                        ast: &self.ast,
+                        appearance: &self.appearance,
+                        bounds: &self.bounds,
+                        wiki_links: &self.wiki_links,
+                    },
+                    &self.spellcheck,
+                    &self.buffer.current,
+                    self.dictionary.as_deref(),
+                );
+                let events = self.find.handle_events(&events, &self.buffer.current);
+                let events = self.attachments.handle_events(
+                    ui,
+                    self.ui_rect,
+                    &events,
+                    &self.core,
+                    self.file_id,
+                );
+                custom_events.extend(self.attachments.take_inserted());
+                self.process_events(&events, &custom_events, touch_mode);
+                if !self.custom_events.is_empty() {
+                    // process follow-up events e.g. table alignment next frame
+                    ui.ctx().request_repaint();
+                }
                 if let Some(to_clipboard) = &self.maybe_to_clipboard {
                     ui.output_mut(|o| o.copied_text = to_clipboard.clone());
                 }
@@ -311,6 +400,11 @@ impl Editor {
                     });
                 }
                 (self.text_updated, self.selection_updated, self.pointer_offset_updated)
+            } else if self.find.has_focus && !self.custom_events.is_empty() {
+                // the find bar selects and replaces matches while it has focus
+                let custom_events = mem::take(&mut self.custom_events);
+                self.process_events(&[], &custom_events, touch_mode);
+                (self.text_updated, self.selection_updated, self.pointer_offset_updated)
             } else {
                 (false, false, false)
             }
@@ -328,6 +422,37 @@ impl Editor {
 
             (true, true, true)
         };
+
+        // exchange edits with the document's other editors
+        if self.collaborative && self.collab.is_none() && self.initialized {
+            if let Some(file_id) = self.file_id {
+                self.collab = Some(Collab::start(
+                    self.core.clone(),
+                    file_id,
+                    &self.buffer.current.text,
+                    ui.ctx().clone(),
+                ));
+            }
+        }
+        let text_updated = if let Some(collab) = &mut self.collab {
+            let edits = collab.update(&self.buffer.current.text, text_updated);
+            for edit in &edits {
+                self.buffer.apply_remote(
+                    (edit.range.0.into(), edit.range.1.into()),
+                    &edit.text,
+                    &mut self.debug,
+                    &mut self.appearance,
+                );
+            }
+            let text_updated = text_updated || !edits.is_empty();
+            if text_updated || selection_updated {
+                collab.set_selection(&self.buffer.current);
+            }
+            text_updated
+        } else {
+            text_updated
+        };
+
         let appearance_updated = {
             let capture_already_disabled = self
                 .appearance
@@ -340,10 +465,45 @@ impl Editor {
                     .markdown_capture_disabled_for_cursor_paragraph
         };
 
+        // load the words the user added to their dictionary
+        self.custom_words.load(&self.core, ui.ctx());
+        let custom_words_updated = self.custom_words.update();
+
         // recalculate dependent state
         if text_updated {
             self.ast = ast::calc(&self.buffer.current);
             self.bounds.ast = bounds::calc_ast(&self.ast);
+            self.bounds.tables = bounds::calc_tables(&self.ast);
+            self.bounds.cells = bounds::calc_cells(&self.ast);
+            self.conflicts = if self.conflicted {
+                conflicts::calc(&self.ast, &self.buffer.current)
+            } else {
+                Vec::new()
+            };
+            self.wiki_links =
+                wiki_links::calc(&self.ast, &self.wiki_links, &self.core, self.file_id);
+            self.highlights = highlighting::calc(
+                &self.ast,
+                &self.buffer.current,
+                &self.highlights,
+                self.highlighter.as_ref(),
+            );
+            self.spellcheck = spellcheck::calc(
+                &self.ast,
+                &self.buffer.current,
+                &self.spellcheck,
+                self.dictionary.as_deref(),
+                &self.custom_words.words,
+            );
+            self.find.update(&self.buffer.current);
+        } else if custom_words_updated {
+            self.spellcheck = spellcheck::calc(
+                &self.ast,
+                &self.buffer.current,
+                &self.spellcheck,
+                self.dictionary.as_deref(),
+                &self.custom_words.words,
+            );
         }
         if text_updated || appearance_updated {
             self.bounds.words = bounds::calc_words(
@@ -369,18 +529,44 @@ impl Editor {
                 bounds::calc_links(&self.buffer.current, &self.bounds.text, &self.ast);
         }
         if text_updated || selection_updated || theme_updated {
-            self.images = images::calc(&self.ast, &self.images, &self.client, &self.core, ui);
+            self.images =
+                images::calc(&self.ast, &self.images, &self.client, &self.core, self.file_id, ui);
+        }
+        if text_updated || appearance_updated {
+            self.math =
+                math::calc(&self.ast, &self.buffer.current, &self.math, &self.appearance, ui);
         }
         self.galleys = galleys::calc(
             &self.ast,
             &self.buffer.current,
             &self.bounds,
             &self.images,
+            &self.highlights,
+            &self.math,
             &self.appearance,
             self.hover_syntax_reveal_debounce_state,
             ui,
         );
         self.bounds.lines = bounds::calc_lines(&self.galleys, &self.bounds.ast, &self.bounds.text);
+        if text_updated || selection_updated {
+            self.link_autocomplete
+                .update(&self.buffer.current, &self.ast, &self.core);
+        }
+
+        // select a heading requested by a link once the document is loaded
+        if self.initialized
+            && (!self.buffer.is_empty() || self.debug.frame_count > 2)
+            && self.pending_heading.is_some()
+        {
+            if let Some(heading) = self.pending_heading.take() {
+                if let Some(offset) = self.heading_offset(&heading) {
+                    self.custom_events.push(Modification::Select {
+                        region: Region::Location(Location::DocCharOffset(offset)),
+                    });
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
         self.initialized = true;
 
         if self.images.any_loading()
@@ -393,9 +579,50 @@ impl Editor {
         }
 
         // draw
+        self.draw_conflicts(ui);
+        self.draw_find_matches(ui);
         self.draw_text(self.ui_rect.size(), ui, touch_mode);
+        self.draw_misspellings(ui);
+        self.draw_remote_cursors(ui);
         if ui.memory(|m| m.has_focus(id)) {
             self.draw_cursor(ui, touch_mode);
+
+            let cursor_end_line = self.buffer.current.cursor.end_line(
+                &self.galleys,
+                &self.bounds.text,
+                &self.appearance,
+            );
+            if let Some(completion) = self.link_autocomplete.show(ui, cursor_end_line[1]) {
+                self.custom_events.push(completion);
+                ui.ctx().request_repaint();
+            }
+            match self.spellcheck_menu.show(ui) {
+                Some(SpellcheckAction::Replace(replacement)) => {
+                    self.custom_events.push(replacement);
+                    ui.ctx().request_repaint();
+                }
+                Some(SpellcheckAction::AddToDictionary(word)) => {
+                    self.custom_words.add(&self.core, word);
+                    self.spellcheck = spellcheck::calc(
+                        &self.ast,
+                        &self.buffer.current,
+                        &self.spellcheck,
+                        self.dictionary.as_deref(),
+                        &self.custom_words.words,
+                    );
+                    ui.ctx().request_repaint();
+                }
+                None => {}
+            }
+        }
+        let find_was_open = self.find.open;
+        let find_modifications = self.find.show(ui, self.ui_rect, &self.buffer.current);
+        if !find_modifications.is_empty() {
+            self.custom_events.extend(find_modifications);
+            ui.ctx().request_repaint();
+        }
+        if find_was_open && !self.find.open {
+            ui.memory_mut(|m| m.request_focus(id));
         }
         if self.debug.draw_enabled {
             self.draw_debug(ui);
@@ -488,6 +715,7 @@ impl Editor {
                 ast: &self.ast,
                 appearance: &self.appearance,
                 bounds: &self.bounds,
+                wiki_links: &self.wiki_links,
             };
             let hovering_link = ui
                 .input(|r| r.pointer.hover_pos())
@@ -542,6 +770,7 @@ impl Editor {
             ast: &self.ast,
             appearance: &self.appearance,
             bounds: &self.bounds,
+            wiki_links: &self.wiki_links,
         };
         let combined_events = events::combine(
             events,
@@ -561,6 +790,14 @@ impl Editor {
             &mut self.appearance,
         );
 
+        // line up the pipes of edited tables once the edit has been parsed
+        let undo_or_redo = combined_events
+            .iter()
+            .any(|m| matches!(m, Modification::Undo | Modification::Redo));
+        if text_updated && !undo_or_redo {
+            self.custom_events.push(Modification::AlignTable);
+        }
+
         // in touch mode, check if we should open the menu
         let click_checker = EditorClickChecker {
             ui_rect: self.ui_rect,
@@ -569,6 +806,7 @@ impl Editor {
             ast: &self.ast,
             appearance: &self.appearance,
             bounds: &self.bounds,
+            wiki_links: &self.wiki_links,
         };
         let pointer_offset = self.pointer_state.pointer_pos.and_then(|pos| {
             if (&click_checker).text(pos).is_some() {
@@ -662,6 +900,26 @@ impl Editor {
         });
     }
 
+    /// Selects the start of the first heading with the given text (ignoring case) once the
+    /// document is loaded. Used to follow links to headings.
+    pub fn select_heading(&mut self, heading: &str) {
+        self.pending_heading = Some(heading.to_string());
+    }
+
+    fn heading_offset(&self, heading: &str) -> Option<DocCharOffset> {
+        let heading = heading.trim();
+        self.ast
+            .nodes
+            .iter()
+            .filter(|node| matches!(node.node_type, MarkdownNode::Block(BlockNode::Heading(..))))
+            .find(|node| {
+                self.buffer.current[node.text_range]
+                    .trim()
+                    .eq_ignore_ascii_case(heading)
+            })
+            .map(|node| node.text_range.0)
+    }
+
     pub fn set_font(&self, ctx: &Context) {
         let mut fonts = FontDefinitions::default();
         register_fonts(&mut fonts);
@@ -0,0 +1,399 @@
+use crate::buffer::SubBuffer;
+use crate::input::canonical::{Location, Modification, Region};
+use crate::offset_types::{DocCharOffset, RangeExt};
+use egui::{Align2, Color32, Event, Key, Order, Rect, RichText, TextEdit, Ui, Vec2};
+use regex::{Regex, RegexBuilder};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Finds (and replaces) text in the document, using a bar opened with Cmd/Ctrl+F (or Cmd/Ctrl+H
+/// to also replace)
+#[derive(Default)]
+pub struct Find {
+    pub open: bool,
+    pub replacing: bool,
+    pub query: String,
+    pub replacement: String,
+    pub match_case: bool,
+    pub regex: bool,
+
+    /// matches of the query, in order
+    pub matches: Vec<(DocCharOffset, DocCharOffset)>,
+
+    /// why the query isn't a valid regex, if it isn't
+    pub error: Option<String>,
+
+    /// where the bar was shown last frame; clicks there are for the bar, not the text
+    pub rect: Option<Rect>,
+
+    /// the query field takes focus the next time the bar is shown
+    pub focus_query: bool,
+
+    /// whether one of the bar's fields had focus last frame, in which case the editor doesn't
+    pub has_focus: bool,
+}
+
+impl Find {
+    /// Opens the bar for the shortcuts that open it, returning the rest of the events
+    pub fn handle_events(&mut self, events: &[Event], buffer: &SubBuffer) -> Vec<Event> {
+        let mut result = Vec::new();
+        for event in events {
+            match event {
+                Event::Key { key: key @ (Key::F | Key::H), pressed, modifiers, .. }
+                    if modifiers.command =>
+                {
+                    if *pressed {
+                        self.open(*key == Key::H, buffer);
+                    }
+                }
+                Event::Key { key: Key::Escape, pressed, .. } if self.open => {
+                    if *pressed {
+                        self.close();
+                    }
+                }
+                _ => result.push(event.clone()),
+            }
+        }
+        result
+    }
+
+    /// Opens the bar, searching for the selected text if any
+    pub fn open(&mut self, replacing: bool, buffer: &SubBuffer) {
+        if let Some(selection) = buffer.cursor.selection() {
+            let selected = &buffer[(selection.start, selection.end)];
+            if !selected.contains('\n') {
+                self.query =
+                    if self.regex { regex::escape(selected) } else { selected.to_string() };
+            }
+        }
+        self.open = true;
+        self.replacing = replacing;
+        self.focus_query = true;
+        self.update(buffer);
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.has_focus = false;
+        self.matches.clear();
+        self.error = None;
+        self.rect = None;
+    }
+
+    /// Finds the query in the document again e.g. after the text changed
+    pub fn update(&mut self, buffer: &SubBuffer) {
+        self.matches.clear();
+        self.error = None;
+        if !self.open || self.query.is_empty() {
+            return;
+        }
+        match self.compile() {
+            Ok(regex) => {
+                self.matches = regex
+                    .find_iter(&buffer.text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| {
+                        buffer
+                            .segs
+                            .range_to_char((m.start().into(), m.end().into()))
+                    })
+                    .collect()
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    fn compile(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex { self.query.clone() } else { regex::escape(&self.query) };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.match_case)
+            .multi_line(true)
+            .build()
+    }
+
+    /// The first match after the selection, or the last match before it if `backwards`, wrapping
+    /// around the document
+    pub fn next_match(
+        &self, selection: (DocCharOffset, DocCharOffset), backwards: bool,
+    ) -> Option<(DocCharOffset, DocCharOffset)> {
+        let (start, end) = (selection.start(), selection.end());
+        if backwards {
+            let mut before = self.matches.iter().rev().filter(|m| m.1 <= start);
+            before.next().or(self.matches.last()).copied()
+        } else {
+            let mut after = self.matches.iter().filter(|m| m.0 >= end);
+            after.next().or(self.matches.first()).copied()
+        }
+    }
+
+    /// The text that replaces each match. When searching by regex, `$1` or `${name}` in the
+    /// replacement is replaced with what the group matched.
+    pub fn replacements(
+        &self, buffer: &SubBuffer,
+    ) -> Vec<((DocCharOffset, DocCharOffset), String)> {
+        let Ok(regex) = self.compile() else {
+            return Vec::new();
+        };
+        regex
+            .captures_iter(&buffer.text)
+            .filter_map(|captures| {
+                let m = captures.get(0)?;
+                if m.is_empty() {
+                    return None;
+                }
+                let range = buffer
+                    .segs
+                    .range_to_char((m.start().into(), m.end().into()));
+                let text = if self.regex {
+                    let mut text = String::new();
+                    captures.expand(&self.replacement, &mut text);
+                    text
+                } else {
+                    self.replacement.clone()
+                };
+                Some((range, text))
+            })
+            .collect()
+    }
+
+    /// Replaces the selected match, if the selection is a match, and selects the next one
+    fn replace(&self, buffer: &SubBuffer) -> Vec<Modification> {
+        let selection = buffer.cursor.selection;
+        let selection = (selection.start(), selection.end());
+        let Some((range, text)) = self
+            .replacements(buffer)
+            .into_iter()
+            .find(|(range, _)| *range == selection)
+        else {
+            return self
+                .next_match(selection, false)
+                .map(select)
+                .into_iter()
+                .collect();
+        };
+
+        let mut result = vec![Modification::Replace {
+            region: Region::BetweenLocations {
+                start: Location::DocCharOffset(range.0),
+                end: Location::DocCharOffset(range.1),
+            },
+            text: text.clone(),
+        }];
+        if let Some(next) = self.next_match(range, false).filter(|&next| next != range) {
+            // matches after the replaced one move by the difference in length
+            let next = if next.0 >= range.1 {
+                let replaced_len = range.1 - range.0;
+                let replacement_len = text.graphemes(true).count();
+                (next.0 + replacement_len - replaced_len, next.1 + replacement_len - replaced_len)
+            } else {
+                next
+            };
+            result.push(select(next));
+        }
+        result
+    }
+
+    /// Shows the bar in the top right of `rect` if it's open, returning the modifications made
+    /// with it: selecting matches and replacing them
+    pub fn show(&mut self, ui: &mut Ui, rect: Rect, buffer: &SubBuffer) -> Vec<Modification> {
+        if !self.open {
+            self.rect = None;
+            self.has_focus = false;
+            return Vec::new();
+        }
+
+        let mut result = Vec::new();
+        let mut query_changed = false;
+        let mut close = false;
+        let response = egui::Area::new(ui.id().with("find"))
+            .order(Order::Foreground)
+            .pivot(Align2::RIGHT_TOP)
+            .fixed_pos(rect.right_top() + Vec2 { x: -20.0, y: 10.0 })
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style())
+                    .show(ui, |ui| {
+                        let (enter, shift, escape) = ui.input(|i| {
+                            (
+                                i.key_pressed(Key::Enter),
+                                i.modifiers.shift,
+                                i.key_pressed(Key::Escape),
+                            )
+                        });
+
+                        let query = ui
+                            .horizontal(|ui| {
+                                let query = ui.add(
+                                    TextEdit::singleline(&mut self.query)
+                                        .hint_text("Find")
+                                        .desired_width(200.0),
+                                );
+                                query_changed |= query.changed();
+                                query_changed |= ui
+                                    .toggle_value(&mut self.match_case, "Aa")
+                                    .on_hover_text("Match case")
+                                    .changed();
+                                query_changed |= ui
+                                    .toggle_value(&mut self.regex, ".*")
+                                    .on_hover_text("Use regular expression")
+                                    .changed();
+
+                                let count = match (&self.error, self.matches.len()) {
+                                    (Some(_), _) => RichText::new("Invalid").color(Color32::RED),
+                                    (None, 0) if !self.query.is_empty() => {
+                                        RichText::new("No results")
+                                    }
+                                    (None, count) => RichText::new(format!("{} found", count)),
+                                };
+                                ui.label(count)
+                                    .on_hover_text(self.error.clone().unwrap_or_default());
+
+                                let selection = buffer.cursor.selection;
+                                if ui.button("↑").on_hover_text("Previous match").clicked() {
+                                    result.extend(self.next_match(selection, true).map(select));
+                                }
+                                if ui.button("↓").on_hover_text("Next match").clicked() {
+                                    result.extend(self.next_match(selection, false).map(select));
+                                }
+                                if ui.button("✕").clicked() {
+                                    close = true;
+                                }
+                                query
+                            })
+                            .inner;
+
+                        // enter in the query field goes to the next match, or the previous with shift
+                        if query.lost_focus() && enter {
+                            let selection = buffer.cursor.selection;
+                            result.extend(self.next_match(selection, shift).map(select));
+                            self.focus_query = true;
+                        }
+                        if self.focus_query {
+                            query.request_focus();
+                            self.focus_query = false;
+                        }
+                        let mut focused = query.has_focus() || query.lost_focus();
+
+                        if self.replacing {
+                            ui.horizontal(|ui| {
+                                let replacement = ui.add(
+                                    TextEdit::singleline(&mut self.replacement)
+                                        .hint_text("Replace")
+                                        .desired_width(200.0),
+                                );
+                                focused |= replacement.has_focus() || replacement.lost_focus();
+                                if ui.button("Replace").clicked()
+                                    || (replacement.lost_focus() && enter)
+                                {
+                                    result.extend(self.replace(buffer));
+                                    if replacement.lost_focus() {
+                                        replacement.request_focus();
+                                    }
+                                }
+                                if ui.button("Replace All").clicked() {
+                                    let replacements = self.replacements(buffer);
+                                    if !replacements.is_empty() {
+                                        result.push(Modification::ReplaceAll { replacements });
+                                    }
+                                }
+                            });
+                        }
+
+                        close |= focused && escape;
+                        focused
+                    })
+                    .inner
+            });
+        self.rect = Some(response.response.rect);
+        self.has_focus = response.inner;
+
+        if close {
+            self.close();
+        } else if query_changed {
+            // select the first match at or after the cursor as the query is typed
+            self.update(buffer);
+            let start = buffer.cursor.selection.start();
+            result.extend(self.next_match((start, start), false).map(select));
+        }
+        if !result.is_empty() {
+            ui.ctx().request_repaint();
+        }
+        result
+    }
+}
+
+fn select(range: (DocCharOffset, DocCharOffset)) -> Modification {
+    Modification::Select {
+        region: Region::BetweenLocations {
+            start: Location::DocCharOffset(range.0),
+            end: Location::DocCharOffset(range.1),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Find;
+    use crate::buffer::SubBuffer;
+    use crate::offset_types::DocCharOffset;
+
+    fn search(text: &str, query: &str, match_case: bool, regex: bool) -> (SubBuffer, Find) {
+        let buffer: SubBuffer = text.into();
+        let mut find =
+            Find { open: true, query: query.to_string(), match_case, regex, ..Default::default() };
+        find.update(&buffer);
+        (buffer, find)
+    }
+
+    fn matches<'a>(buffer: &'a SubBuffer, find: &Find) -> Vec<&'a str> {
+        find.matches.iter().map(|&range| &buffer[range]).collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let (buffer, find) = search("Cat cat CAT c.t", "cat", false, false);
+        assert_eq!(matches(&buffer, &find), vec!["Cat", "cat", "CAT"]);
+
+        let (buffer, find) = search("Cat cat CAT c.t", "cat", true, false);
+        assert_eq!(matches(&buffer, &find), vec!["cat"]);
+
+        let (buffer, find) = search("Cat cat CAT c.t", "c.t", true, false);
+        assert_eq!(matches(&buffer, &find), vec!["c.t"]);
+
+        let (buffer, find) = search("Cat cat CAT c.t", "^c.t|c.t$", false, true);
+        assert_eq!(matches(&buffer, &find), vec!["Cat", "c.t"]);
+
+        let (_, find) = search("text", "(", false, true);
+        assert!(find.matches.is_empty());
+        assert!(find.error.is_some());
+    }
+
+    #[test]
+    fn test_next_match() {
+        let (_, find) = search("a a a", "a", false, false);
+        let offset = |i: usize| (DocCharOffset(i), DocCharOffset(i));
+
+        assert_eq!(find.next_match(offset(0), false), Some(find.matches[0]));
+        assert_eq!(find.next_match(find.matches[0], false), Some(find.matches[1]));
+        assert_eq!(find.next_match(find.matches[2], false), Some(find.matches[0]));
+        assert_eq!(find.next_match(find.matches[1], true), Some(find.matches[0]));
+        assert_eq!(find.next_match(offset(0), true), Some(find.matches[2]));
+    }
+
+    #[test]
+    fn test_replacements() {
+        let (buffer, mut find) =
+            search("2024-05-01 and 2023-12-31", r"(\d+)-(\d+)-(\d+)", false, true);
+        find.replacement = "$3/$2/$1".to_string();
+        let replacements = find
+            .replacements(&buffer)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect::<Vec<_>>();
+        assert_eq!(replacements, vec!["01/05/2024", "31/12/2023"]);
+
+        // without regex, the replacement is literal
+        find.regex = false;
+        find.query = "and".to_string();
+        let replacements = find.replacements(&buffer);
+        assert_eq!(replacements, vec![((11.into(), 14.into()), "$3/$2/$1".to_string())]);
+    }
+}
@@ -1,16 +1,19 @@
 use crate::appearance::Appearance;
-use crate::ast::{Ast, AstTextRangeType};
+use crate::ast::{Ast, AstTextRange, AstTextRangeType};
 use crate::bounds::{self, Bounds, RangesExt, Text};
 use crate::buffer::SubBuffer;
 use crate::editor::HoverSyntaxRevealDebounceState;
+use crate::highlighting::HighlightCache;
 use crate::images::{ImageCache, ImageState};
-use crate::layouts::{Annotation, LayoutJobInfo};
+use crate::layouts::{Annotation, LayoutJobInfo, MathLayout, TableCellLayout, TableRowLayout};
+use crate::math::MathCache;
 use crate::offset_types::{DocCharOffset, RangeExt, RelCharOffset};
-use crate::style::{MarkdownNode, RenderStyle};
+use crate::style::{BlockNode, ColumnAlignment, InlineNode, MarkdownNode, RenderStyle, TableItem};
 use crate::Editor;
 use egui::epaint::text::cursor::Cursor;
 use egui::text::{CCursor, LayoutJob};
-use egui::{Galley, Pos2, Rect, Sense, TextFormat, Ui, Vec2};
+use egui::{Galley, Pos2, Rect, Sense, TextFormat, TextureId, Ui, Vec2};
+use std::collections::HashMap;
 use std::mem;
 use std::ops::{Deref, Index};
 use std::sync::Arc;
@@ -35,6 +38,10 @@ pub struct GalleyInfo {
     pub image: Option<ImageInfo>,
 
     pub annotation_text_format: TextFormat,
+
+    pub table_row: Option<TableRowLayout>,
+
+    pub math: Vec<MathInfo>,
 }
 
 #[derive(Debug)]
@@ -43,20 +50,31 @@ pub struct ImageInfo {
     pub image_state: ImageState,
 }
 
+#[derive(Debug)]
+pub struct MathInfo {
+    pub location: Rect,
+    pub texture: TextureId,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn calc(
-    ast: &Ast, buffer: &SubBuffer, bounds: &Bounds, images: &ImageCache, appearance: &Appearance,
+    ast: &Ast, buffer: &SubBuffer, bounds: &Bounds, images: &ImageCache,
+    highlights: &HighlightCache, math: &MathCache, appearance: &Appearance,
     hover_syntax_reveal_debounce_state: HoverSyntaxRevealDebounceState, ui: &mut Ui,
 ) -> Galleys {
     let cursor_paragraphs = bounds
         .paragraphs
         .find_intersecting(buffer.cursor.selection, true);
 
-    let mut result: Galleys = Default::default();
+    let mut layouts: Vec<LayoutJobInfo> = Default::default();
 
     let mut head_size: RelCharOffset = 0.into();
     let mut annotation: Option<Annotation> = Default::default();
     let mut annotation_text_format = Default::default();
     let mut layout: LayoutJob = Default::default();
+    let mut table_row: Option<TableRowLayout> = Default::default();
+    let mut table_cell_idx: Option<usize> = Default::default();
+    let mut math_layouts: Vec<MathLayout> = Default::default();
 
     // join ast text ranges, paragraphs, plaintext links, selection, and whole document (to ensure everything is captured)
     // emit one galley per paragraph; other data is used to determine style
@@ -111,6 +129,55 @@ pub fn calc(
                 RenderStyle::PlaintextLink.apply_style(&mut text_format, appearance);
             }
 
+            // cells of table rows with captured syntax are laid out in columns
+            let maybe_cell_ancestor = text_range.ancestors.iter().rposition(|&node_idx| {
+                matches!(
+                    ast.nodes[node_idx].node_type,
+                    MarkdownNode::Block(BlockNode::Table(TableItem::Cell(..)))
+                )
+            });
+            if let Some(cell_ancestor) = maybe_cell_ancestor {
+                let cell_idx = text_range.ancestors[cell_ancestor];
+                if table_cell_idx != Some(cell_idx) {
+                    table_cell_idx = Some(cell_idx);
+
+                    let row_idx = text_range.ancestors[cell_ancestor - 1];
+                    let row_captured = bounds::captured(
+                        buffer.cursor,
+                        &bounds.paragraphs,
+                        ast,
+                        &AstTextRange {
+                            range_type: AstTextRangeType::Head,
+                            range: ast.nodes[row_idx].range,
+                            ancestors: text_range.ancestors[..cell_ancestor].to_vec(),
+                        },
+                        hover_syntax_reveal_debounce_state,
+                        appearance,
+                        cursor_paragraphs,
+                    );
+                    if row_captured {
+                        let alignment = match ast.nodes[cell_idx].node_type {
+                            MarkdownNode::Block(BlockNode::Table(TableItem::Cell(alignment))) => {
+                                alignment
+                            }
+                            _ => Default::default(),
+                        };
+                        table_row
+                            .get_or_insert_with(|| TableRowLayout {
+                                table: text_range.ancestors[cell_ancestor - 2],
+                                header: ast.nodes[row_idx].node_type
+                                    == MarkdownNode::Block(BlockNode::Table(TableItem::HeaderRow)),
+                                ..Default::default()
+                            })
+                            .cells
+                            .push(TableCellLayout {
+                                sections: (layout.sections.len(), layout.sections.len()),
+                                alignment,
+                            });
+                    }
+                }
+            }
+
             // only the first portion of a head text range gets that range's annotation
             let mut is_annotation = false;
             if text_range.range_type == AstTextRangeType::Head
@@ -135,6 +202,30 @@ pub fn calc(
                 }
                 this
             };
+
+            // captured math is rendered in place of its source: inline math in space left before
+            // its head and block math above its galley
+            let math_node = match text_range.node(ast) {
+                MarkdownNode::Inline(InlineNode::Math) => Some(false),
+                MarkdownNode::Block(BlockNode::Math) => Some(true),
+                _ => None,
+            };
+            let captured_math = captured && math_node.is_some();
+            if let (true, Some(block), AstTextRangeType::Head) =
+                (captured_math, math_node, &text_range.range_type)
+            {
+                let source = &buffer[ast.nodes[*text_range.ancestors.last().unwrap()].range];
+                if let Some(texture) = math.map.get(source) {
+                    let section = if block {
+                        None
+                    } else {
+                        layout.append("", texture.size.x, text_format.clone());
+                        Some(layout.sections.len() - 1)
+                    };
+                    math_layouts.push(MathLayout { source: source.to_string(), section });
+                }
+            }
+
             match text_range.range_type {
                 AstTextRangeType::Head => {
                     if captured {
@@ -160,8 +251,51 @@ pub fn calc(
                         layout.append(&text, 0.0, text_format);
                     }
                 }
+                AstTextRangeType::Text if captured_math => {
+                    // need to append empty text to layout so that the style is applied
+                    layout.append("", 0.0, text_format);
+                }
                 AstTextRangeType::Text => {
-                    layout.append(&text, 0.0, text_format);
+                    // code in code blocks is split into sections for its highlighted tokens
+                    let tokens = text_range
+                        .ancestors
+                        .iter()
+                        .find_map(|node_idx| highlights.blocks.get(node_idx))
+                        .filter(|_| maybe_link_range.is_none())
+                        .map(|tokens| tokens.as_slice())
+                        .unwrap_or_default();
+                    let mut start = text_range_portion.start();
+                    for (token_range, token_type) in tokens {
+                        let token_range = (
+                            token_range.start().max(text_range_portion.start()),
+                            token_range.end().min(text_range_portion.end()),
+                        );
+                        if token_range.start() >= token_range.end() {
+                            continue;
+                        }
+                        if start < token_range.start() {
+                            layout.append(
+                                &buffer[(start, token_range.start())],
+                                0.0,
+                                text_format.clone(),
+                            );
+                        }
+                        let mut token_format = text_format.clone();
+                        token_format.color = appearance.code_token(*token_type);
+                        layout.append(&buffer[token_range], 0.0, token_format);
+                        start = token_range.end();
+                    }
+                    if start == text_range_portion.start() {
+                        layout.append(&text, 0.0, text_format);
+                    } else if start < text_range_portion.end() {
+                        layout.append(&buffer[(start, text_range_portion.end())], 0.0, text_format);
+                    }
+                }
+            }
+
+            if let (Some(_), Some(table_row)) = (maybe_cell_ancestor, &mut table_row) {
+                if let Some(cell) = table_row.cells.last_mut() {
+                    cell.sections.1 = layout.sections.len();
                 }
             }
         }
@@ -182,14 +316,84 @@ pub fn calc(
                 head_size: mem::take(&mut head_size),
                 tail_size: 0.into(),
                 annotation_text_format: mem::take(&mut annotation_text_format),
+                table_row: mem::take(&mut table_row),
+                math: mem::take(&mut math_layouts),
             };
-            result
-                .galleys
-                .push(GalleyInfo::from(layout_info, images, appearance, ui));
+            table_cell_idx = None;
+            layouts.push(layout_info);
         }
     }
 
-    result
+    align_table_cells(&mut layouts, appearance, ui);
+
+    Galleys {
+        galleys: layouts
+            .into_iter()
+            .map(|layout_info| GalleyInfo::from(layout_info, images, math, appearance, ui))
+            .collect(),
+    }
+}
+
+/// Lines up the cells of table rows into columns, sized to fit the widest cell in each column, by
+/// adding space before each cell.
+fn align_table_cells(layouts: &mut [LayoutJobInfo], appearance: &Appearance, ui: &Ui) {
+    let measure = |layout: &LayoutJobInfo, cell: &TableCellLayout| {
+        let mut job = LayoutJob::default();
+        for section in &layout.job.sections[cell.sections.0..cell.sections.1] {
+            job.append(&layout.job.text[section.byte_range.clone()], 0.0, section.format.clone());
+        }
+        ui.fonts(|f| f.layout_job(job)).size().x
+    };
+
+    let mut cell_widths = Vec::with_capacity(layouts.len());
+    let mut column_widths: HashMap<usize, Vec<f32>> = HashMap::new();
+    for layout in layouts.iter() {
+        let mut widths = Vec::new();
+        if let Some(table_row) = &layout.table_row {
+            let columns = column_widths.entry(table_row.table).or_default();
+            for (column, cell) in table_row.cells.iter().enumerate() {
+                let width = measure(layout, cell);
+                if column == columns.len() {
+                    columns.push(width);
+                }
+                columns[column] = columns[column].max(width);
+                widths.push(width);
+            }
+        }
+        cell_widths.push(widths);
+    }
+
+    let gap = appearance.font_size();
+    for (layout, widths) in layouts.iter_mut().zip(cell_widths) {
+        if let Some(table_row) = &mut layout.table_row {
+            let columns = &column_widths[&table_row.table];
+            let mut column_start = 0.0;
+            let mut prev_cell_end = 0.0;
+            for (column, (cell, width)) in table_row.cells.iter().zip(widths).enumerate() {
+                let slack = columns[column] - width;
+                let cell_start = column_start
+                    + match cell.alignment {
+                        ColumnAlignment::Left => 0.0,
+                        ColumnAlignment::Center => slack / 2.0,
+                        ColumnAlignment::Right => slack,
+                    };
+                if cell.sections.0 < cell.sections.1 {
+                    layout.job.sections[cell.sections.0].leading_space = cell_start - prev_cell_end;
+                }
+                prev_cell_end = cell_start + width;
+                column_start += columns[column] + gap;
+            }
+
+            table_row.separators = columns
+                .iter()
+                .scan(0.0, |x, width| {
+                    *x += width + gap;
+                    Some(*x - gap / 2.0)
+                })
+                .collect();
+            table_row.width = table_row.separators.pop().unwrap_or_default();
+        }
+    }
 }
 
 impl Index<usize> for Galleys {
@@ -311,7 +515,8 @@ pub fn annotation_offset(annotation: &Option<Annotation>, appearance: &Appearanc
 
 impl GalleyInfo {
     pub fn from(
-        mut job: LayoutJobInfo, images: &ImageCache, appearance: &Appearance, ui: &mut Ui,
+        mut job: LayoutJobInfo, images: &ImageCache, math: &MathCache, appearance: &Appearance,
+        ui: &mut Ui,
     ) -> Self {
         let offset = annotation_offset(&job.annotation, appearance);
         job.job.wrap.max_width = ui.available_width() - offset.x;
@@ -342,6 +547,22 @@ impl GalleyInfo {
             None
         };
 
+        // allocate space for block math, centered and scaled down to fit if it's too wide
+        let mut math_infos = Vec::new();
+        for math_layout in job.math.iter().filter(|math| math.section.is_none()) {
+            if let Some(texture) = math.map.get(&math_layout.source) {
+                let padding = appearance.image_padding();
+                let size = texture.size
+                    * f32::min((ui.available_width() - padding * 2.0) / texture.size.x, 1.0);
+                let (space, _) = ui.allocate_exact_size(
+                    Vec2::new(ui.available_width(), size.y + padding * 2.0),
+                    Sense::hover(),
+                );
+                let location = Rect::from_center_size(space.center(), size);
+                math_infos.push(MathInfo { location, texture: texture.texture });
+            }
+        }
+
         let galley = ui.ctx().fonts(|f| f.layout_job(job.job));
 
         // allocate space for text and non-image annotations
@@ -352,6 +573,36 @@ impl GalleyInfo {
 
         let text_location = Pos2::new(offset.x + galley_location.min.x, galley_location.min.y);
 
+        // locate inline math in the space left for it, lined up with the baseline of its row
+        for math_layout in &job.math {
+            let (Some(section), Some(texture)) =
+                (math_layout.section, math.map.get(&math_layout.source))
+            else {
+                continue;
+            };
+            let byte_idx = galley.job.sections[section].byte_range.start;
+            let char_idx = galley.job.text[..byte_idx].chars().count();
+            let cursor = galley.from_ccursor(CCursor::new(char_idx));
+            let pos = galley.pos_from_cursor(&cursor);
+
+            // the space is before the next character, if there is one
+            let x = if char_idx < galley.job.text.chars().count() {
+                (pos.min.x - texture.size.x).max(0.0)
+            } else {
+                pos.min.x
+            };
+            let row = &galley.rows[cursor.rcursor.row];
+            let baseline = row
+                .glyphs
+                .first()
+                .map(|glyph| glyph.pos.y)
+                .unwrap_or(row.rect.max.y);
+
+            let min = text_location + Vec2::new(x, baseline - texture.baseline);
+            let location = Rect::from_min_size(min, texture.size);
+            math_infos.push(MathInfo { location, texture: texture.texture });
+        }
+
         Self {
             range: job.range,
             galley,
@@ -362,6 +613,8 @@ impl GalleyInfo {
             galley_location,
             image,
             annotation_text_format: job.annotation_text_format,
+            table_row: job.table_row,
+            math: math_infos,
         }
     }
 
@@ -0,0 +1,240 @@
+use crate::ast::Ast;
+use crate::buffer::SubBuffer;
+use crate::offset_types::{DocCharOffset, RangeExt};
+use crate::style::{BlockNode, MarkdownNode};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Kinds of code tokens, each highlighted in its own color (see `Appearance::code_token`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenType {
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+}
+
+/// Highlighted tokens in some code, by byte range within the code
+pub type Tokens = Vec<(Range<usize>, TokenType)>;
+
+/// Splits code into tokens to highlight. Editors use `SyntectHighlighter` unless an integration
+/// provides its own.
+pub trait Highlighter: Send + Sync {
+    /// Returns the byte ranges of `code` to highlight, in order and non-overlapping, or `None` if
+    /// `language` (a fenced code block's info string e.g. `rust`) isn't supported.
+    fn highlight(&self, language: &str, code: &str) -> Option<Tokens>;
+}
+
+/// Highlights code using the syntax definitions bundled with syntect
+#[derive(Default)]
+pub struct SyntectHighlighter;
+
+/// syntax definitions are slow to load, so they're loaded once, the first time they're needed
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+impl Highlighter for SyntectHighlighter {
+    fn highlight(&self, language: &str, code: &str) -> Option<Tokens> {
+        let syntaxes = syntaxes();
+        let syntax = syntaxes.find_syntax_by_token(language)?;
+
+        let mut result: Tokens = Vec::new();
+        let mut parse_state = ParseState::new(syntax);
+        let mut scopes = ScopeStack::new();
+        let mut line_start = 0;
+        for line in LinesWithEndings::from(code) {
+            let ops = parse_state.parse_line(line, syntaxes).ok()?;
+
+            // the text between consecutive ops is styled by the scopes in effect after the first
+            let mut token_start = 0;
+            let ends = ops
+                .iter()
+                .map(|(offset, _)| *offset)
+                .chain([line.len()])
+                .collect::<Vec<_>>();
+            let ops = ops.iter().map(|(_, op)| Some(op)).chain([None]);
+            for (token_end, op) in ends.into_iter().zip(ops) {
+                if token_end > token_start {
+                    if let Some(token_type) = token_type(&scopes) {
+                        let range = line_start + token_start..line_start + token_end;
+                        match result.last_mut() {
+                            Some((last, last_type))
+                                if last.end == range.start && *last_type == token_type =>
+                            {
+                                last.end = range.end
+                            }
+                            _ => result.push((range, token_type)),
+                        }
+                    }
+                    token_start = token_end;
+                }
+                if let Some(op) = op {
+                    scopes.apply(op).ok()?;
+                }
+            }
+            line_start += line.len();
+        }
+        Some(result)
+    }
+}
+
+/// The type of token for text in `scopes`, determined by the innermost scope that has one
+fn token_type(scopes: &ScopeStack) -> Option<TokenType> {
+    let types = [
+        ("comment", TokenType::Comment),
+        ("string", TokenType::String),
+        ("constant", TokenType::Number),
+        ("entity.name.function", TokenType::Function),
+        ("support.function", TokenType::Function),
+        ("variable.function", TokenType::Function),
+        ("entity.name", TokenType::Type),
+        ("support.type", TokenType::Type),
+        ("support.class", TokenType::Type),
+        ("keyword.operator", TokenType::Keyword),
+        ("keyword", TokenType::Keyword),
+        ("storage", TokenType::Keyword),
+    ];
+    scopes.as_slice().iter().rev().find_map(|scope| {
+        types.iter().find_map(|(prefix, token_type)| {
+            Scope::new(prefix)
+                .ok()
+                .filter(|prefix| prefix.is_prefix_of(*scope))
+                .map(|_| *token_type)
+        })
+    })
+}
+
+/// Highlighted tokens in a code block, by range within the document
+pub type DocTokens = Vec<((DocCharOffset, DocCharOffset), TokenType)>;
+
+/// The highlighted tokens in each code block, by the index of the code block's ast node.
+/// Highlights are re-used for code blocks whose language and code haven't changed, so editing
+/// one code block doesn't re-highlight the others.
+#[derive(Clone, Default)]
+pub struct HighlightCache {
+    pub blocks: HashMap<usize, DocTokens>,
+
+    /// tokens by language and code
+    pub sources: HashMap<(String, String), Arc<Tokens>>,
+}
+
+pub fn calc(
+    ast: &Ast, buffer: &SubBuffer, prior_cache: &HighlightCache, highlighter: &dyn Highlighter,
+) -> HighlightCache {
+    let mut result = HighlightCache::default();
+    for (node_idx, node) in ast.nodes.iter().enumerate() {
+        if node.node_type != MarkdownNode::Block(BlockNode::Code) {
+            continue;
+        }
+        let language = match language(&buffer[node.head_range()]) {
+            Some(language) => language,
+            None => continue, // unfenced code blocks and fenced code blocks without info strings
+        };
+        let code = &buffer[node.text_range];
+        let key = (language.to_string(), code.to_string());
+
+        let tokens = match (result.sources.get(&key), prior_cache.sources.get(&key)) {
+            (Some(tokens), _) | (None, Some(tokens)) => tokens.clone(),
+            (None, None) => match highlighter.highlight(language, code) {
+                Some(tokens) => Arc::new(tokens),
+                None => continue,
+            },
+        };
+
+        // token ranges are relative to the code; shift them to wherever the code block is now
+        let code_start = buffer.segs.offset_to_byte(node.text_range.start());
+        result.blocks.insert(
+            node_idx,
+            tokens
+                .iter()
+                .map(|(range, token_type)| {
+                    let range = (code_start + range.start, code_start + range.end);
+                    (buffer.segs.range_to_char(range), *token_type)
+                })
+                .collect(),
+        );
+        result.sources.insert(key, tokens);
+    }
+    result
+}
+
+/// The language in the head of a fenced code block e.g. `rust` in ```` ```rust ````
+fn language(head: &str) -> Option<&str> {
+    head.trim_start_matches(['`', '~'])
+        .split_whitespace()
+        .next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HighlightCache, Highlighter, SyntectHighlighter, TokenType, Tokens};
+    use crate::buffer::SubBuffer;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_syntect_highlighter() {
+        let code = "fn main() {\n    // comment\n    let s = \"string\";\n}\n";
+        let tokens = SyntectHighlighter.highlight("rust", code).unwrap();
+        let tokens = tokens
+            .into_iter()
+            .map(|(range, token_type)| (&code[range], token_type))
+            .collect::<Vec<_>>();
+
+        assert!(tokens.contains(&("fn", TokenType::Keyword)));
+        assert!(tokens.contains(&("main", TokenType::Function)));
+        assert!(tokens.contains(&("// comment\n", TokenType::Comment)));
+        assert!(tokens.contains(&("\"string\"", TokenType::String)));
+        assert!(SyntectHighlighter
+            .highlight("not a language", code)
+            .is_none());
+    }
+
+    /// counts the code blocks it highlights
+    #[derive(Default)]
+    struct CountingHighlighter {
+        count: AtomicUsize,
+    }
+
+    impl Highlighter for CountingHighlighter {
+        fn highlight(&self, _language: &str, _code: &str) -> Option<Tokens> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            Some(vec![(0..1, TokenType::Keyword)])
+        }
+    }
+
+    #[test]
+    fn test_unchanged_blocks_not_rehighlighted() {
+        let highlighter = CountingHighlighter::default();
+        let calc = |text: &str, prior_cache: &HighlightCache| {
+            let buffer: SubBuffer = text.into();
+            let ast = crate::ast::calc(&buffer);
+            super::calc(&ast, &buffer, prior_cache, &highlighter)
+        };
+
+        let cache = calc("```rust\na\n```\n\n```rust\nb\n```\n\n```\nc\n```", &Default::default());
+        assert_eq!(highlighter.count.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.blocks.len(), 2);
+
+        // editing text before the blocks moves them without re-highlighting them
+        let cache = calc("moved\n\n```rust\na\n```\n\n```rust\nb\n```", &cache);
+        assert_eq!(highlighter.count.load(Ordering::SeqCst), 2);
+        let mut token_starts = cache
+            .blocks
+            .values()
+            .map(|tokens| tokens[0].0 .0 .0)
+            .collect::<Vec<_>>();
+        token_starts.sort();
+        assert_eq!(token_starts, vec![14, 29]);
+
+        // editing one block re-highlights only that block
+        calc("moved\n\n```rust\na\n```\n\n```rust\nbb\n```", &cache);
+        assert_eq!(highlighter.count.load(Ordering::SeqCst), 3);
+    }
+}
@@ -22,7 +22,7 @@ pub enum ImageState {
 
 pub fn calc(
     ast: &Ast, prior_cache: &ImageCache, client: &reqwest::blocking::Client, core: &lb::Core,
-    ui: &Ui,
+    file_id: Option<Uuid>, ui: &Ui,
 ) -> ImageCache {
     let mut result = ImageCache::default();
 
@@ -53,8 +53,7 @@ pub fn calc(
                 thread::spawn(move || {
                     let texture_manager = ctx.tex_manager();
 
-                    // use core for lb:// urls
-                    // todo: also handle relative paths
+                    // use core for lb:// urls and paths
                     let image_bytes = if let Some(stripped) = url.strip_prefix("lb://") {
                         match Uuid::parse_str(stripped) {
                             Ok(id) => match core.read_document(id) {
@@ -72,6 +71,17 @@ pub fn calc(
                                 return;
                             }
                         }
+                    } else if !url.contains("://") {
+                        match resolve_path(&core, file_id, &url)
+                            .and_then(|id| core.read_document(id).map_err(|e| e.to_string()))
+                        {
+                            Ok(bytes) => bytes,
+                            Err(e) => {
+                                *image_state.lock().unwrap() = ImageState::Failed(e);
+                                ctx.request_repaint();
+                                return;
+                            }
+                        }
                     } else {
                         match download_image(&client, &url) {
                             Ok(image_bytes) => image_bytes,
@@ -121,6 +131,41 @@ pub fn calc(
     result
 }
 
+/// Resolves the path in an image url to a file. Paths are relative to the folder containing the
+/// document being edited unless they start with `/`.
+/// ![](pic.png)
+/// ![](../attachments/pic%20one.png)
+fn resolve_path(core: &lb::Core, file_id: Option<Uuid>, url: &str) -> Result<Uuid, String> {
+    let doc_path = match file_id {
+        Some(file_id) => Some(core.get_path_by_id(file_id).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    core.get_by_path(&image_path(doc_path.as_deref(), url))
+        .map(|file| file.id)
+        .map_err(|e| e.to_string())
+}
+
+/// The path of the file an image url refers to, relative to the document at `doc_path` if there is
+/// one. Urls are percent-decoded.
+fn image_path(doc_path: Option<&str>, url: &str) -> String {
+    let url = lb::percent_decode(url);
+    let mut components = Vec::new();
+    if let (false, Some(doc_path)) = (url.starts_with('/'), doc_path) {
+        components.extend(doc_path.split('/').filter(|c| !c.is_empty()));
+        components.pop(); // the document's name
+    }
+    for component in url.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+    components.join("/")
+}
+
 fn download_image(
     client: &reqwest::blocking::Client, url: &str,
 ) -> Result<Vec<u8>, reqwest::Error> {
@@ -135,3 +180,20 @@ impl ImageCache {
             .any(|state| &ImageState::Loading == state.lock().unwrap().deref())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::image_path;
+
+    #[test]
+    fn test_image_path() {
+        let doc = Some("/notes/today.md");
+
+        assert_eq!(image_path(doc, "pic.png"), "notes/pic.png");
+        assert_eq!(image_path(doc, "./attachments/pic%20one.png"), "notes/attachments/pic one.png");
+        assert_eq!(image_path(doc, "../pic%231%25.png"), "pic#1%.png");
+        assert_eq!(image_path(doc, "../../pic.png"), "pic.png");
+        assert_eq!(image_path(doc, "/attachments/pic.png"), "attachments/pic.png");
+        assert_eq!(image_path(None, "pic.png"), "pic.png");
+    }
+}
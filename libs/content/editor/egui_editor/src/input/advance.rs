@@ -1,10 +1,10 @@
 use std::mem;
 
-use crate::bounds::{Bounds, Text};
+use crate::bounds::{Bounds, RangesExt, Text};
 use crate::buffer::SubBuffer;
 use crate::galleys::{GalleyInfo, Galleys};
 use crate::input::canonical::{Increment, Offset};
-use crate::offset_types::DocCharOffset;
+use crate::offset_types::{DocCharOffset, RangeExt};
 use egui::epaint::text::cursor::Cursor as EguiCursor;
 use egui::{Pos2, Vec2};
 
@@ -25,6 +25,31 @@ impl DocCharOffset {
                 }
                 result
             }
+            Offset::By(Increment::Cell) => self.advance_by_cell(backwards, bounds),
+        }
+    }
+
+    /// moves to the end of the next or previous cell's text in the same table; stays put at the first and last cells
+    fn advance_by_cell(self, backwards: bool, bounds: &Bounds) -> Self {
+        let (table_start, table_end) = bounds.tables.find_containing(self, true, true);
+        let table = if table_start < table_end {
+            bounds.tables[table_start]
+        } else {
+            return self;
+        };
+
+        let (cell_start, cell_end) = bounds.cells.find_containing(self, true, true);
+        let in_cell = cell_start < cell_end;
+        let target = match (backwards, in_cell) {
+            (true, true) => cell_start.checked_sub(1),
+            (true, false) => bounds.cells.iter().rposition(|cell| cell.end() < self),
+            (false, true) => Some(cell_end),
+            (false, false) => bounds.cells.iter().position(|cell| cell.start() > self),
+        };
+
+        match target.and_then(|target| bounds.cells.get(target)) {
+            Some(cell) if table.contains_inclusive(cell.end()) => cell.end(),
+            _ => self,
         }
     }
 
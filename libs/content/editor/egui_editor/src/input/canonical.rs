@@ -29,6 +29,7 @@ pub enum Bound {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Increment {
     Line,
+    Cell,
 }
 
 /// text location relative to some absolute text location
@@ -83,9 +84,11 @@ pub enum Modification {
     StageMarked { highlighted: (RelCharOffset, RelCharOffset), text: String },
     CommitMarked,
     Replace { region: Region, text: String },
+    ReplaceAll { replacements: Vec<((DocCharOffset, DocCharOffset), String)> }, // one undo step
     ToggleStyle { region: Region, style: MarkdownNode },
     Newline { advance_cursor: bool }, // distinct from replace because it triggers auto-bullet, etc
     Indent { deindent: bool },
+    AlignTable,
     Undo,
     Redo,
     Cut,
@@ -336,6 +339,15 @@ pub fn calc(
                 } else {
                     None
                 }
+            } else if let Some(definition) = click_checker.footnote(*pos) {
+                // footnote references jump to their definitions
+                if (touch_mode && !click_dragged) || click_mods.command {
+                    Some(Modification::Select {
+                        region: Region::Location(Location::DocCharOffset(definition)),
+                    })
+                } else {
+                    None
+                }
             } else {
                 None
             }
@@ -446,6 +458,7 @@ mod test {
         text: Option<usize>,
         checkbox: Option<usize>,
         link: Option<String>,
+        footnote: Option<DocCharOffset>,
         offset: DocCharOffset,
     }
 
@@ -466,6 +479,10 @@ mod test {
             self.link.clone()
         }
 
+        fn footnote(&self, _pos: Pos2) -> Option<DocCharOffset> {
+            self.footnote
+        }
+
         fn pos_to_char_offset(&self, _pos: Pos2) -> DocCharOffset {
             self.offset
         }
@@ -8,6 +8,7 @@ use crate::input::mutation;
 use crate::layouts::Annotation;
 use crate::offset_types::{DocCharOffset, RangeExt};
 use crate::style::{InlineNode, ListItem, MarkdownNode};
+use crate::wiki_links::WikiLinkCache;
 use egui::{Pos2, Rect};
 
 pub trait ClickChecker {
@@ -15,6 +16,7 @@ pub trait ClickChecker {
     fn text(&self, pos: Pos2) -> Option<usize>; // returns galley index
     fn checkbox(&self, pos: Pos2, touch_mode: bool) -> Option<usize>; // returns galley index of checkbox
     fn link(&self, pos: Pos2) -> Option<String>; // returns url to open
+    fn footnote(&self, pos: Pos2) -> Option<DocCharOffset>; // returns start of footnote definition
     fn pos_to_char_offset(&self, pos: Pos2) -> DocCharOffset; // converts pos to char offset
 }
 
@@ -25,6 +27,7 @@ pub struct EditorClickChecker<'a> {
     pub buffer: &'a Buffer,
     pub ast: &'a Ast,
     pub appearance: &'a Appearance,
+    pub wiki_links: &'a WikiLinkCache,
 }
 
 impl<'a> ClickChecker for &'a EditorClickChecker<'a> {
@@ -112,6 +115,12 @@ impl<'a> ClickChecker for &'a EditorClickChecker<'a> {
                     return Some(url.to_string());
                 }
             }
+            if let MarkdownNode::Inline(InlineNode::WikiLink(target)) = &ast_node.node_type {
+                if ast_node.range.contains_inclusive(offset) {
+                    // links to files that don't exist can't be opened
+                    return self.wiki_links.map.get(target).cloned().flatten();
+                }
+            }
         }
         for plaintext_link in &self.bounds.links {
             if plaintext_link.contains_inclusive(offset) {
@@ -122,6 +131,29 @@ impl<'a> ClickChecker for &'a EditorClickChecker<'a> {
         None
     }
 
+    fn footnote(&self, pos: Pos2) -> Option<DocCharOffset> {
+        self.text(pos)?;
+        let offset = mutation::pos_to_char_offset(
+            pos,
+            self.galleys,
+            &self.buffer.current.segs,
+            &self.bounds.text,
+        );
+
+        for ast_node in &self.ast.nodes {
+            if let MarkdownNode::Inline(InlineNode::FootnoteReference(label)) = &ast_node.node_type
+            {
+                if ast_node.range.contains_inclusive(offset) {
+                    let definition_idx =
+                        self.ast.footnote_definition(&self.buffer.current, label)?;
+                    return Some(self.ast.nodes[definition_idx].text_range.start());
+                }
+            }
+        }
+
+        None
+    }
+
     fn pos_to_char_offset(&self, pos: Pos2) -> DocCharOffset {
         mutation::pos_to_char_offset(
             pos,
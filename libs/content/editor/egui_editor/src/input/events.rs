@@ -45,6 +45,7 @@ pub fn process(
         .map(|m| match input::mutation::calc(m, &buffer.current, galleys, bounds, ast) {
             EditorMutation::Buffer(mutations) if mutations.is_empty() => (false, None, None),
             EditorMutation::Buffer(mutations) => buffer.apply(mutations, debug, appearance),
+            EditorMutation::Batch(mutations) => buffer.apply_batch(mutations, debug, appearance),
             EditorMutation::Undo => {
                 buffer.undo(debug, appearance);
                 (true, None, None)
@@ -2,12 +2,12 @@ use crate::ast::{Ast, AstTextRangeType};
 use crate::bounds::{AstTextRanges, Bounds, RangesExt, Text};
 use crate::buffer::{EditorMutation, SubBuffer, SubMutation};
 use crate::galleys::Galleys;
-use crate::input::canonical::{Location, Modification, Offset, Region};
+use crate::input::canonical::{Increment, Location, Modification, Offset, Region};
 use crate::input::cursor::Cursor;
 use crate::layouts::Annotation;
 use crate::offset_types::{DocCharOffset, RangeExt, RangeIterExt};
 use crate::style::{
-    BlockNode, BlockNodeType, InlineNodeType, ListItem, MarkdownNode, MarkdownNodeType,
+    BlockNode, BlockNodeType, InlineNodeType, ListItem, MarkdownNode, MarkdownNodeType, TableItem,
 };
 use crate::unicode_segs::UnicodeSegs;
 use egui::Pos2;
@@ -58,6 +58,15 @@ pub fn calc(
             mutation.push(SubMutation::Insert { text, advance_cursor: true });
             mutation.push(SubMutation::Cursor { cursor: current_cursor });
         }
+        Modification::ReplaceAll { replacements } => {
+            // later ranges are adjusted for the text replaced before them as the mutation is applied
+            for (range, text) in replacements {
+                mutation.push(SubMutation::Cursor { cursor: range.into() });
+                mutation.push(SubMutation::Insert { text, advance_cursor: true });
+            }
+            mutation.push(SubMutation::Cursor { cursor: current_cursor });
+            return EditorMutation::Batch(mutation);
+        }
         Modification::ToggleStyle { region, mut style } => {
             let cursor = region_to_cursor(region, current_cursor, buffer, galleys, bounds);
             let unapply = should_unapply(cursor, &style, ast, &bounds.ast);
@@ -235,7 +244,9 @@ pub fn calc(
                             }
                             Some(Annotation::Item(ListItem::Todo(_), _)) => {
                                 let head = galley.head(buffer);
-                                let text = head[0..head.len() - 6].to_string() + "* [ ] ";
+                                let (check_start, check_end) = todo_check(head);
+                                let text =
+                                    format!("{} {}", &head[..check_start], &head[check_end..]);
                                 mutation.push(SubMutation::Insert { text, advance_cursor: true });
                             }
                             Some(Annotation::Image(_, _, _)) => {}
@@ -263,6 +274,22 @@ pub fn calc(
             cursor.selection.0 = cursor.selection.1;
         }
         Modification::Indent { deindent } => {
+            // if we're in a table, tab/shift+tab will move to the next/previous cell
+            let tables = bounds
+                .tables
+                .find_containing(current_cursor.selection.1, true, true);
+            if !tables.is_empty() {
+                let region = Region::ToOffset {
+                    offset: Offset::By(Increment::Cell),
+                    backwards: deindent,
+                    extend_selection: false,
+                };
+                mutation.push(SubMutation::Cursor {
+                    cursor: region_to_cursor(region, current_cursor, buffer, galleys, bounds),
+                });
+                return EditorMutation::Buffer(mutation);
+            }
+
             // if we're in a list item, tab/shift+tab will indent/de-indent
             // otherwise, tab will insert a tab and shift tab will do nothing
             let mut indentation_processed_galleys = HashSet::new();
@@ -538,6 +565,46 @@ pub fn calc(
                 mutation.push(SubMutation::Insert { text: "\t".to_string(), advance_cursor: true });
             }
         }
+        Modification::AlignTable => {
+            // line up the pipes of the table being edited, except while text is being composed
+            let cursor = current_cursor.selection.1;
+            let maybe_table = ast
+                .nodes
+                .iter()
+                .enumerate()
+                .find(|(node_idx, node)| {
+                    node.node_type == MarkdownNode::Block(BlockNode::Table(TableItem::Table))
+                        && ast.parent(*node_idx) == Some(ast.root)
+                        && node.range.contains_inclusive(cursor)
+                })
+                .map(|(_, node)| node.range);
+            if let Some(table) = maybe_table {
+                let text = &buffer[table];
+                let (aligned, aligned_cursor) = align_table(text, (cursor - table.start()).0);
+                if aligned != text
+                    && current_cursor.selection.is_empty()
+                    && current_cursor.mark.is_none()
+                {
+                    // replace the text on either side of the cursor so that the cursor lands between them
+                    let split = aligned
+                        .grapheme_indices(true)
+                        .nth(aligned_cursor)
+                        .map(|(idx, _)| idx)
+                        .unwrap_or(aligned.len());
+                    mutation.push(SubMutation::Cursor { cursor: (cursor, table.end()).into() });
+                    mutation.push(SubMutation::Insert {
+                        text: aligned[split..].to_string(),
+                        advance_cursor: false,
+                    });
+                    mutation.push(SubMutation::Cursor { cursor: (table.start(), cursor).into() });
+                    mutation.push(SubMutation::Insert {
+                        text: aligned[..split].to_string(),
+                        advance_cursor: true,
+                    });
+                    mutation.push(SubMutation::Cursor { cursor: current_cursor });
+                }
+            }
+        }
         Modification::Undo => {
             return EditorMutation::Undo;
         }
@@ -561,15 +628,14 @@ pub fn calc(
         Modification::ToggleCheckbox(galley_idx) => {
             let galley = &galleys[galley_idx];
             if let Some(Annotation::Item(ListItem::Todo(checked), ..)) = galley.annotation {
-                mutation.push(SubMutation::Cursor {
-                    cursor: (
-                        galley.range.start() + galley.head_size - 6,
-                        galley.range.start() + galley.head_size,
-                    )
-                        .into(),
-                });
+                // replace only the check so the item's bullet and spacing are preserved
+                let head = galley.head(buffer);
+                let (check_start, _) = todo_check(head);
+                let check_start = galley.range.start() + head[..check_start].chars().count();
+                mutation
+                    .push(SubMutation::Cursor { cursor: (check_start, check_start + 1).into() });
                 mutation.push(SubMutation::Insert {
-                    text: if checked { "* [ ] " } else { "* [x] " }.to_string(),
+                    text: if checked { " " } else { "x" }.to_string(),
                     advance_cursor: true,
                 });
                 mutation.push(SubMutation::Cursor { cursor: current_cursor });
@@ -579,6 +645,15 @@ pub fn calc(
     EditorMutation::Buffer(mutation)
 }
 
+/// Returns the byte range of the check in the head of a todo list item e.g. `x` in `- [x] `
+fn todo_check(head: &str) -> (usize, usize) {
+    let check_start = head
+        .rfind('[')
+        .map(|bracket| bracket + 1)
+        .unwrap_or_default();
+    (check_start, (check_start + 1).min(head.len()))
+}
+
 /// Returns true if all text in `cursor` has style `style`
 fn should_unapply(
     cursor: Cursor, style: &MarkdownNode, ast: &Ast, ast_ranges: &AstTextRanges,
@@ -868,6 +943,168 @@ fn insert_tail(offset: DocCharOffset, style: MarkdownNode, mutation: &mut Vec<Su
     }
 }
 
+/// Pads the cells of a table so that its pipes line up and regenerates its delimiter row to match.
+/// Returns the aligned table and where the grapheme offset `cursor` in `table` ends up in it.
+fn align_table(table: &str, cursor: usize) -> (String, usize) {
+    let graphemes = table.graphemes(true).collect::<Vec<_>>();
+    let is_whitespace = |grapheme: &str| grapheme.trim().is_empty();
+
+    // split each line into cells on unescaped pipes
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    for line_end in 0..=graphemes.len() {
+        if line_end < graphemes.len() && !matches!(graphemes[line_end], "\n" | "\r\n") {
+            continue;
+        }
+
+        let mut spans = Vec::new();
+        let mut span_start = line_start;
+        for i in line_start..line_end {
+            if graphemes[i] == "|" && (i == line_start || graphemes[i - 1] != "\\") {
+                // a leading pipe doesn't end a cell
+                if graphemes[line_start..i].iter().any(|g| !is_whitespace(g)) {
+                    spans.push((span_start, i));
+                }
+                span_start = i + 1;
+            }
+        }
+        // a trailing pipe doesn't start a cell
+        if spans.is_empty()
+            || graphemes[span_start..line_end]
+                .iter()
+                .any(|g| !is_whitespace(g))
+        {
+            spans.push((span_start, line_end));
+        }
+
+        rows.push(TableRow {
+            range: (line_start, line_end),
+            cells: spans
+                .into_iter()
+                .map(|span| {
+                    let (mut start, mut end) = span;
+                    while start < end && is_whitespace(graphemes[start]) {
+                        start += 1;
+                    }
+                    while end > start && is_whitespace(graphemes[end - 1]) {
+                        end -= 1;
+                    }
+                    // keep whitespace that was just typed at the end of a cell
+                    if end < cursor && cursor <= span.1 {
+                        end = cursor;
+                    }
+                    TableCell { span, content: (start, end) }
+                })
+                .collect(),
+        });
+        line_start = line_end + 1;
+    }
+
+    let columns = rows[0].cells.len();
+    let alignments = (0..columns)
+        .map(|column| match rows.get(1).and_then(|row| row.cells.get(column)) {
+            Some(TableCell { content: (start, end), .. }) if end > start => {
+                (graphemes[*start] == ":", end - start > 1 && graphemes[end - 1] == ":")
+            }
+            _ => (false, false),
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .enumerate()
+                .filter(|&(row_idx, _)| row_idx != 1)
+                .filter_map(|(_, row)| row.cells.get(column))
+                .map(|cell| cell.content.len())
+                .max()
+                .unwrap_or_default()
+                .max(3)
+        })
+        .collect::<Vec<_>>();
+
+    // the cursor keeps its offset into the content of its cell
+    let cursor_row = rows
+        .iter()
+        .position(|row| row.range.contains_inclusive(cursor))
+        .unwrap_or(rows.len() - 1);
+    let cursor_cell = rows[cursor_row]
+        .cells
+        .iter()
+        .rposition(|cell| cell.span.0 <= cursor)
+        .map(|column| {
+            let content = rows[cursor_row].cells[column].content;
+            (column, cursor.clamp(content.0, content.1) - content.0)
+        });
+
+    let mut result = String::new();
+    let mut result_len = 0;
+    let mut result_cursor = 0;
+    for (row_idx, row) in rows.iter().enumerate() {
+        if row_idx > 0 {
+            result.push('\n');
+            result_len += 1;
+        }
+        if row_idx == cursor_row {
+            result_cursor = result_len;
+        }
+
+        result.push('|');
+        result_len += 1;
+        for column in 0..columns.max(row.cells.len()) {
+            let content = if row_idx == 1 && column < columns {
+                let (left, right) = alignments[column];
+                let dashes = widths[column] - left as usize - right as usize;
+                (if left { ":" } else { "" }).to_string()
+                    + &"-".repeat(dashes)
+                    + if right { ":" } else { "" }
+            } else if let Some(cell) = row.cells.get(column) {
+                graphemes[cell.content.0..cell.content.1].concat()
+            } else {
+                String::new()
+            };
+            let content_len = content.graphemes(true).count();
+            let padding = widths
+                .get(column)
+                .copied()
+                .unwrap_or_default()
+                .max(content_len)
+                - content_len;
+            let leading_padding = match alignments.get(column) {
+                Some((true, true)) if row_idx != 1 => padding / 2,
+                Some((false, true)) if row_idx != 1 => padding,
+                _ => 0,
+            };
+
+            result += &" ".repeat(1 + leading_padding);
+            result_len += 1 + leading_padding;
+            if let Some((cursor_column, offset)) = cursor_cell {
+                if row_idx == cursor_row && column == cursor_column {
+                    result_cursor = result_len + offset.min(content_len);
+                }
+            }
+            result += &content;
+            result += &" ".repeat(padding - leading_padding + 1);
+            result.push('|');
+            result_len += content_len + padding - leading_padding + 2;
+        }
+    }
+
+    (result, result_cursor)
+}
+
+struct TableRow {
+    range: (usize, usize),
+    cells: Vec<TableCell>,
+}
+
+struct TableCell {
+    /// text between the cell's pipes
+    span: (usize, usize),
+
+    /// text between the cell's padding
+    content: (usize, usize),
+}
+
 pub fn region_to_cursor(
     region: Region, current_cursor: Cursor, buffer: &SubBuffer, galleys: &Galleys, bounds: &Bounds,
 ) -> Cursor {
@@ -1020,3 +1257,54 @@ fn increment_numbered_list_items(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{align_table, todo_check};
+
+    #[test]
+    fn todo_check_preserves_bullet() {
+        let head = "\t- [X] ";
+        let (start, end) = todo_check(head);
+        assert_eq!(&head[start..end], "X");
+        assert_eq!(format!("{} {}", &head[..start], &head[end..]), "\t- [ ] ");
+    }
+
+    #[test]
+    fn align_table_pads_cells() {
+        let (aligned, _) = align_table("| a | bb |\n|-|:-:|\n| cccc | d |", 0);
+        assert_eq!(aligned, "| a    | bb  |\n| ---- | :-: |\n| cccc |  d  |");
+    }
+
+    #[test]
+    fn align_table_aligned() {
+        let table = "| a   |   b |\n| :-- | --: |\n| c   |   d |";
+        assert_eq!(align_table(table, 0), (table.to_string(), 0));
+    }
+
+    #[test]
+    fn align_table_outer_pipes_and_missing_cells() {
+        let (aligned, _) = align_table("a | b\n--|--\nc", 0);
+        assert_eq!(aligned, "| a   | b   |\n| --- | --- |\n| c   |     |");
+    }
+
+    #[test]
+    fn align_table_escaped_pipe() {
+        let (aligned, _) = align_table("| a \\| b |\n|-|", 0);
+        assert_eq!(aligned, "| a \\| b |\n| ------ |");
+    }
+
+    #[test]
+    fn align_table_cursor() {
+        // cursor after "cc" in the last row
+        let table = "| a | b |\n|-|-|\n| cc | d |";
+        let (aligned, cursor) = align_table(table, 20);
+        assert_eq!(&aligned[..cursor], "| a   | b   |\n| --- | --- |\n| cc");
+
+        // cursor after a space just typed at the end of a cell
+        let table = "| a | b |\n|-|-|\n| cccc  | d |";
+        let (aligned, cursor) = align_table(table, 23);
+        assert_eq!(aligned, "| a     | b   |\n| ----- | --- |\n| cccc  | d   |");
+        assert_eq!(&aligned[..cursor], "| a     | b   |\n| ----- | --- |\n| cccc ");
+    }
+}
@@ -1,5 +1,5 @@
 use crate::offset_types::{DocCharOffset, RelCharOffset};
-use crate::style::{IndentLevel, ListItem, Title, Url};
+use crate::style::{ColumnAlignment, IndentLevel, ListItem, Title, Url};
 use egui::text::LayoutJob;
 use egui::TextFormat;
 use pulldown_cmark::LinkType;
@@ -15,6 +15,10 @@ pub struct LayoutJobInfo {
     pub tail_size: RelCharOffset,
 
     pub annotation_text_format: TextFormat,
+
+    pub table_row: Option<TableRowLayout>,
+
+    pub math: Vec<MathLayout>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -24,3 +28,34 @@ pub enum Annotation {
     HeadingRule,
     Rule,
 }
+
+/// Layout of a table row whose syntax is captured, used to line its cells up with the rest of its table
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableRowLayout {
+    /// index of the table's ast node
+    pub table: usize,
+    pub header: bool,
+    pub cells: Vec<TableCellLayout>,
+
+    /// x offsets of the lines between columns, relative to the start of the text
+    pub separators: Vec<f32>,
+    pub width: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableCellLayout {
+    /// range of the indexes of the layout job sections that render the cell
+    pub sections: (usize, usize),
+    pub alignment: ColumnAlignment,
+}
+
+/// Math rendered in place of its source, which is captured
+#[derive(Clone, Debug, PartialEq)]
+pub struct MathLayout {
+    /// source of the math including its delimiters, which its texture is cached by
+    pub source: String,
+
+    /// block math is rendered above the text of its galley; inline math is rendered in the space
+    /// before the layout job section at this index
+    pub section: Option<usize>,
+}
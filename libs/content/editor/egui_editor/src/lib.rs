@@ -11,19 +11,27 @@ use serde::Serialize;
 
 pub mod appearance;
 pub mod ast;
+pub mod attachments;
 pub mod bounds;
 pub mod buffer;
+pub mod collab;
+pub mod conflicts;
 pub mod debug;
 pub mod draw;
 pub mod editor;
+pub mod find;
 pub mod galleys;
+pub mod highlighting;
 pub mod images;
 pub mod input;
 pub mod layouts;
+pub mod math;
 pub mod offset_types;
+pub mod spellcheck;
 pub mod style;
 pub mod test_input;
 pub mod unicode_segs;
+pub mod wiki_links;
 
 #[cfg(target_vendor = "apple")]
 pub mod apple;
@@ -0,0 +1,792 @@
+use crate::appearance::Appearance;
+use crate::ast::Ast;
+use crate::buffer::SubBuffer;
+use crate::style::{BlockNode, InlineNode, MarkdownNode};
+use ab_glyph::{Font, FontArc, GlyphId, ScaleFont};
+use egui::{Color32, ColorImage, TextureId, Ui, Vec2};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::OnceLock;
+
+/// Textures of rendered math, by the math's source including its delimiters e.g. `$x^2$`
+#[derive(Clone, Default)]
+pub struct MathCache {
+    pub map: HashMap<String, MathTexture>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MathTexture {
+    pub texture: TextureId,
+    pub size: Vec2,
+
+    /// distance from the top of the texture to the math's baseline, which lines up with the
+    /// baseline of surrounding text
+    pub baseline: f32,
+}
+
+pub fn calc(
+    ast: &Ast, buffer: &SubBuffer, prior_cache: &MathCache, appearance: &Appearance, ui: &Ui,
+) -> MathCache {
+    let mut result = MathCache::default();
+    let mut prior_cache = prior_cache.clone();
+    let pixels_per_point = ui.ctx().pixels_per_point();
+    for node in &ast.nodes {
+        let display = match node.node_type {
+            MarkdownNode::Inline(InlineNode::Math) => false,
+            MarkdownNode::Block(BlockNode::Math) => true,
+            _ => continue,
+        };
+        let source = &buffer[node.range];
+        if result.map.contains_key(source) {
+            continue;
+        }
+
+        // re-use math rendered for the previous cache; textures left in the prior cache are freed
+        let texture = match prior_cache.map.remove(source) {
+            Some(texture) => texture,
+            None => {
+                let size = appearance.font_size() * pixels_per_point;
+                let (image, baseline) = render(&buffer[node.text_range], display, size);
+                let size = Vec2::new(image.size[0] as f32, image.size[1] as f32);
+                let texture = ui.ctx().tex_manager().write().alloc(
+                    source.to_string(),
+                    egui::ImageData::Color(image),
+                    Default::default(),
+                );
+                MathTexture {
+                    texture,
+                    size: size / pixels_per_point,
+                    baseline: baseline / pixels_per_point,
+                }
+            }
+        };
+        result.map.insert(source.to_string(), texture);
+    }
+
+    let texture_manager = ui.ctx().tex_manager();
+    for (_, eviction) in prior_cache.map.drain() {
+        texture_manager.write().free(eviction.texture);
+    }
+
+    result
+}
+
+/// Renders LaTeX math into a white image, to be tinted the color of surrounding text, at `size`
+/// pixels per em. Display math (as opposed to inline math) is rendered with larger operators and
+/// fractions. Returns the image and the distance from its top to the math's baseline. Unsupported
+/// commands are rendered as written.
+pub fn render(source: &str, display: bool, size: f32) -> (ColorImage, f32) {
+    let nodes = Parser { chars: source.chars().peekable() }.parse_list(false);
+    let layout = layout_list(&nodes, Style { size, display, script: false });
+
+    let padding = (size * 0.1).ceil();
+    let width = (layout.width + padding * 2.0).ceil().max(1.0) as usize;
+    let height = (layout.ascent + layout.descent + padding * 2.0)
+        .ceil()
+        .max(1.0) as usize;
+    let origin = (padding, padding + layout.ascent);
+
+    let mut coverage = vec![0.0f32; width * height];
+    let mut plot = |x: i64, y: i64, c: f32| {
+        if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+            let pixel = &mut coverage[y as usize * width + x as usize];
+            *pixel = (*pixel + c).min(1.0);
+        }
+    };
+    for item in &layout.items {
+        match *item {
+            Item::Glyph { font, id, size, x, y, italic } => {
+                let font = &fonts()[font];
+                let (x, y) = (origin.0 + x, origin.1 + y);
+                let glyph = id.with_scale_and_position(size, ab_glyph::point(x, y));
+                if let Some(outline) = font.outline_glyph(glyph) {
+                    let bounds = outline.px_bounds();
+                    outline.draw(|px, py, c| {
+                        let py = bounds.min.y + py as f32;
+                        // slant italic glyphs about their baseline
+                        let slant = if italic { (y - py) * 0.2 } else { 0.0 };
+                        plot((bounds.min.x + px as f32 + slant) as i64, py as i64, c);
+                    });
+                }
+            }
+            Item::Line { from, to, thickness } => {
+                let from = (origin.0 + from.0, origin.1 + from.1);
+                let to = (origin.0 + to.0, origin.1 + to.1);
+                draw_line(from, to, thickness, &mut plot);
+            }
+        }
+    }
+
+    let pixels = coverage
+        .into_iter()
+        .map(|c| {
+            let a = (c * 255.0).round() as u8;
+            Color32::from_rgba_premultiplied(a, a, a, a)
+        })
+        .collect();
+    (ColorImage { size: [width, height], pixels }, origin.1)
+}
+
+/// Draws an anti-aliased line segment, plotting the coverage of each pixel near it
+fn draw_line(
+    from: (f32, f32), to: (f32, f32), thickness: f32, plot: &mut impl FnMut(i64, i64, f32),
+) {
+    let radius = thickness / 2.0;
+    let (min_x, max_x) = (from.0.min(to.0) - radius - 1.0, from.0.max(to.0) + radius + 1.0);
+    let (min_y, max_y) = (from.1.min(to.1) - radius - 1.0, from.1.max(to.1) + radius + 1.0);
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_squared = (dx * dx + dy * dy).max(f32::EPSILON);
+    for y in min_y.floor() as i64..=max_y.ceil() as i64 {
+        for x in min_x.floor() as i64..=max_x.ceil() as i64 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let t = (((px - from.0) * dx + (py - from.1) * dy) / length_squared).clamp(0.0, 1.0);
+            let (cx, cy) = (from.0 + t * dx, from.1 + t * dy);
+            let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+            let c = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if c > 0.0 {
+                plot(x, y, c);
+            }
+        }
+    }
+}
+
+/// Fonts math is rendered with; each glyph is taken from the first font that has it
+fn fonts() -> &'static [FontArc] {
+    static FONTS: OnceLock<Vec<FontArc>> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fonts = vec![FontArc::try_from_slice(lb_fonts::PT_SANS_REGULAR).unwrap()];
+        // egui's default fonts cover greek letters and most math symbols
+        let defaults = egui::FontDefinitions::default().font_data;
+        for name in ["Ubuntu-Light", "Hack", "NotoEmoji-Regular"] {
+            if let Some(data) = defaults.get(name) {
+                if let Ok(font) = FontArc::try_from_vec(data.font.to_vec()) {
+                    fonts.push(font);
+                }
+            }
+        }
+        fonts
+    })
+}
+
+fn glyph_id(c: char) -> (usize, GlyphId) {
+    fonts()
+        .iter()
+        .enumerate()
+        .map(|(font, f)| (font, f.glyph_id(c)))
+        .find(|(_, id)| id.0 != 0)
+        .unwrap_or((0, GlyphId(0)))
+}
+
+/// A parsed piece of math with its superscript and subscript, if any
+#[derive(Debug)]
+struct Node {
+    atom: Atom,
+    sup: Option<Vec<Node>>,
+    sub: Option<Vec<Node>>,
+}
+
+#[derive(Debug)]
+enum Atom {
+    /// a symbol; letters are italic
+    Char(char, bool),
+    /// a binary operator or relation, which is spaced from its operands
+    Op(char),
+    /// a large operator e.g. a sum; `true` if its scripts are limits, above and below it in
+    /// display math
+    BigOp(char, bool),
+    /// upright text e.g. `\text{if}` or a function name e.g. `\sin`; `true` if its scripts are
+    /// limits e.g. `\lim_{x \to 0}`
+    Text(String, bool),
+    /// horizontal space in ems
+    Space(f32),
+    Group(Vec<Node>),
+    /// numerator, denominator, and whether there's a bar between them
+    Frac(Vec<Node>, Vec<Node>, bool),
+    Sqrt(Vec<Node>),
+    /// content with an accent over it e.g. `\hat{x}`; `None` for a bar
+    Accent(Vec<Node>, Option<char>),
+}
+
+impl Node {
+    fn new(atom: Atom) -> Self {
+        Self { atom, sup: None, sub: None }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Parses nodes through the end of the source or, if `in_group`, the closing brace
+    fn parse_list(&mut self, in_group: bool) -> Vec<Node> {
+        let mut result: Vec<Node> = Vec::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '}' if in_group => break,
+                '^' | '_' => {
+                    let script = self.parse_arg();
+                    if result.is_empty() {
+                        result.push(Node::new(Atom::Group(Vec::new())));
+                    }
+                    let base = result.last_mut().unwrap();
+                    if c == '^' {
+                        base.sup = Some(script);
+                    } else {
+                        base.sub = Some(script);
+                    }
+                }
+                _ => {
+                    if let Some(atom) = self.parse_atom(c) {
+                        result.push(Node::new(atom));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Parses the argument of a command or script: a group or a single atom
+    fn parse_arg(&mut self) -> Vec<Node> {
+        while let Some(c) = self.chars.next() {
+            match c {
+                '{' => return self.parse_list(true),
+                c if c.is_whitespace() => {}
+                c => return self.parse_atom(c).map(Node::new).into_iter().collect(),
+            }
+        }
+        Vec::new()
+    }
+
+    /// Parses the raw text of an argument e.g. the text in `\text{...}`
+    fn parse_text_arg(&mut self) -> String {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if self.chars.next_if_eq(&'{').is_none() {
+            return self.chars.next().map(String::from).unwrap_or_default();
+        }
+        let mut result = String::new();
+        let mut depth = 0;
+        for c in self.chars.by_ref() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    fn parse_atom(&mut self, c: char) -> Option<Atom> {
+        Some(match c {
+            '{' => Atom::Group(self.parse_list(true)),
+            '\\' => return self.parse_command(),
+            '}' | '&' => return None,
+            c if c.is_whitespace() => return None,
+            '-' => Atom::Op('−'),
+            '*' => Atom::Op('∗'),
+            '+' | '=' | '<' | '>' => Atom::Op(c),
+            '\'' => Atom::Char('′', false),
+            c => Atom::Char(c, c.is_alphabetic()),
+        })
+    }
+
+    fn parse_command(&mut self) -> Option<Atom> {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            // a command named by one symbol e.g. `\{` or `\,`
+            return Some(match self.chars.next()? {
+                ',' => Atom::Space(0.17),
+                ':' | '>' => Atom::Space(0.22),
+                ';' => Atom::Space(0.28),
+                '!' => Atom::Space(-0.17),
+                ' ' => Atom::Space(0.33),
+                '\\' => Atom::Space(1.0),
+                '|' => Atom::Char('‖', false),
+                c => Atom::Char(c, false),
+            });
+        }
+
+        let name = name.as_str();
+        if let Some(c) = greek(name) {
+            // lowercase greek letters are slanted like latin letters
+            return Some(Atom::Char(c, name.starts_with(|c: char| c.is_ascii_lowercase())));
+        }
+        if let Some(c) = symbol(name) {
+            return Some(Atom::Char(c, false));
+        }
+        if let Some(c) = operator(name) {
+            return Some(Atom::Op(c));
+        }
+        Some(match name {
+            "sum" => Atom::BigOp('∑', true),
+            "prod" => Atom::BigOp('∏', true),
+            "coprod" => Atom::BigOp('∐', true),
+            "bigcup" => Atom::BigOp('⋃', true),
+            "bigcap" => Atom::BigOp('⋂', true),
+            "int" => Atom::BigOp('∫', false),
+            "iint" => Atom::BigOp('∬', false),
+            "iiint" => Atom::BigOp('∭', false),
+            "oint" => Atom::BigOp('∮', false),
+            "lim" | "max" | "min" | "sup" | "inf" | "det" | "gcd" => {
+                Atom::Text(name.to_string(), true)
+            }
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "sinh" | "cosh" | "tanh" | "arcsin"
+            | "arccos" | "arctan" | "log" | "ln" | "lg" | "exp" | "deg" | "dim" | "ker" | "arg"
+            | "Pr" => Atom::Text(name.to_string(), false),
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                Atom::Frac(self.parse_arg(), self.parse_arg(), true)
+            }
+            "binom" => Atom::Group(vec![
+                Node::new(Atom::Char('(', false)),
+                Node::new(Atom::Frac(self.parse_arg(), self.parse_arg(), false)),
+                Node::new(Atom::Char(')', false)),
+            ]),
+            "sqrt" => {
+                // the index of an nth root isn't rendered
+                if self.chars.next_if_eq(&'[').is_some() {
+                    while self.chars.next().is_some_and(|c| c != ']') {}
+                }
+                Atom::Sqrt(self.parse_arg())
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mathrm" | "mathbf" | "mathsf" | "mathtt"
+            | "operatorname" => Atom::Text(self.parse_text_arg(), false),
+            "mathit" | "mathnormal" | "mathcal" | "mathbb" | "mathfrak" | "boldsymbol" => {
+                Atom::Group(self.parse_arg())
+            }
+            "bar" | "overline" => Atom::Accent(self.parse_arg(), None),
+            "hat" | "widehat" => Atom::Accent(self.parse_arg(), Some('^')),
+            "tilde" | "widetilde" => Atom::Accent(self.parse_arg(), Some('~')),
+            "vec" | "overrightarrow" => Atom::Accent(self.parse_arg(), Some('→')),
+            "dot" => Atom::Accent(self.parse_arg(), Some('˙')),
+            "ddot" => Atom::Accent(self.parse_arg(), Some('¨')),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl"
+            | "Bigr" | "middle" => {
+                // delimiters aren't sized; `.` is an invisible delimiter
+                self.chars.next_if_eq(&'.');
+                return None;
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => return None,
+            "quad" => Atom::Space(1.0),
+            "qquad" => Atom::Space(2.0),
+            _ => Atom::Text(format!("\\{}", name), false),
+        })
+    }
+}
+
+/// The greek letter for a command
+fn greek(name: &str) -> Option<char> {
+    Some(match name {
+        "alpha" => 'α',
+        "beta" => 'β',
+        "gamma" => 'γ',
+        "delta" => 'δ',
+        "epsilon" => 'ϵ',
+        "varepsilon" => 'ε',
+        "zeta" => 'ζ',
+        "eta" => 'η',
+        "theta" => 'θ',
+        "vartheta" => 'ϑ',
+        "iota" => 'ι',
+        "kappa" => 'κ',
+        "lambda" => 'λ',
+        "mu" => 'μ',
+        "nu" => 'ν',
+        "xi" => 'ξ',
+        "pi" => 'π',
+        "varpi" => 'ϖ',
+        "rho" => 'ρ',
+        "varrho" => 'ϱ',
+        "sigma" => 'σ',
+        "varsigma" => 'ς',
+        "tau" => 'τ',
+        "upsilon" => 'υ',
+        "phi" => 'ϕ',
+        "varphi" => 'φ',
+        "chi" => 'χ',
+        "psi" => 'ψ',
+        "omega" => 'ω',
+        "Gamma" => 'Γ',
+        "Delta" => 'Δ',
+        "Theta" => 'Θ',
+        "Lambda" => 'Λ',
+        "Xi" => 'Ξ',
+        "Pi" => 'Π',
+        "Sigma" => 'Σ',
+        "Upsilon" => 'Υ',
+        "Phi" => 'Φ',
+        "Psi" => 'Ψ',
+        "Omega" => 'Ω',
+        _ => return None,
+    })
+}
+
+/// The symbol for a command that's laid out like a digit
+fn symbol(name: &str) -> Option<char> {
+    Some(match name {
+        "infty" => '∞',
+        "partial" => '∂',
+        "nabla" => '∇',
+        "forall" => '∀',
+        "exists" => '∃',
+        "nexists" => '∄',
+        "neg" | "lnot" => '¬',
+        "emptyset" | "varnothing" => '∅',
+        "ldots" | "dots" => '…',
+        "cdots" => '⋯',
+        "vdots" => '⋮',
+        "ddots" => '⋱',
+        "prime" => '′',
+        "angle" => '∠',
+        "triangle" => '△',
+        "hbar" => 'ℏ',
+        "ell" => 'ℓ',
+        "Re" => 'ℜ',
+        "Im" => 'ℑ',
+        "aleph" => 'ℵ',
+        "circ" => '∘',
+        "degree" => '°',
+        "langle" => '⟨',
+        "rangle" => '⟩',
+        "lfloor" => '⌊',
+        "rfloor" => '⌋',
+        "lceil" => '⌈',
+        "rceil" => '⌉',
+        "vert" => '|',
+        "Vert" => '‖',
+        "backslash" => '\\',
+        "lbrace" => '{',
+        "rbrace" => '}',
+        _ => return None,
+    })
+}
+
+/// The symbol for a command that's laid out as a binary operator or relation
+fn operator(name: &str) -> Option<char> {
+    Some(match name {
+        "cdot" => '⋅',
+        "times" => '×',
+        "div" => '÷',
+        "pm" => '±',
+        "mp" => '∓',
+        "ast" => '∗',
+        "star" => '⋆',
+        "bullet" => '∙',
+        "oplus" => '⊕',
+        "otimes" => '⊗',
+        "cup" => '∪',
+        "cap" => '∩',
+        "setminus" => '∖',
+        "land" | "wedge" => '∧',
+        "lor" | "vee" => '∨',
+        "leq" | "le" => '≤',
+        "geq" | "ge" => '≥',
+        "neq" | "ne" => '≠',
+        "ll" => '≪',
+        "gg" => '≫',
+        "approx" => '≈',
+        "equiv" => '≡',
+        "sim" => '∼',
+        "simeq" => '≃',
+        "cong" => '≅',
+        "propto" => '∝',
+        "coloneqq" => '≔',
+        "in" => '∈',
+        "notin" => '∉',
+        "ni" => '∋',
+        "subset" => '⊂',
+        "supset" => '⊃',
+        "subseteq" => '⊆',
+        "supseteq" => '⊇',
+        "perp" => '⊥',
+        "parallel" => '∥',
+        "mid" => '∣',
+        "to" | "rightarrow" => '→',
+        "leftarrow" | "gets" => '←',
+        "leftrightarrow" => '↔',
+        "Rightarrow" | "implies" => '⇒',
+        "Leftarrow" => '⇐',
+        "Leftrightarrow" | "iff" => '⇔',
+        "mapsto" => '↦',
+        _ => return None,
+    })
+}
+
+#[derive(Clone, Copy)]
+struct Style {
+    /// pixels per em
+    size: f32,
+    /// display math has larger operators and fractions than inline math
+    display: bool,
+    /// scripts are smaller and their operators aren't spaced
+    script: bool,
+}
+
+impl Style {
+    fn script(self) -> Self {
+        Self { size: self.size * 0.7, display: false, script: true }
+    }
+}
+
+/// Laid out math: items positioned relative to the start of the math's baseline, with y
+/// increasing downwards
+#[derive(Default)]
+struct Layout {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    items: Vec<Item>,
+}
+
+enum Item {
+    Glyph { font: usize, id: GlyphId, size: f32, x: f32, y: f32, italic: bool },
+    Line { from: (f32, f32), to: (f32, f32), thickness: f32 },
+}
+
+impl Layout {
+    /// Adds `other`'s items, positioned at `(x, y)`, and grows to fit them
+    fn add(&mut self, other: Layout, x: f32, y: f32) {
+        self.width = self.width.max(x + other.width);
+        self.ascent = self.ascent.max(other.ascent - y);
+        self.descent = self.descent.max(other.descent + y);
+        for item in other.items {
+            self.items.push(match item {
+                Item::Glyph { font, id, size, x: gx, y: gy, italic } => {
+                    Item::Glyph { font, id, size, x: gx + x, y: gy + y, italic }
+                }
+                Item::Line { from, to, thickness } => Item::Line {
+                    from: (from.0 + x, from.1 + y),
+                    to: (to.0 + x, to.1 + y),
+                    thickness,
+                },
+            });
+        }
+    }
+
+    /// Adds `other` centered horizontally in the first `width` of this layout
+    fn add_centered(&mut self, other: Layout, width: f32, y: f32) {
+        let x = (width - other.width) / 2.0;
+        self.add(other, x, y);
+    }
+
+    fn add_line(&mut self, from: (f32, f32), to: (f32, f32), thickness: f32) {
+        let radius = thickness / 2.0;
+        self.width = self.width.max(from.0.max(to.0));
+        self.ascent = self.ascent.max(-from.1.min(to.1) + radius);
+        self.descent = self.descent.max(from.1.max(to.1) + radius);
+        self.items.push(Item::Line { from, to, thickness });
+    }
+
+    /// Repositions items so the layout's bottom is its baseline, for placing it above something
+    fn above(self) -> Layout {
+        let descent = self.descent;
+        let mut result = Layout::default();
+        result.add(self, 0.0, -descent);
+        result
+    }
+}
+
+fn layout_list(nodes: &[Node], style: Style) -> Layout {
+    let mut result = Layout::default();
+    for node in nodes {
+        let layout = layout_node(node, style);
+        let x = result.width;
+        result.add(layout, x, 0.0);
+    }
+    result
+}
+
+fn layout_node(node: &Node, style: Style) -> Layout {
+    let s = style.size;
+    let limits = style.display && matches!(node.atom, Atom::BigOp(_, true) | Atom::Text(_, true));
+    let base = layout_atom(&node.atom, style);
+    if node.sup.is_none() && node.sub.is_none() {
+        return base;
+    }
+    let sup = node
+        .sup
+        .as_ref()
+        .map(|sup| layout_list(sup, style.script()));
+    let sub = node
+        .sub
+        .as_ref()
+        .map(|sub| layout_list(sub, style.script()));
+
+    let mut result = Layout::default();
+    if limits {
+        // scripts centered above and below
+        let width = [Some(&base), sup.as_ref(), sub.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|layout| layout.width)
+            .fold(0.0, f32::max);
+        let (base_ascent, base_descent) = (base.ascent, base.descent);
+        result.add_centered(base, width, 0.0);
+        if let Some(sup) = sup {
+            let y = -(base_ascent + s * 0.1 + sup.descent);
+            result.add_centered(sup, width, y);
+        }
+        if let Some(sub) = sub {
+            let y = base_descent + s * 0.1 + sub.ascent;
+            result.add_centered(sub, width, y);
+        }
+    } else {
+        let x = base.width + s * 0.05;
+        let mut sup_shift = (base.ascent - s * 0.25).max(s * 0.4);
+        let mut sub_shift = (base.descent + s * 0.05).max(s * 0.2);
+        if let (Some(sup), Some(sub)) = (&sup, &sub) {
+            // keep a gap between superscript and subscript
+            let gap = (sup_shift - sup.descent) + (sub_shift - sub.ascent);
+            if gap < s * 0.1 {
+                sub_shift += (s * 0.1 - gap) / 2.0;
+                sup_shift += (s * 0.1 - gap) / 2.0;
+            }
+        }
+        result.add(base, 0.0, 0.0);
+        if let Some(sup) = sup {
+            result.add(sup, x, -sup_shift);
+        }
+        if let Some(sub) = sub {
+            result.add(sub, x, sub_shift);
+        }
+    }
+    result
+}
+
+fn layout_atom(atom: &Atom, style: Style) -> Layout {
+    let s = style.size;
+    // the height of the math axis, which fractions and large operators are centered on
+    let axis = s * 0.25;
+    let thickness = (s * 0.05).max(1.0);
+    match atom {
+        Atom::Char(c, italic) => layout_glyph(*c, s, *italic),
+        Atom::Op(c) => {
+            // operators are spaced except in scripts
+            let space = if style.script { 0.0 } else { s * 0.22 };
+            let mut result = Layout::default();
+            result.add(layout_glyph(*c, s, false), space, 0.0);
+            result.width += space;
+            result
+        }
+        Atom::BigOp(c, _) => {
+            let size = if style.display { s * 1.6 } else { s * 1.2 };
+            let glyph = layout_glyph(*c, size, false);
+            // center the operator on the math axis
+            let y = (glyph.ascent - glyph.descent) / 2.0 - axis;
+            let mut result = Layout::default();
+            result.add(glyph, 0.0, y);
+            result.width += s * 0.1;
+            result
+        }
+        Atom::Text(text, _) => {
+            let mut result = Layout::default();
+            for c in text.chars() {
+                let x = result.width;
+                result.add(layout_glyph(c, s, false), x, 0.0);
+            }
+            result.width += s * 0.1;
+            result
+        }
+        Atom::Space(ems) => Layout { width: ems * s, ..Default::default() },
+        Atom::Group(nodes) => layout_list(nodes, style),
+        Atom::Frac(numerator, denominator, bar) => {
+            let inner =
+                if style.display { Style { display: false, ..style } } else { style.script() };
+            let numerator = layout_list(numerator, inner);
+            let denominator = layout_list(denominator, inner);
+            let gap = if style.display { s * 0.15 } else { s * 0.1 };
+            let padding = s * 0.1;
+            let width = numerator.width.max(denominator.width) + padding * 2.0;
+
+            let mut result = Layout::default();
+            let numerator_y = -(axis + thickness / 2.0 + gap + numerator.descent);
+            let denominator_y = -axis + thickness / 2.0 + gap + denominator.ascent;
+            result.add_centered(numerator, width, numerator_y);
+            result.add_centered(denominator, width, denominator_y);
+            if *bar {
+                result.add_line((padding / 2.0, -axis), (width - padding / 2.0, -axis), thickness);
+            }
+            result
+        }
+        Atom::Sqrt(content) => {
+            let content = layout_list(content, style);
+            let gap = s * 0.1;
+            let top = -(content.ascent.max(s * 0.7) + gap + thickness / 2.0);
+            let bottom = content.descent.max(s * 0.2) + gap;
+            let sign_width = s * 0.55;
+
+            // a tick, a stroke down to the bottom, and a stroke up to the bar over the content
+            let mut result = Layout::default();
+            let middle = bottom - (bottom - top) * 0.4;
+            let points = [
+                (0.0, middle + s * 0.05),
+                (sign_width * 0.25, middle),
+                (sign_width * 0.55, bottom),
+                (sign_width, top),
+                (sign_width + content.width + gap, top),
+            ];
+            for segment in points.windows(2) {
+                result.add_line(segment[0], segment[1], thickness);
+            }
+            result.add(content, sign_width + gap / 2.0, 0.0);
+            result.width += gap;
+            result
+        }
+        Atom::Accent(content, accent) => {
+            let content = layout_list(content, style);
+            let width = content.width;
+            let top = -(content.ascent.max(s * 0.5) + s * 0.1);
+            let mut result = Layout::default();
+            result.add(content, 0.0, 0.0);
+            match accent {
+                Some(accent) => {
+                    let accent = layout_glyph(*accent, s * 0.8, false);
+                    result.add_centered(accent.above(), width, top);
+                }
+                None => result.add_line((0.0, top), (width, top), thickness),
+            }
+            result
+        }
+    }
+}
+
+fn layout_glyph(c: char, size: f32, italic: bool) -> Layout {
+    let (font_idx, id) = glyph_id(c);
+    let font = &fonts()[font_idx];
+    let scaled = font.as_scaled(size);
+    let width = scaled.h_advance(id) + if italic { size * 0.05 } else { 0.0 };
+    let (ascent, descent) = match font.outline_glyph(id.with_scale(size)) {
+        Some(outline) => (-outline.px_bounds().min.y, outline.px_bounds().max.y),
+        None => (0.0, 0.0),
+    };
+    Layout {
+        width,
+        ascent: ascent.max(0.0),
+        descent: descent.max(0.0),
+        items: vec![Item::Glyph { font: font_idx, id, size, x: 0.0, y: 0.0, italic }],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_render() {
+        let (plain, baseline) = super::render("x", false, 20.0);
+        assert!(plain.pixels.iter().any(|pixel| pixel.a() > 0));
+        assert!(baseline > 0.0 && baseline < plain.size[1] as f32);
+
+        // fractions stack their numerator over their denominator
+        let (fraction, _) = super::render("\\frac{x}{x}", false, 20.0);
+        assert!(fraction.size[1] > plain.size[1]);
+
+        // display style is larger than inline style
+        let (inline, _) = super::render("\\sum_{i=1}^{n} i", false, 20.0);
+        let (display, _) = super::render("\\sum_{i=1}^{n} i", true, 20.0);
+        assert!(display.size[1] > inline.size[1]);
+    }
+}
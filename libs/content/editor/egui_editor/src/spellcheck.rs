@@ -0,0 +1,873 @@
+use crate::ast::{Ast, AstTextRangeType};
+use crate::buffer::SubBuffer;
+use crate::input::canonical::{Location, Modification, Region};
+use crate::input::click_checker::ClickChecker;
+use crate::offset_types::{DocCharOffset, RangeExt};
+use crate::style::{BlockNode, InlineNode, MarkdownNode};
+use egui::{Context, Event, Key, Order, PointerButton, Pos2, Rect, Ui};
+use linkify::LinkFinder;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::{env, fs, io, thread};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Maximum number of replacements suggested for a misspelled word
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Namespace of the account settings holding the words the user added to their dictionary, so that
+/// every device signed into the account uses the same words. Each word is its own setting, so
+/// words added on different devices are all kept when sync merges the settings.
+const CUSTOM_WORDS_NAMESPACE: &str = "dictionary";
+
+/// Serializes saving custom words, which reads the saved words then writes them with the new one
+static SAVING_CUSTOM_WORDS: Mutex<()> = Mutex::new(());
+
+/// A flag in a `.dic` or `.aff` file, which names an affix rule or marks a word e.g. as forbidden
+type Flag = u64;
+
+/// How flags are written, set by the `.aff` file's `FLAG` option
+#[derive(Clone, Copy, Default, PartialEq)]
+enum FlagType {
+    /// one character per flag
+    #[default]
+    Char,
+    /// two characters per flag
+    Long,
+    /// comma-separated numbers
+    Num,
+}
+
+/// A character in an affix rule's condition
+#[derive(Clone, Debug)]
+enum ConditionChar {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+/// A prefix or suffix rule from an `.aff` file e.g. `SFX D y ied [^aeiou]y`: words with flag `D`
+/// that end in a consonant then `y` can replace the `y` with `ied`
+#[derive(Clone, Debug)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<ConditionChar>,
+}
+
+impl Affix {
+    /// The word this suffix rule was applied to, to make `word`, if it applies
+    fn remove_suffix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_suffix(self.add.as_str())?;
+        if rest.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", rest, self.strip);
+        let chars = stem.chars().collect::<Vec<_>>();
+        let condition_start = chars.len().checked_sub(self.condition.len())?;
+        matches(&self.condition, &chars[condition_start..]).then_some(stem)
+    }
+
+    /// The word this prefix rule was applied to, to make `word`, if it applies
+    fn remove_prefix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_prefix(self.add.as_str())?;
+        if rest.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", self.strip, rest);
+        let chars = stem.chars().take(self.condition.len()).collect::<Vec<_>>();
+        matches(&self.condition, &chars).then_some(stem)
+    }
+}
+
+/// Whether `chars` match `condition`, char for char. Suffix conditions are matched against the
+/// end of a word and prefix conditions against its start.
+fn matches(condition: &[ConditionChar], chars: &[char]) -> bool {
+    chars.len() == condition.len()
+        && condition
+            .iter()
+            .zip(chars)
+            .all(|(condition, c)| match condition {
+                ConditionChar::Any => true,
+                ConditionChar::OneOf(set) => set.contains(c),
+                ConditionChar::NoneOf(set) => !set.contains(c),
+            })
+}
+
+/// A Hunspell dictionary: a `.dic` file listing words, each with flags naming the rules in the
+/// matching `.aff` file for the prefixes and suffixes the word takes. Supports the options used
+/// to check words and suggest replacements (`FLAG`, `AF`, `PFX`, `SFX`, `TRY`, `REP`,
+/// `FORBIDDENWORD`, `NOSUGGEST`, `NEEDAFFIX` and `ONLYINCOMPOUND`); compounding isn't supported.
+#[derive(Default)]
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    need_affix: Option<Flag>,
+    only_in_compound: Option<Flag>,
+}
+
+impl Dictionary {
+    /// Loads a dictionary from its `.aff` and `.dic` files. Files that aren't UTF-8 are read as
+    /// Latin-1, the encoding of most older dictionaries.
+    pub fn load(aff: &Path, dic: &Path) -> io::Result<Self> {
+        Ok(Self::new(&decode(&fs::read(aff)?), &decode(&fs::read(dic)?)))
+    }
+
+    /// Parses a dictionary from the contents of its `.aff` and `.dic` files
+    pub fn new(aff: &str, dic: &str) -> Self {
+        let mut result = Self::default();
+
+        // the flag type and aliases are needed to parse flags, so options are read first
+        let mut flag_type = FlagType::Char;
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        for line in aff.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => flag_type = FlagType::Long,
+                ["FLAG", "num", ..] => flag_type = FlagType::Num,
+                ["AF", flags, ..] if flags.parse::<usize>().is_err() => {
+                    aliases.push(parse_flags(flags, flag_type))
+                }
+                _ => {}
+            }
+        }
+        let flags = |field: &str| match field.parse::<usize>() {
+            Ok(alias) if !aliases.is_empty() => aliases
+                .get(alias.wrapping_sub(1))
+                .cloned()
+                .unwrap_or_default(),
+            _ => parse_flags(field, flag_type),
+        };
+        let flag = |field: &str| flags(field).first().copied();
+
+        let mut cross_products = HashMap::new();
+        for line in aff.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["TRY", chars, ..] => result.try_chars = chars.chars().collect(),
+                ["REP", from, to, ..] => result
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                ["FORBIDDENWORD", f, ..] => result.forbidden = flag(f),
+                ["NOSUGGEST", f, ..] => result.no_suggest = flag(f),
+                ["NEEDAFFIX", f, ..] | ["PSEUDOROOT", f, ..] => result.need_affix = flag(f),
+                ["ONLYINCOMPOUND", f, ..] => result.only_in_compound = flag(f),
+                [kind @ ("PFX" | "SFX"), f, cross_product @ ("Y" | "N"), count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    cross_products.insert((*kind, flag(f)), *cross_product == "Y");
+                }
+                [kind @ ("PFX" | "SFX"), f, strip, add, rest @ ..] => {
+                    let flag = flag(f);
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag: flag.unwrap_or_default(),
+                        cross_product: cross_products
+                            .get(&(*kind, flag))
+                            .copied()
+                            .unwrap_or_default(),
+                        strip: if *strip == "0" { "" } else { *strip }.to_string(),
+                        add: if add == "0" { "" } else { add }.to_string(),
+                        condition: parse_condition(rest.first().unwrap_or(&".")),
+                    };
+                    if *kind == "PFX" {
+                        result.prefixes.push(affix);
+                    } else {
+                        result.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // the first line is the number of words
+        for line in dic.lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            let (word, word_flags) = match entry.split_once('/') {
+                Some((word, word_flags)) => (word, flags(word_flags)),
+                None => (entry, Vec::new()),
+            };
+            if !word.is_empty() {
+                result
+                    .words
+                    .entry(word.to_string())
+                    .or_default()
+                    .extend(word_flags);
+            }
+        }
+
+        result
+    }
+
+    /// Whether `word` is spelled correctly. Words are also correct capitalized (as at the start of
+    /// a sentence) and in all caps.
+    pub fn check(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        if self.check_exact(&word) {
+            return true;
+        }
+        match Case::of(&word) {
+            Case::Title => self.check_exact(&word.to_lowercase()),
+            Case::Upper => {
+                let lower = word.to_lowercase();
+                self.check_exact(&lower) || self.check_exact(&Case::Title.apply(&lower))
+            }
+            Case::Lower | Case::Mixed => false,
+        }
+    }
+
+    /// Words that `word` may be a misspelling of, most likely first
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let word = word.replace('’', "'");
+
+        // capitalized words are corrected in lowercase then capitalized the same way
+        let case = Case::of(&word);
+        let base = match case {
+            Case::Title | Case::Upper => word.to_lowercase(),
+            Case::Lower | Case::Mixed => word.clone(),
+        };
+
+        let mut result: Vec<String> = Vec::new();
+        for candidate in self.candidates(&base) {
+            let candidate = case.apply(&candidate);
+            if candidate != word
+                && !result.contains(&candidate)
+                && candidate.split(' ').all(|part| self.check(part))
+                && !self.has_flag(&candidate, self.no_suggest)
+            {
+                result.push(candidate);
+                if result.len() == MAX_SUGGESTIONS {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    /// Possible corrections of `word`, most likely first: replacements of common misspellings
+    /// from the `REP` table, then words one edit away, then `word` split into two words
+    fn candidates(&self, word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let try_chars =
+            if self.try_chars.is_empty() { ('a'..='z').collect() } else { self.try_chars.clone() };
+        let mut result = Vec::new();
+
+        for (from, to) in &self.replacements {
+            for (i, _) in word.match_indices(from.as_str()) {
+                result.push(format!("{}{}{}", &word[..i], to, &word[i + from.len()..]));
+            }
+        }
+        for i in 1..chars.len() {
+            let mut transposed = chars.clone();
+            transposed.swap(i - 1, i);
+            result.push(transposed.into_iter().collect());
+        }
+        for i in 0..chars.len() {
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            result.push(deleted.into_iter().collect());
+        }
+        for i in 0..chars.len() {
+            for &c in try_chars.iter().filter(|&&c| c != chars[i]) {
+                let mut substituted = chars.clone();
+                substituted[i] = c;
+                result.push(substituted.into_iter().collect());
+            }
+        }
+        for i in 0..=chars.len() {
+            for &c in &try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                result.push(inserted.into_iter().collect());
+            }
+        }
+        for (i, _) in word.char_indices().skip(1) {
+            result.push(format!("{} {}", &word[..i], &word[i..]));
+        }
+
+        result.retain(|candidate| !candidate.is_empty());
+        result
+    }
+
+    /// Whether `word` is in the dictionary or is a word in the dictionary with affixes it takes
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self.flagged(flags, self.forbidden) {
+                return false;
+            }
+            if !self.flagged(flags, self.need_affix) && !self.flagged(flags, self.only_in_compound)
+            {
+                return true;
+            }
+        }
+
+        for suffix in &self.suffixes {
+            if let Some(stem) = suffix.remove_suffix(word) {
+                if self.takes(&stem, &[suffix.flag]) {
+                    return true;
+                }
+            }
+        }
+        for prefix in &self.prefixes {
+            if let Some(stem) = prefix.remove_prefix(word) {
+                if self.takes(&stem, &[prefix.flag]) {
+                    return true;
+                }
+                if !prefix.cross_product {
+                    continue;
+                }
+                for suffix in self.suffixes.iter().filter(|suffix| suffix.cross_product) {
+                    if let Some(stem) = suffix.remove_suffix(&stem) {
+                        if self.takes(&stem, &[prefix.flag, suffix.flag]) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether `stem` is a dictionary word that takes the affixes with all of `flags`
+    fn takes(&self, stem: &str, flags: &[Flag]) -> bool {
+        match self.words.get(stem) {
+            Some(stem_flags) => {
+                flags.iter().all(|flag| stem_flags.contains(flag))
+                    && !self.flagged(stem_flags, self.forbidden)
+                    && !self.flagged(stem_flags, self.only_in_compound)
+            }
+            None => false,
+        }
+    }
+
+    fn has_flag(&self, word: &str, flag: Option<Flag>) -> bool {
+        self.words
+            .get(word)
+            .map(|flags| self.flagged(flags, flag))
+            .unwrap_or_default()
+    }
+
+    fn flagged(&self, flags: &[Flag], flag: Option<Flag>) -> bool {
+        matches!(flag, Some(flag) if flags.contains(&flag))
+    }
+}
+
+/// How a word is capitalized
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Case {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+impl Case {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let first_upper = letters.next().map(char::is_uppercase).unwrap_or_default();
+        let rest = letters.map(char::is_uppercase).collect::<Vec<_>>();
+        match (first_upper, rest.iter().all(|&upper| upper), rest.iter().any(|&upper| upper)) {
+            (false, _, false) => Case::Lower,
+            (true, _, false) => Case::Title,
+            (true, true, _) => Case::Upper,
+            _ => Case::Mixed,
+        }
+    }
+
+    /// Capitalizes a lowercase word this way
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::Title => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            Case::Upper => word.to_uppercase(),
+            Case::Lower | Case::Mixed => word.to_string(),
+        }
+    }
+}
+
+fn parse_flags(flags: &str, flag_type: FlagType) -> Vec<Flag> {
+    match flag_type {
+        FlagType::Char => flags.chars().map(|c| c as Flag).collect(),
+        FlagType::Long => flags
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| pair.iter().fold(0, |flag, &c| flag << 32 | c as Flag))
+            .collect(),
+        FlagType::Num => flags
+            .split(',')
+            .filter_map(|flag| flag.trim().parse().ok())
+            .collect(),
+    }
+}
+
+/// Parses an affix rule's condition e.g. `[^aeiou]y`; `.` matches any character
+fn parse_condition(condition: &str) -> Vec<ConditionChar> {
+    if condition == "." {
+        return Vec::new();
+    }
+    let mut result = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        result.push(match c {
+            '.' => ConditionChar::Any,
+            '[' => {
+                let set = chars.by_ref().take_while(|&c| c != ']').collect::<Vec<_>>();
+                match set.split_first() {
+                    Some(('^', set)) => ConditionChar::NoneOf(set.to_vec()),
+                    _ => ConditionChar::OneOf(set),
+                }
+            }
+            c => ConditionChar::OneOf(vec![c]),
+        });
+    }
+    result
+}
+
+fn decode(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// The dictionary for the user's language (or US English if there's none), loaded the first time
+/// it's needed from the first directory that has it: those in `DICPATH`, as for Hunspell, then
+/// the usual system locations. `None` if no dictionary is installed.
+pub fn system_dictionary() -> Option<Arc<Dictionary>> {
+    static DICTIONARY: OnceLock<Option<Arc<Dictionary>>> = OnceLock::new();
+    DICTIONARY
+        .get_or_init(|| {
+            let dirs = dictionary_dirs();
+            [language(), "en_US".to_string()]
+                .iter()
+                .find_map(|language| {
+                    dirs.iter().find_map(|dir| {
+                        let aff = dir.join(format!("{}.aff", language));
+                        let dic = dir.join(format!("{}.dic", language));
+                        Dictionary::load(&aff, &dic).ok().map(Arc::new)
+                    })
+                })
+        })
+        .clone()
+}
+
+/// The user's language from the locale e.g. `de_DE` for `de_DE.UTF-8`
+fn language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .filter_map(|locale| locale.split(['.', '@']).next().map(str::to_string))
+        .find(|language| !language.is_empty() && language != "C" && language != "POSIX")
+        .unwrap_or_else(|| "en_US".to_string())
+}
+
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut result = env::var_os("DICPATH")
+        .map(|paths| env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    result.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/share/myspell/dicts",
+            "/usr/local/share/hunspell",
+            "/Library/Spelling",
+        ]
+        .map(PathBuf::from),
+    );
+    if let Some(home) = env::var_os("HOME") {
+        result.push(Path::new(&home).join("Library/Spelling"));
+        result.push(Path::new(&home).join(".local/share/hunspell"));
+    }
+    result
+}
+
+/// The words the user added to their dictionary. They're loaded from the account's settings in the
+/// background when the editor is first shown, and each added word is saved in the background.
+#[derive(Clone, Default)]
+pub struct CustomWords {
+    pub words: HashSet<String>,
+    loading: bool,
+    loaded: Arc<Mutex<Option<HashSet<String>>>>,
+}
+
+impl CustomWords {
+    /// Starts loading the saved words unless they're already loading
+    pub fn load(&mut self, core: &lb::Core, ctx: &Context) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+
+        let loaded = self.loaded.clone();
+        let core = core.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let words = match core.get_settings::<HashMap<String, bool>>(CUSTOM_WORDS_NAMESPACE) {
+                Ok(words) => words.unwrap_or_default(),
+                Err(err) => {
+                    tracing::error!("failed to load custom dictionary: {:?}", err);
+                    return;
+                }
+            };
+            *loaded.lock().unwrap() = Some(words.into_keys().collect());
+            ctx.request_repaint();
+        });
+    }
+
+    /// Adds the words loaded since the last call. Returns whether any were loaded.
+    pub fn update(&mut self) -> bool {
+        match self.loaded.lock().unwrap().take() {
+            Some(words) => {
+                self.words.extend(words);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds a word, which is accepted right away and saved in the background. If it can't be saved,
+    /// it's still accepted until the editor is closed.
+    pub fn add(&mut self, core: &lb::Core, word: String) {
+        self.words.insert(word.clone());
+
+        let core = core.clone();
+        thread::spawn(move || {
+            let _saving = SAVING_CUSTOM_WORDS.lock().unwrap();
+            let result = core
+                .get_settings::<HashMap<String, bool>>(CUSTOM_WORDS_NAMESPACE)
+                .and_then(|words| {
+                    let mut words = words.unwrap_or_default();
+                    words.insert(word.clone(), true);
+                    core.set_settings(CUSTOM_WORDS_NAMESPACE, &words)
+                });
+            if let Err(err) = result {
+                tracing::error!("failed to add {} to custom dictionary: {:?}", word, err);
+            }
+        });
+    }
+}
+
+/// Misspelled words in the document. Whether each word is spelled correctly is remembered so that
+/// editing the document only checks new words.
+#[derive(Clone, Default)]
+pub struct SpellcheckCache {
+    pub misspellings: Vec<(DocCharOffset, DocCharOffset)>,
+    pub words: HashMap<String, bool>,
+}
+
+pub fn calc(
+    ast: &Ast, buffer: &SubBuffer, prior_cache: &SpellcheckCache, dictionary: Option<&Dictionary>,
+    custom_words: &HashSet<String>,
+) -> SpellcheckCache {
+    let mut result = SpellcheckCache::default();
+    let Some(dictionary) = dictionary else {
+        return result;
+    };
+
+    let finder = {
+        let mut this = LinkFinder::new();
+        this.url_must_have_scheme(false);
+        this
+    };
+    for text_range in ast.iter_text_ranges() {
+        if text_range.range_type != AstTextRangeType::Text
+            || text_range
+                .ancestors
+                .iter()
+                .any(|&ancestor| !is_prose(&ast.nodes[ancestor].node_type))
+        {
+            continue;
+        }
+
+        let text = &buffer[text_range.range];
+        let text_start = buffer.segs.offset_to_byte(text_range.range.start());
+        let links = finder.links(text).collect::<Vec<_>>();
+        for (word_start, word) in text.split_word_bound_indices() {
+            let word_end = word_start + word.len();
+            if !word.chars().any(char::is_alphabetic)
+                || word.chars().any(|c| c.is_numeric() || c == '_')
+                || links
+                    .iter()
+                    .any(|link| link.start() < word_end && word_start < link.end())
+            {
+                continue;
+            }
+
+            if custom_words.contains(word) || custom_words.contains(&word.to_lowercase()) {
+                continue;
+            }
+            let correct = match result.words.get(word).or(prior_cache.words.get(word)) {
+                Some(&correct) => correct,
+                None => dictionary.check(word),
+            };
+            result.words.insert(word.to_string(), correct);
+            if !correct {
+                result.misspellings.push(
+                    buffer
+                        .segs
+                        .range_to_char((text_start + word_start, text_start + word_end)),
+                );
+            }
+        }
+    }
+    result
+}
+
+/// Whether text in a node is prose to be spellchecked, as opposed to code, math or link targets
+fn is_prose(node: &MarkdownNode) -> bool {
+    !matches!(
+        node,
+        MarkdownNode::Inline(
+            InlineNode::Code
+                | InlineNode::Link(..)
+                | InlineNode::WikiLink(..)
+                | InlineNode::Image(..)
+                | InlineNode::Math
+                | InlineNode::FootnoteReference(..)
+        ) | MarkdownNode::Block(BlockNode::Code | BlockNode::Math)
+    )
+}
+
+/// Suggests replacements for a misspelled word that was right-clicked
+#[derive(Default)]
+pub struct SpellcheckMenu {
+    pub active: Option<SpellingSuggestions>,
+
+    /// where the menu was shown last frame; clicks there are for the menu, not the text
+    pub rect: Option<Rect>,
+}
+
+pub struct SpellingSuggestions {
+    pub range: (DocCharOffset, DocCharOffset),
+    pub word: String,
+    pub suggestions: Vec<String>,
+    pub pos: Pos2,
+}
+
+/// What was picked from the spellcheck menu
+pub enum SpellcheckAction {
+    /// replaces the misspelled word with a suggestion
+    Replace(Modification),
+    /// adds the misspelled word to the user's dictionary
+    AddToDictionary(String),
+}
+
+impl SpellcheckMenu {
+    /// Opens the menu when a misspelled word is right-clicked and closes it for other clicks and
+    /// escape, returning the rest of the events
+    pub fn handle_events(
+        &mut self, events: &[Event], click_checker: impl ClickChecker, cache: &SpellcheckCache,
+        buffer: &SubBuffer, dictionary: Option<&Dictionary>,
+    ) -> Vec<Event> {
+        let mut result = Vec::new();
+        for event in events {
+            match event {
+                Event::PointerButton { pos, .. }
+                    if self
+                        .rect
+                        .map(|rect| rect.contains(*pos))
+                        .unwrap_or_default() => {}
+                Event::PointerButton { pos, button: PointerButton::Secondary, pressed, .. }
+                    if click_checker.text(*pos).is_some() =>
+                {
+                    if !*pressed {
+                        continue;
+                    }
+                    let offset = click_checker.pos_to_char_offset(*pos);
+                    self.active = cache
+                        .misspellings
+                        .iter()
+                        .find(|range| range.contains_inclusive(offset))
+                        .map(|&range| {
+                            let word = buffer[range].to_string();
+                            let suggestions = dictionary
+                                .map(|dictionary| dictionary.suggest(&word))
+                                .unwrap_or_default();
+                            SpellingSuggestions { range, word, suggestions, pos: *pos }
+                        });
+                }
+                Event::PointerButton { pressed: true, .. } => {
+                    self.active = None;
+                    result.push(event.clone());
+                }
+                Event::Key { key: Key::Escape, pressed, .. } if self.active.is_some() => {
+                    if *pressed {
+                        self.active = None;
+                    }
+                }
+                _ => result.push(event.clone()),
+            }
+        }
+        result
+    }
+
+    /// Shows the menu, returning what was picked from it if anything
+    pub fn show(&mut self, ui: &mut Ui) -> Option<SpellcheckAction> {
+        let Some(active) = &self.active else {
+            self.rect = None;
+            return None;
+        };
+
+        let mut action = None;
+        let response = egui::Area::new(ui.id().with("spellcheck_menu"))
+            .order(Order::Foreground)
+            .fixed_pos(active.pos)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(300.0);
+                    if active.suggestions.is_empty() {
+                        ui.label("No suggestions");
+                    }
+                    for suggestion in &active.suggestions {
+                        if ui.selectable_label(false, suggestion).clicked() {
+                            action = Some(SpellcheckAction::Replace(Modification::Replace {
+                                region: Region::BetweenLocations {
+                                    start: Location::DocCharOffset(active.range.0),
+                                    end: Location::DocCharOffset(active.range.1),
+                                },
+                                text: suggestion.clone(),
+                            }));
+                        }
+                    }
+                    ui.separator();
+                    if ui.selectable_label(false, "Add to Dictionary").clicked() {
+                        action = Some(SpellcheckAction::AddToDictionary(active.word.clone()));
+                    }
+                })
+            })
+            .response;
+        self.rect = Some(response.rect);
+
+        if action.is_some() {
+            self.active = None;
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Dictionary;
+    use crate::buffer::SubBuffer;
+    use std::collections::HashSet;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+NOSUGGEST !
+FORBIDDENWORD X
+
+REP 2
+REP f ph
+REP ph f
+
+PFX A Y 1
+PFX A   0     re         .
+
+SFX D Y 4
+SFX D   0     d          e
+SFX D   y     ied        [^aeiou]y
+SFX D   0     ed         [^ey]
+SFX D   0     ed         [aeiou]y
+
+SFX S Y 3
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [aeiou]y
+SFX S   0     s          [^sxzhy]
+";
+
+    const DIC: &str = "8
+create/ADS
+carry/DS
+play/ADS
+the
+phone/S
+Paris
+damn/!
+irregardless/X
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::new(AFF, DIC)
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = dictionary();
+        for word in [
+            "create",
+            "created",
+            "creates",
+            "recreate",
+            "recreated",
+            "carried",
+            "carries",
+            "played",
+            "replays",
+            "the",
+            "The",
+            "THE",
+            "Paris",
+            "PARIS",
+            "damn",
+        ] {
+            assert!(dictionary.check(word), "{} should be correct", word);
+        }
+        for word in ["creat", "carryed", "recarried", "teh", "paris", "irregardless", "tHe"] {
+            assert!(!dictionary.check(word), "{} should be misspelled", word);
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("teh"), vec!["the"]);
+        assert_eq!(dictionary.suggest("Teh"), vec!["The"]);
+        assert_eq!(dictionary.suggest("fone"), vec!["phone"]);
+        assert_eq!(dictionary.suggest("carryed")[0], "carried");
+        assert_eq!(dictionary.suggest("theplayed"), vec!["the played"]);
+
+        // words marked no-suggest are correct but never suggested
+        assert!(!dictionary.suggest("dam").contains(&"damn".to_string()));
+    }
+
+    #[test]
+    fn test_calc() {
+        let dictionary = dictionary();
+        let text =
+            "Teh phone `teh` [teh](https://teh.com) www.teh.com plaed\n\n```\nteh\n```\n\ncreat";
+        let buffer: SubBuffer = text.into();
+        let ast = crate::ast::calc(&buffer);
+
+        let cache =
+            super::calc(&ast, &buffer, &Default::default(), Some(&dictionary), &HashSet::new());
+        let misspellings = cache
+            .misspellings
+            .iter()
+            .map(|&range| &buffer[range])
+            .collect::<Vec<_>>();
+        assert_eq!(misspellings, vec!["Teh", "plaed", "creat"]);
+
+        let custom_words = HashSet::from(["plaed".to_string(), "teh".to_string()]);
+        let cache = super::calc(&ast, &buffer, &cache, Some(&dictionary), &custom_words);
+        let misspellings = cache
+            .misspellings
+            .iter()
+            .map(|&range| &buffer[range])
+            .collect::<Vec<_>>();
+        assert_eq!(misspellings, vec!["creat"]);
+    }
+}
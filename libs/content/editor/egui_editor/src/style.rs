@@ -1,5 +1,5 @@
 use crate::appearance::Appearance;
-use egui::{FontFamily, Stroke, TextFormat};
+use egui::{Align, FontFamily, Stroke, TextFormat};
 use pulldown_cmark::{HeadingLevel, LinkType};
 use std::hash::Hash;
 use std::sync::Arc;
@@ -25,6 +25,9 @@ impl MarkdownNodeType {
             Self::Inline(InlineNodeType::Italic) => "*",
             Self::Inline(InlineNodeType::Strikethrough) => "~~",
             Self::Inline(InlineNodeType::Link) => "[",
+            Self::Inline(InlineNodeType::WikiLink) => "[[",
+            Self::Inline(InlineNodeType::Math) => "$",
+            Self::Inline(InlineNodeType::FootnoteReference) => "[^",
             Self::Inline(InlineNodeType::Image) => {
                 unimplemented!()
             }
@@ -38,6 +41,9 @@ impl MarkdownNodeType {
             Self::Block(BlockNodeType::Code) => "```\n",
             Self::Block(BlockNodeType::ListItem(item_type)) => item_type.head(),
             Self::Block(BlockNodeType::Rule) => "***",
+            Self::Block(BlockNodeType::Table(..)) => "",
+            Self::Block(BlockNodeType::Math) => "$$\n",
+            Self::Block(BlockNodeType::FootnoteDefinition) => "",
         }
     }
 
@@ -50,6 +56,9 @@ impl MarkdownNodeType {
             Self::Inline(InlineNodeType::Italic) => "*",
             Self::Inline(InlineNodeType::Strikethrough) => "~~",
             Self::Inline(InlineNodeType::Link) => "]()",
+            Self::Inline(InlineNodeType::WikiLink) => "]]",
+            Self::Inline(InlineNodeType::Math) => "$",
+            Self::Inline(InlineNodeType::FootnoteReference) => "]",
             Self::Inline(InlineNodeType::Image) => {
                 unimplemented!()
             }
@@ -58,12 +67,20 @@ impl MarkdownNodeType {
             Self::Block(BlockNodeType::Code) => "\n```",
             Self::Block(BlockNodeType::ListItem(..)) => "",
             Self::Block(BlockNodeType::Rule) => "",
+            Self::Block(BlockNodeType::Table(..)) => "",
+            Self::Block(BlockNodeType::Math) => "\n$$",
+            Self::Block(BlockNodeType::FootnoteDefinition) => "",
         }
     }
 
     /// Returns true if the markdown syntax for the node contains text which should be split into words for word bounds calculation
     pub fn syntax_includes_text(&self) -> bool {
-        matches!(self, Self::Inline(InlineNodeType::Link) | Self::Inline(InlineNodeType::Image))
+        matches!(
+            self,
+            Self::Inline(InlineNodeType::Link)
+                | Self::Inline(InlineNodeType::WikiLink)
+                | Self::Inline(InlineNodeType::Image)
+        )
     }
 
     pub fn conflicts_with(&self, other: &MarkdownNodeType) -> bool {
@@ -78,7 +95,10 @@ pub enum InlineNodeType {
     Italic,
     Strikethrough,
     Link,
+    WikiLink,
     Image,
+    Math,
+    FootnoteReference,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -88,6 +108,9 @@ pub enum BlockNodeType {
     Code,
     ListItem(ListItemType),
     Rule,
+    Table(TableItemType),
+    Math,
+    FootnoteDefinition,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -107,6 +130,14 @@ impl ListItemType {
     }
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TableItemType {
+    Table,
+    HeaderRow,
+    Row,
+    Cell,
+}
+
 /// Represents a style that can be applied to rendered text
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RenderStyle {
@@ -135,6 +166,9 @@ pub enum InlineNode {
     Strikethrough,
     Link(LinkType, Url, Title), // todo: swap strings for text ranges and impl Copy
     Image(LinkType, Url, Title), // todo: swap strings for text ranges and impl Copy
+    WikiLink(LinkTarget),
+    Math,
+    FootnoteReference(FootnoteLabel),
 }
 
 impl InlineNode {
@@ -146,6 +180,9 @@ impl InlineNode {
             Self::Strikethrough => InlineNodeType::Strikethrough,
             Self::Link(..) => InlineNodeType::Link,
             Self::Image(..) => InlineNodeType::Image,
+            Self::WikiLink(..) => InlineNodeType::WikiLink,
+            Self::Math => InlineNodeType::Math,
+            Self::FootnoteReference(..) => InlineNodeType::FootnoteReference,
         }
     }
 }
@@ -163,6 +200,11 @@ impl PartialEq for InlineNode {
             (Self::Image(_, url, title), Self::Image(_, other_url, other_title)) => {
                 url == other_url && title == other_title
             }
+            (Self::WikiLink(target), Self::WikiLink(other_target)) => target == other_target,
+            (Self::Math, Self::Math) => true,
+            (Self::FootnoteReference(label), Self::FootnoteReference(other_label)) => {
+                label == other_label
+            }
             _ => false,
         }
     }
@@ -187,6 +229,15 @@ impl Hash for InlineNode {
                 url.hash(state);
                 title.hash(state);
             }
+            Self::WikiLink(target) => {
+                InlineNodeType::WikiLink.hash(state);
+                target.hash(state);
+            }
+            Self::Math => InlineNodeType::Math.hash(state),
+            Self::FootnoteReference(label) => {
+                InlineNodeType::FootnoteReference.hash(state);
+                label.hash(state);
+            }
         }
     }
 }
@@ -198,6 +249,9 @@ pub enum BlockNode {
     Code,
     ListItem(ListItem, IndentLevel),
     Rule,
+    Table(TableItem),
+    Math,
+    FootnoteDefinition,
 }
 
 impl BlockNode {
@@ -208,6 +262,9 @@ impl BlockNode {
             Self::Code => BlockNodeType::Code,
             Self::ListItem(item, ..) => BlockNodeType::ListItem(item.item_type()),
             Self::Rule => BlockNodeType::Rule,
+            Self::Table(item) => BlockNodeType::Table(item.item_type()),
+            Self::Math => BlockNodeType::Math,
+            Self::FootnoteDefinition => BlockNodeType::FootnoteDefinition,
         }
     }
 }
@@ -223,6 +280,9 @@ impl PartialEq for BlockNode {
                 Self::ListItem(other_item, other_indent_level),
             ) => item == other_item && indent_level == other_indent_level,
             (Self::Rule, Self::Rule) => true,
+            (Self::Table(item), Self::Table(other_item)) => item == other_item,
+            (Self::Math, Self::Math) => true,
+            (Self::FootnoteDefinition, Self::FootnoteDefinition) => true,
             _ => false,
         }
     }
@@ -249,6 +309,16 @@ impl Hash for BlockNode {
             Self::Rule => {
                 BlockNodeType::Rule.hash(state);
             }
+            Self::Table(item) => {
+                BlockNodeType::Table(item.item_type()).hash(state);
+                item.hash(state);
+            }
+            Self::Math => {
+                BlockNodeType::Math.hash(state);
+            }
+            Self::FootnoteDefinition => {
+                BlockNodeType::FootnoteDefinition.hash(state);
+            }
         }
     }
 }
@@ -301,8 +371,41 @@ impl Hash for ListItem {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TableItem {
+    Table,
+    HeaderRow,
+    Row,
+    Cell(ColumnAlignment),
+}
+
+impl TableItem {
+    pub fn item_type(&self) -> TableItemType {
+        match self {
+            TableItem::Table => TableItemType::Table,
+            TableItem::HeaderRow => TableItemType::HeaderRow,
+            TableItem::Row => TableItemType::Row,
+            TableItem::Cell(_) => TableItemType::Cell,
+        }
+    }
+}
+
+/// Horizontal alignment of a table column, set by colons in the table's delimiter row
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColumnAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
 pub type Url = String;
 pub type Title = String;
+/// Target of a wiki link: an id (`lb://<id>`), path, or document name, optionally followed by
+/// `#heading`
+pub type LinkTarget = String;
+/// Label of a footnote e.g. `1` in `[^1]`, which matches a footnote reference to its definition
+pub type FootnoteLabel = String;
 pub type IndentLevel = u8;
 
 impl RenderStyle {
@@ -339,13 +442,27 @@ impl RenderStyle {
             RenderStyle::Markdown(MarkdownNode::Inline(InlineNode::Strikethrough)) => {
                 text_format.strikethrough = Stroke { width: 1.5, color: vis.strikethrough() };
             }
-            RenderStyle::Markdown(MarkdownNode::Inline(InlineNode::Link(..))) => {
+            RenderStyle::Markdown(MarkdownNode::Inline(
+                InlineNode::Link(..) | InlineNode::WikiLink(..),
+            )) => {
                 text_format.color = vis.link();
                 text_format.underline = Stroke { width: 1.5, color: vis.link() };
             }
             RenderStyle::Markdown(MarkdownNode::Inline(InlineNode::Image(..))) => {
                 text_format.italics = true;
             }
+            RenderStyle::Markdown(MarkdownNode::Inline(InlineNode::FootnoteReference(..))) => {
+                text_format.color = vis.link();
+                text_format.font_id.size = vis.font_size() * 0.75;
+                text_format.valign = Align::TOP;
+            }
+            RenderStyle::Markdown(MarkdownNode::Inline(InlineNode::Math))
+            | RenderStyle::Markdown(MarkdownNode::Block(BlockNode::Math)) => {
+                // math source is shown while it's edited and rendered otherwise
+                text_format.font_id.family = FontFamily::Monospace;
+                text_format.font_id.size = vis.monospace_font_size();
+                text_format.color = vis.code();
+            }
             RenderStyle::Markdown(MarkdownNode::Block(BlockNode::Heading(level))) => {
                 if level == &HeadingLevel::H1 {
                     text_format.font_id.family = FontFamily::Name(Arc::from("Bold"));
@@ -363,6 +480,12 @@ impl RenderStyle {
             }
             RenderStyle::Markdown(MarkdownNode::Block(BlockNode::ListItem(..))) => {}
             RenderStyle::Markdown(MarkdownNode::Block(BlockNode::Rule)) => {}
+            RenderStyle::Markdown(MarkdownNode::Block(BlockNode::Table(TableItem::HeaderRow))) => {
+                text_format.color = vis.bold();
+                text_format.font_id.family = FontFamily::Name(Arc::from("Bold"));
+            }
+            RenderStyle::Markdown(MarkdownNode::Block(BlockNode::Table(..))) => {}
+            RenderStyle::Markdown(MarkdownNode::Block(BlockNode::FootnoteDefinition)) => {}
         }
     }
 }
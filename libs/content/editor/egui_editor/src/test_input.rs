@@ -1,6 +1,6 @@
 pub static TEST_MARKDOWN: &str = TEST_MARKDOWN_50;
 
-pub static TEST_MARKDOWN_ALL: [&str; 55] = [
+pub static TEST_MARKDOWN_ALL: [&str; 56] = [
     TEST_MARKDOWN_0,
     TEST_MARKDOWN_1,
     TEST_MARKDOWN_2,
@@ -55,6 +55,7 @@ pub static TEST_MARKDOWN_ALL: [&str; 55] = [
     TEST_MARKDOWN_51,
     TEST_MARKDOWN_52,
     TEST_MARKDOWN_53,
+    TEST_MARKDOWN_55,
     "1. *",
 ];
 
@@ -305,3 +306,15 @@ Rules rule!
 "#;
 
 pub static TEST_MARKDOWN_54: &str = r#""#;
+
+pub static TEST_MARKDOWN_55: &str = r#"# Tables
+| fruit | **count** | notes |
+|:------|:---------:|------:|
+| apple | 3 | crisp \| tart |
+| pear  |   |
+|kiwi|1|fuzzy|extra|
+
+a | b
+--|--
+c | d
+"#;
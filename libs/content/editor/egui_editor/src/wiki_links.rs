@@ -0,0 +1,240 @@
+use crate::ast::Ast;
+use crate::buffer::SubBuffer;
+use crate::input::canonical::{Location, Modification, Region};
+use crate::offset_types::{DocCharOffset, RangeExt};
+use crate::style::{BlockNode, InlineNode, LinkTarget, MarkdownNode};
+use egui::{Event, Key, Order, Pos2, Rect, Ui};
+use lb::{SearchResultItem, Uuid};
+use std::collections::HashMap;
+
+/// Maximum number of files suggested for the wiki link being typed
+const MAX_SUGGESTIONS: usize = 8;
+
+/// The `lb://` urls that wiki link targets resolve to, or `None` for targets that don't resolve to a
+/// file
+#[derive(Clone, Default)]
+pub struct WikiLinkCache {
+    pub map: HashMap<LinkTarget, Option<String>>,
+}
+
+pub fn calc(
+    ast: &Ast, prior_cache: &WikiLinkCache, core: &lb::Core, file_id: Option<Uuid>,
+) -> WikiLinkCache {
+    let mut result = WikiLinkCache::default();
+    for node in &ast.nodes {
+        if let MarkdownNode::Inline(InlineNode::WikiLink(target)) = &node.node_type {
+            if result.map.contains_key(target) {
+                continue;
+            }
+
+            // re-use resolutions from the previous cache; targets that didn't resolve are tried
+            // again because the file they link to may have been created since
+            let url = match prior_cache.map.get(target) {
+                Some(Some(url)) => Some(url.clone()),
+                _ => resolve(core, file_id, target),
+            };
+            result.map.insert(target.clone(), url);
+        }
+    }
+    result
+}
+
+/// Resolves a wiki link target to an `lb://<id>` url, keeping its `#heading` if any. A target
+/// with only a heading links to a heading in the document being edited.
+fn resolve(core: &lb::Core, file_id: Option<Uuid>, target: &str) -> Option<String> {
+    let (path, heading) = match target.split_once('#') {
+        Some((path, heading)) => (path, Some(heading.trim())),
+        None => (target, None),
+    };
+    let id =
+        if path.trim().is_empty() { file_id? } else { core.resolve_link(file_id, path).ok()? };
+    Some(match heading {
+        Some(heading) => format!("lb://{}#{}", id, heading),
+        None => format!("lb://{}", id),
+    })
+}
+
+/// Suggests files to link to while a wiki link is being typed
+#[derive(Default)]
+pub struct LinkAutocomplete {
+    pub active: Option<LinkSuggestions>,
+
+    /// start of a wiki link whose suggestions were dismissed; they aren't suggested again while
+    /// the same link is typed
+    pub dismissed: Option<DocCharOffset>,
+
+    /// where suggestions were shown last frame; clicks there are for the suggestions, not the text
+    pub rect: Option<Rect>,
+}
+
+pub struct LinkSuggestions {
+    /// range replaced by a suggestion: from the link's opening brackets through the cursor, plus
+    /// the link's closing brackets if they follow the cursor
+    pub range: (DocCharOffset, DocCharOffset),
+    pub query: String,
+    pub files: Vec<SearchResultItem>,
+    pub selected: usize,
+}
+
+impl LinkAutocomplete {
+    /// Updates suggestions for the wiki link before the cursor, if any
+    pub fn update(&mut self, buffer: &SubBuffer, ast: &Ast, core: &lb::Core) {
+        let prior = self.active.take();
+        let Some((range, query)) = typed_link(buffer, ast) else {
+            self.dismissed = None;
+            return;
+        };
+        if self.dismissed == Some(range.0) {
+            return;
+        }
+        self.dismissed = None;
+
+        self.active = match prior {
+            Some(prior) if prior.range.0 == range.0 && prior.query == query => {
+                Some(LinkSuggestions { range, ..prior })
+            }
+            _ => {
+                let mut files = core.search_file_paths(&query).unwrap_or_default();
+                files.truncate(MAX_SUGGESTIONS);
+                if files.is_empty() {
+                    None
+                } else {
+                    Some(LinkSuggestions { range, query, files, selected: 0 })
+                }
+            }
+        };
+    }
+
+    /// Handles the keys and clicks used to pick a suggestion, returning the rest of the events.
+    /// Picking a suggestion pushes the modification that completes the link to `custom_events`.
+    pub fn handle_events(
+        &mut self, events: &[Event], custom_events: &mut Vec<Modification>,
+    ) -> Vec<Event> {
+        let mut result = Vec::new();
+        for event in events {
+            let Some(active) = &mut self.active else {
+                result.push(event.clone());
+                continue;
+            };
+            match event {
+                Event::Key { key, pressed, modifiers, .. } if modifiers.is_none() => match key {
+                    Key::ArrowDown => {
+                        if *pressed {
+                            active.selected = (active.selected + 1) % active.files.len();
+                        }
+                    }
+                    Key::ArrowUp => {
+                        if *pressed {
+                            active.selected =
+                                (active.selected + active.files.len() - 1) % active.files.len();
+                        }
+                    }
+                    Key::Enter | Key::Tab => {
+                        if *pressed {
+                            custom_events.push(active.completion(active.selected));
+                            self.active = None;
+                        }
+                    }
+                    Key::Escape => {
+                        if *pressed {
+                            self.dismissed = Some(active.range.0);
+                            self.active = None;
+                        }
+                    }
+                    _ => result.push(event.clone()),
+                },
+                Event::PointerButton { pos, .. }
+                    if self
+                        .rect
+                        .map(|rect| rect.contains(*pos))
+                        .unwrap_or_default() => {}
+                _ => result.push(event.clone()),
+            }
+        }
+        result
+    }
+
+    /// Shows suggestions below `pos`, returning the modification that completes the link if one
+    /// was clicked
+    pub fn show(&mut self, ui: &mut Ui, pos: Pos2) -> Option<Modification> {
+        let Some(active) = &self.active else {
+            self.rect = None;
+            return None;
+        };
+
+        let mut clicked = None;
+        let response = egui::Area::new(ui.id().with("link_autocomplete"))
+            .order(Order::Foreground)
+            .fixed_pos(pos)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(400.0);
+                    for (i, file) in active.files.iter().enumerate() {
+                        if ui
+                            .selectable_label(i == active.selected, &file.path)
+                            .clicked()
+                        {
+                            clicked = Some(i);
+                        }
+                    }
+                })
+            })
+            .response;
+        self.rect = Some(response.rect);
+
+        let completion = active.completion(clicked?);
+        self.active = None;
+        Some(completion)
+    }
+}
+
+impl LinkSuggestions {
+    /// Replaces the typed link with a link to a suggested file. Links use ids so they keep
+    /// working when the file is renamed or moved, labeled with the file's name.
+    pub fn completion(&self, i: usize) -> Modification {
+        let file = &self.files[i];
+        let name = file.path.rsplit('/').next().unwrap_or_default();
+        let name = name.strip_suffix(".md").unwrap_or(name);
+        let label = name.replace(['[', ']', '|'], "");
+        Modification::Replace {
+            region: Region::BetweenLocations {
+                start: Location::DocCharOffset(self.range.0),
+                end: Location::DocCharOffset(self.range.1),
+            },
+            text: format!("[[lb://{}|{}]]", file.id, label),
+        }
+    }
+}
+
+/// Returns the range and query of the wiki link being typed before the cursor, if any
+fn typed_link(buffer: &SubBuffer, ast: &Ast) -> Option<((DocCharOffset, DocCharOffset), String)> {
+    let cursor = buffer.cursor.selection;
+    if !cursor.is_empty() {
+        return None;
+    }
+
+    let cursor_byte = buffer.segs.offset_to_byte(cursor.1).0;
+    let before_cursor = &buffer.text[..cursor_byte];
+    let line = &before_cursor[before_cursor.rfind('\n').map(|i| i + 1).unwrap_or_default()..];
+    let open = line.rfind("[[")?;
+    let query = &line[open + 2..];
+    if query.trim().is_empty() || query.contains(['[', ']', '|', '#']) {
+        return None;
+    }
+
+    let start = buffer
+        .segs
+        .offset_to_char((cursor_byte - line.len() + open).into());
+    for node in &ast.nodes {
+        if matches!(
+            node.node_type,
+            MarkdownNode::Inline(InlineNode::Code) | MarkdownNode::Block(BlockNode::Code)
+        ) && node.range.contains_inclusive(start)
+        {
+            return None;
+        }
+    }
+
+    let end = if buffer.text[cursor_byte..].starts_with("]]") { cursor.1 + 2 } else { cursor.1 };
+    Some(((start, end), query.to_string()))
+}
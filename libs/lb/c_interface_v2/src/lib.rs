@@ -112,6 +112,10 @@ fn lb_error_code(kind: CoreError) -> LbErrorCode {
         CoreError::ServerUnreachable => LbErrorCode::ServerUnreachable,
         CoreError::ShareAlreadyExists => LbErrorCode::ShareAlreadyExists,
         CoreError::ShareNonexistent => LbErrorCode::ShareNonexistent,
+        CoreError::TaskNonexistent => LbErrorCode::TaskNonexistent,
+        CoreError::TeamNameEmpty => LbErrorCode::TeamNameEmpty,
+        CoreError::TeamNameTaken => LbErrorCode::TeamNameTaken,
+        CoreError::TeamNonexistent => LbErrorCode::TeamNonexistent,
         CoreError::TryAgain => LbErrorCode::TryAgain,
         CoreError::UsageIsOverFreeTierDataCap => LbErrorCode::UsageIsOverFreeTierDataCap,
         CoreError::UsageIsOverDataCap => LbErrorCode::UsageIsOverDataCap,
@@ -121,6 +125,8 @@ fn lb_error_code(kind: CoreError) -> LbErrorCode {
         CoreError::UsernameTaken => LbErrorCode::UsernameTaken,
         CoreError::Unexpected(_) => LbErrorCode::Unexpected,
         CoreError::AlreadySyncing => LbErrorCode::AlreadySyncing,
+        CoreError::OperationNotUndoable => LbErrorCode::OperationNotUndoable,
+        CoreError::SyncRequired => LbErrorCode::SyncRequired,
     }
 }
 
@@ -202,6 +208,12 @@ pub enum LbErrorCode {
     UsernamePublicKeyMismatch,
     UsernameTaken,
     AlreadySyncing,
+    OperationNotUndoable,
+    TaskNonexistent,
+    SyncRequired,
+    TeamNameEmpty,
+    TeamNameTaken,
+    TeamNonexistent,
 }
 
 #[repr(C)]
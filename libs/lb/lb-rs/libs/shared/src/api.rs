@@ -9,10 +9,12 @@ use uuid::Uuid;
 
 use crate::account::Account;
 use crate::account::Username;
+use crate::crdt::{EncryptedCollabMessage, EncryptedPresence};
 use crate::crypto::*;
 use crate::file_metadata::{DocumentHmac, FileDiff, FileMetadata, Owner};
 use crate::server_file::ServerFile;
 use crate::signed_file::SignedFile;
+use crate::team::SignedTeam;
 use crate::ValidationFailure;
 
 pub const FREE_TIER_USAGE_SIZE: u64 = 1000000;
@@ -138,6 +140,106 @@ impl Request for GetDocRequest {
     const ROUTE: &'static str = "/get-document";
 }
 
+/// Appends a message to the live editing session of a document, which the server relays to the
+/// document's other editors without being able to read it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SendCollabMessageRequest {
+    pub id: Uuid,
+    /// the session the message is for; `None` starts a session
+    pub session: Option<Uuid>,
+    pub message: EncryptedCollabMessage,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SendCollabMessageResponse {
+    pub session: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum SendCollabMessageError {
+    DocumentNotFound,
+    NotPermissioned,
+    /// Arises when starting a session for a document that already has one
+    SessionAlreadyStarted,
+    /// Arises when the session the message is for has ended, e.g. because its log got too long, or
+    /// when there isn't one
+    SessionEnded,
+}
+
+impl Request for SendCollabMessageRequest {
+    type Response = SendCollabMessageResponse;
+    type Error = SendCollabMessageError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/send-collab-message";
+}
+
+/// Gets the messages in the live editing session of a document, starting from the `since`th.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetCollabMessagesRequest {
+    pub id: Uuid,
+    /// the session to get messages from; `None` gets them from whichever session is live
+    pub session: Option<Uuid>,
+    pub since: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetCollabMessagesResponse {
+    /// the live session, if there is one
+    pub session: Option<Uuid>,
+    pub messages: Vec<EncryptedCollabMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum GetCollabMessagesError {
+    DocumentNotFound,
+    NotPermissioned,
+    /// Arises when the session to get messages from has ended
+    SessionEnded,
+}
+
+impl Request for GetCollabMessagesRequest {
+    type Response = GetCollabMessagesResponse;
+    type Error = GetCollabMessagesError;
+    const METHOD: Method = Method::GET;
+    const ROUTE: &'static str = "/get-collab-messages";
+}
+
+/// Shares where the sender's selection is in a document and gets the selections of its other
+/// editors. Selections that aren't updated for a while are forgotten.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct UpdatePresenceRequest {
+    pub id: Uuid,
+    /// identifies the sender's editor, since a user can have a document open in more than one
+    pub client: Uuid,
+    /// `None` closes the document, forgetting the sender's selection
+    pub presence: Option<EncryptedPresence>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct UpdatePresenceResponse {
+    pub others: Vec<RemotePresence>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RemotePresence {
+    pub username: String,
+    pub client: Uuid,
+    pub presence: EncryptedPresence,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum UpdatePresenceError {
+    DocumentNotFound,
+    NotPermissioned,
+}
+
+impl Request for UpdatePresenceRequest {
+    type Response = UpdatePresenceResponse;
+    type Error = UpdatePresenceError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/update-presence";
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct GetPublicKeyRequest {
     pub username: String,
@@ -161,6 +263,171 @@ impl Request for GetPublicKeyRequest {
     const ROUTE: &'static str = "/get-public-key";
 }
 
+/// An offer to give a file and its descendants to another user, which they accept by taking
+/// ownership of the files
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Transfer {
+    pub id: Uuid,
+    pub from: PublicKey,
+    pub to: PublicKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct OfferTransferRequest {
+    pub id: Uuid,
+    pub to: PublicKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum OfferTransferError {
+    FileNotFound,
+    /// Arises when the file is a root or isn't owned by the caller
+    NotPermissioned,
+    UserNotFound,
+}
+
+impl Request for OfferTransferRequest {
+    type Response = ();
+    type Error = OfferTransferError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/offer-transfer";
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetTransfersRequest {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetTransfersResponse {
+    /// the transfers offered by or to the caller
+    pub transfers: Vec<Transfer>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum GetTransfersError {
+    UserNotFound,
+}
+
+impl Request for GetTransfersRequest {
+    type Response = GetTransfersResponse;
+    type Error = GetTransfersError;
+    const METHOD: Method = Method::GET;
+    const ROUTE: &'static str = "/get-transfers";
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DeleteTransferRequest {
+    pub id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum DeleteTransferError {
+    /// Arises when there's no transfer of the file offered by or to the caller
+    TransferNotFound,
+}
+
+impl Request for DeleteTransferRequest {
+    type Response = ();
+    type Error = DeleteTransferError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/delete-transfer";
+}
+
+/// Creates or changes a team. Membership is only a list of users: removing a member doesn't rotate
+/// or re-wrap the keys of files shared with the team, so the member can still decrypt whatever they
+/// synced while they were a member.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpsertTeamRequest {
+    pub team: SignedTeam,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum UpsertTeamError {
+    /// Arises when the team isn't signed by its admin, or someone other than its admin changes it
+    NotPermissioned,
+    TeamNameEmpty,
+    TeamNameTaken,
+    UserNotFound,
+}
+
+impl Request for UpsertTeamRequest {
+    type Response = ();
+    type Error = UpsertTeamError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/upsert-team";
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetTeamsRequest {}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetTeamsResponse {
+    pub teams: Vec<SignedTeam>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum GetTeamsError {
+    UserNotFound,
+}
+
+impl Request for GetTeamsRequest {
+    type Response = GetTeamsResponse;
+    type Error = GetTeamsError;
+    const METHOD: Method = Method::GET;
+    const ROUTE: &'static str = "/get-teams";
+}
+
+/// The largest account settings the server keeps
+pub const MAX_ACCOUNT_SETTINGS_SIZE: usize = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct UpsertAccountSettingsRequest {
+    /// the version of the settings these replace, or 0 if there aren't any
+    pub old_version: u64,
+    pub settings: EncryptedDocument,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpsertAccountSettingsResponse {
+    pub new_version: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum UpsertAccountSettingsError {
+    /// Arises when the settings were changed elsewhere since the version they replace
+    OldVersionIncorrect,
+    SettingsTooLarge,
+    UserNotFound,
+}
+
+impl Request for UpsertAccountSettingsRequest {
+    type Response = UpsertAccountSettingsResponse;
+    type Error = UpsertAccountSettingsError;
+    const METHOD: Method = Method::POST;
+    const ROUTE: &'static str = "/upsert-account-settings";
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct GetAccountSettingsRequest {}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetAccountSettingsResponse {
+    /// 0 if the account has no settings
+    pub version: u64,
+    pub settings: Option<EncryptedDocument>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum GetAccountSettingsError {
+    UserNotFound,
+}
+
+impl Request for GetAccountSettingsRequest {
+    type Response = GetAccountSettingsResponse;
+    type Error = GetAccountSettingsError;
+    const METHOD: Method = Method::GET;
+    const ROUTE: &'static str = "/get-account-settings";
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct GetUsernameRequest {
     pub key: PublicKey,
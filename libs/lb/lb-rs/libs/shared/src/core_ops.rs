@@ -1,5 +1,5 @@
 use db_rs::LookupTable;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use hmac::{Mac, NewMac};
 use libsecp256k1::PublicKey;
@@ -259,6 +259,98 @@ where
         Ok(result)
     }
 
+    /// Takes a file and its descendants, which their owner is transferring to us, by moving it to
+    /// `new_parent`. Folders get new keys and all shares are dropped, so no one else can read
+    /// what's added to them; documents keep their keys until their contents are re-encrypted with
+    /// [`Self::rekey_document_op`]. Our links to the files are deleted.
+    pub fn transfer_op(
+        &mut self, id: &Uuid, new_parent: &Uuid, account: &Account,
+    ) -> SharedResult<Vec<SignedFile>> {
+        let owner = Owner(account.public_key());
+        validate::not_root(self.find(id)?)?;
+        if self.find(id)?.owner() == owner {
+            return Err(SharedErrorKind::InsufficientPermission.into());
+        }
+        if self.calculate_deleted(id)? {
+            return Err(SharedErrorKind::FileNonexistent.into());
+        }
+        if self.maybe_find(new_parent).is_none()
+            || self.calculate_deleted(new_parent)?
+            || self.find(new_parent)?.owner() != owner
+        {
+            return Err(SharedErrorKind::FileParentNonexistent.into());
+        }
+        validate::is_folder(self.find(new_parent)?)?;
+
+        let mut ids = vec![*id];
+        ids.extend(self.descendants(id)?);
+
+        // deleted files keep their keys, which is the only change to them besides following their
+        // parent to its new owner
+        let mut keys = HashMap::new();
+        for id in &ids {
+            let key = if self.find(id)?.is_folder() && !self.calculate_deleted(id)? {
+                symkey::generate_key()
+            } else {
+                self.decrypt_key(id, account)?
+            };
+            keys.insert(*id, key);
+        }
+
+        let mut result = Vec::new();
+        for id in &ids {
+            let mut file = self.find(id)?.timestamped_value.value.clone();
+            let parent_key = if file.id == ids[0] {
+                file.parent = *new_parent;
+                self.decrypt_key(new_parent, account)?
+            } else {
+                keys[&file.parent]
+            };
+            if !self.calculate_deleted(id)? {
+                file.user_access_keys.clear();
+            }
+            file.owner = owner;
+            file.name = SecretFileName::from_str(&self.name(id, account)?, &keys[id], &parent_key)?;
+            file.folder_access_key = symkey::encrypt(&parent_key, &keys[id])?;
+            result.push(file.sign(account)?);
+        }
+
+        for link in self.owned_ids() {
+            if let FileType::Link { target } = self.find(&link)?.file_type() {
+                if keys.contains_key(&target) && !self.calculate_deleted(&link)? {
+                    result.push(self.delete_op(&link, account)?);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Encrypts a document's contents with a new key, replacing the key that was used to read it
+    pub fn rekey_document_op(
+        &mut self, id: &Uuid, document: &[u8], account: &Account,
+    ) -> SharedResult<(SignedFile, EncryptedDocument)> {
+        let mut file: FileMetadata = self.find(id)?.timestamped_value.value.clone();
+        validate::is_document(&file)?;
+        let key = symkey::generate_key();
+        let parent_key = self.decrypt_key(file.parent(), account)?;
+        file.name = SecretFileName::from_str(&self.name(id, account)?, &key, &parent_key)?;
+        file.folder_access_key = symkey::encrypt(&parent_key, &key)?;
+        let hmac = {
+            let mut mac =
+                HmacSha256::new_from_slice(&key).map_err(SharedErrorKind::HmacCreationError)?;
+            mac.update(document);
+            mac.finalize().into_bytes()
+        }
+        .into();
+        file.document_hmac = Some(hmac);
+        let file = file.sign(account)?;
+        let document = compression_service::compress(document)?;
+        let document = symkey::encrypt(&key, &document)?;
+
+        Ok((file, document))
+    }
+
     pub fn read_document(
         &mut self, d: &impl DocumentService, id: &Uuid, account: &Account,
     ) -> SharedResult<DecryptedDocument> {
@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::crypto::AESEncrypted;
+
+/// Identifies a character inserted into a collaboratively edited document. Ids are ordered by
+/// lamport clock, then by the replica that inserted the character, which is how concurrent
+/// insertions at the same position are ordered.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpId {
+    pub lamport: u64,
+    pub replica: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// Inserts `text` after the character `after`, or at the start of the document. The inserted
+    /// characters have consecutive lamport clocks starting at `id`'s.
+    Insert {
+        id: OpId,
+        after: Option<OpId>,
+        text: String,
+    },
+    Delete {
+        ids: Vec<OpId>,
+    },
+}
+
+/// What editors of a document send each other through the server, encrypted with the document's key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CollabMessage {
+    /// The first message of a session: the text everyone's edits are made against.
+    Start {
+        text: String,
+    },
+    Ops(Vec<Op>),
+}
+
+pub type EncryptedCollabMessage = AESEncrypted<CollabMessage>;
+
+/// Where an editor's selection is, shown to the document's other editors. The ends of the selection
+/// are identified by the characters just before them (`None` for the start of the document) so that
+/// they stay put as others edit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Presence {
+    pub selection: (Option<OpId>, Option<OpId>),
+}
+
+pub type EncryptedPresence = AESEncrypted<Presence>;
+
+/// A change to a document's text: replace the text in the byte range `range` with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: (usize, usize),
+    pub text: String,
+}
+
+impl TextEdit {
+    /// The edit that turns `old` into `new`, replacing the part between their common prefix and
+    /// common suffix.
+    pub fn between(old: &str, new: &str) -> Self {
+        let prefix: usize = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let suffix: usize = old[prefix..]
+            .chars()
+            .rev()
+            .zip(new[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        Self {
+            range: (prefix, old.len() - suffix),
+            text: new[prefix..new.len() - suffix].to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Char {
+    id: OpId,
+    value: char,
+    deleted: bool,
+}
+
+/// A replicated growable array (RGA): text that converges when replicas apply each other's
+/// operations, in any order that respects causality. Deleted characters are kept as tombstones so
+/// that concurrent operations can still refer to them.
+#[derive(Debug, Clone)]
+pub struct Rga {
+    replica: Uuid,
+    clock: u64,
+    chars: Vec<Char>,
+}
+
+impl Rga {
+    /// Text that replicas start from. Its characters have the same ids on every replica.
+    pub fn new(replica: Uuid, text: &str) -> Self {
+        let chars: Vec<Char> = text
+            .chars()
+            .enumerate()
+            .map(|(i, value)| Char {
+                id: OpId { lamport: i as u64 + 1, replica: Uuid::nil() },
+                value,
+                deleted: false,
+            })
+            .collect();
+        Self { replica, clock: chars.len() as u64, chars }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars
+            .iter()
+            .filter(|c| !c.deleted)
+            .map(|c| c.value)
+            .collect()
+    }
+
+    /// Replaces the text in the byte range `range` with `text` and returns the operations that
+    /// make the same change on other replicas.
+    pub fn replace(&mut self, range: (usize, usize), text: &str) -> Vec<Op> {
+        let mut after = None;
+        let mut deleted = Vec::new();
+        let mut offset = 0;
+        for c in self.chars.iter_mut().filter(|c| !c.deleted) {
+            if offset >= range.1 {
+                break;
+            }
+            if offset < range.0 {
+                after = Some(c.id);
+            } else {
+                c.deleted = true;
+                deleted.push(c.id);
+            }
+            offset += c.value.len_utf8();
+        }
+
+        let mut result = Vec::new();
+        if !deleted.is_empty() {
+            result.push(Op::Delete { ids: deleted });
+        }
+        if !text.is_empty() {
+            let id = OpId { lamport: self.clock + 1, replica: self.replica };
+            let op = Op::Insert { id, after, text: text.into() };
+            self.apply(&op);
+            result.push(op);
+        }
+        result
+    }
+
+    /// Applies an operation from another replica and returns the resulting changes to the text, in
+    /// order. Operations that were already applied have no effect.
+    pub fn apply(&mut self, op: &Op) -> Vec<TextEdit> {
+        let mut result: Vec<TextEdit> = Vec::new();
+        match op {
+            Op::Insert { id, after, text } => {
+                // operations are applied whole, so either all of the characters are here or none
+                if self.chars.iter().any(|c| c.id == *id) {
+                    return result;
+                }
+
+                // each character is inserted right after the previous one
+                let (mut start, mut start_offset) = match after {
+                    None => (0, 0),
+                    Some(after) => {
+                        let Some(index) = self.chars.iter().position(|c| c.id == *after) else {
+                            return result; // operations are sent in causal order
+                        };
+                        (index + 1, self.offset(index + 1))
+                    }
+                };
+                for (i, value) in text.chars().enumerate() {
+                    let id = OpId { lamport: id.lamport + i as u64, replica: id.replica };
+                    self.clock = self.clock.max(id.lamport);
+                    let (mut index, mut offset) = (start, start_offset);
+
+                    // skip characters inserted concurrently at the same position with greater ids
+                    while index < self.chars.len() && self.chars[index].id > id {
+                        if !self.chars[index].deleted {
+                            offset += self.chars[index].value.len_utf8();
+                        }
+                        index += 1;
+                    }
+
+                    self.chars.insert(index, Char { id, value, deleted: false });
+                    match result.last_mut() {
+                        Some(edit)
+                            if edit.range.0 == edit.range.1
+                                && edit.range.0 + edit.text.len() == offset =>
+                        {
+                            edit.text.push(value)
+                        }
+                        _ => result.push(TextEdit { range: (offset, offset), text: value.into() }),
+                    }
+
+                    (start, start_offset) = (index + 1, offset + value.len_utf8());
+                }
+            }
+            Op::Delete { ids } => {
+                let ids: HashSet<&OpId> = ids.iter().collect();
+                let mut offset = 0;
+                for c in &mut self.chars {
+                    if c.deleted {
+                        continue;
+                    }
+                    let len = c.value.len_utf8();
+                    if !ids.contains(&c.id) {
+                        offset += len;
+                        continue;
+                    }
+
+                    c.deleted = true;
+                    match result.last_mut() {
+                        Some(edit) if edit.range.0 == offset => edit.range.1 += len,
+                        _ => result
+                            .push(TextEdit { range: (offset, offset + len), text: String::new() }),
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Identifies the position at the byte offset `offset` by the character just before it, or
+    /// `None` for the start of the text.
+    pub fn anchor(&self, offset: usize) -> Option<OpId> {
+        let mut result = None;
+        let mut current = 0;
+        for c in self.chars.iter().filter(|c| !c.deleted) {
+            if current >= offset {
+                break;
+            }
+            result = Some(c.id);
+            current += c.value.len_utf8();
+        }
+        result
+    }
+
+    /// The byte offset of a position identified with `anchor`, or `None` if the character it was
+    /// identified by hasn't been inserted here.
+    pub fn resolve(&self, anchor: Option<OpId>) -> Option<usize> {
+        match anchor {
+            None => Some(0),
+            Some(anchor) => {
+                let index = self.chars.iter().position(|c| c.id == anchor)?;
+                Some(self.offset(index + 1))
+            }
+        }
+    }
+
+    /// The byte offset in the text of the character at `index`.
+    fn offset(&self, index: usize) -> usize {
+        self.chars[..index]
+            .iter()
+            .filter(|c| !c.deleted)
+            .map(|c| c.value.len_utf8())
+            .sum()
+    }
+}
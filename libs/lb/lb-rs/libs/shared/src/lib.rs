@@ -6,6 +6,7 @@ pub mod compression_service;
 pub mod core_config;
 pub mod core_ops;
 pub mod core_tree;
+pub mod crdt;
 pub mod crypto;
 pub mod document_repo;
 pub mod drawing;
@@ -23,6 +24,7 @@ pub mod server_tree;
 pub mod signed_file;
 pub mod staged;
 pub mod symkey;
+pub mod team;
 pub mod tree_like;
 pub mod usage;
 pub mod validate;
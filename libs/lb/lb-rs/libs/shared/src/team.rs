@@ -0,0 +1,56 @@
+use crate::account::Account;
+use crate::clock::get_time;
+use crate::crypto::ECSigned;
+use crate::file::ShareMode;
+use crate::{pubkey, SharedResult};
+use libsecp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub type SignedTeam = ECSigned<TeamMetadata>;
+
+/// A group of users that files are shared with together. Sharing a file with a team shares it with
+/// each member, and members who join later are given access to everything shared with the team.
+/// Members who leave lose the shares the team made with them, but keys aren't rotated.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TeamMetadata {
+    pub id: Uuid,
+    pub name: String,
+    /// the only user who can change the team, and who signs it; always a member
+    pub admin: PublicKey,
+    pub members: Vec<PublicKey>,
+    pub shares: Vec<TeamShare>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TeamShare {
+    pub id: Uuid,
+    pub mode: ShareMode,
+    /// the members the team shared the file with; members who could already access it as the team
+    /// would aren't included, so removing a member only removes access the team gave them
+    pub grants: Vec<TeamGrant>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TeamGrant {
+    pub member: PublicKey,
+    /// the member's share of the file that the team's share replaced, restored when they leave
+    pub replaced: Option<ShareMode>,
+}
+
+impl TeamMetadata {
+    pub fn new(name: &str, account: &Account) -> Self {
+        let admin = account.public_key();
+        TeamMetadata {
+            id: Uuid::new_v4(),
+            name: String::from(name),
+            admin,
+            members: vec![admin],
+            shares: vec![],
+        }
+    }
+
+    pub fn sign(self, account: &Account) -> SharedResult<SignedTeam> {
+        pubkey::sign(&account.private_key, self, get_time)
+    }
+}
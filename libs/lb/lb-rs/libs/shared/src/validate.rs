@@ -7,6 +7,7 @@ use crate::staged::StagedTreeLike;
 use crate::tree_like::TreeLike;
 use crate::{SharedErrorKind, SharedResult, ValidationFailure};
 use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
 pub fn file_name(name: &str) -> SharedResult<()> {
     if name.is_empty() {
@@ -57,9 +58,17 @@ where
     Local: TreeLike<F = T::F>,
 {
     pub fn validate(&mut self, owner: Owner) -> SharedResult<()> {
+        self.validate_transfer(owner, &HashSet::new())
+    }
+
+    /// Validates changes that include taking the `transferred` files, which were transferred to
+    /// `owner`; the server checks that the files were offered to them.
+    pub fn validate_transfer(
+        &mut self, owner: Owner, transferred: &HashSet<Uuid>,
+    ) -> SharedResult<()> {
         // point checks
         self.assert_no_root_changes()?;
-        self.assert_no_changes_to_deleted_files()?;
+        self.assert_no_changes_to_deleted_files(transferred)?;
         self.assert_all_filenames_size_limit()?;
         self.assert_all_files_decryptable(owner)?;
         self.assert_only_folders_have_children()?;
@@ -74,7 +83,7 @@ where
         self.assert_no_owned_links()?;
 
         // authorization check
-        self.assert_changes_authorized(owner)?;
+        self.assert_changes_authorized(owner, transferred)?;
 
         Ok(())
     }
@@ -253,8 +262,13 @@ where
         Ok(())
     }
 
-    pub fn assert_no_owned_links(&self) -> SharedResult<()> {
+    // note: a deleted link to an owned file is allowed, because a user deletes their links to files
+    // transferred to them when they take the files
+    pub fn assert_no_owned_links(&mut self) -> SharedResult<()> {
         for link in self.owned_ids() {
+            if self.calculate_deleted(&link)? {
+                continue;
+            }
             if let FileType::Link { target } = self.find(&link)?.file_type() {
                 if let Some(target_owner) = self.maybe_find(&target).map(|f| f.owner()) {
                     if self.find(&link)?.owner() == target_owner {
@@ -286,12 +300,29 @@ where
         Ok(())
     }
 
-    pub fn assert_no_changes_to_deleted_files(&mut self) -> SharedResult<()> {
+    pub fn assert_no_changes_to_deleted_files(
+        &mut self, transferred: &HashSet<Uuid>,
+    ) -> SharedResult<()> {
         for id in self.tree.staged().owned_ids() {
-            // already deleted files cannot have updates
+            // already deleted files cannot have updates, except to follow their parent to a new
+            // owner, re-encrypted with its new key, when it's transferred
             let mut base = self.tree.base().to_lazy();
             if base.maybe_find(&id).is_some() && base.calculate_deleted(&id)? {
-                Err(SharedErrorKind::DeletedFileUpdated(id))?;
+                if !transferred.contains(&id) {
+                    Err(SharedErrorKind::DeletedFileUpdated(id))?;
+                }
+                let staged = self.find(&id)?;
+                let diff = FileDiff::edit(base.find(&id)?, staged).diff();
+                let follows_owner = diff.contains(&Diff::Owner)
+                    && diff.iter().all(|d| d == &Diff::Owner || d == &Diff::Name);
+                let same_owner_as_parent = self
+                    .maybe_find(staged.parent())
+                    .map(|parent| parent.owner())
+                    == Some(staged.owner());
+                if !follows_owner || !same_owner_as_parent {
+                    Err(SharedErrorKind::DeletedFileUpdated(id))?;
+                }
+                continue;
             }
             // newly deleted files cannot have non-deletion updates
             if self.calculate_deleted(&id)? {
@@ -309,7 +340,9 @@ where
         Ok(())
     }
 
-    pub fn assert_changes_authorized(&mut self, owner: Owner) -> SharedResult<()> {
+    pub fn assert_changes_authorized(
+        &mut self, owner: Owner, transferred: &HashSet<Uuid>,
+    ) -> SharedResult<()> {
         // Design rationale:
         // * No combination of individually valid changes should compose into an invalid change.
         //   * Owner and write access must be indistinguishable, otherwise you could e.g. move a
@@ -337,8 +370,22 @@ where
             }
             new_files
         };
+        let base = self.tree.base().to_lazy();
 
         for file_diff in self.diffs()? {
+            // a user can take files they can write that are transferred to them, making any change
+            // to them
+            if let Some(ref old) = file_diff.old {
+                if transferred.contains(file_diff.id())
+                    && old.owner() != owner
+                    && file_diff.new.owner() == owner
+                {
+                    if base.access_mode(owner, file_diff.id())? < Some(UserAccessMode::Write) {
+                        Err(SharedErrorKind::InsufficientPermission)?;
+                    }
+                    continue;
+                }
+            }
             for field_diff in file_diff.diff() {
                 match field_diff {
                     Diff::New | Diff::Name | Diff::Deleted => {
@@ -0,0 +1,137 @@
+use lockbook_shared::crdt::{Op, Rga, TextEdit};
+use uuid::Uuid;
+
+fn replicas(text: &str) -> (Rga, Rga) {
+    (Rga::new(Uuid::new_v4(), text), Rga::new(Uuid::new_v4(), text))
+}
+
+/// applies `ops` to `rga`, checking that the edits it returns make the same change to its text
+fn apply(rga: &mut Rga, ops: &[Op]) {
+    for op in ops {
+        let mut text = rga.text();
+        for TextEdit { range, text: replacement } in rga.apply(op) {
+            text.replace_range(range.0..range.1, &replacement);
+        }
+        assert_eq!(text, rga.text());
+    }
+}
+
+#[test]
+fn replace() {
+    let (mut a, mut b) = replicas("hello world");
+
+    let ops = a.replace((6, 11), "there");
+    assert_eq!(a.text(), "hello there");
+    apply(&mut b, &ops);
+    assert_eq!(b.text(), "hello there");
+
+    let ops = b.replace((0, 0), "oh, ");
+    apply(&mut a, &ops);
+    assert_eq!(a.text(), "oh, hello there");
+}
+
+#[test]
+fn concurrent_inserts_converge() {
+    let (mut a, mut b) = replicas("ac");
+
+    let a_ops = a.replace((1, 1), "b");
+    let b_ops = b.replace((1, 1), "B");
+    apply(&mut a, &b_ops);
+    apply(&mut b, &a_ops);
+
+    assert_eq!(a.text(), b.text());
+    assert!(a.text() == "abBc" || a.text() == "aBbc");
+}
+
+#[test]
+fn concurrent_typing_stays_contiguous() {
+    let (mut a, mut b) = replicas("");
+
+    let mut a_ops = Vec::new();
+    for (i, c) in "hello".chars().enumerate() {
+        a_ops.extend(a.replace((i, i), &c.to_string()));
+    }
+    let b_ops = b.replace((0, 0), "world");
+    apply(&mut a, &b_ops);
+    apply(&mut b, &a_ops);
+
+    assert_eq!(a.text(), b.text());
+    assert!(a.text() == "helloworld" || a.text() == "worldhello");
+}
+
+#[test]
+fn concurrent_delete_and_insert() {
+    let (mut a, mut b) = replicas("one two three");
+
+    let a_ops = a.replace((3, 7), "");
+    let b_ops = b.replace((5, 5), "w");
+    apply(&mut a, &b_ops);
+    apply(&mut b, &a_ops);
+
+    assert_eq!(a.text(), "onew three");
+    assert_eq!(b.text(), "onew three");
+}
+
+#[test]
+fn ops_apply_once() {
+    let (mut a, mut b) = replicas("abc");
+
+    let ops = a.replace((1, 2), "xy");
+    apply(&mut b, &ops);
+    apply(&mut b, &ops);
+    apply(&mut a, &ops);
+
+    assert_eq!(a.text(), "axyc");
+    assert_eq!(b.text(), "axyc");
+}
+
+#[test]
+fn multi_byte_characters() {
+    let (mut a, mut b) = replicas("naïve café");
+
+    let ops = a.replace(("naïve ".len(), "naïve café".len()), "résumé 🎉");
+    apply(&mut b, &ops);
+    assert_eq!(b.text(), "naïve résumé 🎉");
+
+    let ops = b.replace((0, "naïve".len()), "");
+    apply(&mut a, &ops);
+    assert_eq!(a.text(), " résumé 🎉");
+}
+
+#[test]
+fn anchors_follow_edits() {
+    let (mut a, mut b) = replicas("hello world");
+
+    let start = a.anchor(0);
+    let world = a.anchor(6);
+    let end = a.anchor(11);
+
+    let ops = b.replace((0, 0), "oh, ");
+    apply(&mut a, &ops);
+    assert_eq!(a.text(), "oh, hello world");
+    assert_eq!(a.resolve(start), Some(0));
+    assert_eq!(a.resolve(world), Some(10));
+    assert_eq!(a.resolve(end), Some(15));
+
+    // positions after deleted characters move to where they were
+    let ops = b.replace((4, 10), "");
+    apply(&mut a, &ops);
+    assert_eq!(a.text(), "oh, world");
+    assert_eq!(a.resolve(world), Some(4));
+
+    // positions identified by characters that haven't arrived can't be found
+    let mut c = Rga::new(Uuid::new_v4(), "hello world");
+    c.replace((11, 11), "!");
+    assert_eq!(a.resolve(c.anchor(12)), None);
+}
+
+#[test]
+fn text_edit_between() {
+    assert_eq!(
+        TextEdit::between("hello world", "hello there world"),
+        TextEdit { range: (6, 6), text: "there ".into() }
+    );
+    assert_eq!(TextEdit::between("aaa", "aa"), TextEdit { range: (2, 3), text: "".into() });
+    assert_eq!(TextEdit::between("café", "cafés"), TextEdit { range: (5, 5), text: "s".into() });
+    assert_eq!(TextEdit::between("same", "same"), TextEdit { range: (4, 4), text: "".into() });
+}
@@ -80,6 +80,20 @@ pub fn another_client(c: &Core) -> Core {
     new_core
 }
 
+/// Syncs two clients of the same account until each has the other's changes, then checks that
+/// they agree and have nothing left to sync.
+pub fn sync_and_assert_stuff(c1: &Core, c2: &Core) {
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    c1.validate().unwrap();
+    assert::cores_equal(c1, c2);
+    assert::local_work_paths(c1, &[]);
+    assert::server_work_paths(c1, &[]);
+}
+
 fn err_to_string<E: Debug>(e: E) -> String {
     format!("{}: {:?}", std::any::type_name::<E>(), e)
 }
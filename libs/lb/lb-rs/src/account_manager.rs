@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use lockbook_shared::clock::get_time;
+
+use crate::model::errors::core_err_unexpected;
+use crate::service::log_service;
+use crate::{unexpected_only, Config, Core, CoreError, LbError, LbResult, UnexpectedError};
+
+static ACCOUNTS_FOLDER: &str = "accounts";
+static CURRENT_ACCOUNT_FILE: &str = "current_account";
+
+/// Keeps several accounts on one device, each with its own `Core` and storage. The first account
+/// is kept in the folder given, the same as when a client has only one, and the others are kept
+/// in the `accounts` folder within it. Accounts are identified by their usernames.
+#[derive(Clone)]
+pub struct AccountManager {
+    inner: Arc<Mutex<AccountManagerState>>,
+}
+
+struct AccountManagerState {
+    config: Config,
+    cores: Vec<Core>,
+}
+
+impl AccountManager {
+    pub fn init(config: &Config) -> Result<Self, UnexpectedError> {
+        log_service::init(config)?;
+
+        // logs for all accounts go to the folder given
+        let core_config = |writeable_path| Config { writeable_path, logs: false, ..config.clone() };
+
+        let mut cores = vec![Core::init(&core_config(config.writeable_path.clone()))?];
+        let accounts_path = PathBuf::from(&config.writeable_path).join(ACCOUNTS_FOLDER);
+        if accounts_path.exists() {
+            let mut paths = fs::read_dir(&accounts_path)
+                .map_err(|err| unexpected_only!("{:#?}", err))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| unexpected_only!("{:#?}", err))?;
+            // folders are named by when they were added
+            paths.sort();
+            for path in paths.into_iter().filter(|path| path.is_dir()) {
+                cores.push(Core::init(&core_config(path.to_string_lossy().to_string()))?);
+            }
+        }
+
+        let state = AccountManagerState { config: config.clone(), cores };
+        Ok(Self { inner: Arc::new(Mutex::new(state)) })
+    }
+
+    /// The usernames of the accounts on this device, in the order they were added.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn list_accounts(&self) -> Result<Vec<String>, UnexpectedError> {
+        let state = self.inner.lock()?;
+        let mut result = Vec::new();
+        for core in &state.cores {
+            if let Some(username) = account_username(core)? {
+                result.push(username);
+            }
+        }
+        Ok(result)
+    }
+
+    /// The account last switched to, or the first account if there isn't one. If there are no
+    /// accounts, it's a `Core` without one, for the user to create or import an account with.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn current(&self) -> Result<Core, UnexpectedError> {
+        let current = self.inner.lock()?.read_current();
+        if let Some(username) = current {
+            match self.get(&username) {
+                Ok(core) => return Ok(core),
+                Err(err) if err.kind == CoreError::AccountNonexistent => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let first = self.list_accounts()?.into_iter().next();
+        match first {
+            Some(username) => Ok(self.get(&username)?),
+            None => self.new_account_core(),
+        }
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get(&self, username: &str) -> Result<Core, LbError> {
+        let state = self.inner.lock().map_err(core_err_unexpected)?;
+        for core in &state.cores {
+            if account_username(core)?.as_deref() == Some(username) {
+                return Ok(core.clone());
+            }
+        }
+        Err(CoreError::AccountNonexistent.into())
+    }
+
+    /// Makes the account `username` the current one, including the next time the manager is
+    /// initialized.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn switch(&self, username: &str) -> Result<Core, LbError> {
+        let core = self.get(username)?;
+        let state = self.inner.lock().map_err(core_err_unexpected)?;
+        fs::write(state.current_account_path(), username)?;
+        Ok(core)
+    }
+
+    /// A `Core` without an account, with its own storage, for the user to create or import another
+    /// account with. Once they have, the account is listed like the others.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn new_account_core(&self) -> Result<Core, UnexpectedError> {
+        let mut state = self.inner.lock()?;
+        for core in &state.cores {
+            if account_username(core)?.is_none() {
+                return Ok(core.clone());
+            }
+        }
+
+        let writeable_path = PathBuf::from(&state.config.writeable_path)
+            .join(ACCOUNTS_FOLDER)
+            .join(get_time().0.to_string())
+            .to_string_lossy()
+            .to_string();
+        let config = Config { writeable_path, logs: false, ..state.config.clone() };
+        let core = Core::init(&config)?;
+        state.cores.push(core.clone());
+        Ok(core)
+    }
+}
+
+impl AccountManagerState {
+    fn current_account_path(&self) -> PathBuf {
+        PathBuf::from(&self.config.writeable_path).join(CURRENT_ACCOUNT_FILE)
+    }
+
+    fn read_current(&self) -> Option<String> {
+        fs::read_to_string(self.current_account_path()).ok()
+    }
+}
+
+fn account_username(core: &Core) -> LbResult<Option<String>> {
+    match core.get_account() {
+        Ok(account) => Ok(Some(account.username)),
+        Err(err) if err.kind == CoreError::AccountNonexistent => Ok(None),
+        Err(err) => Err(err),
+    }
+}
@@ -4,13 +4,14 @@ extern crate tracing;
 pub mod model;
 pub mod service;
 
+mod account_manager;
 mod repo;
 
 pub use base64;
 pub use basic_human_duration::ChronoHumanDuration;
 pub use libsecp256k1::PublicKey;
 pub use lockbook_shared::document_repo::{DocumentService, OnDiskDocuments};
-pub use time::Duration;
+pub use time::{Date, Duration};
 pub use uuid::Uuid;
 
 pub use lockbook_shared::account::Account;
@@ -21,6 +22,7 @@ pub use lockbook_shared::api::{
 };
 pub use lockbook_shared::clock;
 pub use lockbook_shared::core_config::Config;
+pub use lockbook_shared::crdt::TextEdit;
 pub use lockbook_shared::crypto::DecryptedDocument;
 pub use lockbook_shared::drawing::{ColorAlias, ColorRGB, Drawing, Stroke};
 pub use lockbook_shared::file::{File, Share, ShareMode};
@@ -30,18 +32,36 @@ pub use lockbook_shared::filename::NameComponents;
 pub use lockbook_shared::lazy::LazyTree;
 pub use lockbook_shared::path_ops::Filter;
 pub use lockbook_shared::server_file::ServerFile;
+pub use lockbook_shared::team::{TeamGrant, TeamShare};
 pub use lockbook_shared::tree_like::{TreeLike, TreeLikeMut};
 pub use lockbook_shared::usage::bytes_to_human;
 pub use lockbook_shared::work_unit::WorkUnit;
 
+pub use crate::account_manager::AccountManager;
 pub use crate::model::drawing::SupportedImageFormats;
 pub use crate::model::errors::{
     CoreError, LbError, LbResult, TestRepoError, UnexpectedError, Warning,
 };
 pub use crate::service::activity_service::RankingWeights;
+pub use crate::service::collab_service::{CollabSession, RemoteCursor};
+pub use crate::service::conflict_service::{
+    find_conflict_hunks, resolve_conflict_hunks, Conflict, ConflictChoice, ConflictHunk,
+};
+pub use crate::service::daily_note_service::{daily_note_path, DEFAULT_DAILY_NOTE_PATTERN};
+pub use crate::service::diff_service::{
+    DiffAgainst, DiffHunk, DiffLine, DiffLineKind, DiffWord, DocumentDiff, DrawingDiff,
+};
 pub use crate::service::import_export_service::{ExportFileInfo, ImportStatus};
+pub use crate::service::link_service::{percent_decode, LinkGraph};
 pub use crate::service::search_service::{SearchResultItem, StartSearchInfo};
-pub use crate::service::sync_service::{SyncProgress, SyncStatus};
+pub use crate::service::share_service::Permission;
+pub use crate::service::sync_service::{
+    PlannedChange, PlannedConflict, PlannedOp, SyncPlan, SyncProgress, SyncStatus,
+};
+pub use crate::service::task_service::{Task, TaskFilter};
+pub use crate::service::team_service::Team;
+pub use crate::service::template_service::{today, TEMPLATES_FOLDER_PATH};
+pub use crate::service::undo_service::{FileOperation, RecentOperation};
 pub use crate::service::usage_service::{UsageItemMetric, UsageMetrics};
 
 use std::collections::HashMap;
@@ -53,6 +73,8 @@ use lockbook_shared::account::Username;
 use lockbook_shared::api::{
     AccountInfo, AdminFileInfoResponse, AdminValidateAccount, AdminValidateServer,
 };
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::repo::CoreDb;
 use crate::service::api_service::{Network, Requester};
@@ -252,6 +274,16 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
             .expected_errs(&[CoreError::FileNotDocument, CoreError::FileNonexistent])
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn diff_document(&self, id: Uuid, against: DiffAgainst) -> Result<DocumentDiff, LbError> {
+        self.in_tx(|s| s.diff_document(id, against))
+            .expected_errs(&[
+                CoreError::FileNonexistent,
+                CoreError::FileNotDocument,
+                CoreError::DrawingInvalid,
+            ])
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn list_metadatas(&self) -> Result<Vec<File>, UnexpectedError> {
         Ok(self.in_tx(|s| s.list_metadatas())?)
@@ -286,6 +318,24 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
             ])
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn list_recent_operations(&self) -> Result<Vec<RecentOperation>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.list_recent_operations())?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn undo_last_operation(&self) -> Result<Option<RecentOperation>, LbError> {
+        self.in_tx(|s| s.undo_last_operation()).expected_errs(&[
+            CoreError::OperationNotUndoable,
+            CoreError::FileNonexistent,
+            CoreError::FileParentNonexistent,
+            CoreError::FolderMovedIntoSelf,
+            CoreError::InsufficientPermission,
+            CoreError::LinkInSharedFolder,
+            CoreError::PathTaken,
+        ])
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn share_file(&self, id: Uuid, username: &str, mode: ShareMode) -> Result<(), LbError> {
         self.in_tx(|s| s.share_file(id, username, mode))
@@ -298,6 +348,69 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
             ])
     }
 
+    /// Offers a file and everything in it to another user, who becomes its owner if they accept.
+    /// The file is shared with them so they can see it. The offer is made immediately, so the
+    /// file must have been synced; the share is made next time you sync.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn transfer_ownership(&self, id: Uuid, username: &str) -> Result<(), LbError> {
+        self.in_tx(|s| s.transfer_ownership(&id, username))
+            .expected_errs(&[
+                CoreError::AccountNonexistent,
+                CoreError::ClientUpdateRequired,
+                CoreError::FileNonexistent,
+                CoreError::InsufficientPermission,
+                CoreError::LinkInSharedFolder,
+                CoreError::RootModificationInvalid,
+                CoreError::ServerUnreachable,
+                CoreError::SyncRequired,
+            ])
+    }
+
+    /// Lists the files other users have offered to transfer to this user, once they've synced.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_pending_transfers(&self) -> Result<Vec<File>, LbError> {
+        self.in_tx(|s| s.get_pending_transfers())
+            .expected_errs(&[CoreError::ClientUpdateRequired, CoreError::ServerUnreachable])
+    }
+
+    /// Accepts a transfer, becoming the owner of the file and everything in it and moving it to
+    /// `parent`. It's uploaded immediately, so the file and `parent` can't have changes that
+    /// haven't been synced.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn accept_transfer(&self, id: Uuid, parent: Uuid) -> Result<(), LbError> {
+        self.in_tx(|s| s.accept_transfer(&id, &parent))
+            .expected_errs(&[
+                CoreError::ClientUpdateRequired,
+                CoreError::FileNonexistent,
+                CoreError::FileNotFolder,
+                CoreError::FileParentNonexistent,
+                CoreError::InsufficientPermission,
+                CoreError::PathTaken,
+                CoreError::ServerUnreachable,
+                CoreError::SyncRequired,
+                CoreError::UsageIsOverDataCap,
+            ])
+    }
+
+    /// Declines a transfer offered to this user. The file stays shared with them until they
+    /// delete the pending share.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn reject_transfer(&self, id: Uuid) -> Result<(), LbError> {
+        self.in_tx(|s| s.reject_transfer(&id)).expected_errs(&[
+            CoreError::ClientUpdateRequired,
+            CoreError::FileNonexistent,
+            CoreError::ServerUnreachable,
+        ])
+    }
+
+    /// Lists everyone who can access a file - its owner first, then each user it's shared with
+    /// directly or through a shared ancestor, along with the file whose share grants them access.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn effective_permissions(&self, id: Uuid) -> Result<Vec<Permission>, LbError> {
+        self.in_tx(|s| s.effective_permissions(&id))
+            .expected_errs(&[CoreError::FileNonexistent])
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn get_pending_shares(&self) -> Result<Vec<File>, UnexpectedError> {
         Ok(self.in_tx(|s| s.get_pending_shares())?)
@@ -312,6 +425,71 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
         .expected_errs(&[CoreError::FileNonexistent, CoreError::ShareNonexistent])
     }
 
+    /// Lists the teams this user administers or is a member of.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_teams(&self) -> Result<Vec<Team>, LbError> {
+        self.in_tx(|s| s.get_teams())
+            .expected_errs(&[CoreError::ServerUnreachable, CoreError::ClientUpdateRequired])
+    }
+
+    /// Creates a team administered by this user, who is its first member.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn create_team(&self, name: &str) -> Result<Team, LbError> {
+        self.in_tx(|s| s.create_team(name)).expected_errs(&[
+            CoreError::ClientUpdateRequired,
+            CoreError::ServerUnreachable,
+            CoreError::TeamNameEmpty,
+            CoreError::TeamNameTaken,
+        ])
+    }
+
+    /// Adds a user to a team administered by this user and shares everything shared with the team
+    /// with them. The shares are made locally, like [`Self::share_file`].
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn add_team_member(&self, team: &str, username: &str) -> Result<(), LbError> {
+        self.in_tx(|s| s.add_team_member(team, username))
+            .expected_errs(&[
+                CoreError::AccountNonexistent,
+                CoreError::ClientUpdateRequired,
+                CoreError::InsufficientPermission,
+                CoreError::ServerUnreachable,
+                CoreError::TeamNonexistent,
+            ])
+    }
+
+    /// Removes a user from a team administered by this user and undoes the shares the team made
+    /// with them; shares made with them directly are kept. Keys aren't rotated, so they can still
+    /// read what they synced while they were a member. The admin can't be removed.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn remove_team_member(&self, team: &str, username: &str) -> Result<(), LbError> {
+        self.in_tx(|s| s.remove_team_member(team, username))
+            .expected_errs(&[
+                CoreError::AccountNonexistent,
+                CoreError::ClientUpdateRequired,
+                CoreError::InsufficientPermission,
+                CoreError::ServerUnreachable,
+                CoreError::TeamNonexistent,
+            ])
+    }
+
+    /// Shares a file with every member of a team administered by this user, and with anyone who
+    /// joins the team later.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn share_file_with_team(
+        &self, id: Uuid, team: &str, mode: ShareMode,
+    ) -> Result<(), LbError> {
+        self.in_tx(|s| s.share_file_with_team(id, team, mode))
+            .expected_errs(&[
+                CoreError::ClientUpdateRequired,
+                CoreError::FileNonexistent,
+                CoreError::InsufficientPermission,
+                CoreError::LinkInSharedFolder,
+                CoreError::RootModificationInvalid,
+                CoreError::ServerUnreachable,
+                CoreError::TeamNonexistent,
+            ])
+    }
+
     #[instrument(level = "debug", skip_all, err(Debug))]
     pub fn create_link_at_path(
         &self, path_and_name: &str, target_id: Uuid,
@@ -348,6 +526,103 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
             .expected_errs(&[CoreError::FileNonexistent])
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn resolve_link(&self, from: Option<Uuid>, target: &str) -> Result<Uuid, LbError> {
+        self.in_tx(|s| s.resolve_link(from, target))
+            .expected_errs(&[CoreError::FileNonexistent])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_backlinks(&self, id: Uuid) -> Result<Vec<Uuid>, LbError> {
+        self.in_tx(|s| s.get_backlinks(id))
+            .expected_errs(&[CoreError::FileNonexistent])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn link_graph(&self) -> Result<LinkGraph, UnexpectedError> {
+        Ok(self.in_tx(|s| s.link_graph())?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn list_tasks(&self, filter: TaskFilter) -> Result<Vec<Task>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.list_tasks(filter))?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn set_task_state(&self, task: &Task, checked: bool) -> Result<(), LbError> {
+        self.in_tx(|s| s.set_task_state(task, checked))
+            .expected_errs(&[CoreError::FileNonexistent, CoreError::TaskNonexistent])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn list_templates(&self) -> Result<Vec<File>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.list_templates())?)
+    }
+
+    #[instrument(level = "debug", skip(self, name), err(Debug))]
+    pub fn create_from_template(
+        &self, template_id: Uuid, parent: Uuid, name: &str,
+    ) -> Result<File, LbError> {
+        self.in_tx(|s| s.create_from_template(template_id, parent, name))
+            .expected_errs(&[
+                CoreError::FileNonexistent,
+                CoreError::FileNotDocument,
+                CoreError::FileNameContainsSlash,
+                CoreError::FileNameEmpty,
+                CoreError::FileNameTooLong,
+                CoreError::FileNotFolder,
+                CoreError::FileParentNonexistent,
+                CoreError::InsufficientPermission,
+                CoreError::PathTaken,
+            ])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_or_create_daily_note(
+        &self, date: Date, pattern: &str, template: Option<Uuid>,
+    ) -> Result<File, LbError> {
+        self.in_tx(|s| s.get_or_create_daily_note(date, pattern, template))
+            .expected_errs(&[
+                CoreError::FileNonexistent,
+                CoreError::FileNotDocument,
+                CoreError::FileNotFolder,
+                CoreError::FileNameTooLong,
+                CoreError::InsufficientPermission,
+                CoreError::PathContainsEmptyFileName,
+            ])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn start_collab(&self, id: Uuid) -> Result<CollabSession, LbError> {
+        CollabSession::start(self, id).expected_errs(&[
+            CoreError::FileNonexistent,
+            CoreError::FileNotDocument,
+            CoreError::InsufficientPermission,
+            CoreError::ServerUnreachable,
+            CoreError::ClientUpdateRequired,
+        ])
+    }
+
+    #[instrument(level = "debug", skip(self, session), err(Debug))]
+    pub fn sync_collab(&self, session: &CollabSession) -> Result<bool, LbError> {
+        session.sync(self).expected_errs(&[
+            CoreError::FileNonexistent,
+            CoreError::InsufficientPermission,
+            CoreError::ServerUnreachable,
+            CoreError::ClientUpdateRequired,
+        ])
+    }
+
+    #[instrument(level = "debug", skip(self, session), err(Debug))]
+    pub fn leave_collab(&self, session: &CollabSession) -> Result<(), LbError> {
+        session.leave(self).expected_errs(&[
+            CoreError::FileNonexistent,
+            CoreError::InsufficientPermission,
+            CoreError::ServerUnreachable,
+            CoreError::ClientUpdateRequired,
+        ])
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn get_path_by_id(&self, id: Uuid) -> Result<String, UnexpectedError> {
         Ok(self.in_tx(|s| s.get_path_by_id(id))?)
@@ -370,12 +645,56 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
         })?)
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn describe_local_changes(&self) -> Result<Vec<PlannedChange>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.describe_local_changes())?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn revert_local_change(&self, id: Uuid) -> Result<(), LbError> {
+        self.in_tx(|s| s.revert_local_change(&id)).expected_errs(&[
+            CoreError::FileNonexistent,
+            CoreError::FileParentNonexistent,
+            CoreError::FolderMovedIntoSelf,
+            CoreError::LinkInSharedFolder,
+            CoreError::LinkTargetNonexistent,
+            CoreError::MultipleLinksToSameFile,
+            CoreError::PathTaken,
+        ])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn revert_all_local_changes(&self) -> Result<(), UnexpectedError> {
+        Ok(self.in_tx(|s| s.revert_all_local_changes())?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_conflicts(&self) -> Result<Vec<Conflict>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.get_conflicts())?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn resolve_conflict(&self, id: Uuid, choice: ConflictChoice) -> Result<(), LbError> {
+        self.in_tx(|s| s.resolve_conflict(id, choice))
+            .expected_errs(&[
+                CoreError::FileNonexistent,
+                CoreError::FileNotDocument,
+                CoreError::InsufficientPermission,
+            ])
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn calculate_work(&self) -> Result<SyncStatus, LbError> {
         self.in_tx(|s| s.calculate_work())
             .expected_errs(&[CoreError::ServerUnreachable, CoreError::ClientUpdateRequired])
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn plan_sync(&self) -> Result<SyncPlan, LbError> {
+        self.in_tx(|s| s.plan_sync())
+            .expected_errs(&[CoreError::ServerUnreachable, CoreError::ClientUpdateRequired])
+    }
+
     // todo: expose work calculated (return value)
     #[instrument(level = "debug", skip_all, err(Debug))]
     pub fn sync(&self, f: Option<Box<dyn Fn(SyncProgress)>>) -> Result<SyncStatus, LbError> {
@@ -409,6 +728,53 @@ impl<Client: Requester, Docs: DocumentService> CoreLib<Client, Docs> {
         Ok(self.in_tx(|s| s.suggested_docs(settings))?)
     }
 
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn pin_file(&self, id: Uuid) -> Result<(), LbError> {
+        self.in_tx(|s| s.pin_file(id))
+            .expected_errs(&[CoreError::FileNonexistent])
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn unpin_file(&self, id: Uuid) -> Result<(), UnexpectedError> {
+        Ok(self.in_tx(|s| s.unpin_file(id))?)
+    }
+
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn list_pinned(&self) -> Result<Vec<File>, UnexpectedError> {
+        Ok(self.in_tx(|s| s.list_pinned())?)
+    }
+
+    /// Returns the settings a client saved under `namespace` with `set_settings`, or `None` if it
+    /// hasn't saved any. Settings missing from what was saved are filled in as `T` deserializes
+    /// them, e.g. with `#[serde(default)]`.
+    #[instrument(level = "debug", skip(self), err(Debug))]
+    pub fn get_settings<T: DeserializeOwned>(
+        &self, namespace: &str,
+    ) -> Result<Option<T>, UnexpectedError> {
+        let values = self.in_tx(|s| s.client_settings(namespace))?;
+        values
+            .map(|values| serde_json::from_value(serde_json::Value::Object(values)))
+            .transpose()
+            .map_err(|err| unexpected_only!("couldn't read {} settings: {}", namespace, err))
+    }
+
+    /// Saves a client's settings, which must serialize to a map, under `namespace`. They're synced
+    /// with the account, and each setting changed on this device is kept when sync merges them
+    /// with changes from other devices.
+    #[instrument(level = "debug", skip(self, settings), err(Debug))]
+    pub fn set_settings<T: Serialize>(
+        &self, namespace: &str, settings: &T,
+    ) -> Result<(), UnexpectedError> {
+        let values = match serde_json::to_value(settings) {
+            Ok(serde_json::Value::Object(values)) => values,
+            Ok(_) => return Err(unexpected_only!("{} settings aren't a map", namespace)),
+            Err(err) => {
+                return Err(unexpected_only!("couldn't save {} settings: {}", namespace, err))
+            }
+        };
+        Ok(self.in_tx(|s| s.set_client_settings(namespace, values))?)
+    }
+
     #[instrument(level = "debug", skip(self), err(Debug))]
     pub fn get_usage(&self) -> Result<UsageMetrics, LbError> {
         self.in_tx(|s| s.get_usage())
@@ -104,6 +104,7 @@ impl Display for CoreError {
                 write!(f, "you're out of space, you can purchase additional space")
             }
             CoreError::OldCardDoesNotExist => write!(f, "no existing card found"),
+            CoreError::OperationNotUndoable => write!(f, "that operation can no longer be undone"),
             CoreError::PathContainsEmptyFileName => {
                 write!(f, "that path contains an empty file name")
             }
@@ -117,6 +118,11 @@ impl Display for CoreError {
             CoreError::ServerUnreachable => write!(f, "could not reach server"),
             CoreError::ShareAlreadyExists => write!(f, "that share already exists"),
             CoreError::ShareNonexistent => write!(f, "share non-existent"),
+            CoreError::SyncRequired => write!(f, "you need to sync before doing that"),
+            CoreError::TaskNonexistent => write!(f, "that task does not exist"),
+            CoreError::TeamNameEmpty => write!(f, "team names cannot be empty"),
+            CoreError::TeamNameTaken => write!(f, "you already have a team with that name"),
+            CoreError::TeamNonexistent => write!(f, "that team does not exist"),
             CoreError::TryAgain => write!(f, "please try again"),
             CoreError::UsernameInvalid => write!(f, "that username is invalid"),
             CoreError::UsernameNotFound => write!(f, "username not found"),
@@ -285,6 +291,7 @@ pub enum CoreError {
     UsageIsOverDataCap,
     UsageIsOverFreeTierDataCap,
     OldCardDoesNotExist,
+    OperationNotUndoable,
     PathContainsEmptyFileName,
     PathTaken,
     RootModificationInvalid,
@@ -293,6 +300,11 @@ pub enum CoreError {
     ServerUnreachable,
     ShareAlreadyExists,
     ShareNonexistent,
+    SyncRequired,
+    TaskNonexistent,
+    TeamNameEmpty,
+    TeamNameTaken,
+    TeamNonexistent,
     TryAgain,
     UsernameInvalid,
     UsernameNotFound,
@@ -364,6 +376,109 @@ impl From<ApiError<api::GetPublicKeyError>> for LbError {
     }
 }
 
+impl From<ApiError<api::OfferTransferError>> for LbError {
+    fn from(err: ApiError<api::OfferTransferError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::OfferTransferError::FileNotFound) => CoreError::FileNonexistent,
+            ApiError::Endpoint(api::OfferTransferError::NotPermissioned) => {
+                CoreError::InsufficientPermission
+            }
+            ApiError::Endpoint(api::OfferTransferError::UserNotFound) => {
+                CoreError::AccountNonexistent
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::GetTransfersError>> for LbError {
+    fn from(err: ApiError<api::GetTransfersError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::GetTransfersError::UserNotFound) => {
+                CoreError::AccountNonexistent
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::DeleteTransferError>> for LbError {
+    fn from(err: ApiError<api::DeleteTransferError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::DeleteTransferError::TransferNotFound) => {
+                CoreError::FileNonexistent
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::UpsertTeamError>> for LbError {
+    fn from(err: ApiError<api::UpsertTeamError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::UpsertTeamError::NotPermissioned) => {
+                CoreError::InsufficientPermission
+            }
+            ApiError::Endpoint(api::UpsertTeamError::TeamNameEmpty) => CoreError::TeamNameEmpty,
+            ApiError::Endpoint(api::UpsertTeamError::TeamNameTaken) => CoreError::TeamNameTaken,
+            ApiError::Endpoint(api::UpsertTeamError::UserNotFound) => CoreError::AccountNonexistent,
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::GetTeamsError>> for LbError {
+    fn from(err: ApiError<api::GetTeamsError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::GetTeamsError::UserNotFound) => CoreError::AccountNonexistent,
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::UpsertAccountSettingsError>> for LbError {
+    fn from(err: ApiError<api::UpsertAccountSettingsError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::UpsertAccountSettingsError::UserNotFound) => {
+                CoreError::AccountNonexistent
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::GetAccountSettingsError>> for LbError {
+    fn from(err: ApiError<api::GetAccountSettingsError>) -> Self {
+        match err {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::GetAccountSettingsError::UserNotFound) => {
+                CoreError::AccountNonexistent
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
 impl From<ApiError<api::GetUsernameError>> for LbError {
     fn from(err: ApiError<api::GetUsernameError>) -> Self {
         match err {
@@ -411,6 +526,57 @@ impl From<ApiError<api::GetDocumentError>> for LbError {
     }
 }
 
+impl From<ApiError<api::SendCollabMessageError>> for LbError {
+    fn from(e: ApiError<api::SendCollabMessageError>) -> Self {
+        match e {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::SendCollabMessageError::DocumentNotFound) => {
+                CoreError::FileNonexistent
+            }
+            ApiError::Endpoint(api::SendCollabMessageError::NotPermissioned) => {
+                CoreError::InsufficientPermission
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::GetCollabMessagesError>> for LbError {
+    fn from(e: ApiError<api::GetCollabMessagesError>) -> Self {
+        match e {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::GetCollabMessagesError::DocumentNotFound) => {
+                CoreError::FileNonexistent
+            }
+            ApiError::Endpoint(api::GetCollabMessagesError::NotPermissioned) => {
+                CoreError::InsufficientPermission
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
+impl From<ApiError<api::UpdatePresenceError>> for LbError {
+    fn from(e: ApiError<api::UpdatePresenceError>) -> Self {
+        match e {
+            ApiError::SendFailed(_) => CoreError::ServerUnreachable,
+            ApiError::ClientUpdateRequired => CoreError::ClientUpdateRequired,
+            ApiError::Endpoint(api::UpdatePresenceError::DocumentNotFound) => {
+                CoreError::FileNonexistent
+            }
+            ApiError::Endpoint(api::UpdatePresenceError::NotPermissioned) => {
+                CoreError::InsufficientPermission
+            }
+            e => core_err_unexpected(e),
+        }
+        .into()
+    }
+}
+
 impl From<ApiError<api::UpsertError>> for LbError {
     fn from(e: ApiError<api::UpsertError>) -> Self {
         match e {
@@ -8,6 +8,10 @@ use lockbook_shared::signed_file::SignedFile;
 use uuid::Uuid;
 
 use crate::service::activity_service::DocEvent;
+use crate::service::link_service::IndexedLinks;
+use crate::service::settings_service::AccountSettings;
+use crate::service::task_service::IndexedTasks;
+use crate::service::undo_service::JournalEntry;
 
 pub type CoreDb = CoreV3;
 
@@ -21,4 +25,14 @@ pub struct CoreV3 {
     pub base_metadata: LookupTable<Uuid, SignedFile>,
     pub pub_key_lookup: LookupTable<Owner, String>,
     pub doc_events: List<DocEvent>,
+    pub op_journal: List<JournalEntry>,
+    pub conflicts: LookupTable<Uuid, i64>,
+    pub links: LookupTable<Uuid, IndexedLinks>,
+    pub tasks: LookupTable<Uuid, IndexedTasks>,
+    /// files created at well-known paths that haven't been synced, by when they were created
+    pub well_known_files: LookupTable<Uuid, i64>,
+    /// the account's settings as of the last sync, with the version the server gave them
+    pub base_account_settings: Single<(u64, AccountSettings)>,
+    /// the account's settings, if they were changed since the last sync
+    pub local_account_settings: Single<AccountSettings>,
 }
@@ -158,6 +158,7 @@ pub mod no_network {
                     google_play_client,
                     app_store_client,
                     document_service,
+                    collab_sessions: Default::default(),
                 },
                 runtime,
             };
@@ -209,6 +210,15 @@ pub mod no_network {
                 }
                 ChangeDocRequest::ROUTE => call!(ServerState::change_doc, self, account, request),
                 GetDocRequest::ROUTE => call!(ServerState::get_document, self, account, request),
+                SendCollabMessageRequest::ROUTE => {
+                    call!(ServerState::send_collab_message, self, account, request)
+                }
+                GetCollabMessagesRequest::ROUTE => {
+                    call!(ServerState::get_collab_messages, self, account, request)
+                }
+                UpdatePresenceRequest::ROUTE => {
+                    call!(ServerState::update_presence, self, account, request)
+                }
                 GetPublicKeyRequest::ROUTE => {
                     call!(ServerState::get_public_key, self, account, request)
                 }
@@ -220,6 +230,25 @@ pub mod no_network {
                 GetUsernameRequest::ROUTE => {
                     call!(ServerState::get_username, self, account, request)
                 }
+                UpsertTeamRequest::ROUTE => {
+                    call!(ServerState::upsert_team, self, account, request)
+                }
+                GetTeamsRequest::ROUTE => call!(ServerState::get_teams, self, account, request),
+                OfferTransferRequest::ROUTE => {
+                    call!(ServerState::offer_transfer, self, account, request)
+                }
+                GetTransfersRequest::ROUTE => {
+                    call!(ServerState::get_transfers, self, account, request)
+                }
+                DeleteTransferRequest::ROUTE => {
+                    call!(ServerState::delete_transfer, self, account, request)
+                }
+                UpsertAccountSettingsRequest::ROUTE => {
+                    call!(ServerState::upsert_account_settings, self, account, request)
+                }
+                GetAccountSettingsRequest::ROUTE => {
+                    call!(ServerState::get_account_settings, self, account, request)
+                }
                 AdminValidateServerRequest::ROUTE => {
                     call!(ServerState::admin_validate_server, self, account, request)
                 }
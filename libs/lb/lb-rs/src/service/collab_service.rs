@@ -0,0 +1,397 @@
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use lockbook_shared::account::Account;
+use lockbook_shared::api::{
+    GetCollabMessagesError, GetCollabMessagesRequest, SendCollabMessageError,
+    SendCollabMessageRequest, UpdatePresenceRequest,
+};
+use lockbook_shared::crdt::{CollabMessage, Op, OpId, Presence, Rga, TextEdit};
+use lockbook_shared::crypto::AESKey;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::symkey;
+use lockbook_shared::tree_like::TreeLike;
+use uuid::Uuid;
+
+use crate::service::api_service::ApiError;
+use crate::{CoreError, CoreLib, CoreState, LbResult, Requester};
+
+/// how many times joining a session is attempted when other editors start one at the same time
+const JOIN_ATTEMPTS: usize = 3;
+
+/// A live session editing a document together with its other editors. Edits are exchanged through
+/// the server as operations on a CRDT, encrypted with the document's key, so everyone's text
+/// converges as they type instead of when they sync.
+///
+/// Clones share state, so that one thread can edit the text with `replace` and `receive` while
+/// another exchanges edits with the server using `Core::sync_collab`.
+#[derive(Debug, Clone)]
+pub struct CollabSession {
+    /// the document being edited
+    pub id: Uuid,
+    inner: Arc<Mutex<CollabSessionState>>,
+}
+
+#[derive(Debug)]
+struct CollabSessionState {
+    /// the server's id for the session, which changes if the session ends and is restarted
+    session: Uuid,
+    /// the text the session started from
+    start: String,
+    text: Rga,
+    /// the text with only the edits the server has, i.e. without local edits that weren't sent
+    acked: Rga,
+    /// how many of the session's messages have been received
+    next: u64,
+    outgoing: Vec<Op>,
+    incoming: Vec<Incoming>,
+
+    /// identifies this editor to the others, who see its selection
+    client: Uuid,
+    selection: (Option<OpId>, Option<OpId>),
+    /// the other editors' usernames and presence, by client
+    others: Vec<(Uuid, String, Presence)>,
+}
+
+/// Another editor's selection in a document being edited together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteCursor {
+    pub username: String,
+    /// byte offsets of the ends of the selection in the session's text, which are equal when
+    /// nothing is selected
+    pub selection: (usize, usize),
+}
+
+#[derive(Debug)]
+enum Incoming {
+    Ops(Vec<Op>),
+    /// the session was restarted and the text is now `text`; local edits since `base` are merged
+    /// into it
+    Rejoined {
+        text: Rga,
+        base: String,
+    },
+}
+
+impl CollabSession {
+    pub fn text(&self) -> String {
+        self.inner.lock().unwrap().text.text()
+    }
+
+    /// Replaces the text in the byte range `range` with `text`. The edit is sent to the session's
+    /// other editors on the next sync.
+    pub fn replace(&self, range: (usize, usize), text: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let ops = inner.text.replace(range, text);
+        inner.outgoing.extend(ops);
+    }
+
+    /// Makes the edits that turned `base`, the document's text before the session was joined, into
+    /// `text`, e.g. edits typed while offline. They're sent like any other edits, so they're merged
+    /// with the other editors' instead of being overwritten. If the session started from other
+    /// text than `base`, they're merged the way sync merges documents, which can leave conflict
+    /// markers.
+    pub fn merge(&self, base: &str, text: &str) {
+        self.inner.lock().unwrap().merge(base, text);
+    }
+
+    /// Sets the selection, as byte offsets in the text, shown to the session's other editors.
+    pub fn set_selection(&self, selection: (usize, usize)) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.selection = (inner.text.anchor(selection.0), inner.text.anchor(selection.1));
+    }
+
+    /// The selections of the session's other editors, as byte offsets in the text. Selections
+    /// around edits that haven't been received yet are left out.
+    pub fn cursors(&self) -> Vec<RemoteCursor> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .others
+            .iter()
+            .filter_map(|(_, username, presence)| {
+                let start = inner.text.resolve(presence.selection.0)?;
+                let end = inner.text.resolve(presence.selection.1)?;
+                Some(RemoteCursor { username: username.clone(), selection: (start, end) })
+            })
+            .collect()
+    }
+
+    /// Applies the edits received from the session's other editors since the last call and returns
+    /// the changes they make to the text, in order.
+    pub fn receive(&self) -> Vec<TextEdit> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut result = Vec::new();
+        for incoming in mem::take(&mut inner.incoming) {
+            match incoming {
+                Incoming::Ops(ops) => {
+                    for op in ops {
+                        result.extend(inner.text.apply(&op));
+                    }
+                }
+                Incoming::Rejoined { text, base } => {
+                    // edits that weren't sent to the previous session are sent to this one
+                    let mine = inner.text.text();
+                    inner.text = text;
+                    inner.outgoing.clear();
+                    inner.merge(&base, &mine);
+                    let edit = TextEdit::between(&mine, &inner.text.text());
+                    if edit.range.0 != edit.range.1 || !edit.text.is_empty() {
+                        result.push(edit);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// What exchanging a document's edits with the server needs, taken from the core so that it isn't
+/// locked while waiting on the server.
+struct CollabContext<Client: Requester> {
+    client: Client,
+    account: Account,
+    id: Uuid,
+    key: AESKey,
+}
+
+impl CollabSession {
+    /// Joins the live editing session of a document, starting one with the document's text if
+    /// there isn't one.
+    pub(crate) fn start<Client: Requester, Docs: DocumentService>(
+        core: &CoreLib<Client, Docs>, id: Uuid,
+    ) -> LbResult<Self> {
+        let (context, text) = core.in_tx(|s| {
+            let context = s.collab_context(id)?;
+            let text = String::from_utf8_lossy(&s.read_document(id)?).to_string();
+            Ok((context, text))
+        })?;
+        let (session, start, text, next) = context.join(&text)?;
+
+        let state = CollabSessionState {
+            session,
+            start,
+            acked: text.clone(),
+            text,
+            next,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            client: Uuid::new_v4(),
+            selection: (None, None),
+            others: Vec::new(),
+        };
+        Ok(CollabSession { id, inner: Arc::new(Mutex::new(state)) })
+    }
+
+    /// Sends the session's local edits and selection to the server and fetches other editors',
+    /// returning whether any changed. If the session ended, e.g. because nobody edited for a while,
+    /// it is rejoined or restarted with the current text.
+    pub(crate) fn sync<Client: Requester, Docs: DocumentService>(
+        &self, core: &CoreLib<Client, Docs>,
+    ) -> LbResult<bool> {
+        let context = core.in_tx(|s| s.collab_context(self.id))?;
+
+        let (session_id, next, outgoing, selection) = {
+            let mut inner = self.inner.lock().unwrap();
+            // edits made before a rejoin is received are merged into the new session when it is
+            let rejoining = inner
+                .incoming
+                .iter()
+                .any(|incoming| matches!(incoming, Incoming::Rejoined { .. }));
+            let outgoing = if rejoining { Vec::new() } else { mem::take(&mut inner.outgoing) };
+            (inner.session, inner.next, outgoing, inner.selection)
+        };
+
+        if !outgoing.is_empty() {
+            let message = symkey::encrypt(&context.key, &CollabMessage::Ops(outgoing.clone()))?;
+            let request =
+                SendCollabMessageRequest { id: self.id, session: Some(session_id), message };
+            match context.client.request(&context.account, request) {
+                // readers can follow a session but their edits stay their own
+                Ok(_) | Err(ApiError::Endpoint(SendCollabMessageError::NotPermissioned)) => {}
+                Err(ApiError::Endpoint(SendCollabMessageError::SessionEnded)) => {
+                    return self.rejoin(&context);
+                }
+                Err(err) => {
+                    // try again next time; operations that reach the server twice apply once
+                    let mut inner = self.inner.lock().unwrap();
+                    let newer = mem::replace(&mut inner.outgoing, outgoing);
+                    inner.outgoing.extend(newer);
+                    return Err(err.into());
+                }
+            }
+        }
+
+        let request =
+            GetCollabMessagesRequest { id: self.id, session: Some(session_id), since: next };
+        let messages = match context.client.request(&context.account, request) {
+            Ok(response) => response.messages,
+            Err(ApiError::Endpoint(GetCollabMessagesError::SessionEnded)) => {
+                return self.rejoin(&context);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut ops = Vec::new();
+        for message in &messages {
+            match symkey::decrypt(&context.key, message)? {
+                CollabMessage::Ops(message_ops) => ops.extend(message_ops),
+                CollabMessage::Start { .. } => {
+                    return Err(CoreError::Unexpected("session started twice".into()).into());
+                }
+            }
+        }
+
+        let others = self.update_presence(&context, Some(Presence { selection }))?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.next += messages.len() as u64;
+        for op in &ops {
+            inner.acked.apply(op);
+        }
+        let mut updated = !ops.is_empty();
+        if updated {
+            inner.incoming.push(Incoming::Ops(ops));
+        }
+        if inner.others != others {
+            inner.others = others;
+            updated = true;
+        }
+        Ok(updated)
+    }
+
+    /// Tells the session's other editors that the document was closed, so they stop showing its
+    /// selection.
+    pub(crate) fn leave<Client: Requester, Docs: DocumentService>(
+        &self, core: &CoreLib<Client, Docs>,
+    ) -> LbResult<()> {
+        let context = core.in_tx(|s| s.collab_context(self.id))?;
+        self.update_presence(&context, None)?;
+        Ok(())
+    }
+
+    /// Sends the editor's presence, or `None` if it closed the document, and returns the other
+    /// editors'.
+    fn update_presence<Client: Requester>(
+        &self, context: &CollabContext<Client>, presence: Option<Presence>,
+    ) -> LbResult<Vec<(Uuid, String, Presence)>> {
+        let client = self.inner.lock().unwrap().client;
+        let presence = match presence {
+            Some(presence) => Some(symkey::encrypt(&context.key, &presence)?),
+            None => None,
+        };
+        let request = UpdatePresenceRequest { id: self.id, client, presence };
+        let response = context.client.request(&context.account, request)?;
+
+        let mut result = Vec::new();
+        for other in response.others {
+            let presence = symkey::decrypt(&context.key, &other.presence)?;
+            result.push((other.client, other.username, presence));
+        }
+        result.sort_by_key(|(client, _, _)| *client);
+        Ok(result)
+    }
+
+    fn rejoin<Client: Requester>(&self, context: &CollabContext<Client>) -> LbResult<bool> {
+        let (session_id, start, text, next) = context.join(&self.text())?;
+
+        let mut inner = self.inner.lock().unwrap();
+        let base = inner.acked.text();
+        inner.session = session_id;
+        inner.start = start;
+        inner.acked = text.clone();
+        inner.next = next;
+        inner.outgoing.clear();
+        inner.incoming.push(Incoming::Rejoined { text, base });
+        Ok(true)
+    }
+}
+
+impl CollabSessionState {
+    fn merge(&mut self, base: &str, text: &str) {
+        if base == text {
+            return;
+        }
+
+        let ops = if base == self.start {
+            // the edits apply to the session's text like any other editor's
+            let edit = TextEdit::between(base, text);
+            let ops = Rga::new(Uuid::new_v4(), base).replace(edit.range, &edit.text);
+            for op in &ops {
+                self.text.apply(op);
+            }
+            ops
+        } else {
+            let theirs = self.text.text();
+            let merged = match diffy::MergeOptions::new()
+                .set_conflict_style(diffy::ConflictStyle::Merge)
+                .merge(base, text, &theirs)
+            {
+                Ok(merged) | Err(merged) => merged,
+            };
+            let edit = TextEdit::between(&theirs, &merged);
+            self.text.replace(edit.range, &edit.text)
+        };
+        self.outgoing.extend(ops);
+    }
+}
+
+impl<Client: Requester> CollabContext<Client> {
+    /// Joins the document's session, or starts one with `text`. Returns the session's id, the text
+    /// it started from, its text and the number of its messages that were received.
+    fn join(&self, text: &str) -> LbResult<(Uuid, String, Rga, u64)> {
+        let (id, key) = (self.id, &self.key);
+
+        for _ in 0..JOIN_ATTEMPTS {
+            let request = GetCollabMessagesRequest { id, session: None, since: 0 };
+            let response = self.client.request(&self.account, request)?;
+            if let Some(session) = response.session {
+                let mut messages = response.messages.iter();
+                let (start, mut result) = match messages.next().map(|m| symkey::decrypt(key, m)) {
+                    Some(Ok(CollabMessage::Start { text })) => {
+                        let result = Rga::new(Uuid::new_v4(), &text);
+                        (text, result)
+                    }
+                    Some(Err(err)) => return Err(err.into()),
+                    _ => return Err(CoreError::Unexpected("session not started".into()).into()),
+                };
+                for message in messages {
+                    if let CollabMessage::Ops(ops) = symkey::decrypt(key, message)? {
+                        for op in ops {
+                            result.apply(&op);
+                        }
+                    }
+                }
+                return Ok((session, start, result, response.messages.len() as u64));
+            }
+
+            let message = symkey::encrypt(key, &CollabMessage::Start { text: text.into() })?;
+            let request = SendCollabMessageRequest { id, session: None, message };
+            match self.client.request(&self.account, request) {
+                Ok(response) => {
+                    return Ok((response.session, text.into(), Rga::new(Uuid::new_v4(), text), 1))
+                }
+                Err(ApiError::Endpoint(SendCollabMessageError::SessionAlreadyStarted)) => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Err(CoreError::TryAgain.into())
+    }
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    fn collab_context(&mut self, id: Uuid) -> LbResult<CollabContext<Client>> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+
+        if !tree.find(&id)?.is_document() {
+            return Err(CoreError::FileNotDocument.into());
+        }
+        let key = tree.decrypt_key(&id, account)?;
+
+        Ok(CollabContext { client: self.client.clone(), account: account.clone(), id, key })
+    }
+}
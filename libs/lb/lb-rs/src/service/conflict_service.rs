@@ -0,0 +1,191 @@
+use std::ops::Range;
+
+use lockbook_shared::clock::get_time;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::tree_like::TreeLike;
+use lockbook_shared::SharedErrorKind;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+const MINE_MARKER: &str = "<<<<<<<";
+const ORIGINAL_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A document that sync merged with conflicting changes and that still contains conflict markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub id: Uuid,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+/// One region of a document that was changed both on this device and elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictHunk {
+    /// byte range of the hunk in the document, including its marker lines
+    pub range: Range<usize>,
+    /// the version of the region written on this device
+    pub mine: String,
+    /// the version of the region pulled from the server
+    pub theirs: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictChoice {
+    KeepMine,
+    KeepTheirs,
+    KeepBoth,
+}
+
+impl ConflictHunk {
+    /// The text that replaces the hunk (markers included) when it is resolved with `choice`.
+    pub fn resolve(&self, choice: ConflictChoice) -> String {
+        match choice {
+            ConflictChoice::KeepMine => self.mine.clone(),
+            ConflictChoice::KeepTheirs => self.theirs.clone(),
+            ConflictChoice::KeepBoth => {
+                let mut both = self.mine.clone();
+                if !both.is_empty() && !both.ends_with('\n') {
+                    both.push('\n');
+                }
+                both.push_str(&self.theirs);
+                both
+            }
+        }
+    }
+}
+
+/// Finds the conflict hunks in a document written by a conflicting merge, in document order.
+/// Both the two-way (`<<<<<<<`, `=======`, `>>>>>>>`) and three-way (with an additional `|||||||`
+/// section holding the original text) marker styles are understood; unterminated hunks are ignored.
+pub fn find_conflict_hunks(text: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Outside,
+        Mine,
+        Original,
+        Theirs,
+    }
+
+    let mut hunks = Vec::new();
+    let mut section = Section::Outside;
+    let mut start = 0;
+    let mut mine = String::new();
+    let mut theirs = String::new();
+
+    let mut pos = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = pos;
+        pos += line.len();
+
+        // a new hunk can start anywhere, abandoning an unterminated one
+        if is_marker(line, MINE_MARKER) {
+            section = Section::Mine;
+            start = line_start;
+            mine.clear();
+            theirs.clear();
+            continue;
+        }
+
+        match section {
+            Section::Outside => {}
+            Section::Mine if is_marker(line, ORIGINAL_MARKER) => section = Section::Original,
+            Section::Mine | Section::Original if is_marker(line, SEPARATOR_MARKER) => {
+                section = Section::Theirs
+            }
+            Section::Mine => mine.push_str(line),
+            Section::Original => {}
+            Section::Theirs if is_marker(line, THEIRS_MARKER) => {
+                hunks.push(ConflictHunk {
+                    range: start..pos,
+                    mine: std::mem::take(&mut mine),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                section = Section::Outside;
+            }
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+
+    hunks
+}
+
+/// Resolves every conflict hunk in `text` with the same choice.
+pub fn resolve_conflict_hunks(text: &str, choice: ConflictChoice) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for hunk in find_conflict_hunks(text) {
+        result.push_str(&text[pos..hunk.range.start]);
+        result.push_str(&hunk.resolve(choice));
+        pos = hunk.range.end;
+    }
+    result.push_str(&text[pos..]);
+    result
+}
+
+fn is_marker(line: &str, marker: &str) -> bool {
+    match line.trim_end_matches(['\n', '\r']).strip_prefix(marker) {
+        Some(rest) => rest.is_empty() || rest.starts_with(' '),
+        None => false,
+    }
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    pub(crate) fn record_conflicts(&mut self, ids: &[Uuid]) -> LbResult<()> {
+        let now = get_time().0;
+        for id in ids {
+            self.db.conflicts.insert(*id, now)?;
+        }
+        Ok(())
+    }
+
+    /// Documents merged with conflicts that have not been resolved yet, oldest first. Documents that
+    /// were deleted or no longer contain conflict markers are forgotten.
+    pub(crate) fn get_conflicts(&mut self) -> LbResult<Vec<Conflict>> {
+        let mut recorded = self
+            .db
+            .conflicts
+            .get()
+            .iter()
+            .map(|(id, timestamp)| (*timestamp, *id))
+            .collect::<Vec<_>>();
+        recorded.sort();
+
+        let mut result = Vec::new();
+        for (_, id) in recorded {
+            // read without going through read_document, which would count as the user opening it
+            let mut tree = (&self.db.base_metadata)
+                .to_staged(&self.db.local_metadata)
+                .to_lazy();
+            let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+            let document = match tree.read_document(&self.docs, &id, account) {
+                Ok(document) => document,
+                Err(err) if err.kind == SharedErrorKind::FileNonexistent => {
+                    self.db.conflicts.remove(&id)?;
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let hunks = find_conflict_hunks(&String::from_utf8_lossy(&document));
+            if hunks.is_empty() {
+                self.db.conflicts.remove(&id)?;
+                continue;
+            }
+            result.push(Conflict { id, hunks });
+        }
+        Ok(result)
+    }
+
+    /// Resolves every conflict hunk in a document with the same choice and forgets the conflict.
+    pub(crate) fn resolve_conflict(&mut self, id: Uuid, choice: ConflictChoice) -> LbResult<()> {
+        let document = self.read_document(id)?;
+        let text = String::from_utf8_lossy(&document);
+        let resolved = resolve_conflict_hunks(&text, choice);
+        if resolved != text {
+            self.write_document(id, resolved.as_bytes())?;
+        }
+        self.db.conflicts.remove(&id)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file::File;
+use time::Date;
+use uuid::Uuid;
+
+use crate::service::template_service::format_date;
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// Where daily notes are kept unless another pattern is given; see `daily_note_path`.
+pub const DEFAULT_DAILY_NOTE_PATTERN: &str = "/journal/{{year}}/{{month}}/{{date}}.md";
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    /// Returns the daily note for `date`, creating it and any folders it's in if it doesn't exist,
+    /// with the content of the document `template` if one is given. If another device creates
+    /// the same note before they're synced, sync combines the two instead of renaming one.
+    pub(crate) fn get_or_create_daily_note(
+        &mut self, date: Date, pattern: &str, template: Option<Uuid>,
+    ) -> LbResult<File> {
+        let path = daily_note_path(pattern, date);
+        match self.get_by_path(&path) {
+            Ok(file) if file.is_document() => return Ok(file),
+            Ok(_) => return Err(CoreError::FileNotDocument.into()),
+            Err(err) if err.kind == CoreError::FileNonexistent => {}
+            Err(err) => return Err(err),
+        }
+        if path.ends_with('/') {
+            return Err(CoreError::FileNotDocument.into());
+        }
+
+        let name = path.rsplit('/').next().unwrap_or_default().to_string();
+        let content = match template {
+            Some(template) => Some(self.template_content(template, &name, date)?),
+            None => None,
+        };
+
+        let file = self.create_at_well_known_path(&path)?;
+        if let Some(content) = content {
+            self.write_document(file.id, &content)?;
+        }
+        Ok(file)
+    }
+}
+
+/// The path of the daily note for `date`, in which `{{year}}`, `{{month}}` and `{{day}}` in
+/// `pattern` are replaced with the parts of the date and `{{date}}` is replaced with the date as
+/// YYYY-MM-DD.
+pub fn daily_note_path(pattern: &str, date: Date) -> String {
+    pattern
+        .replace("{{date}}", &format_date(date))
+        .replace("{{year}}", &format!("{:04}", date.year()))
+        .replace("{{month}}", &format!("{:02}", date.month() as u8))
+        .replace("{{day}}", &format!("{:02}", date.day()))
+}
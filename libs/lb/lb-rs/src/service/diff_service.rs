@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use diffy::Line;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::filename::DocumentType;
+use lockbook_shared::tree_like::TreeLike;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::model::drawing;
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// Word-level diffs are skipped for pairs of lines with more tokens than this, multiplied.
+const MAX_WORD_DIFF_COMPLEXITY: usize = 1_000_000;
+
+/// The version a document is compared to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiffAgainst {
+    /// the last synced version of the same document; empty if it was never synced
+    Base,
+    /// the current version of another document
+    Document(Uuid),
+}
+
+/// The changes from the version a document is compared to (old) to its current version (new).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DocumentDiff {
+    Text { hunks: Vec<DiffHunk> },
+    Drawing(DrawingDiff),
+    Other { old_size: usize, new_size: usize, identical: bool },
+}
+
+/// A contiguous region of changed lines, with up to three lines of unchanged context around it.
+/// Line numbers start at 1, as in unified diffs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// the line without its line terminator
+    pub text: String,
+    /// for a deleted line replaced by an inserted line (or vice versa), the line split into words
+    /// that are either shared by both lines or changed; empty otherwise
+    pub words: Vec<DiffWord>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiffLineKind {
+    Context,
+    Deleted,
+    Inserted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffWord {
+    pub text: String,
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DrawingDiff {
+    pub strokes_added: usize,
+    pub strokes_removed: usize,
+    pub strokes_unchanged: usize,
+}
+
+impl DocumentDiff {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            DocumentDiff::Text { hunks } => hunks.is_empty(),
+            DocumentDiff::Drawing(d) => d.strokes_added == 0 && d.strokes_removed == 0,
+            DocumentDiff::Other { identical, .. } => *identical,
+        }
+    }
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    pub(crate) fn diff_document(
+        &mut self, id: Uuid, against: DiffAgainst,
+    ) -> LbResult<DocumentDiff> {
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+
+        let name = tree.name(&id, account)?;
+        let new = tree.read_document(&self.docs, &id, account)?;
+        let old = match against {
+            DiffAgainst::Base => {
+                let mut base = (&self.db.base_metadata).to_lazy();
+                match base.maybe_find(&id) {
+                    Some(file) if file.is_document() => {
+                        base.read_document(&self.docs, &id, account)?
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            DiffAgainst::Document(other) => tree.read_document(&self.docs, &other, account)?,
+        };
+
+        Ok(match DocumentType::from_file_name_using_extension(&name) {
+            DocumentType::Text => DocumentDiff::Text {
+                hunks: diff_text(&String::from_utf8_lossy(&old), &String::from_utf8_lossy(&new)),
+            },
+            DocumentType::Drawing => DocumentDiff::Drawing(diff_drawing(&old, &new)?),
+            DocumentType::Other => DocumentDiff::Other {
+                old_size: old.len(),
+                new_size: new.len(),
+                identical: old == new,
+            },
+        })
+    }
+}
+
+fn diff_text(old: &str, new: &str) -> Vec<DiffHunk> {
+    let patch = diffy::create_patch(old, new);
+    let mut hunks = Vec::new();
+    for hunk in patch.hunks() {
+        let mut lines = Vec::new();
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        for line in hunk.lines() {
+            match line {
+                Line::Context(text) => {
+                    flush_changes(&mut lines, &mut deleted, &mut inserted);
+                    lines.push(DiffLine {
+                        kind: DiffLineKind::Context,
+                        text: trim_line(text),
+                        words: Vec::new(),
+                    });
+                }
+                Line::Delete(text) => {
+                    // a deletion after insertions starts a new block of changes
+                    if !inserted.is_empty() {
+                        flush_changes(&mut lines, &mut deleted, &mut inserted);
+                    }
+                    deleted.push(trim_line(text));
+                }
+                Line::Insert(text) => inserted.push(trim_line(text)),
+            }
+        }
+        flush_changes(&mut lines, &mut deleted, &mut inserted);
+
+        hunks.push(DiffHunk {
+            old_start: hunk.old_range().start(),
+            old_len: hunk.old_range().len(),
+            new_start: hunk.new_range().start(),
+            new_len: hunk.new_range().len(),
+            lines,
+        });
+    }
+    hunks
+}
+
+/// Appends a block of deleted lines followed by the inserted lines that replace them, pairing
+/// the n-th deleted line with the n-th inserted line for a word-level diff.
+fn flush_changes(lines: &mut Vec<DiffLine>, deleted: &mut Vec<String>, inserted: &mut Vec<String>) {
+    let mut deleted_words = vec![Vec::new(); deleted.len()];
+    let mut inserted_words = vec![Vec::new(); inserted.len()];
+    for i in 0..deleted.len().min(inserted.len()) {
+        (deleted_words[i], inserted_words[i]) = diff_words(&deleted[i], &inserted[i]);
+    }
+
+    for (text, words) in deleted.drain(..).zip(deleted_words) {
+        lines.push(DiffLine { kind: DiffLineKind::Deleted, text, words });
+    }
+    for (text, words) in inserted.drain(..).zip(inserted_words) {
+        lines.push(DiffLine { kind: DiffLineKind::Inserted, text, words });
+    }
+}
+
+/// Splits both lines into words, whitespace, and punctuation and marks the tokens not part of their
+/// longest common subsequence as changed.
+fn diff_words(old: &str, new: &str) -> (Vec<DiffWord>, Vec<DiffWord>) {
+    let old = tokenize(old);
+    let new = tokenize(new);
+    if old.len() * new.len() > MAX_WORD_DIFF_COMPLEXITY {
+        return (Vec::new(), Vec::new());
+    }
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_words = Vec::new();
+    let mut new_words = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push_word(&mut old_words, old[i], false);
+            push_word(&mut new_words, new[j], false);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_word(&mut old_words, old[i], true);
+            i += 1;
+        } else {
+            push_word(&mut new_words, new[j], true);
+            j += 1;
+        }
+    }
+    (old_words, new_words)
+}
+
+/// Appends a token, merging it into the previous word if both are changed or both are unchanged.
+fn push_word(words: &mut Vec<DiffWord>, token: &str, changed: bool) {
+    match words.last_mut() {
+        Some(last) if last.changed == changed => last.text.push_str(token),
+        _ => words.push(DiffWord { text: token.to_string(), changed }),
+    }
+}
+
+fn tokenize(line: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<Class> = None;
+    for (i, c) in line.char_indices() {
+        let current = class(c);
+        // punctuation is compared one character at a time
+        if let Some(prev) = &prev {
+            if *prev != current || current == Class::Other {
+                tokens.push(&line[start..i]);
+                start = i;
+            }
+        }
+        prev = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+fn trim_line(line: &str) -> String {
+    line.trim_end_matches('\n')
+        .trim_end_matches('\r')
+        .to_string()
+}
+
+/// Compares strokes by value; drawings have no stable stroke ids.
+fn diff_drawing(old: &[u8], new: &[u8]) -> LbResult<DrawingDiff> {
+    let old = drawing::parse_drawing(old)?;
+    let new = drawing::parse_drawing(new)?;
+
+    let mut old_strokes: HashMap<String, usize> = HashMap::new();
+    for stroke in &old.strokes {
+        *old_strokes
+            .entry(serde_json::to_string(stroke)?)
+            .or_default() += 1;
+    }
+
+    let mut diff = DrawingDiff { strokes_added: 0, strokes_removed: 0, strokes_unchanged: 0 };
+    for stroke in &new.strokes {
+        match old_strokes.get_mut(&serde_json::to_string(stroke)?) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                diff.strokes_unchanged += 1;
+            }
+            _ => diff.strokes_added += 1,
+        }
+    }
+    diff.strokes_removed = old_strokes.values().sum();
+    Ok(diff)
+}
@@ -35,9 +35,14 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
             FileType::Link { target } => target,
         };
         let encrypted_document = tree.update_document(&id, content, account)?;
-        let hmac = tree.find(&id)?.document_hmac();
-        self.docs.insert(&id, hmac, &encrypted_document)?;
+        let hmac = tree.find(&id)?.document_hmac().copied();
+        self.docs.insert(&id, hmac.as_ref(), &encrypted_document)?;
+        let is_markdown = tree.name(&id, account)?.ends_with(".md");
 
+        if is_markdown {
+            self.index_document_links(id, hmac, content)?;
+            self.index_document_tasks(id, hmac, content)?;
+        }
         self.add_doc_event(activity_service::DocEvent::Write(id, get_time().0))?;
         self.cleanup()?;
         Ok(())
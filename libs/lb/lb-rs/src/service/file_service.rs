@@ -1,11 +1,14 @@
+use crate::service::undo_service::JournaledOp;
 use crate::{CoreError, CoreState, LbResult, Requester};
 use lockbook_shared::access_info::UserAccessMode;
 use lockbook_shared::document_repo::DocumentService;
 use lockbook_shared::file::File;
+use lockbook_shared::file_like::FileLike;
 use lockbook_shared::file_metadata::{FileType, Owner};
 use lockbook_shared::filename::MAX_FILENAME_LENGTH;
 use lockbook_shared::symkey;
 use lockbook_shared::tree_like::TreeLike;
+use std::collections::HashMap;
 use std::iter;
 use uuid::Uuid;
 
@@ -35,31 +38,60 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
         if new_name.len() > MAX_FILENAME_LENGTH {
             return Err(CoreError::FileNameTooLong.into());
         }
+        let links = self.links_affected_by(id, Some(new_name));
         let mut tree = (&self.db.base_metadata)
             .to_staged(&mut self.db.local_metadata)
             .to_lazy();
         let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
 
         let id = &tree.linked_by(id)?.unwrap_or(*id);
+        let old_name = tree.find(id)?.secret_name().clone();
 
         tree.rename(id, new_name, account)?;
 
+        let new_name = tree.find(id)?.secret_name().clone();
+        self.journal_operation(JournaledOp::Rename { id: *id, old_name, new_name })?;
+        self.fix_links_affected_by(links);
+
         Ok(())
     }
 
     pub(crate) fn move_file(&mut self, id: &Uuid, new_parent: &Uuid) -> LbResult<()> {
+        let links = self.links_affected_by(id, None);
         let mut tree = (&self.db.base_metadata)
             .to_staged(&mut self.db.local_metadata)
             .to_lazy();
         let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
 
         let id = &tree.linked_by(id)?.unwrap_or(*id);
+        let old_parent = *tree.find(id)?.parent();
 
         tree.move_file(id, new_parent, account)?;
 
+        self.journal_operation(JournaledOp::Move { id: *id, old_parent, new_parent: *new_parent })?;
+        self.fix_links_affected_by(links);
+
         Ok(())
     }
 
+    /// Fixing links is best-effort: a rename or move succeeds even if the links it broke can't be
+    /// fixed, e.g. because a document can't be read.
+    pub(crate) fn links_affected_by(
+        &mut self, id: &Uuid, new_name: Option<&str>,
+    ) -> HashMap<Uuid, Vec<(String, Uuid)>> {
+        self.resolve_links_affected_by(id, new_name)
+            .unwrap_or_else(|err| {
+                warn!("failed to find the links affected by changing {id}: {:?}", err);
+                HashMap::new()
+            })
+    }
+
+    pub(crate) fn fix_links_affected_by(&mut self, links: HashMap<Uuid, Vec<(String, Uuid)>>) {
+        if let Err(err) = self.fix_broken_links(links) {
+            warn!("failed to fix broken links: {:?}", err);
+        }
+    }
+
     pub(crate) fn delete(&mut self, id: &Uuid) -> LbResult<()> {
         let mut tree = (&self.db.base_metadata)
             .to_staged(&mut self.db.local_metadata)
@@ -70,6 +102,8 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
 
         tree.delete(id, account)?;
 
+        self.journal_operation(JournaledOp::Delete { id: *id })?;
+
         Ok(())
     }
 
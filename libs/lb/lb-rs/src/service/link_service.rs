@@ -0,0 +1,595 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use lockbook_shared::access_info::UserAccessMode;
+use lockbook_shared::account::Account;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::{DocumentHmac, FileType, Owner};
+use lockbook_shared::lazy::LazyStaged1;
+use lockbook_shared::signed_file::SignedFile;
+use lockbook_shared::tree_like::TreeLike;
+use lockbook_shared::SharedErrorKind;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// The targets of the links in a markdown document, as of the version of the document with `hmac`.
+/// Targets are stored as written (without any `#heading`) and resolved when the index is queried,
+/// so the index stays correct when the files they point to are created, renamed or moved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedLinks {
+    pub hmac: Option<DocumentHmac>,
+    pub targets: Vec<String>,
+}
+
+/// Links between documents, as found in markdown documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkGraph {
+    /// `(from, to)` for each document `from` that links to file `to`
+    pub links: Vec<(Uuid, Uuid)>,
+    /// `(from, target)` for each link target in document `from` that doesn't resolve to a file
+    pub broken_links: Vec<(Uuid, String)>,
+}
+
+/// A link in a markdown document: a wiki link (`[[target]]`), a link (`[text](target)`) or an
+/// image (`![alt](target)`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MarkdownLink {
+    /// byte range of the target as written, without any `#heading`
+    range: Range<usize>,
+    /// the target, with markdown link targets percent-decoded
+    target: String,
+    /// whether spaces in the target must be written as `%20`
+    escape_spaces: bool,
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    /// Resolves the target of a link in the document `from` to the id of a document. Targets are
+    /// tried as an id (`lb://<id>`), then as a path relative to the folder containing `from` (or to
+    /// the root, when the path starts with `/` or there is no `from`), then as the name of a
+    /// document anywhere in the tree, preferring the document with the shortest path. The `.md`
+    /// extension may be omitted.
+    pub(crate) fn resolve_link(&mut self, from: Option<Uuid>, target: &str) -> LbResult<Uuid> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let root = *self.db.root.get().ok_or(CoreError::RootNonexistent)?;
+
+        resolve_link_target(&mut tree, account, &root, from, target)?
+            .ok_or_else(|| CoreError::FileNonexistent.into())
+    }
+
+    /// Documents that link to `id`, ordered by path.
+    pub(crate) fn get_backlinks(&mut self, id: Uuid) -> LbResult<Vec<Uuid>> {
+        let id = {
+            let mut tree = (&self.db.base_metadata)
+                .to_staged(&self.db.local_metadata)
+                .to_lazy();
+            let id = match tree.find(&id)?.file_type() {
+                FileType::Link { target } => target,
+                _ => id,
+            };
+            if tree.calculate_deleted(&id)? {
+                return Err(CoreError::FileNonexistent.into());
+            }
+            id
+        };
+
+        let graph = self.link_graph()?;
+
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let mut backlinks = Vec::new();
+        for (from, to) in graph.links {
+            if to == id {
+                backlinks.push((tree.id_to_path(&from, account)?, from));
+            }
+        }
+        backlinks.sort();
+
+        Ok(backlinks.into_iter().map(|(_, from)| from).collect())
+    }
+
+    pub(crate) fn link_graph(&mut self) -> LbResult<LinkGraph> {
+        self.update_link_index()?;
+
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let root = *self.db.root.get().ok_or(CoreError::RootNonexistent)?;
+
+        let mut graph = LinkGraph::default();
+        for (&from, indexed) in self.db.links.get() {
+            let mut resolved = HashSet::new();
+            for target in &indexed.targets {
+                match resolve_link_target(&mut tree, account, &root, Some(from), target)? {
+                    Some(to) => {
+                        if resolved.insert(to) {
+                            graph.links.push((from, to));
+                        }
+                    }
+                    None => graph.broken_links.push((from, target.clone())),
+                }
+            }
+        }
+        graph.links.sort();
+        graph.broken_links.sort();
+        graph.broken_links.dedup();
+
+        Ok(graph)
+    }
+
+    /// Indexes the links in markdown documents that changed since they were last indexed and
+    /// forgets documents that were deleted. Only changed documents are read.
+    pub(crate) fn update_link_index(&mut self) -> LbResult<()> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+
+        let mut markdown_ids = HashSet::new();
+        for id in tree.owned_ids() {
+            if !tree.find(&id)?.is_document()
+                || tree.calculate_deleted(&id)?
+                || tree.in_pending_share(&id)?
+                || !is_markdown(&tree.name(&id, account)?)
+            {
+                continue;
+            }
+            markdown_ids.insert(id);
+
+            let hmac = tree.find(&id)?.document_hmac().copied();
+            if matches!(self.db.links.get().get(&id), Some(indexed) if indexed.hmac == hmac) {
+                continue;
+            }
+
+            let document = tree.read_document(&self.docs, &id, account)?;
+            let targets = find_links(&String::from_utf8_lossy(&document))
+                .into_iter()
+                .map(|link| link.target)
+                .collect();
+            self.db.links.insert(id, IndexedLinks { hmac, targets })?;
+        }
+
+        let forgotten = self
+            .db
+            .links
+            .get()
+            .keys()
+            .filter(|id| !markdown_ids.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in forgotten {
+            self.db.links.remove(&id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Indexes the links in a markdown document that was just written.
+    pub(crate) fn index_document_links(
+        &mut self, id: Uuid, hmac: Option<DocumentHmac>, content: &[u8],
+    ) -> LbResult<()> {
+        let targets = find_links(&String::from_utf8_lossy(content))
+            .into_iter()
+            .map(|link| link.target)
+            .collect();
+        self.db.links.insert(id, IndexedLinks { hmac, targets })?;
+        Ok(())
+    }
+
+    /// The file each path link that renaming or moving `id` could break resolves to: links to `id`
+    /// and the files in it, links by name that a rename to `new_name` could make resolve to `id`
+    /// instead, and relative links from the documents in it. Links by id are left out
+    /// because they can't be broken by renaming or moving files. Used with `fix_broken_links` to
+    /// find the links a rename or move broke. Only links whose last path component names a document
+    /// in `id` are resolved, and the index is used as it is, so documents that changed since they
+    /// were last indexed may be missed.
+    pub(crate) fn resolve_links_affected_by(
+        &mut self, id: &Uuid, new_name: Option<&str>,
+    ) -> LbResult<HashMap<Uuid, Vec<(String, Uuid)>>> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let root = *self.db.root.get().ok_or(CoreError::RootNonexistent)?;
+
+        let mut subtree = tree.descendants_using_links(id)?;
+        subtree.insert(*id);
+        let mut names = HashSet::new();
+        for id in &subtree {
+            if tree.find(id)?.is_document() {
+                names.insert(tree.name_using_links(id, account)?);
+            }
+        }
+        names.extend(new_name.map(str::to_string));
+
+        let mut result = HashMap::new();
+        for (&from, indexed) in self.db.links.get() {
+            let from_subtree = subtree.contains(&from);
+            let mut resolved = Vec::new();
+            for target in &indexed.targets {
+                if target.starts_with("lb://") {
+                    continue;
+                }
+                let name = target.trim().rsplit('/').next().unwrap_or_default();
+                let links_into_subtree =
+                    names.contains(name) || names.contains(&format!("{}.md", name));
+                let relative = !target.trim().starts_with('/') && target.contains('/');
+                if !(links_into_subtree || from_subtree && relative) {
+                    continue;
+                }
+                if let Some(to) =
+                    resolve_link_target(&mut tree, account, &root, Some(from), target)?
+                {
+                    resolved.push((target.clone(), to));
+                }
+            }
+            if !resolved.is_empty() {
+                result.insert(from, resolved);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Rewrites links that resolved to a file before a rename or move (as returned by
+    /// `resolve_links_affected_by`) but no longer do, so that they point to the same file again. Links
+    /// keep their style where possible: names stay names and relative paths stay relative.
+    pub(crate) fn fix_broken_links(
+        &mut self, before: HashMap<Uuid, Vec<(String, Uuid)>>,
+    ) -> LbResult<()> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let root = *self.db.root.get().ok_or(CoreError::RootNonexistent)?;
+
+        let mut fixes: HashMap<Uuid, HashMap<String, String>> = HashMap::new();
+        for (from, links) in before {
+            if tree.maybe_find(&from).is_none()
+                || tree.calculate_deleted(&from)?
+                || tree.access_mode(Owner(account.public_key()), &from)?
+                    < Some(UserAccessMode::Write)
+            {
+                continue;
+            }
+            for (target, to) in links {
+                if tree.maybe_find(&to).is_none() || tree.calculate_deleted(&to)? {
+                    continue;
+                }
+                let resolved = resolve_link_target(&mut tree, account, &root, Some(from), &target)?;
+                if resolved == Some(to) {
+                    continue;
+                }
+                let new_target = link_target_to(&mut tree, account, &root, from, to, &target)?;
+                fixes.entry(from).or_default().insert(target, new_target);
+            }
+        }
+
+        let mut fixed_documents = Vec::new();
+        for (id, fixes) in fixes {
+            let document = tree.read_document(&self.docs, &id, account)?;
+            let mut text = String::from_utf8_lossy(&document).to_string();
+            for link in find_links(&text).into_iter().rev() {
+                if let Some(new_target) = fixes.get(&link.target) {
+                    let new_target = if link.escape_spaces {
+                        new_target.replace(' ', "%20")
+                    } else {
+                        new_target.clone()
+                    };
+                    text.replace_range(link.range, &new_target);
+                }
+            }
+            info!("fixing {} broken links in {id}", fixes.len());
+            fixed_documents.push((id, text));
+        }
+
+        for (id, text) in fixed_documents {
+            self.write_document(id, text.as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn is_markdown(name: &str) -> bool {
+    name.ends_with(".md")
+}
+
+/// Resolves a link target as described in `CoreState::resolve_link`, returning `None` if the target
+/// doesn't resolve to a document.
+fn resolve_link_target<Base, Local>(
+    tree: &mut LazyStaged1<Base, Local>, account: &Account, root: &Uuid, from: Option<Uuid>,
+    target: &str,
+) -> LbResult<Option<Uuid>>
+where
+    Base: TreeLike<F = SignedFile>,
+    Local: TreeLike<F = Base::F>,
+{
+    let target = target.trim();
+    if let Some(id) = target.strip_prefix("lb://") {
+        let id = match Uuid::parse_str(id) {
+            Ok(id) => id,
+            Err(_) => return Ok(None),
+        };
+        if tree.maybe_find(&id).is_none() || tree.calculate_deleted(&id)? {
+            return Ok(None);
+        }
+        return Ok(Some(id));
+    }
+
+    let (mut folder, mut path) = match (target.strip_prefix('/'), from) {
+        (Some(path), _) => (*root, path),
+        (None, Some(from)) => (*tree.find(&from)?.parent(), target),
+        (None, None) => (*root, target),
+    };
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix("../") {
+            folder = *tree.find(&folder)?.parent();
+            path = rest;
+        } else {
+            break;
+        }
+    }
+    if path.is_empty() {
+        return Ok(None);
+    }
+
+    for path in [path.to_string(), format!("{}.md", path)] {
+        match tree.path_to_id(&path, &folder, account) {
+            Ok(id) => {
+                if tree.find(&id)?.is_document() {
+                    return Ok(Some(id));
+                }
+            }
+            Err(err) if err.kind == SharedErrorKind::FileNonexistent => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    if target.contains('/') {
+        return Ok(None);
+    }
+
+    let md_target = format!("{}.md", target);
+    let mut candidates = Vec::new();
+    for id in tree.owned_ids() {
+        if tree.calculate_deleted(&id)? || tree.in_pending_share(&id)? {
+            continue;
+        }
+        if !tree.find(&id)?.is_document() {
+            continue;
+        }
+        let name = tree.name_using_links(&id, account)?;
+        if name == target || name == md_target {
+            candidates.push((tree.id_to_path(&id, account)?, id));
+        }
+    }
+    candidates.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    Ok(candidates.first().map(|&(_, id)| id))
+}
+
+/// A target for a link in `from` to the document `to`, written in the style of `old_target` if it
+/// resolves to `to`, otherwise linking by id.
+fn link_target_to<Base, Local>(
+    tree: &mut LazyStaged1<Base, Local>, account: &Account, root: &Uuid, from: Uuid, to: Uuid,
+    old_target: &str,
+) -> LbResult<String>
+where
+    Base: TreeLike<F = SignedFile>,
+    Local: TreeLike<F = Base::F>,
+{
+    let keep_extension = |target: String| {
+        if old_target.ends_with(".md") {
+            target
+        } else {
+            target
+                .strip_suffix(".md")
+                .map(str::to_string)
+                .unwrap_or(target)
+        }
+    };
+
+    let to_path = tree.id_to_path(&to, account)?;
+    let mut candidates = Vec::new();
+    if old_target.starts_with('/') {
+        candidates.push(keep_extension(to_path));
+    } else {
+        if !old_target.contains('/') {
+            candidates.push(keep_extension(tree.name_using_links(&to, account)?));
+        }
+        let from_folder = *tree.find(&from)?.parent();
+        let from_folder_path = tree.id_to_path(&from_folder, account)?;
+        candidates.push(keep_extension(relative_path(&from_folder_path, &to_path)));
+    }
+
+    for candidate in candidates {
+        if resolve_link_target(tree, account, root, Some(from), &candidate)? == Some(to) {
+            return Ok(candidate);
+        }
+    }
+    Ok(format!("lb://{}", to))
+}
+
+/// The path of the file at `to_path` relative to the folder at `folder_path`
+fn relative_path(folder_path: &str, to_path: &str) -> String {
+    let folder = folder_path
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    let to = to_path
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    let common = folder
+        .iter()
+        .zip(&to[..to.len().saturating_sub(1)])
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut result = "../".repeat(folder.len() - common);
+    result.push_str(&to[common..].join("/"));
+    result
+}
+
+/// Finds the links to other files in a markdown document, in document order. Links in code and
+/// links to websites are ignored.
+fn find_links(text: &str) -> Vec<MarkdownLink> {
+    let mut result = Vec::new();
+    let mut in_code_block = false;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            if rest.starts_with('`') {
+                // skip inline code, which ends at the next run of as many backticks
+                let ticks = rest.len() - rest.trim_start_matches('`').len();
+                let fence = &rest[..ticks];
+                i += ticks;
+                if let Some(end) = line[i..].find(fence) {
+                    i += end + ticks;
+                }
+            } else if rest.starts_with("[[") {
+                match wiki_link(rest) {
+                    Some((len, target_range)) => {
+                        let target = rest[target_range.clone()].to_string();
+                        let range = offset + i + target_range.start..offset + i + target_range.end;
+                        result.push(MarkdownLink { range, target, escape_spaces: false });
+                        i += len;
+                    }
+                    None => i += 1,
+                }
+            } else if let Some(destination) = rest.strip_prefix("](") {
+                match link_destination(destination) {
+                    Some((len, target_range, angle_brackets)) => {
+                        let target = percent_decode(&destination[target_range.clone()]);
+                        let start = offset + i + 2;
+                        let range = start + target_range.start..start + target_range.end;
+                        if is_file_link(&target) {
+                            result.push(MarkdownLink {
+                                range,
+                                target,
+                                escape_spaces: !angle_brackets,
+                            });
+                        }
+                        i += 2 + len;
+                    }
+                    None => i += 2,
+                }
+            } else {
+                i += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+            }
+        }
+    }
+    result
+}
+
+/// Parses a wiki link at the start of `text`, returning its length and the range of its target
+/// (without any `#heading`). Links to headings in the same document have no target.
+/// [[target]]
+/// [[target#heading|label]]
+fn wiki_link(text: &str) -> Option<(usize, Range<usize>)> {
+    let content_len = text[2..].find("]]")?;
+    let content = &text[2..2 + content_len];
+    if content.contains(['[', ']', '\n']) {
+        return None;
+    }
+
+    let target = content.split(['|', '#']).next().unwrap_or_default();
+    let trimmed = target.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let start = 2 + (target.len() - target.trim_start().len());
+    Some((content_len + 4, start..start + trimmed.len()))
+}
+
+/// Parses the destination of a markdown link or image after its `](`, returning the length of the
+/// destination through its closing `)`, the range of its target (without any `#heading` or
+/// title), and whether it's written in angle brackets.
+/// [text](target)
+/// [text](<target with spaces>)
+/// ![alt](target#heading "title")
+fn link_destination(text: &str) -> Option<(usize, Range<usize>, bool)> {
+    if let Some(rest) = text.strip_prefix('<') {
+        let end = rest.find(['>', '\n'])?;
+        if !rest[end..].starts_with('>') {
+            return None;
+        }
+        let close = 1 + end + 1 + text[1 + end + 1..].find(')')?;
+        let target_len = rest[..end].find('#').unwrap_or(end);
+        return Some((close + 1, 1..1 + target_len, true));
+    }
+
+    let close = text.find([')', '\n'])?;
+    if !text[close..].starts_with(')') {
+        return None;
+    }
+    let destination = &text[..close];
+    let end = destination
+        .find(char::is_whitespace)
+        .unwrap_or(destination.len());
+    let target_len = destination[..end].find('#').unwrap_or(end);
+    Some((close + 1, 0..target_len, false))
+}
+
+/// Whether a markdown link target refers to a file rather than a website, email address, etc.
+fn is_file_link(target: &str) -> bool {
+    if target.is_empty() {
+        return false;
+    }
+    if target.starts_with("lb://") {
+        return true;
+    }
+    match target.find(':') {
+        // schemes (`https:`, `mailto:`) come before any path separator
+        Some(colon) => target[..colon].contains('/'),
+        None => true,
+    }
+}
+
+/// Decodes the `%XX` escapes in a link target, leaving it as it is if the result isn't UTF-8.
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| text.to_string())
+}
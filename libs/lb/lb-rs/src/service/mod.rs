@@ -3,14 +3,24 @@ pub mod activity_service;
 pub mod admin_service;
 pub mod api_service;
 pub mod billing_service;
+pub mod collab_service;
+pub mod conflict_service;
+pub mod daily_note_service;
+pub mod diff_service;
 pub mod document_service;
 pub mod drawing_service;
 pub mod file_service;
 pub mod import_export_service;
 pub mod integrity_service;
+pub mod link_service;
 pub mod log_service;
 pub mod path_service;
 pub mod search_service;
+pub mod settings_service;
 pub mod share_service;
 pub mod sync_service;
+pub mod task_service;
+pub mod team_service;
+pub mod template_service;
+pub mod undo_service;
 pub mod usage_service;
@@ -1,7 +1,11 @@
+use std::collections::HashSet;
+
 use crate::{CoreError, LbResult};
 use crate::{CoreState, Requester};
+use lockbook_shared::clock::get_time;
 use lockbook_shared::document_repo::DocumentService;
 use lockbook_shared::file::File;
+use lockbook_shared::file_like::FileLike;
 use lockbook_shared::path_ops::Filter;
 use lockbook_shared::tree_like::TreeLike;
 use uuid::Uuid;
@@ -39,6 +43,23 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
         Ok(ui_file)
     }
 
+    /// Creates a file at a path that other devices may also create a file at, such as a daily
+    /// note's. The file and the folders created for it are recorded so that if another device
+    /// creates the same file before they're synced, sync combines the two instead of renaming one.
+    pub(crate) fn create_at_well_known_path(&mut self, path: &str) -> LbResult<File> {
+        let existing: HashSet<Uuid> = self.db.local_metadata.get().keys().copied().collect();
+        let file = self.create_at_path(path)?;
+
+        let tree = (&self.db.base_metadata).to_staged(&self.db.local_metadata);
+        let mut id = file.id;
+        while !existing.contains(&id) && self.db.base_metadata.maybe_find(&id).is_none() {
+            self.db.well_known_files.insert(id, get_time().0)?;
+            id = *tree.find(&id)?.parent();
+        }
+
+        Ok(file)
+    }
+
     pub(crate) fn get_by_path(&mut self, path: &str) -> LbResult<File> {
         let mut tree = (&self.db.base_metadata)
             .to_staged(&self.db.local_metadata)
@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use lockbook_shared::api::{GetAccountSettingsResponse, UpsertAccountSettingsRequest};
+use lockbook_shared::crypto::AESKey;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file::File;
+use lockbook_shared::symkey;
+use lockbook_shared::tree_like::TreeLike;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// Settings that follow the user's account across devices. They're kept apart from the user's
+/// files, on the server encrypted with the key of the account's root folder, and sync merges
+/// concurrent changes to them setting by setting.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSettings {
+    /// pinned files, in the order they were pinned
+    #[serde(default)]
+    pub pinned: Vec<Uuid>,
+    /// clients' settings by namespace, like `egui`, then by key
+    #[serde(default)]
+    pub clients: BTreeMap<String, Map<String, Value>>,
+}
+
+impl AccountSettings {
+    fn from_document(document: &[u8]) -> LbResult<Self> {
+        if document.is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_slice(document).map_err(|err| {
+            CoreError::Unexpected(format!("account settings could not be parsed: {err}")).into()
+        })
+    }
+
+    /// Combines the changes made to the settings `base` on this device, `local`, with those made
+    /// elsewhere, `remote`.
+    fn merge(base: Self, local: Self, remote: Self) -> Self {
+        let mut pinned: Vec<Uuid> = remote
+            .pinned
+            .into_iter()
+            .filter(|id| local.pinned.contains(id) || !base.pinned.contains(id))
+            .collect();
+        for id in local.pinned {
+            if !base.pinned.contains(&id) && !pinned.contains(&id) {
+                pinned.push(id);
+            }
+        }
+
+        let mut clients = BTreeMap::new();
+        let namespaces: BTreeSet<&String> = base
+            .clients
+            .keys()
+            .chain(local.clients.keys())
+            .chain(remote.clients.keys())
+            .collect();
+        for namespace in namespaces {
+            let empty = Map::new();
+            let base = base.clients.get(namespace).unwrap_or(&empty);
+            let local = local.clients.get(namespace).unwrap_or(&empty);
+            let remote = remote.clients.get(namespace).unwrap_or(&empty);
+
+            // each setting that changed locally wins over the remote value
+            let mut values = Map::new();
+            let keys: BTreeSet<&String> = base
+                .keys()
+                .chain(local.keys())
+                .chain(remote.keys())
+                .collect();
+            for key in keys {
+                let value =
+                    if local.get(key) == base.get(key) { remote.get(key) } else { local.get(key) };
+                if let Some(value) = value {
+                    values.insert(key.clone(), value.clone());
+                }
+            }
+            if !values.is_empty() {
+                clients.insert(namespace.clone(), values);
+            }
+        }
+
+        Self { pinned, clients }
+    }
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    pub(crate) fn account_settings(&mut self) -> LbResult<AccountSettings> {
+        Ok(match self.db.local_account_settings.get() {
+            Some(settings) => settings.clone(),
+            None => self
+                .db
+                .base_account_settings
+                .get()
+                .map(|(_, settings)| settings.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub(crate) fn set_account_settings(&mut self, settings: &AccountSettings) -> LbResult<()> {
+        self.db.local_account_settings.insert(settings.clone())?;
+        Ok(())
+    }
+
+    /// Merges the settings fetched from the server with the changes made on this device since the
+    /// last sync. Returns the request that uploads the merged settings and the settings it uploads,
+    /// unless they're the server's.
+    pub(crate) fn merge_account_settings(
+        &mut self, remote: GetAccountSettingsResponse,
+    ) -> LbResult<Option<(UpsertAccountSettingsRequest, AccountSettings)>> {
+        let key = self.account_settings_key()?;
+        let remote_settings = match &remote.settings {
+            Some(settings) => AccountSettings::from_document(&symkey::decrypt(&key, settings)?)?,
+            None => AccountSettings::default(),
+        };
+        let (base_version, base) = self
+            .db
+            .base_account_settings
+            .get()
+            .cloned()
+            .unwrap_or_default();
+        let local = self.db.local_account_settings.get().cloned();
+
+        if remote.version != base_version {
+            let merged = match local {
+                Some(local) => AccountSettings::merge(base, local, remote_settings.clone()),
+                None => remote_settings.clone(),
+            };
+            self.db
+                .base_account_settings
+                .insert((remote.version, remote_settings.clone()))?;
+            if merged == remote_settings {
+                self.db.local_account_settings.clear()?;
+            } else {
+                self.db.local_account_settings.insert(merged)?;
+            }
+        } else if local == Some(base) {
+            self.db.local_account_settings.clear()?;
+        }
+
+        Ok(match self.db.local_account_settings.get().cloned() {
+            Some(settings) => {
+                let document = serde_json::to_vec(&settings)
+                    .map_err(|err| CoreError::Unexpected(err.to_string()))?;
+                let request = UpsertAccountSettingsRequest {
+                    old_version: remote.version,
+                    settings: symkey::encrypt(&key, &document)?,
+                };
+                Some((request, settings))
+            }
+            None => None,
+        })
+    }
+
+    /// Records that `settings` were uploaded as version `version` of the account's settings.
+    /// Changes made since they were merged stay local until the next sync.
+    pub(crate) fn commit_account_settings(
+        &mut self, version: u64, settings: AccountSettings,
+    ) -> LbResult<()> {
+        if self.db.local_account_settings.get() == Some(&settings) {
+            self.db.local_account_settings.clear()?;
+        }
+        self.db.base_account_settings.insert((version, settings))?;
+        Ok(())
+    }
+
+    /// The key of the account's root folder, which only the account's devices can decrypt
+    fn account_settings_key(&mut self) -> LbResult<AESKey> {
+        let account = self.get_account()?.clone();
+        let root = *self.db.root.get().ok_or(CoreError::RootNonexistent)?;
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        Ok(tree.decrypt_key(&root, &account)?)
+    }
+
+    pub(crate) fn pin_file(&mut self, id: Uuid) -> LbResult<()> {
+        self.get_file_by_id(&id)?;
+
+        let mut settings = self.account_settings()?;
+        if !settings.pinned.contains(&id) {
+            settings.pinned.push(id);
+            self.set_account_settings(&settings)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn unpin_file(&mut self, id: Uuid) -> LbResult<()> {
+        let mut settings = self.account_settings()?;
+        if settings.pinned.contains(&id) {
+            settings.pinned.retain(|&pinned| pinned != id);
+            self.set_account_settings(&settings)?;
+        }
+        Ok(())
+    }
+
+    /// The settings a client saved under `namespace`, or `None` if it hasn't saved any.
+    pub(crate) fn client_settings(
+        &mut self, namespace: &str,
+    ) -> LbResult<Option<Map<String, Value>>> {
+        Ok(self.account_settings()?.clients.remove(namespace))
+    }
+
+    /// Saves the settings of a client under `namespace`, replacing all of the ones saved before.
+    /// Sync compares them with the synced settings key by key, so only the keys whose values
+    /// changed here win over changes made on other devices.
+    pub(crate) fn set_client_settings(
+        &mut self, namespace: &str, values: Map<String, Value>,
+    ) -> LbResult<()> {
+        if namespace.is_empty() {
+            return Err(CoreError::Unexpected("settings namespace is empty".to_string()).into());
+        }
+
+        let mut settings = self.account_settings()?;
+        if settings.clients.get(namespace) != Some(&values) {
+            settings.clients.insert(namespace.to_string(), values);
+            self.set_account_settings(&settings)?;
+        }
+        Ok(())
+    }
+
+    /// The pinned files in the order they were pinned, leaving out files that were deleted.
+    pub(crate) fn list_pinned(&mut self) -> LbResult<Vec<File>> {
+        let mut result = Vec::new();
+        for id in self.account_settings()?.pinned {
+            match self.get_file_by_id(&id) {
+                Ok(file) => result.push(file),
+                Err(err) if err.kind == CoreError::FileNonexistent => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(result)
+    }
+}
@@ -1,15 +1,32 @@
+use std::collections::{HashMap, HashSet};
+
 use libsecp256k1::PublicKey;
+use lockbook_shared::access_info::UserAccessMode;
 use lockbook_shared::document_repo::DocumentService;
 use uuid::Uuid;
 
-use lockbook_shared::api::GetPublicKeyRequest;
+use lockbook_shared::api::{
+    ChangeDocRequest, DeleteTransferRequest, GetPublicKeyRequest, GetTransfersRequest,
+    OfferTransferRequest, UpsertError, UpsertRequest,
+};
 use lockbook_shared::file::{File, ShareMode};
 use lockbook_shared::file_like::FileLike;
-use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::file_metadata::{FileDiff, Owner};
 use lockbook_shared::tree_like::TreeLike;
 
+use crate::service::api_service::ApiError;
 use crate::{CoreError, CoreState, LbError, LbResult, Requester};
 
+/// A user who can access a file, and how they came to have that access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permission {
+    pub username: String,
+    pub mode: ShareMode,
+    /// the file whose share grants the access - the file itself or one of its ancestors. `None`
+    /// for the owner of the file
+    pub granted_by: Option<Uuid>,
+}
+
 impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
     pub(crate) fn share_file(&mut self, id: Uuid, username: &str, mode: ShareMode) -> LbResult<()> {
         let mut tree = (&self.db.base_metadata)
@@ -33,6 +50,158 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
         Ok(())
     }
 
+    /// Offers a file and its descendants to another user, sharing it with them so they can take
+    /// it. The offer is made immediately, so the file must have been synced.
+    pub(crate) fn transfer_ownership(&mut self, id: &Uuid, username: &str) -> LbResult<()> {
+        let account = self.get_account()?.clone();
+        let owner = Owner(account.public_key());
+        let new_owner = Owner(
+            self.client
+                .request(&account, GetPublicKeyRequest { username: String::from(username) })?
+                .key,
+        );
+        if new_owner == owner {
+            return Ok(());
+        }
+        if self.db.base_metadata.maybe_find(id).is_none() {
+            return Err(CoreError::SyncRequired.into());
+        }
+
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&mut self.db.local_metadata)
+            .to_lazy();
+        if tree.calculate_deleted(id)? {
+            return Err(CoreError::FileNonexistent.into());
+        }
+        if tree.find(id)?.owner() != owner {
+            return Err(CoreError::InsufficientPermission.into());
+        }
+        let share = if tree.access_mode(new_owner, id)? < Some(UserAccessMode::Write) {
+            Some(tree.add_share_op(*id, new_owner, ShareMode::Write, &account)?)
+        } else {
+            None
+        };
+        let mut tree = tree.stage(share);
+        tree.validate(owner)?;
+
+        self.client
+            .request(&account, OfferTransferRequest { id: *id, to: new_owner.0 })?;
+
+        tree.promote()?;
+        self.db
+            .pub_key_lookup
+            .insert(new_owner, String::from(username))?;
+
+        Ok(())
+    }
+
+    pub(crate) fn get_pending_transfers(&mut self) -> LbResult<Vec<File>> {
+        let account = self.get_account()?.clone();
+        let transfers = self
+            .client
+            .request(&account, GetTransfersRequest {})?
+            .transfers;
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+
+        // transfers show up once the files have been synced
+        let mut result = Vec::new();
+        for transfer in transfers {
+            if transfer.to != account.public_key()
+                || tree.maybe_find(&transfer.id).is_none()
+                || tree.calculate_deleted(&transfer.id)?
+            {
+                continue;
+            }
+            result.push(tree.decrypt(&account, &transfer.id, &mut self.db.pub_key_lookup)?);
+        }
+        Ok(result)
+    }
+
+    /// Takes a file and its descendants that were offered to this user, moving them to `parent`.
+    /// The files get new keys and their documents are re-encrypted, so the previous owner can't
+    /// read what's added to them afterwards. It's uploaded immediately, so neither the files nor
+    /// `parent` can have changes that haven't been synced.
+    pub(crate) fn accept_transfer(&mut self, id: &Uuid, parent: &Uuid) -> LbResult<()> {
+        let account = self.get_account()?.clone();
+        let owner = Owner(account.public_key());
+        let transfers = self
+            .client
+            .request(&account, GetTransfersRequest {})?
+            .transfers;
+        if !transfers
+            .iter()
+            .any(|transfer| transfer.id == *id && transfer.to == owner.0)
+        {
+            return Err(CoreError::FileNonexistent.into());
+        }
+
+        let mut base = (&mut self.db.base_metadata).to_lazy();
+        if base.maybe_find(id).is_none() || base.maybe_find(parent).is_none() {
+            return Err(CoreError::SyncRequired.into());
+        }
+        let transfer = base.transfer_op(id, parent, &account)?;
+        if transfer
+            .iter()
+            .any(|file| self.db.local_metadata.maybe_find(file.id()).is_some())
+        {
+            return Err(CoreError::SyncRequired.into());
+        }
+
+        // documents are read before the files are re-keyed
+        let mut documents = Vec::new();
+        for file in &transfer {
+            if file.is_document()
+                && file.document_hmac().is_some()
+                && !base.calculate_deleted(file.id())?
+            {
+                documents.push((*file.id(), base.read_document(&self.docs, file.id(), &account)?));
+            }
+        }
+
+        let mut updates = Vec::new();
+        for file in &transfer {
+            updates.push(FileDiff::edit(base.find(file.id())?, file));
+        }
+        let ids = transfer.iter().map(|file| *file.id()).collect();
+        let mut transferred = base.stage(transfer);
+        transferred.validate_transfer(owner, &ids)?;
+
+        self.client
+            .request(&account, UpsertRequest { updates })
+            .map_err(|err| match err {
+                ApiError::Endpoint(UpsertError::OldVersionIncorrect) => {
+                    CoreError::SyncRequired.into()
+                }
+                err => LbError::from(err),
+            })?;
+        transferred.promote()?;
+
+        // keys cached from before the files were re-keyed can't be used
+        let mut transferred = (&mut self.db.base_metadata).to_lazy();
+
+        let mut rekeyed = Vec::new();
+        for (id, document) in documents {
+            let (file, encrypted) = transferred.rekey_document_op(&id, &document, &account)?;
+            let diff = FileDiff::edit(transferred.find(&id)?, &file);
+            self.client
+                .request(&account, ChangeDocRequest { diff, new_content: encrypted.clone() })?;
+            self.docs.insert(&id, file.document_hmac(), &encrypted)?;
+            rekeyed.push(file);
+        }
+        transferred.stage(rekeyed).promote()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn reject_transfer(&mut self, id: &Uuid) -> LbResult<()> {
+        let account = self.get_account()?.clone();
+        self.client
+            .request(&account, DeleteTransferRequest { id: *id })?;
+        Ok(())
+    }
+
     // todo: move to tree
     pub(crate) fn get_pending_shares(&mut self) -> LbResult<Vec<File>> {
         let account = &self.get_account()?.clone(); // todo: don't clone
@@ -67,6 +236,81 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
         Ok(result)
     }
 
+    pub(crate) fn effective_permissions(&mut self, id: &Uuid) -> LbResult<Vec<Permission>> {
+        let account = self.get_account()?.clone();
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        if tree.calculate_deleted(id)? {
+            return Err(CoreError::FileNonexistent.into());
+        }
+        let owner = tree.find(id)?.owner();
+
+        // a file is accessible with the greatest access granted by it or any ancestor; the closest
+        // file granting that access is reported
+        let mut granted: HashMap<Owner, (UserAccessMode, Uuid)> = HashMap::new();
+        let mut current = *id;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            let file = tree.find(&current)?;
+            for key in file.user_access_keys() {
+                let user = Owner(key.encrypted_for);
+                if key.deleted || user == owner {
+                    continue;
+                }
+                match granted.get(&user) {
+                    Some((mode, _)) if *mode >= key.mode => {}
+                    _ => {
+                        granted.insert(user, (key.mode, current));
+                    }
+                }
+            }
+            if file.is_root() {
+                break;
+            }
+            match tree.maybe_find(file.parent()) {
+                Some(parent) => current = *parent.id(),
+                None => break, // share root
+            }
+        }
+
+        let username = |user: &Owner| {
+            if user.0 == account.public_key() {
+                account.username.clone()
+            } else {
+                self.db
+                    .pub_key_lookup
+                    .get()
+                    .get(user)
+                    .cloned()
+                    .unwrap_or_else(|| String::from("<unknown>"))
+            }
+        };
+
+        let mut shares = Vec::new();
+        for (user, (mode, granted_by)) in granted {
+            let mode = match mode {
+                UserAccessMode::Read => ShareMode::Read,
+                UserAccessMode::Write => ShareMode::Write,
+                UserAccessMode::Owner => continue,
+            };
+            shares.push(Permission {
+                username: username(&user),
+                mode,
+                granted_by: Some(granted_by),
+            });
+        }
+        shares.sort_by(|a, b| a.username.cmp(&b.username));
+
+        let mut result = vec![Permission {
+            username: username(&owner),
+            mode: ShareMode::Write,
+            granted_by: None,
+        }];
+        result.extend(shares);
+        Ok(result)
+    }
+
     pub(crate) fn delete_share(
         &mut self, id: &Uuid, maybe_encrypted_for: Option<PublicKey>,
     ) -> LbResult<()> {
@@ -4,17 +4,20 @@ use std::fmt::{Display, Formatter};
 use lockbook_shared::access_info::UserAccessMode;
 use lockbook_shared::account::Account;
 use lockbook_shared::api::{
-    ChangeDocRequest, GetDocRequest, GetFileIdsRequest, GetUpdatesRequest, GetUpdatesResponse,
-    GetUsernameError, GetUsernameRequest, UpsertRequest,
+    ChangeDocRequest, GetAccountSettingsRequest, GetDocRequest, GetFileIdsRequest,
+    GetUpdatesRequest, GetUpdatesResponse, GetUsageRequest, GetUsernameError, GetUsernameRequest,
+    UpsertAccountSettingsError, UpsertRequest,
 };
 use lockbook_shared::document_repo::DocumentService;
 use lockbook_shared::file::ShareMode;
 use lockbook_shared::file_like::FileLike;
 use lockbook_shared::file_metadata::{FileDiff, FileType, Owner};
 use lockbook_shared::filename::{DocumentType, NameComponents};
+use lockbook_shared::lazy::LazyTree;
 use lockbook_shared::signed_file::SignedFile;
 use lockbook_shared::staged::StagedTreeLikeMut;
 use lockbook_shared::tree_like::TreeLike;
+use lockbook_shared::usage::bytes_to_human;
 use lockbook_shared::work_unit::WorkUnit;
 use lockbook_shared::{symkey, SharedErrorKind, ValidationFailure};
 
@@ -41,6 +44,7 @@ pub struct SyncContext<Client: Requester, Docs: DocumentService> {
     root: Option<Uuid>,
     pushed_metas: Vec<FileDiff<SignedFile>>,
     pushed_docs: Vec<FileDiff<SignedFile>>,
+    conflicts: Vec<Uuid>,
 }
 
 impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
@@ -57,7 +61,8 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
             .and_then(|_| context.merge())
             .and_then(|_| context.push_meta())
             .and_then(|_| context.push_docs())
-            .and_then(|_| context.commit_last_synced());
+            .and_then(|_| context.commit_last_synced())
+            .and_then(|_| context.sync_account_settings());
 
         let cleanup = context.must_cleanup();
 
@@ -85,7 +90,7 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
         let pk_cache = inner.db.pub_key_lookup.get().clone();
 
         let current = 0;
-        let total = 7;
+        let total = 8;
 
         Ok(Self {
             core,
@@ -104,6 +109,7 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
             remote_changes: Default::default(),
             pushed_docs: Default::default(),
             pushed_metas: Default::default(),
+            conflicts: Default::default(),
         })
     }
 
@@ -221,7 +227,12 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
 
     fn merge(&mut self) -> LbResult<()> {
         self.msg("Reconciling updates locally...");
-        self.core.in_tx(|tx| tx.merge(&self.remote_changes))
+        self.conflicts = self.core.in_tx(|tx| {
+            let conflicts = tx.merge(&self.remote_changes)?;
+            tx.update_link_index()?;
+            Ok(conflicts)
+        })?;
+        Ok(())
     }
 
     /// Updates remote and base metadata to local.
@@ -345,6 +356,29 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
             Ok(())
         })
     }
+
+    fn sync_account_settings(&mut self) -> LbResult<()> {
+        self.msg("Syncing settings...");
+        let remote = self
+            .client
+            .request(&self.account, GetAccountSettingsRequest {})?;
+
+        if let Some((request, settings)) =
+            self.core.in_tx(|tx| tx.merge_account_settings(remote))?
+        {
+            match self.client.request(&self.account, request) {
+                Ok(response) => self
+                    .core
+                    .in_tx(|tx| tx.commit_account_settings(response.new_version, settings))?,
+                // changed elsewhere since they were fetched; merged again next sync
+                Err(ApiError::Endpoint(UpsertAccountSettingsError::OldVersionIncorrect)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
     fn must_cleanup(&self) -> LbResult<()> {
         self.core.in_tx(|tx| {
             tx.syncing = false;
@@ -379,7 +413,11 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
             work_units.push(WorkUnit::ServerChange(*id));
         }
 
-        SyncStatus { work_units, latest_server_ts: self.update_as_of }
+        SyncStatus {
+            work_units,
+            latest_server_ts: self.update_as_of,
+            conflicts: self.conflicts.clone(),
+        }
     }
 
     fn msg(&mut self, msg: &str) {
@@ -423,6 +461,8 @@ impl<Client: Requester, Docs: DocumentService> SyncContext<Client, Docs> {
 pub struct SyncStatus {
     pub work_units: Vec<WorkUnit>,
     pub latest_server_ts: u64,
+    /// text documents that were merged with conflicting changes; see `Core::get_conflicts`
+    pub conflicts: Vec<Uuid>,
 }
 
 #[derive(Clone)]
@@ -439,6 +479,107 @@ impl Display for SyncProgress {
     }
 }
 
+/// A dry-run report of what the next sync would do. Computing a plan does not modify local or
+/// remote state.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SyncPlan {
+    /// changes made on this device that would be pushed
+    pub local_changes: Vec<PlannedChange>,
+    /// changes made elsewhere that would be pulled
+    pub server_changes: Vec<PlannedChange>,
+    /// how sync would resolve concurrent changes
+    pub conflicts: Vec<PlannedConflict>,
+    pub latest_server_ts: u64,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.local_changes.is_empty() && self.server_changes.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct PlannedChange {
+    pub id: Uuid,
+    /// path of the file after the change is applied, or before it is deleted
+    pub path: String,
+    pub op: PlannedOp,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub enum PlannedOp {
+    Create { file_type: FileType },
+    Rename { old_name: String, new_name: String },
+    Move { old_parent: String, new_parent: String },
+    Delete,
+    UploadDocument { size: u64 },
+    DownloadDocument { size: u64 },
+    Share { username: String, mode: ShareMode },
+    Unshare { username: String },
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub enum PlannedConflict {
+    /// a local file would be renamed because a file with the same path was created on the server
+    Rename { id: Uuid, path: String, new_name: String },
+    /// a text document edited both locally and on the server would be 3-way merged
+    Merge { id: Uuid, path: String },
+    /// a non-text document edited both locally and on the server would have its local version
+    /// saved as a new file alongside the server version
+    Duplicate { id: Uuid, path: String, new_name: String },
+}
+
+impl Display for PlannedChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.op {
+            PlannedOp::Create { file_type } => {
+                write!(f, "create {} ({})", self.path, format!("{:?}", file_type).to_lowercase())
+            }
+            PlannedOp::Rename { old_name, new_name } => {
+                write!(f, "rename {}: {} -> {}", self.path, old_name, new_name)
+            }
+            PlannedOp::Move { old_parent, new_parent } => {
+                write!(f, "move {}: {} -> {}", self.path, old_parent, new_parent)
+            }
+            PlannedOp::Delete => write!(f, "delete {}", self.path),
+            PlannedOp::UploadDocument { size } => {
+                write!(f, "upload {} ({})", self.path, bytes_to_human(*size))
+            }
+            PlannedOp::DownloadDocument { size } => {
+                write!(f, "download {} ({})", self.path, bytes_to_human(*size))
+            }
+            PlannedOp::Share { username, mode } => {
+                write!(
+                    f,
+                    "share {} with {} ({})",
+                    self.path,
+                    username,
+                    mode.to_string().to_lowercase()
+                )
+            }
+            PlannedOp::Unshare { username } => {
+                write!(f, "unshare {} with {}", self.path, username)
+            }
+        }
+    }
+}
+
+impl Display for PlannedConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlannedConflict::Rename { path, new_name, .. } => {
+                write!(f, "path conflict: {} will be renamed to {}", path, new_name)
+            }
+            PlannedConflict::Merge { path, .. } => {
+                write!(f, "edit conflict: {} will be merged", path)
+            }
+            PlannedConflict::Duplicate { path, new_name, .. } => {
+                write!(f, "edit conflict: local version of {} will be saved as {}", path, new_name)
+            }
+        }
+    }
+}
+
 impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
     #[instrument(level = "debug", skip_all, err(Debug))]
     pub(crate) fn calculate_work(&mut self) -> LbResult<SyncStatus> {
@@ -468,7 +609,185 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
 
         let mut work_units: Vec<WorkUnit> = Vec::new();
         work_units.extend(locally_dirty.chain(remote_dirty));
-        Ok(SyncStatus { work_units, latest_server_ts })
+        Ok(SyncStatus { work_units, latest_server_ts, conflicts: Vec::new() })
+    }
+
+    /// Describes the changes made on this device since the last sync. Does not contact the server.
+    pub(crate) fn describe_local_changes(&mut self) -> LbResult<Vec<PlannedChange>> {
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let pk_cache = self.db.pub_key_lookup.get();
+
+        let mut base = (&self.db.base_metadata).to_lazy();
+        let mut local = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+
+        let mut local_sizes = HashMap::new();
+        for id in self.db.local_metadata.owned_ids() {
+            if let Some(hmac) = local.find(&id)?.document_hmac() {
+                let size = self
+                    .docs
+                    .maybe_get(&id, Some(hmac))?
+                    .map(|doc| doc.value.len() as u64)
+                    .unwrap_or_default();
+                local_sizes.insert(id, size);
+            }
+        }
+
+        plan_changes(
+            &mut base,
+            &mut local,
+            self.db.local_metadata.owned_ids(),
+            &local_sizes,
+            true,
+            account,
+            pk_cache,
+        )
+    }
+
+    /// Describes what the next sync would do without modifying local or remote state. Conflict
+    /// resolutions are predicted from the same rules sync uses, but sync remains the authority.
+    #[instrument(level = "debug", skip_all, err(Debug))]
+    pub(crate) fn plan_sync(&mut self) -> LbResult<SyncPlan> {
+        let last_synced = self.db.last_synced.get().copied().unwrap_or_default() as u64;
+        let updates = self.client.request(
+            self.get_account()?,
+            GetUpdatesRequest { since_metadata_version: last_synced },
+        )?;
+        let (remote_changes, latest_server_ts, _) = self.dedup(updates)?;
+        let server_ids = self
+            .client
+            .request(self.get_account()?, GetFileIdsRequest {})?
+            .ids;
+        let server_sizes = self
+            .client
+            .request(self.get_account()?, GetUsageRequest {})?
+            .usages
+            .into_iter()
+            .map(|usage| (usage.file_id, usage.size_bytes))
+            .collect::<HashMap<Uuid, u64>>();
+
+        let local_changes = self.describe_local_changes()?;
+
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let pk_cache = self.db.pub_key_lookup.get();
+
+        let mut base = (&self.db.base_metadata).to_lazy();
+        let mut remote = (&self.db.base_metadata)
+            .to_staged(remote_changes.clone())
+            .to_lazy();
+        let mut local = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+
+        let mut plan = SyncPlan { local_changes, latest_server_ts, ..Default::default() };
+
+        plan.server_changes = plan_changes(
+            &mut base,
+            &mut remote,
+            remote_changes.owned_ids(),
+            &server_sizes,
+            false,
+            account,
+            pk_cache,
+        )?;
+
+        // files that the server no longer has will be pruned
+        for id in base.owned_ids() {
+            if server_ids.contains(&id) || base.calculate_deleted(&id)? {
+                continue;
+            }
+            let parent = *base.find(&id)?.parent();
+            if !server_ids.contains(&parent) && base.maybe_find(&parent).is_some() {
+                continue; // only report the top-most pruned file
+            }
+            plan.server_changes.push(PlannedChange {
+                id,
+                path: display_path(&mut base, &id, account)?,
+                op: PlannedOp::Delete,
+            });
+        }
+
+        for id in self.db.local_metadata.owned_ids() {
+            if local.calculate_deleted(&id)? {
+                continue;
+            }
+            if remote.maybe_find(&id).is_some() && remote.calculate_deleted(&id)? {
+                continue; // local edits to files deleted on the server are dropped
+            }
+
+            let local_file = local.find(&id)?.clone();
+            let local_name = local.name(&id, account)?;
+            let parent = *local_file.parent();
+
+            // path conflicts: a server change put a different file at this file's path
+            if remote.maybe_find(&parent).is_some() && !remote.calculate_deleted(&parent)? {
+                let mut sibling_names = HashSet::new();
+                let mut conflicted = false;
+                for sibling in remote.children(&parent)? {
+                    if sibling == id || remote.calculate_deleted(&sibling)? {
+                        continue;
+                    }
+                    let sibling_name = if self.db.local_metadata.maybe_find(&sibling).is_some() {
+                        if local.find(&sibling)?.parent() != &parent {
+                            continue;
+                        }
+                        local.name(&sibling, account)?
+                    } else {
+                        remote.name(&sibling, account)?
+                    };
+                    if sibling_name == local_name {
+                        conflicted = true;
+                    }
+                    sibling_names.insert(sibling_name);
+                }
+                if conflicted && self.db.well_known_files.get().contains_key(&id) {
+                    // combined with the file created elsewhere
+                    if local_file.is_document() {
+                        let path = display_path(&mut local, &id, account)?;
+                        plan.conflicts.push(PlannedConflict::Merge { id, path });
+                    }
+                } else if conflicted {
+                    let mut increment = 1;
+                    let new_name = loop {
+                        let candidate = NameComponents::from(&local_name)
+                            .generate_incremented(increment)
+                            .to_name();
+                        if !sibling_names.contains(&candidate) {
+                            break candidate;
+                        }
+                        increment += 1;
+                    };
+                    plan.conflicts.push(PlannedConflict::Rename {
+                        id,
+                        path: display_path(&mut local, &id, account)?,
+                        new_name,
+                    });
+                }
+            }
+
+            // edit conflicts: the document was edited both locally and on the server
+            let base_hmac = base
+                .maybe_find(&id)
+                .and_then(|f| f.document_hmac().cloned());
+            let remote_hmac = remote
+                .maybe_find(&id)
+                .and_then(|f| f.document_hmac().cloned());
+            let local_hmac = local_file.document_hmac().cloned();
+            if local_hmac != base_hmac && remote_hmac != base_hmac && remote_hmac != local_hmac {
+                let path = display_path(&mut local, &id, account)?;
+                match DocumentType::from_file_name_using_extension(&local_name) {
+                    DocumentType::Text => plan.conflicts.push(PlannedConflict::Merge { id, path }),
+                    DocumentType::Drawing | DocumentType::Other => {
+                        let new_name = NameComponents::from(&local_name).generate_next().to_name();
+                        plan.conflicts
+                            .push(PlannedConflict::Duplicate { id, path, new_name });
+                    }
+                }
+            }
+        }
+
+        Ok(plan)
     }
 
     fn dedup(
@@ -507,12 +826,13 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
 
     /// Pulls remote changes and constructs a changeset Merge such that Stage<Stage<Stage<Base, Remote>, Local>, Merge> is valid.
     /// Promotes Base to Stage<Base, Remote> and Local to Stage<Local, Merge>
-    fn merge(&mut self, remote_changes: &Vec<SignedFile>) -> LbResult<()> {
+    /// Returns the ids of text documents that were merged with conflicts, which are also recorded.
+    fn merge(&mut self, remote_changes: &Vec<SignedFile>) -> LbResult<Vec<Uuid>> {
         // fetch document updates and local documents for merge
         let me = Owner(self.get_public_key()?);
 
         // compute merge changes
-        let merge_changes = {
+        let (merge_changes, conflicts) = {
             // assemble trees
             let mut base = (&self.db.base_metadata).to_lazy();
             let remote_unlazy = (&self.db.base_metadata).to_staged(remote_changes);
@@ -527,8 +847,13 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
             let mut links_to_delete: HashSet<Uuid> = HashSet::new();
             let mut rename_increments: HashMap<Uuid, usize> = HashMap::new();
             let mut duplicate_file_ids: HashMap<Uuid, Uuid> = HashMap::new();
+            let mut merged_creations: HashMap<Uuid, Uuid> = HashMap::new();
+            let well_known_files: HashSet<Uuid> =
+                self.db.well_known_files.get().keys().copied().collect();
 
             'merge_construction: loop {
+                let mut conflicted_documents: HashSet<Uuid> = HashSet::new();
+
                 // process just the edits which allow us to check deletions in the result
                 let mut deletions = {
                     let mut deletions = remote_unlazy.stage(Vec::new()).to_lazy();
@@ -536,7 +861,10 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                     // creations
                     let mut deletion_creations = HashSet::new();
                     for id in self.db.local_metadata.owned_ids() {
-                        if remote.maybe_find(&id).is_none() && !links_to_delete.contains(&id) {
+                        if remote.maybe_find(&id).is_none()
+                            && !links_to_delete.contains(&id)
+                            && !merged_creations.contains_key(&id)
+                        {
                             deletion_creations.insert(id);
                         }
                     }
@@ -545,10 +873,13 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                             // create
                             let id = *id;
                             let local_file = local.find(&id)?.clone();
+                            let parent = merged_creations
+                                .get(local_file.parent())
+                                .unwrap_or(local_file.parent());
                             let result = deletions.create_unvalidated(
                                 id,
                                 symkey::generate_key(),
-                                local_file.parent(),
+                                parent,
                                 &local.name(&id, self.get_account()?)?,
                                 local_file.file_type(),
                                 self.get_account()?,
@@ -584,11 +915,10 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                                 && !files_to_unmove.contains(&id)
                             {
                                 // move
-                                deletions.move_unvalidated(
-                                    &id,
-                                    local_file.parent(),
-                                    self.get_account()?,
-                                )?;
+                                let parent = merged_creations
+                                    .get(local_file.parent())
+                                    .unwrap_or(local_file.parent());
+                                deletions.move_unvalidated(&id, parent, self.get_account()?)?;
                             }
                         }
                     }
@@ -624,10 +954,13 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                             // create
                             let id = *id;
                             let local_file = local.find(&id)?.clone();
+                            let parent = merged_creations
+                                .get(local_file.parent())
+                                .unwrap_or(local_file.parent());
                             let result = merge.create_unvalidated(
                                 id,
                                 local.decrypt_key(&id, self.get_account()?)?,
-                                local_file.parent(),
+                                parent,
                                 &local.name(&id, self.get_account()?)?,
                                 local_file.file_type(),
                                 self.get_account()?,
@@ -680,11 +1013,10 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                                 && remote_file.parent() == base_file.parent()
                                 && !files_to_unmove.contains(&id)
                             {
-                                merge.move_unvalidated(
-                                    &id,
-                                    local_file.parent(),
-                                    self.get_account()?,
-                                )?;
+                                let parent = merged_creations
+                                    .get(local_file.parent())
+                                    .unwrap_or(local_file.parent());
+                                merge.move_unvalidated(&id, parent, self.get_account()?)?;
                             }
 
                             // rename
@@ -784,14 +1116,19 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                                 };
                                 match document_type {
                                     DocumentType::Text => {
-                                        // 3-way merge
-                                        let merged_document = match diffy::merge_bytes(
-                                            &base_document,
-                                            &remote_document,
-                                            &local_document,
-                                        ) {
+                                        // 3-way merge; on conflict, local changes are 'ours'
+                                        let merged_document = match diffy::MergeOptions::new()
+                                            .set_conflict_style(diffy::ConflictStyle::Merge)
+                                            .merge_bytes(
+                                                &base_document,
+                                                &local_document,
+                                                &remote_document,
+                                            ) {
                                             Ok(without_conflicts) => without_conflicts,
-                                            Err(with_conflicts) => with_conflicts,
+                                            Err(with_conflicts) => {
+                                                conflicted_documents.insert(id);
+                                                with_conflicts
+                                            }
                                         };
                                         let encrypted_document = merge
                                             .update_document_unvalidated(
@@ -862,6 +1199,46 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                         }
                     }
 
+                    // files created at a well-known path both locally and elsewhere are combined
+                    for (&local_id, &remote_id) in &merged_creations {
+                        if !local.find(&local_id)?.is_document() {
+                            continue;
+                        }
+                        let local_document = if local.find(&local_id)?.document_hmac().is_some() {
+                            local.read_document(&self.docs, &local_id, self.get_account()?)?
+                        } else {
+                            Vec::new()
+                        };
+                        let remote_document = if remote.find(&remote_id)?.document_hmac().is_some()
+                        {
+                            remote.read_document(&self.docs, &remote_id, self.get_account()?)?
+                        } else {
+                            Vec::new()
+                        };
+                        if local_document.is_empty() || local_document == remote_document {
+                            continue;
+                        }
+
+                        // 2-way merge; on conflict, local changes are 'ours'
+                        let merged_document = match diffy::MergeOptions::new()
+                            .set_conflict_style(diffy::ConflictStyle::Merge)
+                            .merge_bytes(&[], &local_document, &remote_document)
+                        {
+                            Ok(without_conflicts) => without_conflicts,
+                            Err(with_conflicts) => {
+                                conflicted_documents.insert(remote_id);
+                                with_conflicts
+                            }
+                        };
+                        let encrypted_document = merge.update_document_unvalidated(
+                            &remote_id,
+                            &merged_document,
+                            self.get_account()?,
+                        )?;
+                        let hmac = merge.find(&remote_id)?.document_hmac();
+                        self.docs.insert(&remote_id, hmac, &encrypted_document)?;
+                    }
+
                     // deletes
                     // moves happen first in case a file is moved into a deleted folder
                     for id in self.db.local_metadata.owned_ids() {
@@ -910,7 +1287,7 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                     // merge changeset is valid
                     Ok(_) => {
                         let (_, merge_changes) = merge.unstage();
-                        break merge_changes;
+                        break (merge_changes, conflicted_documents);
                     }
                     Err(ref err) => match err.kind {
                         SharedErrorKind::ValidationFailure(ref vf) => match vf {
@@ -934,15 +1311,42 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
                                 }
                             }
                             ValidationFailure::PathConflict(ids) => {
-                                // pick one local id and generate a non-conflicting filename
+                                // combine a file created at a well-known path with the one
+                                // created elsewhere, otherwise pick one local id and generate a
+                                // non-conflicting filename
                                 let mut progress = false;
                                 for &id in ids {
-                                    if duplicate_file_ids.values().any(|&dup| dup == id) {
-                                        *rename_increments.entry(id).or_insert(0) += 1;
-                                        progress = true;
+                                    if !well_known_files.contains(&id)
+                                        || base.maybe_find(&id).is_some()
+                                        || remote.maybe_find(&id).is_some()
+                                    {
+                                        continue;
+                                    }
+                                    let file_type = local.find(&id)?.file_type();
+                                    for &other in ids {
+                                        if remote.maybe_find(&other).is_some()
+                                            && remote.find(&other)?.file_type() == file_type
+                                            && merge.access_mode(me, &other)?
+                                                >= Some(UserAccessMode::Write)
+                                        {
+                                            merged_creations.insert(id, other);
+                                            progress = true;
+                                            break;
+                                        }
+                                    }
+                                    if progress {
                                         break;
                                     }
                                 }
+                                if !progress {
+                                    for &id in ids {
+                                        if duplicate_file_ids.values().any(|&dup| dup == id) {
+                                            *rename_increments.entry(id).or_insert(0) += 1;
+                                            progress = true;
+                                            break;
+                                        }
+                                    }
+                                }
                                 if !progress {
                                     for &id in ids {
                                         if self.db.local_metadata.maybe_find(&id).is_some() {
@@ -1061,7 +1465,20 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
             .promote()?;
         self.cleanup_local_metadata()?;
 
-        Ok(())
+        // files created at well-known paths can only conflict until they're pushed
+        let well_known_files: Vec<Uuid> = self.db.well_known_files.get().keys().copied().collect();
+        for id in well_known_files {
+            if self.db.base_metadata.maybe_find(&id).is_some()
+                || self.db.local_metadata.maybe_find(&id).is_none()
+            {
+                self.db.well_known_files.remove(&id)?;
+            }
+        }
+
+        let conflicts = conflicts.into_iter().collect::<Vec<_>>();
+        self.record_conflicts(&conflicts)?;
+
+        Ok(conflicts)
     }
 
     pub(crate) fn prune_remote_orphans(
@@ -1126,3 +1543,141 @@ impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
         Ok(())
     }
 }
+
+/// Describes how each of `ids` differs between `before` and `after`. Document sizes are looked up
+/// in `sizes`; `upload` selects whether document changes are reported as uploads or downloads.
+fn plan_changes<Before, After>(
+    before: &mut LazyTree<Before>, after: &mut LazyTree<After>, ids: HashSet<Uuid>,
+    sizes: &HashMap<Uuid, u64>, upload: bool, account: &Account, pk_cache: &HashMap<Owner, String>,
+) -> LbResult<Vec<PlannedChange>>
+where
+    Before: TreeLike<F = SignedFile>,
+    After: TreeLike<F = SignedFile>,
+{
+    let mut changes = Vec::new();
+    for id in ids {
+        let after_file = after.find(&id)?.clone();
+        let mut ops = Vec::new();
+
+        let before_file = match before.maybe_find(&id).cloned() {
+            Some(before_file) => {
+                if before.calculate_deleted(&id)? {
+                    continue;
+                }
+                Some(before_file)
+            }
+            None => {
+                if after.calculate_deleted(&id)? {
+                    continue; // created and deleted before ever being synced
+                }
+                ops.push(PlannedOp::Create { file_type: after_file.file_type() });
+                None
+            }
+        };
+
+        if let Some(before_file) = &before_file {
+            if after_file.explicitly_deleted() {
+                changes.push(PlannedChange {
+                    id,
+                    path: display_path(before, &id, account)?,
+                    op: PlannedOp::Delete,
+                });
+                continue;
+            }
+            if after.calculate_deleted(&id)? {
+                continue; // reported as the deletion of an ancestor
+            }
+
+            let old_name = before.name(&id, account)?;
+            let new_name = after.name(&id, account)?;
+            if old_name != new_name {
+                ops.push(PlannedOp::Rename { old_name, new_name });
+            }
+
+            if before_file.parent() != after_file.parent() {
+                ops.push(PlannedOp::Move {
+                    old_parent: display_path(before, before_file.parent(), account)?,
+                    new_parent: display_path(after, after_file.parent(), account)?,
+                });
+            }
+        }
+
+        let before_hmac = before_file.as_ref().and_then(|f| f.document_hmac());
+        if after_file.document_hmac().is_some() && after_file.document_hmac() != before_hmac {
+            let size = sizes.get(&id).copied().unwrap_or_default();
+            ops.push(if upload {
+                PlannedOp::UploadDocument { size }
+            } else {
+                PlannedOp::DownloadDocument { size }
+            });
+        }
+
+        let mut before_keys = HashMap::new();
+        if let Some(before_file) = &before_file {
+            for key in before_file.user_access_keys() {
+                before_keys.insert(Owner(key.encrypted_for), (key.mode, key.deleted));
+            }
+        }
+        for key in after_file.user_access_keys() {
+            if key.encrypted_by == key.encrypted_for {
+                continue;
+            }
+            let username = if key.encrypted_for == account.public_key() {
+                account.username.clone()
+            } else {
+                pk_cache
+                    .get(&Owner(key.encrypted_for))
+                    .cloned()
+                    .unwrap_or_else(|| String::from("<unknown>"))
+            };
+            let before_key = before_keys.get(&Owner(key.encrypted_for)).copied();
+            if key.deleted {
+                if let Some((_, false)) = before_key {
+                    ops.push(PlannedOp::Unshare { username });
+                }
+            } else if before_key != Some((key.mode, false)) {
+                let mode = match key.mode {
+                    UserAccessMode::Read => ShareMode::Read,
+                    UserAccessMode::Write => ShareMode::Write,
+                    UserAccessMode::Owner => continue,
+                };
+                ops.push(PlannedOp::Share { username, mode });
+            }
+        }
+
+        if !ops.is_empty() {
+            let path = display_path(after, &id, account)?;
+            for op in ops {
+                changes.push(PlannedChange { id, path: path.clone(), op });
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// The path of a file for display purposes. Files whose ancestors are not in the tree, such as the
+/// roots of pending shares, are shown relative to their top-most present ancestor.
+fn display_path<T: TreeLike<F = SignedFile>>(
+    tree: &mut LazyTree<T>, id: &Uuid, account: &Account,
+) -> LbResult<String> {
+    let file = tree.find(id)?;
+    if file.is_root() {
+        return Ok("/".to_string());
+    }
+    let mut path = if file.is_folder() { "/".to_string() } else { String::new() };
+    let mut current = *id;
+    loop {
+        let file = tree.find(&current)?;
+        if file.is_root() {
+            return Ok(path);
+        }
+        let parent = *file.parent();
+        path = format!("/{}{}", tree.name(&current, account)?, path);
+        if tree.maybe_find(&parent).is_none() {
+            return Ok(path);
+        }
+        current = parent;
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::DocumentHmac;
+use lockbook_shared::tree_like::TreeLike;
+use serde::{Deserialize, Serialize};
+use time::{Date, Month};
+use uuid::Uuid;
+
+use crate::service::link_service::is_markdown;
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// A task list item in a markdown document e.g. `- [ ] water the plants @due(2024-05-01)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    /// the document containing the task
+    pub id: Uuid,
+    /// the line the task is on, counting from 1
+    pub line: usize,
+    /// the task's text, without its due date annotation
+    pub text: String,
+    pub checked: bool,
+    /// the date in the task's `@due(YYYY-MM-DD)` annotation, if it has a valid one
+    pub due: Option<String>,
+}
+
+/// Which tasks `list_tasks` returns. The default filter matches every task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskFilter {
+    /// only tasks that are (or aren't) checked
+    pub checked: Option<bool>,
+    /// only tasks due on or before this date (`YYYY-MM-DD`)
+    pub due_by: Option<String>,
+    /// only tasks in this document or in documents in this folder, recursively
+    pub parent: Option<Uuid>,
+}
+
+/// The tasks in a markdown document, as of the version of the document with `hmac`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedTasks {
+    pub hmac: Option<DocumentHmac>,
+    pub tasks: Vec<Task>,
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    /// Tasks in all markdown documents that match `filter`, ordered by due date (tasks without one
+    /// last), then by path and line.
+    pub(crate) fn list_tasks(&mut self, filter: TaskFilter) -> LbResult<Vec<Task>> {
+        self.update_task_index()?;
+
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+
+        let mut result = Vec::new();
+        for (id, indexed) in self.db.tasks.get() {
+            if let Some(parent) = filter.parent {
+                if parent != *id && !tree.ancestors(id)?.contains(&parent) {
+                    continue;
+                }
+            }
+            let path = tree.id_to_path(id, account)?;
+            for task in &indexed.tasks {
+                if matches!(filter.checked, Some(checked) if checked != task.checked) {
+                    continue;
+                }
+                if let Some(due_by) = &filter.due_by {
+                    // iso dates sort chronologically
+                    if !matches!(&task.due, Some(due) if due <= due_by) {
+                        continue;
+                    }
+                }
+                result.push((task.due.is_none(), task.due.clone(), path.clone(), task.clone()));
+            }
+        }
+        result.sort_by(|a, b| (a.0, &a.1, &a.2, a.3.line).cmp(&(b.0, &b.1, &b.2, b.3.line)));
+
+        Ok(result.into_iter().map(|(_, _, _, task)| task).collect())
+    }
+
+    /// Checks or unchecks `task`. The document is read again first and only the task's check is
+    /// changed, so edits made since the task was listed are kept. If the task moved to another
+    /// line, it's found by its text.
+    pub(crate) fn set_task_state(&mut self, task: &Task, checked: bool) -> LbResult<()> {
+        let document = self.read_document(task.id)?;
+        let mut content = String::from_utf8_lossy(&document).into_owned();
+
+        let current = find_tasks(task.id, &content)
+            .into_iter()
+            .filter(|current| current.text == task.text)
+            .min_by_key(|current| current.line.abs_diff(task.line))
+            .ok_or(CoreError::TaskNonexistent)?;
+        if current.checked == checked {
+            return Ok(());
+        }
+
+        let line_start = content
+            .split_inclusive('\n')
+            .take(current.line - 1)
+            .map(str::len)
+            .sum::<usize>();
+        let line = &content[line_start..];
+        let check = line_start + line.find('[').ok_or(CoreError::TaskNonexistent)? + 1;
+        content.replace_range(check..check + 1, if checked { "x" } else { " " });
+
+        self.write_document(task.id, content.as_bytes())
+    }
+
+    /// Indexes the tasks in markdown documents that changed since they were last indexed and
+    /// forgets documents that were deleted. Only changed documents are read.
+    pub(crate) fn update_task_index(&mut self) -> LbResult<()> {
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+
+        let mut markdown_ids = HashSet::new();
+        for id in tree.owned_ids() {
+            if !tree.find(&id)?.is_document()
+                || tree.calculate_deleted(&id)?
+                || tree.in_pending_share(&id)?
+                || !is_markdown(&tree.name(&id, account)?)
+            {
+                continue;
+            }
+            markdown_ids.insert(id);
+
+            let hmac = tree.find(&id)?.document_hmac().copied();
+            if matches!(self.db.tasks.get().get(&id), Some(indexed) if indexed.hmac == hmac) {
+                continue;
+            }
+
+            let document = tree.read_document(&self.docs, &id, account)?;
+            let tasks = find_tasks(id, &String::from_utf8_lossy(&document));
+            self.db.tasks.insert(id, IndexedTasks { hmac, tasks })?;
+        }
+
+        let forgotten = self
+            .db
+            .tasks
+            .get()
+            .keys()
+            .filter(|id| !markdown_ids.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in forgotten {
+            self.db.tasks.remove(&id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Indexes the tasks in a markdown document that was just written.
+    pub(crate) fn index_document_tasks(
+        &mut self, id: Uuid, hmac: Option<DocumentHmac>, content: &[u8],
+    ) -> LbResult<()> {
+        let tasks = find_tasks(id, &String::from_utf8_lossy(content));
+        self.db.tasks.insert(id, IndexedTasks { hmac, tasks })?;
+        Ok(())
+    }
+}
+
+/// Finds the task list items in a markdown document, skipping fenced code blocks.
+/// - [ ] task
+/// * [x] done
+/// 1. [ ] numbered task @due(2024-05-01)
+fn find_tasks(id: Uuid, text: &str) -> Vec<Task> {
+    let mut result = Vec::new();
+    let mut in_code_block = false;
+    for (idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let Some(rest) = list_item_text(trimmed) else {
+            continue;
+        };
+        let checked = if rest.starts_with("[ ]") {
+            false
+        } else if rest.starts_with("[x]") || rest.starts_with("[X]") {
+            true
+        } else {
+            continue;
+        };
+        let rest = &rest[3..];
+        if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+            continue;
+        }
+
+        let (text, due) = match due_annotation(rest) {
+            Some((range, due)) => {
+                let text = format!("{}{}", &rest[..range.start], &rest[range.end..]);
+                (text.split_whitespace().collect::<Vec<_>>().join(" "), Some(due))
+            }
+            None => (rest.trim().to_string(), None),
+        };
+        result.push(Task { id, line: idx + 1, text, checked, due });
+    }
+    result
+}
+
+/// The text of a list item after its bullet (`-`, `*` or `+`) or number (`1.` or `1)`) and the
+/// whitespace after it, or `None` if `line` isn't a list item
+fn list_item_text(line: &str) -> Option<&str> {
+    let marker_len = if line.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 || digits > 9 || !line[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let rest = &line[marker_len..];
+    let text = rest.trim_start_matches([' ', '\t']);
+    if text.len() == rest.len() {
+        return None; // the marker must be followed by whitespace
+    }
+    Some(text)
+}
+
+/// The byte range and date of the first valid `@due(YYYY-MM-DD)` annotation in `text`
+fn due_annotation(text: &str) -> Option<(std::ops::Range<usize>, String)> {
+    let mut search_start = 0;
+    while let Some(start) = text[search_start..].find("@due(") {
+        let start = search_start + start;
+        let date_start = start + "@due(".len();
+        let date = text.get(date_start..date_start + 10);
+        if let (Some(date), Some(")")) = (date, text.get(date_start + 10..date_start + 11)) {
+            if is_date(date) {
+                return Some((start..date_start + 11, date.to_string()));
+            }
+        }
+        search_start = date_start;
+    }
+    None
+}
+
+/// Whether `date` is a valid date written `YYYY-MM-DD`
+fn is_date(date: &str) -> bool {
+    let is_digit_or_dash = |(i, b): (usize, &u8)| match i {
+        4 | 7 => *b == b'-',
+        _ => b.is_ascii_digit(),
+    };
+    if date.len() != 10 || !date.as_bytes().iter().enumerate().all(is_digit_or_dash) {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) =
+        (date[..4].parse::<i32>(), date[5..7].parse::<u8>(), date[8..].parse::<u8>())
+    else {
+        return false;
+    };
+    match Month::try_from(month) {
+        Ok(month) => Date::from_calendar_date(year, month, day).is_ok(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,296 @@
+use libsecp256k1::PublicKey;
+use lockbook_shared::access_info::UserAccessMode;
+use lockbook_shared::api::{
+    GetPublicKeyRequest, GetTeamsRequest, GetUsernameError, GetUsernameRequest, UpsertTeamRequest,
+};
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file::ShareMode;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::team::{TeamGrant, TeamMetadata, TeamShare};
+use lockbook_shared::tree_like::TreeLike;
+use uuid::Uuid;
+
+use crate::service::api_service::ApiError;
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// A group of users that files are shared with together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub id: Uuid,
+    pub name: String,
+    pub admin: String,
+    /// includes the admin
+    pub members: Vec<String>,
+    pub shares: Vec<TeamShare>,
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    pub(crate) fn get_teams(&mut self) -> LbResult<Vec<Team>> {
+        let mut result = Vec::new();
+        for team in self.fetch_teams()? {
+            result.push(self.team_info(team)?);
+        }
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(result)
+    }
+
+    pub(crate) fn create_team(&mut self, name: &str) -> LbResult<Team> {
+        if name.trim().is_empty() {
+            return Err(CoreError::TeamNameEmpty.into());
+        }
+        let team = TeamMetadata::new(name, self.get_account()?);
+        self.upsert_team(team.clone())?;
+        self.team_info(team)
+    }
+
+    pub(crate) fn add_team_member(&mut self, team: &str, username: &str) -> LbResult<()> {
+        let mut team = self.admin_team(team)?;
+        let member = self.public_key_of(username)?;
+        if !team.members.contains(&member) {
+            team.members.push(member);
+        }
+
+        // new members get access to everything already shared with the team, except files that
+        // have since been deleted; the team is saved even if sharing fails so it can undo what it
+        // shared
+        let mut result = Ok(());
+        for share in &mut team.shares {
+            if share.grants.iter().any(|grant| grant.member == member) {
+                continue;
+            }
+            match self.share_with_member(share, member) {
+                Ok(Some(grant)) => share.grants.push(grant),
+                Ok(None) => {}
+                Err(err) if err.kind == CoreError::FileNonexistent => {}
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+        self.upsert_team(team)?;
+        result
+    }
+
+    pub(crate) fn remove_team_member(&mut self, team: &str, username: &str) -> LbResult<()> {
+        let mut team = self.admin_team(team)?;
+        let member = self.public_key_of(username)?;
+        if member == team.admin {
+            return Err(CoreError::InsufficientPermission.into());
+        }
+        team.members.retain(|m| m != &member);
+
+        // only the shares the team made are undone; the member keeps anything shared with them
+        // directly, and whatever they already synced stays readable to them
+        // todo: rotate the keys of team shared folders the admin owns, re-encrypting descendants
+        // like accepting a transfer does, so a removed member can't read what's added later
+        for share in &mut team.shares {
+            if let Some(grant) = share.grants.iter().find(|g| g.member == member).cloned() {
+                self.unshare_with_member(share, &grant)?;
+                share.grants.retain(|g| g.member != member);
+            }
+        }
+        self.upsert_team(team)
+    }
+
+    pub(crate) fn share_file_with_team(
+        &mut self, id: Uuid, team: &str, mode: ShareMode,
+    ) -> LbResult<()> {
+        let mut team = self.admin_team(team)?;
+
+        // checked up front so a team with no other members can't record a share no one could accept
+        let tree = (&self.db.base_metadata).to_staged(&self.db.local_metadata);
+        if tree.find(&id)?.is_root() {
+            return Err(CoreError::RootModificationInvalid.into());
+        }
+
+        // members the team already shared the file with have their share changed to the new mode
+        let previous = team
+            .shares
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.grants.clone())
+            .unwrap_or_default();
+        let mut share = TeamShare { id, mode, grants: Vec::new() };
+        for &member in &team.members {
+            if member == team.admin {
+                continue;
+            }
+            if let Some(grant) = previous.iter().find(|g| g.member == member) {
+                self.reshare_with_member(&share, member)?;
+                share.grants.push(grant.clone());
+            } else if let Some(grant) = self.share_with_member(&share, member)? {
+                share.grants.push(grant);
+            }
+        }
+
+        team.shares.retain(|s| s.id != id);
+        team.shares.push(share);
+        self.upsert_team(team)
+    }
+
+    /// Shares a file with a member of a team, unless they can already access it as the team would.
+    /// Returns the grant to record if the file was shared.
+    fn share_with_member(
+        &mut self, share: &TeamShare, member: PublicKey,
+    ) -> LbResult<Option<TeamGrant>> {
+        let account = self.get_account()?.clone();
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&mut self.db.local_metadata)
+            .to_lazy();
+
+        let mode = access_mode(share.mode);
+        if tree.calculate_deleted(&share.id)? {
+            return Err(CoreError::FileNonexistent.into());
+        }
+        let file = tree.find(&share.id)?;
+        if file.owner() == Owner(member)
+            || tree.access_mode(Owner(member), &share.id)? >= Some(mode)
+        {
+            return Ok(None);
+        }
+
+        // sharing replaces the member's own share of the file, which can only be a read share
+        let replaced = file
+            .user_access_keys()
+            .iter()
+            .any(|k| !k.deleted && k.encrypted_for == member)
+            .then_some(ShareMode::Read);
+        tree.add_share(share.id, Owner(member), share.mode, &account)?;
+        Ok(Some(TeamGrant { member, replaced }))
+    }
+
+    /// Changes the share a team made with one of its members to the team's share mode.
+    fn reshare_with_member(&mut self, share: &TeamShare, member: PublicKey) -> LbResult<()> {
+        let account = self.get_account()?.clone();
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&mut self.db.local_metadata)
+            .to_lazy();
+
+        let mode = access_mode(share.mode);
+        let current = tree
+            .find(&share.id)?
+            .user_access_keys()
+            .iter()
+            .find(|k| !k.deleted && k.encrypted_for == member)
+            .map(|k| k.mode);
+        if current.is_some() && current != Some(mode) {
+            tree.add_share(share.id, Owner(member), share.mode, &account)?;
+        }
+        Ok(())
+    }
+
+    /// Undoes the share a team made with one of its members, restoring the share of theirs that it
+    /// replaced. A share that's been changed since is left as it is.
+    fn unshare_with_member(&mut self, share: &TeamShare, grant: &TeamGrant) -> LbResult<()> {
+        let account = self.get_account()?.clone();
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&mut self.db.local_metadata)
+            .to_lazy();
+
+        if tree.maybe_find(&share.id).is_none() || tree.calculate_deleted(&share.id)? {
+            return Ok(());
+        }
+        let mode = access_mode(share.mode);
+        let shared_by_team = tree
+            .find(&share.id)?
+            .user_access_keys()
+            .iter()
+            .any(|k| !k.deleted && k.encrypted_for == grant.member && k.mode == mode);
+        if !shared_by_team {
+            return Ok(());
+        }
+        match grant.replaced {
+            Some(replaced) => tree.add_share(share.id, Owner(grant.member), replaced, &account)?,
+            None => tree.delete_share(&share.id, Some(grant.member), &account)?,
+        }
+        Ok(())
+    }
+
+    /// Finds a team by name among the teams this user administers.
+    fn admin_team(&mut self, name: &str) -> LbResult<TeamMetadata> {
+        let me = self.get_public_key()?;
+        let mut is_member = false;
+        for team in self.fetch_teams()? {
+            if team.name == name {
+                if team.admin == me {
+                    return Ok(team);
+                }
+                is_member = true;
+            }
+        }
+        Err(if is_member { CoreError::InsufficientPermission } else { CoreError::TeamNonexistent }
+            .into())
+    }
+
+    fn fetch_teams(&mut self) -> LbResult<Vec<TeamMetadata>> {
+        let account = self.get_account()?;
+        Ok(self
+            .client
+            .request(account, GetTeamsRequest {})?
+            .teams
+            .into_iter()
+            .map(|team| team.timestamped_value.value)
+            .collect())
+    }
+
+    fn upsert_team(&mut self, team: TeamMetadata) -> LbResult<()> {
+        let account = self.get_account()?;
+        let team = team.sign(account)?;
+        self.client.request(account, UpsertTeamRequest { team })?;
+        Ok(())
+    }
+
+    fn team_info(&mut self, team: TeamMetadata) -> LbResult<Team> {
+        let mut members = Vec::new();
+        for member in &team.members {
+            members.push(self.username_of(*member)?);
+        }
+        Ok(Team {
+            id: team.id,
+            name: team.name,
+            admin: self.username_of(team.admin)?,
+            members,
+            shares: team.shares,
+        })
+    }
+
+    fn public_key_of(&mut self, username: &str) -> LbResult<PublicKey> {
+        let account = self.get_account()?;
+        let key = self
+            .client
+            .request(account, GetPublicKeyRequest { username: String::from(username) })?
+            .key;
+        self.db
+            .pub_key_lookup
+            .insert(Owner(key), String::from(username))?;
+        Ok(key)
+    }
+
+    fn username_of(&mut self, key: PublicKey) -> LbResult<String> {
+        let account = self.get_account()?;
+        if key == account.public_key() {
+            return Ok(account.username.clone());
+        }
+        if let Some(username) = self.db.pub_key_lookup.get().get(&Owner(key)) {
+            return Ok(username.clone());
+        }
+        let username = match self.client.request(account, GetUsernameRequest { key }) {
+            Ok(response) => response.username,
+            Err(ApiError::Endpoint(GetUsernameError::UserNotFound)) => String::from("<unknown>"),
+            Err(err) => return Err(err.into()),
+        };
+        self.db
+            .pub_key_lookup
+            .insert(Owner(key), username.clone())?;
+        Ok(username)
+    }
+}
+
+fn access_mode(mode: ShareMode) -> UserAccessMode {
+    match mode {
+        ShareMode::Read => UserAccessMode::Read,
+        ShareMode::Write => UserAccessMode::Write,
+    }
+}
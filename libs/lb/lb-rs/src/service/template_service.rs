@@ -0,0 +1,92 @@
+use chrono::Datelike;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file::File;
+use lockbook_shared::file_metadata::FileType;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// Documents in this folder, or in folders inside it, are offered as templates for new documents.
+pub const TEMPLATES_FOLDER_PATH: &str = "/templates/";
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    /// The documents in the templates folder and the folders inside it, ordered by path.
+    pub(crate) fn list_templates(&mut self) -> LbResult<Vec<File>> {
+        let folder = match self.get_by_path(TEMPLATES_FOLDER_PATH) {
+            Ok(folder) => folder,
+            Err(err) if err.kind == CoreError::FileNonexistent => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut result = Vec::new();
+        for file in self.get_and_get_children_recursively(&folder.id)? {
+            if file.is_document() {
+                result.push((self.get_path_by_id(file.id)?, file));
+            }
+        }
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(result.into_iter().map(|(_, file)| file).collect())
+    }
+
+    /// Creates a document named `name` in `parent` with the content of the document
+    /// `template_id`, in which `{{date}}`, `{{title}}` and `{{username}}` are replaced with today's
+    /// date, the new document's name without its extension, and the user's username.
+    pub(crate) fn create_from_template(
+        &mut self, template_id: Uuid, parent: Uuid, name: &str,
+    ) -> LbResult<File> {
+        let content = self.template_content(template_id, name, today())?;
+        let file = self.create_file(name, &parent, FileType::Document)?;
+        self.write_document(file.id, &content)?;
+        Ok(file)
+    }
+
+    /// The content of a new document named `name` created on `date` from the document
+    /// `template_id`.
+    pub(crate) fn template_content(
+        &mut self, template_id: Uuid, name: &str, date: Date,
+    ) -> LbResult<Vec<u8>> {
+        if !self.get_file_by_id(&template_id)?.is_document() {
+            return Err(CoreError::FileNotDocument.into());
+        }
+        let username = self
+            .db
+            .account
+            .get()
+            .ok_or(CoreError::AccountNonexistent)?
+            .username
+            .clone();
+
+        Ok(match String::from_utf8(self.read_document(template_id)?) {
+            Ok(template) => fill_template(&template, name, date, &username).into_bytes(),
+            // templates that aren't text e.g. drawings are copied as they are
+            Err(err) => err.into_bytes(),
+        })
+    }
+}
+
+/// The template with its variables replaced for a new document named `name`.
+pub fn fill_template(template: &str, name: &str, date: Date, username: &str) -> String {
+    let title = match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => name,
+    };
+    template
+        .replace("{{date}}", &format_date(date))
+        .replace("{{title}}", title)
+        .replace("{{username}}", username)
+}
+
+/// The date as YYYY-MM-DD.
+pub(crate) fn format_date(date: Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year(), date.month() as u8, date.day())
+}
+
+/// Today's date in the user's timezone, or in UTC if it can't be determined.
+pub fn today() -> Date {
+    // unlike `time`, chrono can find the local offset while other threads are running
+    let today = chrono::Local::now().date_naive();
+    Date::from_ordinal_date(today.year(), today.ordinal() as u16)
+        .unwrap_or_else(|_| OffsetDateTime::now_utc().date())
+}
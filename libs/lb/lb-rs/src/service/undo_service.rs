@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet};
+
+use lockbook_shared::clock::get_time;
+use lockbook_shared::document_repo::DocumentService;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::secret_filename::SecretFileName;
+use lockbook_shared::signed_file::SignedFile;
+use lockbook_shared::tree_like::TreeLike;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{CoreError, CoreState, LbResult, Requester};
+
+/// How many operations are kept in the journal; older operations can no longer be undone.
+const MAX_JOURNALED_OPERATIONS: usize = 100;
+
+/// A file tree operation as recorded in the local journal. Names are stored encrypted, the same
+/// way they are in file metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub op: JournaledOp,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournaledOp {
+    Rename { id: Uuid, old_name: SecretFileName, new_name: SecretFileName },
+    Move { id: Uuid, old_parent: Uuid, new_parent: Uuid },
+    Delete { id: Uuid },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentOperation {
+    pub op: FileOperation,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperation {
+    Rename { id: Uuid, old_name: String, new_name: String },
+    Move { id: Uuid, old_parent: Uuid, new_parent: Uuid },
+    Delete { id: Uuid },
+}
+
+impl JournaledOp {
+    fn id(&self) -> Uuid {
+        match self {
+            JournaledOp::Rename { id, .. } => *id,
+            JournaledOp::Move { id, .. } => *id,
+            JournaledOp::Delete { id } => *id,
+        }
+    }
+}
+
+impl<Client: Requester, Docs: DocumentService> CoreState<Client, Docs> {
+    pub(crate) fn journal_operation(&mut self, op: JournaledOp) -> LbResult<()> {
+        if self.db.op_journal.get().len() >= MAX_JOURNALED_OPERATIONS {
+            self.db.op_journal.remove(0)?;
+        }
+        self.db
+            .op_journal
+            .push(JournalEntry { op, timestamp: get_time().0 })?;
+        Ok(())
+    }
+
+    /// Most recent first.
+    pub(crate) fn list_recent_operations(&mut self) -> LbResult<Vec<RecentOperation>> {
+        let entries = self.db.op_journal.get().to_vec();
+        let mut result = Vec::new();
+        for entry in entries.into_iter().rev() {
+            match self.describe_operation(entry) {
+                Ok(op) => result.push(op),
+                Err(err) if err.kind == CoreError::FileNonexistent => continue, // pruned
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Reverts the most recent journaled operation and removes it from the journal. If the file has
+    /// not been synced since, its local changes are dropped once they match the synced version
+    /// again; otherwise the inverse operation is staged like any other local change. Returns the
+    /// operation that was undone, or `None` if there was nothing to undo. If the operation can't be
+    /// undone right now, e.g. because its old path was taken, it stays in the journal. Operations
+    /// on files that were since deleted can never be undone and are removed from it, and those on
+    /// files that were pruned aren't listed, so they're skipped.
+    pub(crate) fn undo_last_operation(&mut self) -> LbResult<Option<RecentOperation>> {
+        loop {
+            let entry = match self.db.op_journal.get().last() {
+                Some(entry) => entry.clone(),
+                None => return Ok(None),
+            };
+            let undone = match self.describe_operation(entry.clone()) {
+                Ok(undone) => undone,
+                Err(err) if err.kind == CoreError::FileNonexistent => {
+                    self.db.op_journal.pop()?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            match self.undo_operation(entry.op) {
+                Ok(()) => {}
+                Err(err) if err.kind == CoreError::OperationNotUndoable => {
+                    // it never will be
+                    self.db.op_journal.pop()?;
+                    return Err(err);
+                }
+                Err(err) => return Err(err),
+            }
+            self.db.op_journal.pop()?;
+
+            return Ok(Some(undone));
+        }
+    }
+
+    fn undo_operation(&mut self, op: JournaledOp) -> LbResult<()> {
+        let id = op.id();
+
+        let old_name = {
+            let mut tree = (&self.db.base_metadata)
+                .to_staged(&self.db.local_metadata)
+                .to_lazy();
+            let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+            // the file was pruned, or deleted since without the deletion being journaled first
+            let deleted = tree.maybe_find(&id).is_none()
+                || !matches!(op, JournaledOp::Delete { .. }) && tree.calculate_deleted(&id)?;
+            if deleted {
+                return Err(CoreError::OperationNotUndoable.into());
+            }
+            match &op {
+                JournaledOp::Rename { old_name, .. } => {
+                    Some(old_name.to_string(&tree.decrypt_key(&id, account)?)?)
+                }
+                _ => None,
+            }
+        };
+        // links are fixed like they are for the rename or move being undone
+        let links = match op {
+            JournaledOp::Delete { .. } => HashMap::new(),
+            _ => self.links_affected_by(&id, old_name.as_deref()),
+        };
+
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&mut self.db.local_metadata)
+            .to_lazy();
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+
+        match op {
+            JournaledOp::Rename { id, .. } => {
+                let old_name = old_name.unwrap_or_default();
+                tree.rename(&id, &old_name, account)?;
+            }
+            JournaledOp::Move { id, old_parent, .. } => {
+                tree.move_file(&id, &old_parent, account)?;
+            }
+            JournaledOp::Delete { id } => {
+                // deleted files are pruned after sync and the server rejects updates to them
+                let deleted_on_server = match self.db.base_metadata.get().get(&id) {
+                    Some(base) => base.explicitly_deleted(),
+                    None => false,
+                };
+                if deleted_on_server {
+                    return Err(CoreError::OperationNotUndoable.into());
+                }
+                let mut file = tree.find(&id)?.timestamped_value.value.clone();
+                file.is_deleted = false;
+                let file = file.sign(account)?;
+
+                // validated against the synced tree because local changes to deleted files are
+                // otherwise rejected
+                let mut local = self
+                    .db
+                    .local_metadata
+                    .get()
+                    .values()
+                    .filter(|f| f.id() != &id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                local.push(file.clone());
+                (&self.db.base_metadata)
+                    .to_staged(local)
+                    .to_lazy()
+                    .validate(Owner(account.public_key()))?;
+                self.db.local_metadata.insert(id, file)?;
+            }
+        }
+
+        self.revert_if_unmodified(&id)?;
+        self.fix_links_affected_by(links);
+        Ok(())
+    }
+
+    /// Discards the local changes to a file, restoring its metadata and content to the last synced
+    /// version. Reverting a file that was created locally also discards the local changes to its
+    /// descendants, which either did not exist before or were moved into it.
+    pub(crate) fn revert_local_change(&mut self, id: &Uuid) -> LbResult<()> {
+        if !self.db.local_metadata.get().contains_key(id) {
+            return if self.db.base_metadata.get().contains_key(id) {
+                Ok(()) // nothing to revert
+            } else {
+                Err(CoreError::FileNonexistent.into())
+            };
+        }
+
+        let mut reverted = HashSet::from([*id]);
+        if !self.db.base_metadata.get().contains_key(id) {
+            let mut tree = (&self.db.base_metadata)
+                .to_staged(&self.db.local_metadata)
+                .to_lazy();
+            for descendant in tree.descendants(id)? {
+                if self.db.local_metadata.get().contains_key(&descendant) {
+                    reverted.insert(descendant);
+                }
+            }
+        }
+
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let remaining = self
+            .db
+            .local_metadata
+            .get()
+            .values()
+            .filter(|f| !reverted.contains(f.id()))
+            .cloned()
+            .collect::<Vec<_>>();
+        (&self.db.base_metadata)
+            .to_staged(remaining)
+            .to_lazy()
+            .validate(Owner(account.public_key()))?;
+
+        self.discard_local_changes(reverted)
+    }
+
+    /// Discards all local changes, restoring every file to its last synced version.
+    pub(crate) fn revert_all_local_changes(&mut self) -> LbResult<()> {
+        let reverted = self.db.local_metadata.get().keys().copied().collect();
+        self.discard_local_changes(reverted)
+    }
+
+    fn discard_local_changes(&mut self, ids: HashSet<Uuid>) -> LbResult<()> {
+        let links = self.links_affected_by_reverting(&ids)?;
+
+        for id in &ids {
+            let local_hmac = self
+                .db
+                .local_metadata
+                .get()
+                .get(id)
+                .and_then(|f| f.document_hmac().copied());
+            let base_hmac = self
+                .db
+                .base_metadata
+                .get()
+                .get(id)
+                .and_then(|f| f.document_hmac().copied());
+            if local_hmac.is_some() && local_hmac != base_hmac {
+                self.docs.delete(id, local_hmac.as_ref())?;
+            }
+            self.db.local_metadata.remove(id)?;
+        }
+
+        // journaled operations on these files no longer describe the current state of the tree
+        for i in (0..self.db.op_journal.get().len()).rev() {
+            if ids.contains(&self.db.op_journal.get()[i].op.id()) {
+                self.db.op_journal.remove(i)?;
+            }
+        }
+
+        self.fix_links_affected_by(links);
+        Ok(())
+    }
+
+    /// The links that reverting the local renames and moves of `ids` could break, as
+    /// `links_affected_by` finds them for a rename or move.
+    fn links_affected_by_reverting(
+        &mut self, ids: &HashSet<Uuid>,
+    ) -> LbResult<HashMap<Uuid, Vec<(String, Uuid)>>> {
+        let account = self
+            .db
+            .account
+            .get()
+            .ok_or(CoreError::AccountNonexistent)?
+            .clone();
+        let mut result: HashMap<Uuid, Vec<(String, Uuid)>> = HashMap::new();
+        for id in ids {
+            let (base, local) =
+                match (self.db.base_metadata.get().get(id), self.db.local_metadata.get().get(id)) {
+                    (Some(base), Some(local)) => (base.clone(), local.clone()),
+                    _ => continue,
+                };
+            if base.parent() == local.parent() && base.secret_name() == local.secret_name() {
+                continue;
+            }
+            let base_name = (&self.db.base_metadata).to_lazy().name(id, &account)?;
+            for (from, links) in self.links_affected_by(id, Some(&base_name)) {
+                let affected = result.entry(from).or_default();
+                for link in links {
+                    if !affected.contains(&link) {
+                        affected.push(link);
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Drops the local version of a file if it is equivalent to the synced version.
+    fn revert_if_unmodified(&mut self, id: &Uuid) -> LbResult<()> {
+        let base = match self.db.base_metadata.get().get(id) {
+            Some(base) => base.clone(),
+            None => return Ok(()),
+        };
+        let local = match self.db.local_metadata.get().get(id) {
+            Some(local) => local.clone(),
+            None => return Ok(()),
+        };
+
+        let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+        let mut tree = (&self.db.base_metadata)
+            .to_staged(&self.db.local_metadata)
+            .to_lazy();
+        let key = tree.decrypt_key(id, account)?;
+
+        let access = |file: &SignedFile| {
+            file.user_access_keys()
+                .iter()
+                .map(|k| (Owner(k.encrypted_by), Owner(k.encrypted_for), k.mode, k.deleted))
+                .collect::<HashSet<_>>()
+        };
+
+        if base.parent() == local.parent()
+            && base.explicitly_deleted() == local.explicitly_deleted()
+            && base.document_hmac() == local.document_hmac()
+            && access(&base) == access(&local)
+            && base.secret_name().to_string(&key)? == local.secret_name().to_string(&key)?
+        {
+            self.db.local_metadata.remove(id)?;
+        }
+
+        Ok(())
+    }
+
+    fn describe_operation(&mut self, entry: JournalEntry) -> LbResult<RecentOperation> {
+        let op = match entry.op {
+            JournaledOp::Rename { id, old_name, new_name } => {
+                let mut tree = (&self.db.base_metadata)
+                    .to_staged(&self.db.local_metadata)
+                    .to_lazy();
+                let account = self.db.account.get().ok_or(CoreError::AccountNonexistent)?;
+                let key = tree.decrypt_key(&id, account)?;
+                FileOperation::Rename {
+                    id,
+                    old_name: old_name.to_string(&key)?,
+                    new_name: new_name.to_string(&key)?,
+                }
+            }
+            JournaledOp::Move { id, old_parent, new_parent } => {
+                FileOperation::Move { id, old_parent, new_parent }
+            }
+            JournaledOp::Delete { id } => FileOperation::Delete { id },
+        };
+        Ok(RecentOperation { op, timestamp: entry.timestamp })
+    }
+}
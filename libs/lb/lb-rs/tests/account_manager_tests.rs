@@ -0,0 +1,96 @@
+use lb_rs::{AccountManager, Core, CoreError};
+use test_utils::*;
+
+fn create_account(core: &Core) -> String {
+    let username = random_name();
+    core.create_account(&username, &url(), false).unwrap();
+    username
+}
+
+#[test]
+fn no_accounts() {
+    let manager = AccountManager::init(&test_config()).unwrap();
+    assert!(manager.list_accounts().unwrap().is_empty());
+    assert!(matches!(
+        manager.current().unwrap().get_account().unwrap_err().kind,
+        CoreError::AccountNonexistent
+    ));
+}
+
+#[test]
+fn multiple_accounts() {
+    let config = test_config();
+    let manager = AccountManager::init(&config).unwrap();
+    let personal = create_account(&manager.current().unwrap());
+    let work = create_account(&manager.new_account_core().unwrap());
+    assert_eq!(manager.list_accounts().unwrap(), vec![personal.clone(), work.clone()]);
+    assert_eq!(manager.current().unwrap().get_account().unwrap().username, personal);
+
+    manager.switch(&work).unwrap();
+    assert_eq!(manager.current().unwrap().get_account().unwrap().username, work);
+    drop(manager);
+
+    let manager = AccountManager::init(&config).unwrap();
+    assert_eq!(manager.list_accounts().unwrap(), vec![personal, work.clone()]);
+    assert_eq!(manager.current().unwrap().get_account().unwrap().username, work);
+}
+
+#[test]
+fn accounts_have_separate_storage() {
+    let manager = AccountManager::init(&test_config()).unwrap();
+    let personal = create_account(&manager.current().unwrap());
+    let work = create_account(&manager.new_account_core().unwrap());
+
+    manager
+        .get(&work)
+        .unwrap()
+        .create_at_path("/work.md")
+        .unwrap();
+    let personal = manager.get(&personal).unwrap();
+    assert!(matches!(
+        personal.get_by_path("/work.md").unwrap_err().kind,
+        CoreError::FileNonexistent
+    ));
+    assert_ne!(
+        personal.get_config().unwrap().writeable_path,
+        manager
+            .get(&work)
+            .unwrap()
+            .get_config()
+            .unwrap()
+            .writeable_path
+    );
+}
+
+#[test]
+fn new_account_core_reused_until_signed_in() {
+    let manager = AccountManager::init(&test_config()).unwrap();
+    create_account(&manager.current().unwrap());
+
+    let core = manager.new_account_core().unwrap();
+    let again = manager.new_account_core().unwrap();
+    assert_eq!(
+        core.get_config().unwrap().writeable_path,
+        again.get_config().unwrap().writeable_path
+    );
+}
+
+#[test]
+fn unknown_account() {
+    let manager = AccountManager::init(&test_config()).unwrap();
+    create_account(&manager.current().unwrap());
+
+    assert!(matches!(manager.get("nobody").err().unwrap().kind, CoreError::AccountNonexistent));
+    assert!(matches!(manager.switch("nobody").err().unwrap().kind, CoreError::AccountNonexistent));
+}
+
+#[test]
+fn existing_account_listed() {
+    let config = test_config();
+    let core = Core::init(&config).unwrap();
+    let username = create_account(&core);
+    drop(core);
+
+    let manager = AccountManager::init(&config).unwrap();
+    assert_eq!(manager.list_accounts().unwrap(), vec![username]);
+}
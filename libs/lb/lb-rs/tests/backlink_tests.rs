@@ -0,0 +1,181 @@
+use lb_rs::{Core, CoreError, Uuid};
+use test_utils::*;
+
+fn read(core: &Core, id: Uuid) -> String {
+    String::from_utf8(core.read_document(id).unwrap()).unwrap()
+}
+
+#[test]
+fn backlinks() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let by_name = core.create_at_path("/by_name.md").unwrap();
+    let by_path = core.create_at_path("/a/by_path.md").unwrap();
+    let by_id = core.create_at_path("/b/by_id.md").unwrap();
+    let unrelated = core.create_at_path("/unrelated.md").unwrap();
+
+    core.write_document(by_name.id, b"see [[target]]").unwrap();
+    core.write_document(by_path.id, b"see [the target](target.md#heading)")
+        .unwrap();
+    core.write_document(by_id.id, format!("![t](lb://{})", target.id).as_bytes())
+        .unwrap();
+    core.write_document(unrelated.id, b"`[[target]]` [target](https://target.md)")
+        .unwrap();
+
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![by_path.id, by_id.id, by_name.id]);
+    assert!(core.get_backlinks(by_name.id).unwrap().is_empty());
+}
+
+#[test]
+fn backlinks_update_on_write() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+
+    core.write_document(from.id, b"[[target]]").unwrap();
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+
+    core.write_document(from.id, b"no more links").unwrap();
+    assert!(core.get_backlinks(target.id).unwrap().is_empty());
+}
+
+#[test]
+fn backlinks_deleted_file() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[[target]]").unwrap();
+
+    core.delete_file(from.id).unwrap();
+    assert!(core.get_backlinks(target.id).unwrap().is_empty());
+
+    core.delete_file(target.id).unwrap();
+    assert_eq!(core.get_backlinks(target.id).unwrap_err().kind, CoreError::FileNonexistent);
+}
+
+#[test]
+fn backlinks_after_sync() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[[target]]").unwrap();
+    core.sync(None).unwrap();
+
+    let core2 = another_client(&core);
+    core2.sync(None).unwrap();
+    assert_eq!(core2.get_backlinks(target.id).unwrap(), vec![from.id]);
+
+    core.write_document(from.id, b"unlinked").unwrap();
+    core.sync(None).unwrap();
+    core2.sync(None).unwrap();
+    assert!(core2.get_backlinks(target.id).unwrap().is_empty());
+}
+
+#[test]
+fn link_graph() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let b = core.create_at_path("/b.md").unwrap();
+    let image = core.create_at_path("/image.png").unwrap();
+
+    core.write_document(a.id, b"[[b]] [[b#heading]] ![img](image.png) [[missing]]")
+        .unwrap();
+    core.write_document(b.id, b"[back](<a.md>) [[#local heading]]")
+        .unwrap();
+
+    let graph = core.link_graph().unwrap();
+    let mut expected_links = vec![(a.id, b.id), (a.id, image.id), (b.id, a.id)];
+    expected_links.sort();
+    assert_eq!(graph.links, expected_links);
+    assert_eq!(graph.broken_links, vec![(a.id, "missing".to_string())]);
+}
+
+#[test]
+fn rename_fixes_links_by_name() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"see [[target|the target]] and [[target#heading]]")
+        .unwrap();
+
+    core.rename_file(target.id, "renamed.md").unwrap();
+
+    assert_eq!(read(&core, from.id), "see [[renamed|the target]] and [[renamed#heading]]");
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
+
+#[test]
+fn rename_fixes_links_by_path() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/a/").unwrap();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[t](a/target.md) [[/a/target]]")
+        .unwrap();
+
+    core.rename_file(folder.id, "new folder").unwrap();
+
+    assert_eq!(read(&core, from.id), "[t](new%20folder/target.md) [[/new folder/target]]");
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
+
+#[test]
+fn move_fixes_links_to_file() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let folder = core.create_at_path("/b/c/").unwrap();
+    let from = core.create_at_path("/a/from.md").unwrap();
+    core.write_document(from.id, b"[t](./target.md)").unwrap();
+
+    core.move_file(target.id, folder.id).unwrap();
+
+    assert_eq!(read(&core, from.id), "[t](../b/c/target.md)");
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
+
+#[test]
+fn move_fixes_links_from_file() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let folder = core.create_at_path("/b/").unwrap();
+    let from = core.create_at_path("/a/from.md").unwrap();
+    core.write_document(from.id, b"[t](./target.md) [[target]]")
+        .unwrap();
+
+    core.move_file(from.id, folder.id).unwrap();
+
+    // links by name still resolve
+    assert_eq!(read(&core, from.id), "[t](../a/target.md) [[target]]");
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
+
+#[test]
+fn rename_leaves_working_links() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/target.md").unwrap();
+    let other = core.create_at_path("/other.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    let content = format!("[[lb://{}]] [[other]] [[missing]]", target.id);
+    core.write_document(from.id, content.as_bytes()).unwrap();
+
+    core.rename_file(target.id, "renamed.md").unwrap();
+    core.rename_file(other.id, "other2.md").unwrap();
+    core.rename_file(other.id, "other.md").unwrap();
+
+    assert_eq!(read(&core, from.id), content);
+}
+
+#[test]
+fn rename_fixes_links_captured_by_new_name() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let other = core.create_at_path("/other.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[[target]]").unwrap();
+
+    // a document with a shorter path would now be linked instead
+    core.rename_file(other.id, "target.md").unwrap();
+
+    assert_eq!(read(&core, from.id), "[[a/target]]");
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
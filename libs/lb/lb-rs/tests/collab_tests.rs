@@ -0,0 +1,219 @@
+use lb_rs::service::api_service::Requester;
+use lb_rs::{CollabSession, Core, CoreError, RemoteCursor, ShareMode, TextEdit, Uuid};
+use lockbook_shared::api::UpdatePresenceRequest;
+use test_utils::*;
+
+/// a document owned by the first core and shared with the second with `mode`, synced to both
+fn shared_document(content: &str, mode: ShareMode) -> (Core, Core, Uuid) {
+    let cores = [test_core_with_account(), test_core_with_account()];
+    let sharee = cores[1].get_account().unwrap();
+
+    let document = cores[0].create_at_path("/document.md").unwrap();
+    cores[0]
+        .write_document(document.id, content.as_bytes())
+        .unwrap();
+    cores[0]
+        .share_file(document.id, &sharee.username, mode)
+        .unwrap();
+    cores[0].sync(None).unwrap();
+    cores[1].sync(None).unwrap();
+
+    let [sharer, sharee] = cores;
+    (sharer, sharee, document.id)
+}
+
+/// syncs a session and applies the edits it received to `text`, which should match the session's
+fn sync(core: &Core, session: &CollabSession, text: &mut String) {
+    core.sync_collab(session).unwrap();
+    for TextEdit { range, text: replacement } in session.receive() {
+        text.replace_range(range.0..range.1, &replacement);
+    }
+    assert_eq!(*text, session.text());
+}
+
+#[test]
+fn collab_edits() {
+    let (sharer, sharee, id) = shared_document("hello", ShareMode::Write);
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+    let (mut a_text, mut b_text) = (a.text(), b.text());
+    assert_eq!(a_text, "hello");
+    assert_eq!(b_text, "hello");
+
+    a.replace((5, 5), " world");
+    a_text = a.text();
+    sync(&sharer, &a, &mut a_text);
+    sync(&sharee, &b, &mut b_text);
+    assert_eq!(b_text, "hello world");
+
+    b.replace((0, 5), "goodbye");
+    b_text = b.text();
+    sync(&sharee, &b, &mut b_text);
+    sync(&sharer, &a, &mut a_text);
+    assert_eq!(a_text, "goodbye world");
+}
+
+#[test]
+fn collab_concurrent_edits_converge() {
+    let (sharer, sharee, id) = shared_document("one two", ShareMode::Write);
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+
+    a.replace((3, 3), " and a half");
+    b.replace((7, 7), " three");
+    b.replace((0, 3), "zero");
+    let (mut a_text, mut b_text) = (a.text(), b.text());
+    sync(&sharer, &a, &mut a_text);
+    sync(&sharee, &b, &mut b_text);
+    sync(&sharer, &a, &mut a_text);
+
+    assert_eq!(a_text, "zero and a half two three");
+    assert_eq!(b_text, "zero and a half two three");
+}
+
+#[test]
+fn collab_join_adopts_session_text() {
+    let (sharer, sharee, id) = shared_document("draft", ShareMode::Write);
+
+    let a = sharer.start_collab(id).unwrap();
+    a.replace((0, 5), "final");
+    sharer.sync_collab(&a).unwrap();
+
+    // the sharee's copy of the document is out of date, but the session's text is not
+    let b = sharee.start_collab(id).unwrap();
+    assert_eq!(String::from_utf8(sharee.read_document(id).unwrap()).unwrap(), "draft");
+    assert_eq!(b.text(), "final");
+}
+
+#[test]
+fn collab_join_merges_earlier_edits() {
+    let (sharer, sharee, id) = shared_document("one two", ShareMode::Write);
+
+    let a = sharer.start_collab(id).unwrap();
+    a.replace((7, 7), " three");
+    sharer.sync_collab(&a).unwrap();
+
+    // the sharee typed before joining, e.g. while offline
+    let b = sharee.start_collab(id).unwrap();
+    b.merge("one two", "zero two");
+    assert_eq!(b.text(), "zero two three");
+
+    let (mut a_text, mut b_text) = (a.text(), b.text());
+    sync(&sharee, &b, &mut b_text);
+    sync(&sharer, &a, &mut a_text);
+    assert_eq!(a_text, "zero two three");
+}
+
+#[test]
+fn collab_long_session_restarts() {
+    let (sharer, sharee, id) = shared_document("one\nand\ntwo\n", ShareMode::Write);
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+    b.replace((0, 3), "1");
+    let mut a_text = a.text();
+
+    // the session ends once its log is full and is restarted from the editor's text
+    for _ in 0..1000 {
+        a.replace((a_text.len() - 1, a_text.len() - 1), "!");
+        a_text = a.text();
+        sync(&sharer, &a, &mut a_text);
+    }
+
+    // edits that weren't sent before the session ended aren't lost
+    let mut b_text = b.text();
+    sync(&sharee, &b, &mut b_text);
+    sync(&sharee, &b, &mut b_text);
+    sync(&sharer, &a, &mut a_text);
+    assert_eq!(a_text, format!("1\nand\ntwo{}\n", "!".repeat(1000)));
+    assert_eq!(b_text, a_text);
+}
+
+#[test]
+fn collab_read_share() {
+    let (sharer, sharee, id) = shared_document("text", ShareMode::Read);
+
+    // readers can't start a session, but can follow one
+    assert!(matches!(sharee.start_collab(id).unwrap_err().kind, CoreError::InsufficientPermission));
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+    a.replace((4, 4), "!");
+    sharer.sync_collab(&a).unwrap();
+
+    let mut b_text = b.text();
+    sync(&sharee, &b, &mut b_text);
+    assert_eq!(b_text, "text!");
+
+    // their own edits aren't shared, but don't stop them from receiving others'
+    b.replace((5, 5), " mine");
+    a.replace((0, 0), "the ");
+    b_text = b.text();
+    sharer.sync_collab(&a).unwrap();
+    sync(&sharee, &b, &mut b_text);
+    assert_eq!(b_text, "the text! mine");
+}
+
+#[test]
+fn collab_presence() {
+    let (sharer, sharee, id) = shared_document("hello world", ShareMode::Write);
+    let sharer_username = sharer.get_account().unwrap().username;
+    let sharee_username = sharee.get_account().unwrap().username;
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+    a.set_selection((6, 11));
+    sharer.sync_collab(&a).unwrap();
+    sharee.sync_collab(&b).unwrap();
+    sharer.sync_collab(&a).unwrap();
+    assert_eq!(b.cursors(), vec![RemoteCursor { username: sharer_username, selection: (6, 11) }]);
+    assert_eq!(a.cursors(), vec![RemoteCursor { username: sharee_username, selection: (0, 0) }]);
+
+    // selections stay around the same text as it's edited
+    b.replace((0, 0), "oh, ");
+    assert_eq!(b.cursors()[0].selection, (10, 15));
+
+    // editors that close the document stop being shown
+    sharer.leave_collab(&a).unwrap();
+    sharee.sync_collab(&b).unwrap();
+    assert_eq!(b.cursors(), vec![]);
+}
+
+#[test]
+fn collab_presence_of_other_user_unchanged() {
+    let (sharer, sharee, id) = shared_document("hello world", ShareMode::Write);
+    let sharer_username = sharer.get_account().unwrap().username;
+
+    let a = sharer.start_collab(id).unwrap();
+    let b = sharee.start_collab(id).unwrap();
+    a.set_selection((6, 11));
+    sharer.sync_collab(&a).unwrap();
+    sharee.sync_collab(&b).unwrap();
+
+    // another user who replays an editor's client id can't close it for them
+    let account = sharee.get_account().unwrap();
+    sharee
+        .in_tx(|s| {
+            let request = UpdatePresenceRequest { id, client: Uuid::new_v4(), presence: None };
+            let others = s.client.request(&account, request).unwrap().others;
+            for other in others {
+                let request = UpdatePresenceRequest { id, client: other.client, presence: None };
+                s.client.request(&account, request).unwrap();
+            }
+            Ok(())
+        })
+        .unwrap();
+    sharee.sync_collab(&b).unwrap();
+    assert_eq!(b.cursors(), vec![RemoteCursor { username: sharer_username, selection: (6, 11) }]);
+}
+
+#[test]
+fn collab_folder() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    core.sync(None).unwrap();
+
+    assert!(matches!(core.start_collab(folder.id).unwrap_err().kind, CoreError::FileNotDocument));
+}
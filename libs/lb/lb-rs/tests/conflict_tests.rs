@@ -0,0 +1,160 @@
+use lb_rs::{find_conflict_hunks, resolve_conflict_hunks, ConflictChoice, Core, File};
+use test_utils::*;
+
+/// Makes conflicting edits to the same line of a document on two clients and syncs both.
+fn conflicting_edit() -> (Core, Core, File) {
+    let core1 = test_core_with_account();
+    let document = core1.create_at_path("/document.md").unwrap();
+    core1
+        .write_document(document.id, b"title\nline\nfooter\n")
+        .unwrap();
+    core1.sync(None).unwrap();
+    let core2 = test_core_from(&core1);
+
+    core1
+        .write_document(document.id, b"title\nremote line\nfooter\n")
+        .unwrap();
+    core1.sync(None).unwrap();
+    core2
+        .write_document(document.id, b"title\nlocal line\nfooter\n")
+        .unwrap();
+
+    (core1, core2, document)
+}
+
+#[test]
+fn sync_reports_conflicts() {
+    let (_core1, core2, document) = conflicting_edit();
+
+    let status = core2.sync(None).unwrap();
+    assert_eq!(status.conflicts, vec![document.id]);
+
+    let conflicts = core2.get_conflicts().unwrap();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].id, document.id);
+    assert_eq!(conflicts[0].hunks.len(), 1);
+    assert_eq!(conflicts[0].hunks[0].mine, "local line\n");
+    assert_eq!(conflicts[0].hunks[0].theirs, "remote line\n");
+}
+
+#[test]
+fn clean_merge_reports_no_conflicts() {
+    let core1 = test_core_with_account();
+    let document = core1.create_at_path("/document.md").unwrap();
+    core1.write_document(document.id, b"a\nb\nc\n").unwrap();
+    core1.sync(None).unwrap();
+    let core2 = test_core_from(&core1);
+
+    core1.write_document(document.id, b"a2\nb\nc\n").unwrap();
+    core1.sync(None).unwrap();
+    core2.write_document(document.id, b"a\nb\nc2\n").unwrap();
+
+    assert!(core2.sync(None).unwrap().conflicts.is_empty());
+    assert!(core2.get_conflicts().unwrap().is_empty());
+    assert_eq!(core2.read_document(document.id).unwrap(), b"a2\nb\nc2\n");
+}
+
+#[test]
+fn resolve_keep_mine() {
+    let (core1, core2, document) = conflicting_edit();
+    core2.sync(None).unwrap();
+
+    core2
+        .resolve_conflict(document.id, ConflictChoice::KeepMine)
+        .unwrap();
+
+    assert_eq!(core2.read_document(document.id).unwrap(), b"title\nlocal line\nfooter\n");
+    assert!(core2.get_conflicts().unwrap().is_empty());
+
+    core2.sync(None).unwrap();
+    core1.sync(None).unwrap();
+    assert_eq!(core1.read_document(document.id).unwrap(), b"title\nlocal line\nfooter\n");
+}
+
+#[test]
+fn resolve_keep_theirs() {
+    let (_core1, core2, document) = conflicting_edit();
+    core2.sync(None).unwrap();
+
+    core2
+        .resolve_conflict(document.id, ConflictChoice::KeepTheirs)
+        .unwrap();
+
+    assert_eq!(core2.read_document(document.id).unwrap(), b"title\nremote line\nfooter\n");
+    assert!(core2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn resolve_keep_both() {
+    let (_core1, core2, document) = conflicting_edit();
+    core2.sync(None).unwrap();
+
+    core2
+        .resolve_conflict(document.id, ConflictChoice::KeepBoth)
+        .unwrap();
+
+    assert_eq!(
+        core2.read_document(document.id).unwrap(),
+        b"title\nlocal line\nremote line\nfooter\n"
+    );
+    assert!(core2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn conflicts_resolved_by_editing_are_forgotten() {
+    let (_core1, core2, document) = conflicting_edit();
+    core2.sync(None).unwrap();
+
+    core2
+        .write_document(document.id, b"title\nmerged by hand\nfooter\n")
+        .unwrap();
+
+    assert!(core2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn conflicts_in_deleted_documents_are_forgotten() {
+    let (_core1, core2, document) = conflicting_edit();
+    core2.sync(None).unwrap();
+
+    core2.delete_file(document.id).unwrap();
+
+    assert!(core2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn find_hunks() {
+    let text = "a\n<<<<<<< ours\nmine\n=======\ntheirs\n>>>>>>> theirs\nb\n";
+    let hunks = find_conflict_hunks(text);
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].mine, "mine\n");
+    assert_eq!(hunks[0].theirs, "theirs\n");
+    assert_eq!(
+        &text[hunks[0].range.clone()],
+        "<<<<<<< ours\nmine\n=======\ntheirs\n>>>>>>> theirs\n"
+    );
+}
+
+#[test]
+fn find_hunks_with_original() {
+    let text = "<<<<<<< ours\nmine\n||||||| original\nbase\n=======\ntheirs\n>>>>>>> theirs";
+    let hunks = find_conflict_hunks(text);
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].mine, "mine\n");
+    assert_eq!(hunks[0].theirs, "theirs\n");
+    assert_eq!(hunks[0].range, 0..text.len());
+}
+
+#[test]
+fn find_hunks_ignores_unterminated() {
+    assert!(find_conflict_hunks("<<<<<<< ours\nmine\n=======\ntheirs\n").is_empty());
+    assert!(find_conflict_hunks("=======\n>>>>>>> theirs\n").is_empty());
+}
+
+#[test]
+fn resolve_hunks() {
+    let text = "<<<<<<< ours\n1\n=======\n2\n>>>>>>> theirs\nx\n<<<<<<< ours\n3\n=======\n4\n>>>>>>> theirs\n";
+    assert_eq!(resolve_conflict_hunks(text, ConflictChoice::KeepMine), "1\nx\n3\n");
+    assert_eq!(resolve_conflict_hunks(text, ConflictChoice::KeepTheirs), "2\nx\n4\n");
+    assert_eq!(resolve_conflict_hunks(text, ConflictChoice::KeepBoth), "1\n2\nx\n3\n4\n");
+}
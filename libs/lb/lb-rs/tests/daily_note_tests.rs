@@ -0,0 +1,154 @@
+use lb_rs::{daily_note_path, Core, CoreError, Date, DEFAULT_DAILY_NOTE_PATTERN};
+use test_utils::*;
+use time::Month;
+
+fn date() -> Date {
+    Date::from_calendar_date(2024, Month::March, 7).unwrap()
+}
+
+fn read(core: &Core, path: &str) -> String {
+    let id = core.get_by_path(path).unwrap().id;
+    String::from_utf8(core.read_document(id).unwrap()).unwrap()
+}
+
+#[test]
+fn daily_note_paths() {
+    assert_eq!(
+        daily_note_path(DEFAULT_DAILY_NOTE_PATTERN, date()),
+        "/journal/2024/03/2024-03-07.md"
+    );
+    assert_eq!(daily_note_path("/{{year}}/{{month}}-{{day}}.md", date()), "/2024/03-07.md");
+}
+
+#[test]
+fn get_or_create_daily_note() {
+    let core = test_core_with_account();
+
+    let note = core
+        .get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    assert_eq!(core.get_path_by_id(note.id).unwrap(), "/journal/2024/03/2024-03-07.md");
+
+    core.write_document(note.id, b"dear diary").unwrap();
+    let again = core
+        .get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    assert_eq!(again.id, note.id);
+    assert_eq!(read(&core, "/journal/2024/03/2024-03-07.md"), "dear diary");
+}
+
+#[test]
+fn daily_note_from_template() {
+    let core = test_core_with_account();
+    let template = core.create_at_path("/templates/daily.md").unwrap();
+    core.write_document(template.id, b"# {{title}}\n").unwrap();
+
+    core.get_or_create_daily_note(date(), "/days/{{date}}.md", Some(template.id))
+        .unwrap();
+    assert_eq!(read(&core, "/days/2024-03-07.md"), "# 2024-03-07\n");
+}
+
+#[test]
+fn daily_note_folder() {
+    let core = test_core_with_account();
+    core.create_at_path("/journal/2024-03-07/").unwrap();
+
+    assert!(matches!(
+        core.get_or_create_daily_note(date(), "/journal/{{date}}", None)
+            .unwrap_err()
+            .kind,
+        CoreError::FileNotDocument
+    ));
+    assert!(matches!(
+        core.get_or_create_daily_note(date(), "/journal/{{year}}/", None)
+            .unwrap_err()
+            .kind,
+        CoreError::FileNotDocument
+    ));
+}
+
+#[test]
+fn concurrent_create_daily_notes() {
+    let c1 = test_core_with_account();
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+
+    let note = c1
+        .get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    c1.write_document(note.id, b"morning\n").unwrap();
+    let note = c2
+        .get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    c2.write_document(note.id, b"evening\n").unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert::all_paths(
+        &c2,
+        &[
+            "/",
+            "/journal/",
+            "/journal/2024/",
+            "/journal/2024/03/",
+            "/journal/2024/03/2024-03-07.md",
+        ],
+    );
+    let content = read(&c2, "/journal/2024/03/2024-03-07.md");
+    assert!(content.contains("morning") && content.contains("evening"));
+}
+
+#[test]
+fn concurrent_create_daily_notes_same_content() {
+    let c1 = test_core_with_account();
+    let c2 = another_client(&c1);
+    c1.create_at_path("/journal/").unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    c1.get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    c2.get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    c2.get_or_create_daily_note(date().next_day().unwrap(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert::all_paths(
+        &c2,
+        &[
+            "/",
+            "/journal/",
+            "/journal/2024/",
+            "/journal/2024/03/",
+            "/journal/2024/03/2024-03-07.md",
+            "/journal/2024/03/2024-03-08.md",
+        ],
+    );
+    assert!(c2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn concurrent_create_documents_still_renamed() {
+    let c1 = test_core_with_account();
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+    c1.get_or_create_daily_note(date(), DEFAULT_DAILY_NOTE_PATTERN, None)
+        .unwrap();
+    c2.create_at_path("/journal/2024/03/2024-03-07.md").unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert::all_paths(
+        &c2,
+        &[
+            "/",
+            "/journal/",
+            "/journal-1/",
+            "/journal/2024/",
+            "/journal-1/2024/",
+            "/journal/2024/03/",
+            "/journal-1/2024/03/",
+            "/journal/2024/03/2024-03-07.md",
+            "/journal-1/2024/03/2024-03-07.md",
+        ],
+    );
+}
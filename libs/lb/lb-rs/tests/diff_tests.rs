@@ -0,0 +1,167 @@
+use lb_rs::{
+    ColorAlias, CoreError, DiffAgainst, DiffLineKind, DiffWord, DocumentDiff, Drawing, Stroke,
+};
+use test_utils::*;
+
+fn stroke(x: f32) -> Stroke {
+    Stroke {
+        points_x: vec![x, x + 10.0],
+        points_y: vec![0.0, 10.0],
+        points_girth: vec![1.0, 1.0],
+        color: ColorAlias::Black,
+        alpha: 1.0,
+    }
+}
+
+#[test]
+fn diff_unchanged_document() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"a\nb\nc\n").unwrap();
+    core.sync(None).unwrap();
+
+    let diff = core.diff_document(document.id, DiffAgainst::Base).unwrap();
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn diff_text_against_base() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"1\n2\n3\n4\n5\n6\n7\n8\n9\n")
+        .unwrap();
+    core.sync(None).unwrap();
+    core.write_document(document.id, b"1\n2\n3\n4\nfive\n6\n7\n8\n9\n")
+        .unwrap();
+
+    let hunks = match core.diff_document(document.id, DiffAgainst::Base).unwrap() {
+        DocumentDiff::Text { hunks } => hunks,
+        diff => panic!("expected text diff, got {:?}", diff),
+    };
+    assert_eq!(hunks.len(), 1);
+    assert_eq!((hunks[0].old_start, hunks[0].old_len), (2, 7));
+    assert_eq!((hunks[0].new_start, hunks[0].new_len), (2, 7));
+
+    let changes = hunks[0]
+        .lines
+        .iter()
+        .filter(|line| line.kind != DiffLineKind::Context)
+        .map(|line| (line.kind, line.text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(changes, vec![(DiffLineKind::Deleted, "5"), (DiffLineKind::Inserted, "five")]);
+}
+
+#[test]
+fn diff_text_words() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"the quick brown fox\n")
+        .unwrap();
+    core.sync(None).unwrap();
+    core.write_document(document.id, b"the slow brown fox\n")
+        .unwrap();
+
+    let hunks = match core.diff_document(document.id, DiffAgainst::Base).unwrap() {
+        DocumentDiff::Text { hunks } => hunks,
+        diff => panic!("expected text diff, got {:?}", diff),
+    };
+    let word = |text: &str, changed| DiffWord { text: text.to_string(), changed };
+    assert_eq!(
+        hunks[0].lines[0].words,
+        vec![word("the ", false), word("quick", true), word(" brown fox", false)]
+    );
+    assert_eq!(
+        hunks[0].lines[1].words,
+        vec![word("the ", false), word("slow", true), word(" brown fox", false)]
+    );
+}
+
+#[test]
+fn diff_new_document() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"a\nb\n").unwrap();
+
+    let hunks = match core.diff_document(document.id, DiffAgainst::Base).unwrap() {
+        DocumentDiff::Text { hunks } => hunks,
+        diff => panic!("expected text diff, got {:?}", diff),
+    };
+    assert_eq!(hunks.len(), 1);
+    assert!(hunks[0]
+        .lines
+        .iter()
+        .all(|line| line.kind == DiffLineKind::Inserted));
+    assert_eq!(hunks[0].lines.len(), 2);
+}
+
+#[test]
+fn diff_against_other_document() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    let other = core.create_at_path("/other.md").unwrap();
+    core.write_document(document.id, b"a\nb\n").unwrap();
+    core.write_document(other.id, b"a\nc\n").unwrap();
+
+    let hunks = match core
+        .diff_document(document.id, DiffAgainst::Document(other.id))
+        .unwrap()
+    {
+        DocumentDiff::Text { hunks } => hunks,
+        diff => panic!("expected text diff, got {:?}", diff),
+    };
+    let changes = hunks[0]
+        .lines
+        .iter()
+        .filter(|line| line.kind != DiffLineKind::Context)
+        .map(|line| (line.kind, line.text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(changes, vec![(DiffLineKind::Deleted, "c"), (DiffLineKind::Inserted, "b")]);
+}
+
+#[test]
+fn diff_drawing() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/drawing.draw").unwrap();
+    let mut drawing = Drawing { strokes: vec![stroke(0.0), stroke(20.0)], ..Default::default() };
+    core.save_drawing(document.id, &drawing).unwrap();
+    core.sync(None).unwrap();
+
+    drawing.strokes = vec![stroke(0.0), stroke(40.0), stroke(60.0)];
+    core.save_drawing(document.id, &drawing).unwrap();
+
+    match core.diff_document(document.id, DiffAgainst::Base).unwrap() {
+        DocumentDiff::Drawing(diff) => {
+            assert_eq!(diff.strokes_added, 2);
+            assert_eq!(diff.strokes_removed, 1);
+            assert_eq!(diff.strokes_unchanged, 1);
+        }
+        diff => panic!("expected drawing diff, got {:?}", diff),
+    }
+}
+
+#[test]
+fn diff_other() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/data.bin").unwrap();
+    core.write_document(document.id, &[0, 1, 2]).unwrap();
+    core.sync(None).unwrap();
+    core.write_document(document.id, &[0, 1, 2, 3]).unwrap();
+
+    assert_eq!(
+        core.diff_document(document.id, DiffAgainst::Base).unwrap(),
+        DocumentDiff::Other { old_size: 3, new_size: 4, identical: false }
+    );
+}
+
+#[test]
+fn diff_folder() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+
+    assert_eq!(
+        core.diff_document(folder.id, DiffAgainst::Base)
+            .unwrap_err()
+            .kind,
+        CoreError::FileNotDocument
+    );
+}
@@ -0,0 +1,130 @@
+use lb_rs::CoreError;
+use test_utils::*;
+
+#[test]
+fn resolve_link_by_id() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/from.md").unwrap();
+    let target = core.create_at_path("/folder/target.md").unwrap();
+
+    assert_eq!(
+        core.resolve_link(Some(from.id), &format!("lb://{}", target.id))
+            .unwrap(),
+        target.id
+    );
+}
+
+#[test]
+fn resolve_link_by_id_survives_rename_and_move() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/from.md").unwrap();
+    let target = core.create_at_path("/folder/target.md").unwrap();
+    let other_folder = core.create_at_path("/other/").unwrap();
+    core.rename_file(target.id, "renamed.md").unwrap();
+    core.move_file(target.id, other_folder.id).unwrap();
+
+    assert_eq!(
+        core.resolve_link(Some(from.id), &format!("lb://{}", target.id))
+            .unwrap(),
+        target.id
+    );
+}
+
+#[test]
+fn resolve_link_by_id_deleted() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/from.md").unwrap();
+    let target = core.create_at_path("/target.md").unwrap();
+    core.delete_file(target.id).unwrap();
+
+    assert_eq!(
+        core.resolve_link(Some(from.id), &format!("lb://{}", target.id))
+            .unwrap_err()
+            .kind,
+        CoreError::FileNonexistent
+    );
+}
+
+#[test]
+fn resolve_link_relative_path() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/a/from.md").unwrap();
+    let sibling = core.create_at_path("/a/sibling.md").unwrap();
+    let nested = core.create_at_path("/a/b/nested.md").unwrap();
+    let cousin = core.create_at_path("/c/cousin.md").unwrap();
+
+    assert_eq!(core.resolve_link(Some(from.id), "sibling.md").unwrap(), sibling.id);
+    assert_eq!(core.resolve_link(Some(from.id), "./sibling").unwrap(), sibling.id);
+    assert_eq!(core.resolve_link(Some(from.id), "b/nested").unwrap(), nested.id);
+    assert_eq!(core.resolve_link(Some(from.id), "../c/cousin.md").unwrap(), cousin.id);
+}
+
+#[test]
+fn resolve_link_absolute_path() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/a/from.md").unwrap();
+    let target = core.create_at_path("/target.md").unwrap();
+    core.create_at_path("/a/target.md").unwrap();
+
+    assert_eq!(core.resolve_link(Some(from.id), "/target").unwrap(), target.id);
+}
+
+#[test]
+fn resolve_link_by_name() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/a/from.md").unwrap();
+    core.create_at_path("/b/c/Note Name.md").unwrap();
+    let shallow = core.create_at_path("/d/Note Name.md").unwrap();
+
+    assert_eq!(core.resolve_link(Some(from.id), "Note Name").unwrap(), shallow.id);
+}
+
+#[test]
+fn resolve_link_prefers_relative_path() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/a/b/from.md").unwrap();
+    core.create_at_path("/note.md").unwrap();
+    let sibling = core.create_at_path("/a/b/note.md").unwrap();
+
+    assert_eq!(core.resolve_link(Some(from.id), "note").unwrap(), sibling.id);
+}
+
+#[test]
+fn resolve_link_without_from() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+
+    assert_eq!(core.resolve_link(None, "a/target").unwrap(), target.id);
+    assert_eq!(core.resolve_link(None, "target").unwrap(), target.id);
+}
+
+#[test]
+fn resolve_link_folder() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.create_at_path("/folder/").unwrap();
+
+    assert_eq!(
+        core.resolve_link(Some(from.id), "folder").unwrap_err().kind,
+        CoreError::FileNonexistent
+    );
+}
+
+#[test]
+fn resolve_link_nonexistent() {
+    let core = test_core_with_account();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.create_at_path("/a/note.md").unwrap();
+
+    assert_eq!(
+        core.resolve_link(Some(from.id), "missing")
+            .unwrap_err()
+            .kind,
+        CoreError::FileNonexistent
+    );
+    // paths are not searched for by name
+    assert_eq!(
+        core.resolve_link(Some(from.id), "b/note").unwrap_err().kind,
+        CoreError::FileNonexistent
+    );
+}
@@ -0,0 +1,148 @@
+use lb_rs::{Core, CoreError, Permission, ShareMode};
+use test_utils::*;
+use uuid::Uuid;
+
+fn username(core: &Core) -> String {
+    core.get_account().unwrap().username
+}
+
+fn owner(core: &Core) -> Permission {
+    Permission { username: username(core), mode: ShareMode::Write, granted_by: None }
+}
+
+fn share(core: &Core, mode: ShareMode, granted_by: Uuid) -> Permission {
+    Permission { username: username(core), mode, granted_by: Some(granted_by) }
+}
+
+#[test]
+fn unshared() {
+    let c1 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+
+    assert_eq!(c1.effective_permissions(document.id).unwrap(), vec![owner(&c1)]);
+}
+
+#[test]
+fn shared_directly() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.share_file(document.id, &username(&c2), ShareMode::Read)
+        .unwrap();
+
+    assert_eq!(
+        c1.effective_permissions(document.id).unwrap(),
+        vec![owner(&c1), share(&c2, ShareMode::Read, document.id)]
+    );
+}
+
+#[test]
+fn shared_through_ancestor() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let document = c1.create_at_path("/folder/nested/document.md").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+
+    assert_eq!(
+        c1.effective_permissions(document.id).unwrap(),
+        vec![owner(&c1), share(&c2, ShareMode::Write, folder.id)]
+    );
+}
+
+#[test]
+fn greatest_access_reported() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let nested = c1.create_at_path("/folder/nested/").unwrap();
+    let document = c1.create_at_path("/folder/nested/document.md").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.share_file(nested.id, &username(&c2), ShareMode::Read)
+        .unwrap();
+    c1.share_file(document.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+
+    // the closest share granting the greatest access is the one reported
+    assert_eq!(
+        c1.effective_permissions(nested.id).unwrap(),
+        vec![owner(&c1), share(&c2, ShareMode::Write, folder.id)]
+    );
+    assert_eq!(
+        c1.effective_permissions(document.id).unwrap(),
+        vec![owner(&c1), share(&c2, ShareMode::Write, document.id)]
+    );
+}
+
+#[test]
+fn multiple_users() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let c3 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let document = c1.create_at_path("/folder/document.md").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Read)
+        .unwrap();
+    c1.share_file(document.id, &username(&c3), ShareMode::Write)
+        .unwrap();
+
+    let mut expected =
+        vec![share(&c2, ShareMode::Read, folder.id), share(&c3, ShareMode::Write, document.id)];
+    expected.sort_by(|a, b| a.username.cmp(&b.username));
+    expected.insert(0, owner(&c1));
+    assert_eq!(c1.effective_permissions(document.id).unwrap(), expected);
+}
+
+#[test]
+fn deleted_share() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.share_file(document.id, &username(&c2), ShareMode::Read)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    c2.delete_pending_share(document.id).unwrap();
+    c2.sync(None).unwrap();
+    c1.sync(None).unwrap();
+
+    assert_eq!(c1.effective_permissions(document.id).unwrap(), vec![owner(&c1)]);
+}
+
+#[test]
+fn as_sharee() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let document = c1.create_at_path("/folder/document.md").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    c2.create_link_at_path("/link", folder.id).unwrap();
+
+    assert_eq!(
+        c2.effective_permissions(document.id).unwrap(),
+        vec![owner(&c1), share(&c2, ShareMode::Write, folder.id)]
+    );
+}
+
+#[test]
+fn deleted_file() {
+    let c1 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.delete_file(document.id).unwrap();
+
+    let result = c1.effective_permissions(document.id);
+    assert_eq!(result.unwrap_err().kind, CoreError::FileNonexistent);
+}
+
+#[test]
+fn nonexistent_file() {
+    let c1 = test_core_with_account();
+
+    let result = c1.effective_permissions(Uuid::new_v4());
+    assert_eq!(result.unwrap_err().kind, CoreError::FileNonexistent);
+}
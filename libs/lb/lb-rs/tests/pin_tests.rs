@@ -0,0 +1,92 @@
+use lb_rs::{Core, CoreError, Uuid};
+use test_utils::*;
+
+fn pinned(core: &Core) -> Vec<Uuid> {
+    core.list_pinned()
+        .unwrap()
+        .into_iter()
+        .map(|file| file.id)
+        .collect()
+}
+
+#[test]
+fn pin_and_unpin() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let b = core.create_at_path("/folder/").unwrap();
+    assert_eq!(pinned(&core), vec![]);
+
+    core.pin_file(b.id).unwrap();
+    core.pin_file(a.id).unwrap();
+    core.pin_file(b.id).unwrap();
+    assert_eq!(pinned(&core), vec![b.id, a.id]);
+
+    core.unpin_file(b.id).unwrap();
+    core.unpin_file(b.id).unwrap();
+    assert_eq!(pinned(&core), vec![a.id]);
+}
+
+#[test]
+fn pin_nonexistent_file() {
+    let core = test_core_with_account();
+    assert!(matches!(core.pin_file(Uuid::new_v4()).unwrap_err().kind, CoreError::FileNonexistent));
+}
+
+#[test]
+fn pinned_deleted_file() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let b = core.create_at_path("/b.md").unwrap();
+    core.pin_file(a.id).unwrap();
+    core.pin_file(b.id).unwrap();
+
+    core.delete_file(a.id).unwrap();
+    assert_eq!(pinned(&core), vec![b.id]);
+}
+
+#[test]
+fn pins_sync() {
+    let c1 = test_core_with_account();
+    let a = c1.create_at_path("/a.md").unwrap();
+    c1.pin_file(a.id).unwrap();
+    c1.sync(None).unwrap();
+
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+    assert_eq!(pinned(&c2), vec![a.id]);
+}
+
+#[test]
+fn concurrent_pins_merge() {
+    let c1 = test_core_with_account();
+    let [a, b, c] = ["/a.md", "/b.md", "/c.md"].map(|path| c1.create_at_path(path).unwrap().id);
+    c1.pin_file(a).unwrap();
+    c1.sync(None).unwrap();
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+
+    c1.pin_file(b).unwrap();
+    c2.unpin_file(a).unwrap();
+    c2.pin_file(c).unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert_eq!(pinned(&c2), vec![b, c]);
+    assert!(c2.get_conflicts().unwrap().is_empty());
+}
+
+#[test]
+fn concurrent_first_pins_merge() {
+    let c1 = test_core_with_account();
+    let c2 = another_client(&c1);
+    let [a, b] = ["/a.md", "/b.md"].map(|path| c1.create_at_path(path).unwrap().id);
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    // neither device has synced settings yet
+    c1.pin_file(a).unwrap();
+    c2.pin_file(b).unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert::all_paths(&c2, &["/", "/a.md", "/b.md"]);
+    assert_eq!(pinned(&c2), vec![a, b]);
+}
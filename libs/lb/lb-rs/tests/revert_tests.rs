@@ -0,0 +1,162 @@
+use lb_rs::{CoreError, PlannedOp};
+use test_utils::*;
+
+#[test]
+fn revert_rename() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.revert_local_change(document.id).unwrap();
+
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document.md");
+    assert!(core.get_local_changes().unwrap().is_empty());
+}
+
+#[test]
+fn revert_rename_fixes_links() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[t](a/target.md)").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(target.id, "renamed.md").unwrap();
+    core.revert_local_change(target.id).unwrap();
+
+    assert_eq!(core.read_document(from.id).unwrap(), b"[t](a/target.md)");
+    assert_eq!(core.resolve_link(Some(from.id), "a/target.md").unwrap(), target.id);
+}
+
+#[test]
+fn revert_move() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.move_file(document.id, folder.id).unwrap();
+    core.revert_local_change(document.id).unwrap();
+
+    assert_eq!(core.get_file_by_id(document.id).unwrap().parent, folder.parent);
+    assert!(core.get_local_changes().unwrap().is_empty());
+}
+
+#[test]
+fn revert_delete() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/folder/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.delete_file(folder.id).unwrap();
+    core.revert_local_change(folder.id).unwrap();
+
+    core.get_file_by_id(document.id).unwrap();
+    assert!(core.get_local_changes().unwrap().is_empty());
+}
+
+#[test]
+fn revert_edit() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"synced").unwrap();
+    core.sync(None).unwrap();
+
+    core.write_document(document.id, b"local").unwrap();
+    core.revert_local_change(document.id).unwrap();
+
+    assert_eq!(core.read_document(document.id).unwrap(), b"synced");
+    assert!(core.get_local_changes().unwrap().is_empty());
+}
+
+#[test]
+fn revert_create() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    let folder = core.create_at_path("/folder/").unwrap();
+    let child = core.create_at_path("/folder/child.md").unwrap();
+    core.move_file(document.id, folder.id).unwrap();
+    core.revert_local_change(folder.id).unwrap();
+
+    assert_eq!(core.get_file_by_id(folder.id).unwrap_err().kind, CoreError::FileNonexistent);
+    assert_eq!(core.get_file_by_id(child.id).unwrap_err().kind, CoreError::FileNonexistent);
+    assert_eq!(core.get_file_by_id(document.id).unwrap().parent, folder.parent);
+    assert!(core.get_local_changes().unwrap().is_empty());
+}
+
+#[test]
+fn revert_path_taken() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.create_at_path("/document.md").unwrap();
+
+    assert_eq!(core.revert_local_change(document.id).unwrap_err().kind, CoreError::PathTaken);
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document2.md");
+}
+
+#[test]
+fn revert_unchanged() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.revert_local_change(document.id).unwrap();
+    assert_eq!(
+        core.revert_local_change(lb_rs::Uuid::new_v4())
+            .unwrap_err()
+            .kind,
+        CoreError::FileNonexistent
+    );
+}
+
+#[test]
+fn revert_clears_journal() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.revert_local_change(document.id).unwrap();
+
+    assert!(core.list_recent_operations().unwrap().is_empty());
+}
+
+#[test]
+fn revert_all() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.write_document(document.id, b"synced").unwrap();
+    core.sync(None).unwrap();
+
+    core.write_document(document.id, b"local").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    let created = core.create_at_path("/folder/created.md").unwrap();
+    core.revert_all_local_changes().unwrap();
+
+    assert!(core.get_local_changes().unwrap().is_empty());
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document.md");
+    assert_eq!(core.read_document(document.id).unwrap(), b"synced");
+    assert_eq!(core.get_file_by_id(created.id).unwrap_err().kind, CoreError::FileNonexistent);
+    core.validate().unwrap();
+}
+
+#[test]
+fn describe_local_changes() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.delete_file(document.id).unwrap();
+
+    let changes = core.describe_local_changes().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "/document.md");
+    assert_eq!(changes[0].op, PlannedOp::Delete);
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use test_utils::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    theme: String,
+    auto_sync: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { theme: "system".to_string(), auto_sync: true }
+    }
+}
+
+fn settings(theme: &str, auto_sync: bool) -> Settings {
+    Settings { theme: theme.to_string(), auto_sync }
+}
+
+#[test]
+fn get_and_set_settings() {
+    let core = test_core_with_account();
+    assert_eq!(core.get_settings::<Settings>("test").unwrap(), None);
+
+    core.set_settings("test", &settings("dark", false)).unwrap();
+    assert_eq!(core.get_settings("test").unwrap(), Some(settings("dark", false)));
+    assert_eq!(core.get_settings::<Settings>("other").unwrap(), None);
+}
+
+#[test]
+fn missing_settings_default() {
+    let core = test_core_with_account();
+    core.set_settings("test", &serde_json::json!({ "theme": "light" }))
+        .unwrap();
+    assert_eq!(core.get_settings("test").unwrap(), Some(settings("light", true)));
+}
+
+#[test]
+fn settings_not_a_map() {
+    let core = test_core_with_account();
+    core.set_settings("test", &true).unwrap_err();
+}
+
+#[test]
+fn unchanged_settings_not_written() {
+    let core = test_core_with_account();
+    core.set_settings("test", &settings("dark", false)).unwrap();
+    core.sync(None).unwrap();
+
+    core.set_settings("test", &settings("dark", false)).unwrap();
+    assert::local_work_paths(&core, &[]);
+}
+
+#[test]
+fn settings_sync() {
+    let c1 = test_core_with_account();
+    c1.set_settings("test", &settings("dark", false)).unwrap();
+    c1.sync(None).unwrap();
+
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+    assert_eq!(c2.get_settings("test").unwrap(), Some(settings("dark", false)));
+}
+
+#[test]
+fn settings_not_files() {
+    let core = test_core_with_account();
+    core.set_settings("test", &settings("dark", false)).unwrap();
+    core.pin_file(core.get_root().unwrap().id).unwrap();
+    core.sync(None).unwrap();
+
+    assert::all_paths(&core, &["/"]);
+    assert::server_work_paths(&core, &[]);
+}
+
+#[test]
+fn concurrent_settings_merge() {
+    let c1 = test_core_with_account();
+    c1.set_settings("test", &Settings::default()).unwrap();
+    c1.sync(None).unwrap();
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+
+    c1.set_settings("test", &settings("dark", true)).unwrap();
+    c2.set_settings("test", &settings("system", false)).unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    assert_eq!(c2.get_settings("test").unwrap(), Some(settings("dark", false)));
+}
+
+#[test]
+fn concurrent_settings_same_key() {
+    let c1 = test_core_with_account();
+    c1.set_settings("test", &Settings::default()).unwrap();
+    c1.sync(None).unwrap();
+    let c2 = another_client(&c1);
+    c2.sync(None).unwrap();
+
+    c1.set_settings("test", &settings("dark", true)).unwrap();
+    c2.set_settings("test", &settings("light", true)).unwrap();
+
+    // the device that syncs last keeps its change
+    sync_and_assert_stuff(&c1, &c2);
+    assert_eq!(c1.get_settings("test").unwrap(), Some(settings("light", true)));
+}
+
+#[test]
+fn concurrent_settings_and_pins_merge() {
+    let c1 = test_core_with_account();
+    let c2 = another_client(&c1);
+    let a = c1.create_at_path("/a.md").unwrap().id;
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    c1.pin_file(a).unwrap();
+    c2.set_settings("test", &settings("dark", false)).unwrap();
+
+    sync_and_assert_stuff(&c1, &c2);
+    let pinned: Vec<_> = c1
+        .list_pinned()
+        .unwrap()
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    assert_eq!(pinned, vec![a]);
+    assert_eq!(c1.get_settings("test").unwrap(), Some(settings("dark", false)));
+}
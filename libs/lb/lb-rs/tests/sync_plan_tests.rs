@@ -0,0 +1,187 @@
+use lb_rs::{PlannedConflict, PlannedOp};
+use lockbook_shared::file::ShareMode;
+use lockbook_shared::file_metadata::FileType;
+use test_utils::*;
+
+#[test]
+fn plan_empty() {
+    let core = test_core_with_account();
+    assert!(core.plan_sync().unwrap().is_empty());
+}
+
+#[test]
+fn plan_does_not_sync() {
+    let core = test_core_with_account();
+    core.create_at_path("/document.md").unwrap();
+    core.plan_sync().unwrap();
+
+    assert_eq!(core.calculate_work().unwrap().work_units.len(), 1);
+    assert!(!core.plan_sync().unwrap().is_empty());
+}
+
+#[test]
+fn plan_local_create() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/folder/document.md").unwrap();
+    core.write_document(document.id, b"hello").unwrap();
+
+    let plan = core.plan_sync().unwrap();
+    assert!(plan.server_changes.is_empty());
+    assert!(plan.conflicts.is_empty());
+    assert_eq!(plan.local_changes.len(), 3);
+    assert_eq!(plan.local_changes[0].id, folder.id);
+    assert_eq!(plan.local_changes[0].path, "/folder/");
+    assert_eq!(plan.local_changes[0].op, PlannedOp::Create { file_type: FileType::Folder });
+    assert_eq!(plan.local_changes[1].path, "/folder/document.md");
+    assert_eq!(plan.local_changes[1].op, PlannedOp::Create { file_type: FileType::Document });
+    assert!(matches!(plan.local_changes[2].op, PlannedOp::UploadDocument { size } if size > 0));
+}
+
+#[test]
+fn plan_local_rename_move_delete() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let renamed = core.create_at_path("/renamed.md").unwrap();
+    let moved = core.create_at_path("/moved.md").unwrap();
+    let deleted = core.create_at_path("/deleted.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(renamed.id, "renamed2.md").unwrap();
+    core.move_file(moved.id, folder.id).unwrap();
+    core.delete_file(deleted.id).unwrap();
+
+    let plan = core.plan_sync().unwrap();
+    assert!(plan.server_changes.is_empty());
+    assert_eq!(plan.local_changes.len(), 3);
+    assert_eq!(plan.local_changes[0].id, deleted.id);
+    assert_eq!(plan.local_changes[0].op, PlannedOp::Delete);
+    assert_eq!(plan.local_changes[1].id, moved.id);
+    assert_eq!(plan.local_changes[1].path, "/folder/moved.md");
+    assert_eq!(
+        plan.local_changes[1].op,
+        PlannedOp::Move { old_parent: "/".to_string(), new_parent: "/folder/".to_string() }
+    );
+    assert_eq!(plan.local_changes[2].id, renamed.id);
+    assert_eq!(
+        plan.local_changes[2].op,
+        PlannedOp::Rename {
+            old_name: "renamed.md".to_string(),
+            new_name: "renamed2.md".to_string()
+        }
+    );
+}
+
+#[test]
+fn plan_delete_reports_only_top_most() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    core.create_at_path("/folder/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.delete_file(folder.id).unwrap();
+
+    let plan = core.plan_sync().unwrap();
+    assert_eq!(plan.local_changes.len(), 1);
+    assert_eq!(plan.local_changes[0].path, "/folder/");
+    assert_eq!(plan.local_changes[0].op, PlannedOp::Delete);
+}
+
+#[test]
+fn plan_server_changes() {
+    let core1 = test_core_with_account();
+    let core2 = test_core_from(&core1);
+
+    let document = core1.create_at_path("/document.md").unwrap();
+    core1.write_document(document.id, b"hello world").unwrap();
+    core1.sync(None).unwrap();
+
+    let plan = core2.plan_sync().unwrap();
+    assert!(plan.local_changes.is_empty());
+    assert_eq!(plan.server_changes.len(), 2);
+    assert_eq!(plan.server_changes[0].path, "/document.md");
+    assert_eq!(plan.server_changes[0].op, PlannedOp::Create { file_type: FileType::Document });
+    assert!(matches!(plan.server_changes[1].op, PlannedOp::DownloadDocument { size } if size > 0));
+}
+
+#[test]
+fn plan_server_delete() {
+    let core1 = test_core_with_account();
+    let document = core1.create_at_path("/document.md").unwrap();
+    core1.sync(None).unwrap();
+    let core2 = test_core_from(&core1);
+
+    core1.delete_file(document.id).unwrap();
+    core1.sync(None).unwrap();
+
+    let plan = core2.plan_sync().unwrap();
+    assert_eq!(plan.server_changes.len(), 1);
+    assert_eq!(plan.server_changes[0].id, document.id);
+    assert_eq!(plan.server_changes[0].op, PlannedOp::Delete);
+}
+
+#[test]
+fn plan_share() {
+    let core1 = test_core_with_account();
+    let core2 = test_core_with_account();
+    let username = core2.get_account().unwrap().username;
+    let document = core1.create_at_path("/document.md").unwrap();
+    core1.sync(None).unwrap();
+
+    core1
+        .share_file(document.id, &username, ShareMode::Write)
+        .unwrap();
+
+    let plan = core1.plan_sync().unwrap();
+    assert_eq!(plan.local_changes.len(), 1);
+    assert_eq!(plan.local_changes[0].op, PlannedOp::Share { username, mode: ShareMode::Write });
+}
+
+#[test]
+fn plan_path_conflict() {
+    let core1 = test_core_with_account();
+    let core2 = test_core_from(&core1);
+
+    core1.create_at_path("/new.md").unwrap();
+    core1.sync(None).unwrap();
+    let local = core2.create_at_path("/new.md").unwrap();
+
+    let plan = core2.plan_sync().unwrap();
+    assert_eq!(
+        plan.conflicts,
+        vec![PlannedConflict::Rename {
+            id: local.id,
+            path: "/new.md".to_string(),
+            new_name: "new-1.md".to_string()
+        }]
+    );
+
+    core2.sync(None).unwrap();
+    assert_eq!(core2.get_file_by_id(local.id).unwrap().name, "new-1.md");
+}
+
+#[test]
+fn plan_edit_conflicts() {
+    let core1 = test_core_with_account();
+    let text = core1.create_at_path("/text.md").unwrap();
+    let other = core1.create_at_path("/image.png").unwrap();
+    core1.sync(None).unwrap();
+    let core2 = test_core_from(&core1);
+
+    core1.write_document(text.id, b"remote").unwrap();
+    core1.write_document(other.id, b"remote").unwrap();
+    core1.sync(None).unwrap();
+    core2.write_document(text.id, b"local").unwrap();
+    core2.write_document(other.id, b"local").unwrap();
+
+    let plan = core2.plan_sync().unwrap();
+    assert_eq!(plan.conflicts.len(), 2);
+    assert!(plan
+        .conflicts
+        .contains(&PlannedConflict::Merge { id: text.id, path: "/text.md".to_string() }));
+    assert!(plan.conflicts.contains(&PlannedConflict::Duplicate {
+        id: other.id,
+        path: "/image.png".to_string(),
+        new_name: "image-1.png".to_string()
+    }));
+}
@@ -0,0 +1,141 @@
+use lb_rs::{Core, CoreError, TaskFilter, Uuid};
+use test_utils::*;
+
+fn read(core: &Core, id: Uuid) -> String {
+    String::from_utf8(core.read_document(id).unwrap()).unwrap()
+}
+
+fn texts(core: &Core, filter: TaskFilter) -> Vec<String> {
+    core.list_tasks(filter)
+        .unwrap()
+        .into_iter()
+        .map(|task| task.text)
+        .collect()
+}
+
+#[test]
+fn list_tasks() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let b = core.create_at_path("/folder/b.md").unwrap();
+    let text = core.create_at_path("/c.txt").unwrap();
+
+    core.write_document(
+        a.id,
+        b"# todo\n- [ ] water the plants\n* [x] done @due(2024-01-01)\n\n```\n- [ ] code\n```\n",
+    )
+    .unwrap();
+    core.write_document(
+        b.id,
+        b"1. [ ] pay rent @due(2024-05-01)\n- [ ] later @due(2024-13-01)\n-[ ] not a task",
+    )
+    .unwrap();
+    core.write_document(text.id, b"- [ ] not markdown").unwrap();
+
+    let tasks = core.list_tasks(TaskFilter::default()).unwrap();
+    assert_eq!(
+        tasks
+            .iter()
+            .map(|task| (task.id, task.line, task.text.as_str(), task.checked, task.due.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            (a.id, 3, "done", true, Some("2024-01-01")),
+            (b.id, 1, "pay rent", false, Some("2024-05-01")),
+            (a.id, 2, "water the plants", false, None),
+            (b.id, 2, "later @due(2024-13-01)", false, None),
+        ]
+    );
+}
+
+#[test]
+fn list_tasks_filtered() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let b = core.create_at_path("/folder/nested/b.md").unwrap();
+
+    core.write_document(a.id, b"- [ ] a @due(2024-01-01)\n- [x] checked @due(2024-01-01)")
+        .unwrap();
+    core.write_document(b.id, b"- [ ] b @due(2024-02-01)\n- [ ] someday")
+        .unwrap();
+
+    let open = TaskFilter { checked: Some(false), ..Default::default() };
+    assert_eq!(texts(&core, open), vec!["a", "b", "someday"]);
+
+    let due = TaskFilter { due_by: Some("2024-01-31".to_string()), ..Default::default() };
+    assert_eq!(texts(&core, due), vec!["a", "checked"]);
+
+    let in_folder = TaskFilter { parent: Some(folder.id), ..Default::default() };
+    assert_eq!(texts(&core, in_folder), vec!["b", "someday"]);
+
+    let in_doc = TaskFilter { parent: Some(a.id), checked: Some(true), ..Default::default() };
+    assert_eq!(texts(&core, in_doc), vec!["checked"]);
+}
+
+#[test]
+fn list_tasks_after_edit_and_delete() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    let b = core.create_at_path("/b.md").unwrap();
+    core.write_document(a.id, b"- [ ] a").unwrap();
+    core.write_document(b.id, b"- [ ] b").unwrap();
+    assert_eq!(texts(&core, TaskFilter::default()), vec!["a", "b"]);
+
+    core.write_document(a.id, b"- [ ] edited").unwrap();
+    core.delete_file(b.id).unwrap();
+    assert_eq!(texts(&core, TaskFilter::default()), vec!["edited"]);
+}
+
+#[test]
+fn list_tasks_after_sync() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    core.write_document(a.id, b"- [ ] a").unwrap();
+    core.sync(None).unwrap();
+
+    let core2 = another_client(&core);
+    core2.sync(None).unwrap();
+    assert_eq!(texts(&core2, TaskFilter::default()), vec!["a"]);
+
+    core.write_document(a.id, b"- [ ] a\n- [ ] b").unwrap();
+    core.sync(None).unwrap();
+    core2.sync(None).unwrap();
+    assert_eq!(texts(&core2, TaskFilter::default()), vec!["a", "b"]);
+}
+
+#[test]
+fn set_task_state() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    core.write_document(a.id, b"intro\n  * [ ] water the plants @due(2024-01-01)\n")
+        .unwrap();
+
+    let task = core.list_tasks(TaskFilter::default()).unwrap().remove(0);
+    core.set_task_state(&task, true).unwrap();
+    assert_eq!(read(&core, a.id), "intro\n  * [x] water the plants @due(2024-01-01)\n");
+    assert!(core.list_tasks(TaskFilter::default()).unwrap()[0].checked);
+
+    core.set_task_state(&task, false).unwrap();
+    assert_eq!(read(&core, a.id), "intro\n  * [ ] water the plants @due(2024-01-01)\n");
+}
+
+#[test]
+fn set_task_state_after_edit() {
+    let core = test_core_with_account();
+    let a = core.create_at_path("/a.md").unwrap();
+    core.write_document(a.id, b"- [ ] a\n- [ ] b").unwrap();
+    let task = core.list_tasks(TaskFilter::default()).unwrap().remove(1);
+
+    // the task moved down a line after it was listed
+    core.write_document(a.id, b"new line\n- [ ] a\n- [ ] b")
+        .unwrap();
+    core.set_task_state(&task, true).unwrap();
+    assert_eq!(read(&core, a.id), "new line\n- [ ] a\n- [x] b");
+
+    // the task was removed after it was listed
+    core.write_document(a.id, b"- [ ] a").unwrap();
+    assert!(matches!(
+        core.set_task_state(&task, false).unwrap_err().kind,
+        CoreError::TaskNonexistent
+    ));
+}
@@ -0,0 +1,283 @@
+use lb_rs::{Core, CoreError, ShareMode, TeamGrant, TeamShare};
+use test_utils::*;
+
+fn username(core: &Core) -> String {
+    core.get_account().unwrap().username
+}
+
+fn grant(core: &Core, replaced: Option<ShareMode>) -> TeamGrant {
+    TeamGrant { member: core.get_account().unwrap().public_key(), replaced }
+}
+
+fn shared_with(core: &Core, id: lb_rs::Uuid, user: &Core) -> Vec<ShareMode> {
+    core.get_file_by_id(id)
+        .unwrap()
+        .shares
+        .into_iter()
+        .filter(|share| share.shared_with == username(user))
+        .map(|share| share.mode)
+        .collect()
+}
+
+fn pending_share_ids(core: &Core) -> Vec<lb_rs::Uuid> {
+    core.get_pending_shares()
+        .unwrap()
+        .into_iter()
+        .map(|f| f.id)
+        .collect()
+}
+
+#[test]
+fn create_team() {
+    let c1 = test_core_with_account();
+    let team = c1.create_team("engineering").unwrap();
+
+    assert_eq!(team.name, "engineering");
+    assert_eq!(team.admin, username(&c1));
+    assert_eq!(team.members, vec![username(&c1)]);
+    assert_eq!(c1.get_teams().unwrap(), vec![team]);
+}
+
+#[test]
+fn create_team_name_taken() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+
+    let result = c1.create_team("engineering");
+    assert_eq!(result.unwrap_err().kind, CoreError::TeamNameTaken);
+
+    // names only need to be unique among the teams a user administers
+    c2.create_team("engineering").unwrap();
+}
+
+#[test]
+fn create_team_name_empty() {
+    let c1 = test_core_with_account();
+
+    let result = c1.create_team(" ");
+    assert_eq!(result.unwrap_err().kind, CoreError::TeamNameEmpty);
+}
+
+#[test]
+fn add_member() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+
+    let teams = c2.get_teams().unwrap();
+    assert_eq!(teams.len(), 1);
+    assert_eq!(teams[0].admin, username(&c1));
+    assert_eq!(teams[0].members, vec![username(&c1), username(&c2)]);
+}
+
+#[test]
+fn share_with_team() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let c3 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    c1.add_team_member("engineering", &username(&c3)).unwrap();
+
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+
+    for c in [&c2, &c3] {
+        c.sync(None).unwrap();
+        assert_eq!(pending_share_ids(c), vec![folder.id]);
+    }
+    assert_eq!(
+        c1.get_teams().unwrap()[0].shares,
+        vec![TeamShare {
+            id: folder.id,
+            mode: ShareMode::Write,
+            grants: vec![grant(&c2, None), grant(&c3, None)]
+        }]
+    );
+}
+
+#[test]
+fn new_member_gets_team_shares() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Read)
+        .unwrap();
+    c1.share_file_with_team(document.id, "engineering", ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    c1.sync(None).unwrap();
+
+    c2.sync(None).unwrap();
+    let mut pending = pending_share_ids(&c2);
+    pending.sort();
+    let mut expected = vec![folder.id, document.id];
+    expected.sort();
+    assert_eq!(pending, expected);
+}
+
+#[test]
+fn new_member_skips_deleted_files() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Read)
+        .unwrap();
+    c1.delete_file(folder.id).unwrap();
+    c1.sync(None).unwrap();
+
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    assert!(pending_share_ids(&c2).is_empty());
+}
+
+#[test]
+fn remove_member() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Read)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    c1.remove_team_member("engineering", &username(&c2))
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    assert!(pending_share_ids(&c2).is_empty());
+    assert!(c2.get_teams().unwrap().is_empty());
+    assert_eq!(c1.get_teams().unwrap()[0].members, vec![username(&c1)]);
+}
+
+#[test]
+fn remove_member_keeps_direct_share() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Read)
+        .unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Write)
+        .unwrap();
+    assert_eq!(
+        c1.get_teams().unwrap()[0].shares[0].grants,
+        vec![grant(&c2, Some(ShareMode::Read))]
+    );
+    assert_eq!(shared_with(&c1, folder.id, &c2), vec![ShareMode::Write]);
+
+    // the team's write share is undone, leaving the read share it replaced
+    c1.remove_team_member("engineering", &username(&c2))
+        .unwrap();
+    assert_eq!(shared_with(&c1, folder.id, &c2), vec![ShareMode::Read]);
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    assert_eq!(pending_share_ids(&c2), vec![folder.id]);
+    assert!(c1.get_teams().unwrap()[0].shares[0].grants.is_empty());
+}
+
+#[test]
+fn remove_member_keeps_prior_access() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+
+    // the member could already access the file as the team would, so the team didn't share it
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Read)
+        .unwrap();
+    assert!(c1.get_teams().unwrap()[0].shares[0].grants.is_empty());
+
+    c1.remove_team_member("engineering", &username(&c2))
+        .unwrap();
+    assert_eq!(shared_with(&c1, folder.id, &c2), vec![ShareMode::Write]);
+}
+
+#[test]
+fn reshare_with_team() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Write)
+        .unwrap();
+    c1.share_file_with_team(folder.id, "engineering", ShareMode::Read)
+        .unwrap();
+    assert_eq!(shared_with(&c1, folder.id, &c2), vec![ShareMode::Read]);
+    c1.sync(None).unwrap();
+
+    c1.remove_team_member("engineering", &username(&c2))
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    assert!(pending_share_ids(&c2).is_empty());
+}
+
+#[test]
+fn remove_admin() {
+    let c1 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+
+    let result = c1.remove_team_member("engineering", &username(&c1));
+    assert_eq!(result.unwrap_err().kind, CoreError::InsufficientPermission);
+}
+
+#[test]
+fn member_cannot_change_team() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let c3 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+
+    let result = c2.add_team_member("engineering", &username(&c3));
+    assert_eq!(result.unwrap_err().kind, CoreError::InsufficientPermission);
+}
+
+#[test]
+fn nonexistent_team() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+
+    let result = c1.add_team_member("engineering", &username(&c2));
+    assert_eq!(result.unwrap_err().kind, CoreError::TeamNonexistent);
+}
+
+#[test]
+fn add_nonexistent_user() {
+    let c1 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+
+    let result = c1.add_team_member("engineering", &random_name());
+    assert_eq!(result.unwrap_err().kind, CoreError::AccountNonexistent);
+}
+
+#[test]
+fn share_root_with_team() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c1.create_team("engineering").unwrap();
+    c1.add_team_member("engineering", &username(&c2)).unwrap();
+
+    let root = c1.get_root().unwrap();
+    let result = c1.share_file_with_team(root.id, "engineering", ShareMode::Read);
+    assert_eq!(result.unwrap_err().kind, CoreError::RootModificationInvalid);
+    assert!(c1.get_teams().unwrap()[0].shares.is_empty());
+}
@@ -0,0 +1,97 @@
+use lb_rs::service::template_service::fill_template;
+use lb_rs::{Core, CoreError, Uuid};
+use test_utils::*;
+use time::{Date, Month};
+
+fn read(core: &Core, id: Uuid) -> String {
+    String::from_utf8(core.read_document(id).unwrap()).unwrap()
+}
+
+#[test]
+fn fill_template_variables() {
+    let date = Date::from_calendar_date(2024, Month::March, 7).unwrap();
+    assert_eq!(
+        fill_template(
+            "# {{title}}\n{{date}} by {{username}} {{unknown}}",
+            "standup.md",
+            date,
+            "parth"
+        ),
+        "# standup\n2024-03-07 by parth {{unknown}}"
+    );
+    assert_eq!(fill_template("{{title}}", "notes", date, "parth"), "notes");
+    assert_eq!(fill_template("{{title}}", ".hidden", date, "parth"), ".hidden");
+}
+
+#[test]
+fn create_from_template() {
+    let core = test_core_with_account();
+    let username = core.get_account().unwrap().username;
+    let template = core.create_at_path("/templates/meeting.md").unwrap();
+    core.write_document(template.id, b"# {{title}}\nattendees: {{username}}\n")
+        .unwrap();
+    let folder = core.create_at_path("/meetings/").unwrap();
+
+    let file = core
+        .create_from_template(template.id, folder.id, "standup.md")
+        .unwrap();
+    assert_eq!(core.get_path_by_id(file.id).unwrap(), "/meetings/standup.md");
+    assert_eq!(read(&core, file.id), format!("# standup\nattendees: {username}\n"));
+
+    // the template is unchanged and can be used again, but not for a file that exists
+    assert_eq!(read(&core, template.id), "# {{title}}\nattendees: {{username}}\n");
+    assert!(matches!(
+        core.create_from_template(template.id, folder.id, "standup.md")
+            .unwrap_err()
+            .kind,
+        CoreError::PathTaken
+    ));
+}
+
+#[test]
+fn create_from_template_date() {
+    let core = test_core_with_account();
+    let template = core.create_at_path("/templates/daily.md").unwrap();
+    core.write_document(template.id, b"{{date}}").unwrap();
+    let root = core.get_root().unwrap();
+
+    let file = core
+        .create_from_template(template.id, root.id, "today.md")
+        .unwrap();
+    let date = read(&core, file.id);
+    assert_eq!(date.len(), "YYYY-MM-DD".len());
+    assert!(date.chars().all(|c| c.is_ascii_digit() || c == '-'));
+}
+
+#[test]
+fn create_from_folder_template() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/templates/projects/").unwrap();
+    let root = core.get_root().unwrap();
+
+    assert!(matches!(
+        core.create_from_template(folder.id, root.id, "project.md")
+            .unwrap_err()
+            .kind,
+        CoreError::FileNotDocument
+    ));
+}
+
+#[test]
+fn list_templates() {
+    let core = test_core_with_account();
+    assert!(core.list_templates().unwrap().is_empty());
+
+    let b = core.create_at_path("/templates/b.md").unwrap();
+    let a = core.create_at_path("/templates/reports/a.md").unwrap();
+    core.create_at_path("/templates/empty/").unwrap();
+    core.create_at_path("/not-a-template.md").unwrap();
+
+    let templates: Vec<Uuid> = core
+        .list_templates()
+        .unwrap()
+        .into_iter()
+        .map(|file| file.id)
+        .collect();
+    assert_eq!(templates, vec![b.id, a.id]);
+}
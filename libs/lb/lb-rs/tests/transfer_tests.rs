@@ -0,0 +1,429 @@
+use lb_rs::service::api_service::{ApiError, Requester};
+use lb_rs::{Core, CoreError, ShareMode, Uuid};
+use lockbook_shared::api::{UpsertError, UpsertRequest, FREE_TIER_USAGE_SIZE};
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::{FileDiff, Owner};
+use lockbook_shared::tree_like::TreeLike;
+use test_utils::*;
+
+fn assert_stuff(c1: &Core, c2: &Core) {
+    for c in [c1, c2] {
+        c.validate().unwrap();
+        assert::local_work_paths(c, &[]);
+        assert::server_work_paths(c, &[]);
+    }
+}
+
+fn username(core: &Core) -> String {
+    core.get_account().unwrap().username
+}
+
+fn usage(core: &Core) -> u64 {
+    core.get_usage().unwrap().server_usage.exact
+}
+
+/// Offers `id` from `c1` to `c2` and syncs both so `c2` can accept it
+fn offer(c1: &Core, c2: &Core, id: Uuid) {
+    c1.transfer_ownership(id, &username(c2)).unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+}
+
+fn accept(c2: &Core, id: Uuid) -> Result<(), CoreError> {
+    let root = c2.get_root().unwrap().id;
+    c2.accept_transfer(id, root).map_err(|err| err.kind)
+}
+
+#[test]
+fn transfer_folder() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    c2.create_at_path("/existing.md").unwrap();
+    c2.sync(None).unwrap();
+
+    let folder = c1.create_at_path("/notes/").unwrap();
+    let document = c1.create_at_path("/notes/todo/list.md").unwrap();
+    c1.write_document(document.id, b"groceries").unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    let pending: Vec<Uuid> = c2
+        .get_pending_transfers()
+        .unwrap()
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    assert_eq!(pending, vec![folder.id]);
+
+    accept(&c2, folder.id).unwrap();
+    assert!(c2.get_pending_transfers().unwrap().is_empty());
+    assert::all_paths(
+        &c2,
+        &["/", "/existing.md", "/notes/", "/notes/todo/", "/notes/todo/list.md"],
+    );
+    assert::all_document_contents(
+        &c2,
+        &[("/existing.md", b""), ("/notes/todo/list.md", b"groceries")],
+    );
+    assert!(c2.get_file_by_id(folder.id).unwrap().shares.is_empty());
+    c2.sync(None).unwrap();
+
+    // the re-encrypted documents can be read on the new owner's other devices
+    let c3 = another_client(&c2);
+    c3.sync(None).unwrap();
+    assert::all_document_contents(
+        &c3,
+        &[("/existing.md", b""), ("/notes/todo/list.md", b"groceries")],
+    );
+
+    c1.sync(None).unwrap();
+    assert::all_paths(&c1, &["/"]);
+    assert_stuff(&c1, &c2);
+    assert_stuff(&c1, &c3);
+}
+
+#[test]
+fn transfer_not_accepted() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.write_document(document.id, &[0; 1000]).unwrap();
+    c1.sync(None).unwrap();
+    let before = usage(&c2);
+
+    // until it's accepted, an offer is only a share
+    offer(&c1, &c2, document.id);
+    assert::all_paths(&c1, &["/", "/document.md"]);
+    assert::all_paths(&c2, &["/"]);
+    assert_eq!(c2.get_pending_shares().unwrap()[0].id, document.id);
+    assert_eq!(usage(&c2), before);
+    assert_stuff(&c1, &c2);
+}
+
+#[test]
+fn transfer_rejected() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, document.id);
+    c2.reject_transfer(document.id).unwrap();
+    assert!(c2.get_pending_transfers().unwrap().is_empty());
+    assert_eq!(accept(&c2, document.id).unwrap_err(), CoreError::FileNonexistent);
+    assert::all_paths(&c1, &["/", "/document.md"]);
+}
+
+#[test]
+fn transfer_not_offered() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    // sharing a file doesn't let the user it's shared with take it
+    assert_eq!(accept(&c2, folder.id).unwrap_err(), CoreError::FileNonexistent);
+    assert!(c2.get_pending_transfers().unwrap().is_empty());
+}
+
+#[test]
+fn transfer_taken_without_offer() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    let root = c2.get_root().unwrap().id;
+    let account = c2.get_account().unwrap();
+
+    // the server doesn't let a user take a file that wasn't offered to them
+    c2.in_tx(|s| {
+        let mut base = (&s.db.base_metadata).to_lazy();
+        let transfer = base.transfer_op(&folder.id, &root, &account).unwrap();
+        let updates = transfer
+            .iter()
+            .map(|file| FileDiff::edit(base.find(file.id()).unwrap(), file))
+            .collect();
+        let result = s.client.request(&account, UpsertRequest { updates });
+        assert_matches!(
+            result,
+            Err(ApiError::<UpsertError>::Endpoint(UpsertError::NotPermissioned))
+        );
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn transfer_given_without_acceptance() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+    c1.transfer_ownership(document.id, &username(&c2)).unwrap();
+    c1.sync(None).unwrap();
+    let new_owner = Owner(c2.get_account().unwrap().public_key());
+    let account = c1.get_account().unwrap();
+
+    // an offer doesn't let the user making it give the file away
+    c1.in_tx(|s| {
+        let old = s.db.base_metadata.get().get(&document.id).unwrap();
+        let mut new = old.clone();
+        new.timestamped_value.value.owner = new_owner;
+        let result = s
+            .client
+            .request(&account, UpsertRequest { updates: vec![FileDiff::edit(old, &new)] });
+        assert_matches!(result, Err(ApiError::<UpsertError>::Endpoint(UpsertError::Validation(_))));
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn transfer_then_edit() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, document.id);
+    accept(&c2, document.id).unwrap();
+
+    let folder = c2.create_at_path("/folder/").unwrap();
+    c2.move_file(document.id, folder.id).unwrap();
+    c2.write_document(document.id, b"edited").unwrap();
+    c2.sync(None).unwrap();
+
+    let c3 = another_client(&c2);
+    c3.sync(None).unwrap();
+    assert::all_document_contents(&c3, &[("/folder/document.md", b"edited")]);
+    assert_stuff(&c2, &c3);
+}
+
+#[test]
+fn transfer_into_folder() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+    let folder = c2.create_at_path("/from c1/").unwrap();
+    c2.sync(None).unwrap();
+
+    offer(&c1, &c2, document.id);
+    c2.accept_transfer(document.id, folder.id).unwrap();
+    assert::all_paths(&c2, &["/", "/from c1/", "/from c1/document.md"]);
+    c2.sync(None).unwrap();
+    assert_stuff(&c1, &c2);
+}
+
+#[test]
+fn transfer_with_deleted_files() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.create_at_path("/folder/kept.md").unwrap();
+    let deleted = c1.create_at_path("/folder/deleted/x.md").unwrap();
+    c1.sync(None).unwrap();
+    c1.delete_file(deleted.parent).unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    accept(&c2, folder.id).unwrap();
+    c2.sync(None).unwrap();
+    assert::all_paths(&c2, &["/", "/folder/", "/folder/kept.md"]);
+
+    // the account the files were transferred from can still sync changes
+    c1.create_at_path("/after.md").unwrap();
+    c1.sync(None).unwrap();
+    assert_stuff(&c1, &c2);
+}
+
+#[test]
+fn transfer_pruned_on_other_devices() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.create_at_path("/folder/document.md").unwrap();
+    c1.sync(None).unwrap();
+    let c3 = another_client(&c1);
+    c3.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    accept(&c2, folder.id).unwrap();
+    c3.sync(None).unwrap();
+    assert::all_paths(&c3, &["/"]);
+    c1.sync(None).unwrap();
+    assert_stuff(&c1, &c3);
+}
+
+#[test]
+fn transfer_drops_shares() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let c3 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.create_at_path("/folder/document.md").unwrap();
+    c1.share_file(folder.id, &username(&c3), ShareMode::Read)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c3.sync(None).unwrap();
+    c3.create_link_at_path("/link", folder.id).unwrap();
+    c3.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    accept(&c2, folder.id).unwrap();
+    c2.sync(None).unwrap();
+    c3.sync(None).unwrap();
+    assert::all_paths(&c3, &["/"]);
+    assert_stuff(&c2, &c3);
+}
+
+#[test]
+fn transfer_accepted_share() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+    c2.create_link_at_path("/link", folder.id).unwrap();
+    c2.sync(None).unwrap();
+
+    // the link to the file is removed once it's owned
+    offer(&c1, &c2, folder.id);
+    accept(&c2, folder.id).unwrap();
+    c2.sync(None).unwrap();
+    assert::all_paths(&c2, &["/", "/folder/"]);
+    c1.sync(None).unwrap();
+    assert_stuff(&c1, &c2);
+}
+
+#[test]
+fn transfer_usage() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.write_document(document.id, &[0; 1000]).unwrap();
+    c1.sync(None).unwrap();
+    let (before1, before2) = (usage(&c1), usage(&c2));
+
+    offer(&c1, &c2, document.id);
+    accept(&c2, document.id).unwrap();
+    assert!(usage(&c1) < before1);
+    assert!(usage(&c2) > before2);
+}
+
+#[test]
+fn transfer_over_data_cap() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let content: Vec<u8> = (0..((FREE_TIER_USAGE_SIZE as f64 * 0.6) as i64))
+        .map(|_| rand::random::<u8>())
+        .collect();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.write_document(document.id, &content).unwrap();
+    c1.sync(None).unwrap();
+    let other = c2.create_at_path("/other.md").unwrap();
+    c2.write_document(other.id, &content).unwrap();
+    c2.sync(None).unwrap();
+
+    offer(&c1, &c2, document.id);
+    assert_eq!(accept(&c2, document.id).unwrap_err(), CoreError::UsageIsOverDataCap);
+    assert_eq!(c2.get_pending_transfers().unwrap()[0].id, document.id);
+    c1.sync(None).unwrap();
+    assert::all_paths(&c1, &["/", "/document.md"]);
+}
+
+#[test]
+fn transfer_unsynced() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+
+    let result = c1.transfer_ownership(folder.id, &username(&c2));
+    assert_eq!(result.unwrap_err().kind, CoreError::SyncRequired);
+}
+
+#[test]
+fn transfer_accept_unsynced() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    let document = c1.create_at_path("/folder/document.md").unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    c2.write_document(document.id, b"edited").unwrap();
+    assert_eq!(accept(&c2, folder.id).unwrap_err(), CoreError::SyncRequired);
+    c2.sync(None).unwrap();
+    accept(&c2, folder.id).unwrap();
+    assert::all_document_contents(&c2, &[("/folder/document.md", b"edited")]);
+}
+
+#[test]
+fn transfer_outdated() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.sync(None).unwrap();
+
+    offer(&c1, &c2, folder.id);
+    c1.rename_file(folder.id, "renamed").unwrap();
+    c1.sync(None).unwrap();
+
+    assert_eq!(accept(&c2, folder.id).unwrap_err(), CoreError::SyncRequired);
+    c2.sync(None).unwrap();
+    accept(&c2, folder.id).unwrap();
+    assert::all_paths(&c2, &["/", "/renamed/"]);
+}
+
+#[test]
+fn transfer_not_owned() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let c3 = test_core_with_account();
+    let folder = c1.create_at_path("/folder/").unwrap();
+    c1.share_file(folder.id, &username(&c2), ShareMode::Write)
+        .unwrap();
+    c1.sync(None).unwrap();
+    c2.sync(None).unwrap();
+
+    let result = c2.transfer_ownership(folder.id, &username(&c3));
+    assert_eq!(result.unwrap_err().kind, CoreError::InsufficientPermission);
+}
+
+#[test]
+fn transfer_root() {
+    let c1 = test_core_with_account();
+    let c2 = test_core_with_account();
+    let root = c1.get_root().unwrap();
+
+    let result = c1.transfer_ownership(root.id, &username(&c2));
+    assert_eq!(result.unwrap_err().kind, CoreError::RootModificationInvalid);
+}
+
+#[test]
+fn transfer_to_nonexistent_user() {
+    let c1 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+
+    let result = c1.transfer_ownership(document.id, &random_name());
+    assert_eq!(result.unwrap_err().kind, CoreError::AccountNonexistent);
+}
+
+#[test]
+fn transfer_to_self() {
+    let c1 = test_core_with_account();
+    let document = c1.create_at_path("/document.md").unwrap();
+    c1.sync(None).unwrap();
+
+    c1.transfer_ownership(document.id, &username(&c1)).unwrap();
+    assert::all_paths(&c1, &["/", "/document.md"]);
+}
@@ -0,0 +1,243 @@
+use lb_rs::{CoreError, FileOperation};
+use test_utils::*;
+
+#[test]
+fn undo_nothing() {
+    let core = test_core_with_account();
+    assert_eq!(core.undo_last_operation().unwrap(), None);
+    assert!(core.list_recent_operations().unwrap().is_empty());
+}
+
+#[test]
+fn list_recent_operations() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/document.md").unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.move_file(document.id, folder.id).unwrap();
+    core.delete_file(folder.id).unwrap();
+
+    let ops = core
+        .list_recent_operations()
+        .unwrap()
+        .into_iter()
+        .map(|op| op.op)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        vec![
+            FileOperation::Delete { id: folder.id },
+            FileOperation::Move {
+                id: document.id,
+                old_parent: folder.parent,
+                new_parent: folder.id
+            },
+            FileOperation::Rename {
+                id: document.id,
+                old_name: "document.md".to_string(),
+                new_name: "document2.md".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn undo_unsynced_rename() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document.md");
+    assert!(core.calculate_work().unwrap().work_units.is_empty());
+}
+
+#[test]
+fn undo_rename_fixes_links() {
+    let core = test_core_with_account();
+    let target = core.create_at_path("/a/target.md").unwrap();
+    let from = core.create_at_path("/from.md").unwrap();
+    core.write_document(from.id, b"[t](a/target.md) [[target]]")
+        .unwrap();
+
+    core.rename_file(target.id, "renamed.md").unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    assert_eq!(core.read_document(from.id).unwrap(), b"[t](a/target.md) [[target]]");
+    assert_eq!(core.resolve_link(Some(from.id), "a/target.md").unwrap(), target.id);
+    assert_eq!(core.get_backlinks(target.id).unwrap(), vec![from.id]);
+}
+
+#[test]
+fn undo_unsynced_move() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.move_file(document.id, folder.id).unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    assert_eq!(core.get_file_by_id(document.id).unwrap().parent, folder.parent);
+    assert!(core.calculate_work().unwrap().work_units.is_empty());
+}
+
+#[test]
+fn undo_unsynced_delete() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/folder/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.delete_file(folder.id).unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    core.get_file_by_id(folder.id).unwrap();
+    core.get_file_by_id(document.id).unwrap();
+    assert!(core.calculate_work().unwrap().work_units.is_empty());
+}
+
+#[test]
+fn undo_new_file_delete() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+
+    core.delete_file(document.id).unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    core.get_file_by_id(document.id).unwrap();
+    core.sync(None).unwrap();
+    core.get_file_by_id(document.id).unwrap();
+}
+
+#[test]
+fn undo_keeps_other_changes() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.write_document(document.id, b"content").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.undo_last_operation().unwrap().unwrap();
+
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document.md");
+    assert_eq!(core.read_document(document.id).unwrap(), b"content");
+    assert_eq!(core.calculate_work().unwrap().work_units.len(), 1);
+}
+
+#[test]
+fn undo_in_order() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.rename_file(document.id, "document3.md").unwrap();
+
+    core.undo_last_operation().unwrap().unwrap();
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document2.md");
+    core.undo_last_operation().unwrap().unwrap();
+    assert_eq!(core.get_file_by_id(document.id).unwrap().name, "document.md");
+    assert_eq!(core.undo_last_operation().unwrap(), None);
+}
+
+#[test]
+fn undo_synced_rename() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.sync(None).unwrap();
+
+    core.undo_last_operation().unwrap().unwrap();
+    assert_eq!(core.calculate_work().unwrap().work_units.len(), 1);
+    core.sync(None).unwrap();
+
+    let core2 = test_core_from(&core);
+    assert_eq!(core2.get_file_by_id(document.id).unwrap().name, "document.md");
+}
+
+#[test]
+fn undo_synced_move() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.move_file(document.id, folder.id).unwrap();
+    core.sync(None).unwrap();
+
+    core.undo_last_operation().unwrap().unwrap();
+    core.sync(None).unwrap();
+
+    let core2 = test_core_from(&core);
+    assert_eq!(core2.get_file_by_id(document.id).unwrap().parent, folder.parent);
+}
+
+#[test]
+fn undo_synced_delete() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.sync(None).unwrap();
+    core.delete_file(document.id).unwrap();
+    core.sync(None).unwrap();
+
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::OperationNotUndoable);
+}
+
+#[test]
+fn undo_file_deleted_elsewhere() {
+    let core = test_core_with_account();
+    let folder = core.create_at_path("/folder/").unwrap();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.move_file(document.id, folder.id).unwrap();
+    core.sync(None).unwrap();
+
+    let core2 = test_core_from(&core);
+    core2.delete_file(document.id).unwrap();
+    core2.sync(None).unwrap();
+    core.sync(None).unwrap();
+
+    // the file was deleted on another device, so neither operation can be undone
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::OperationNotUndoable);
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::OperationNotUndoable);
+    assert_eq!(core.undo_last_operation().unwrap(), None);
+}
+
+#[test]
+fn undo_skips_pruned_file() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.delete_file(document.id).unwrap();
+    core.sync(None).unwrap();
+
+    // the deletion can't be undone once synced, and the rename of the pruned file isn't listed
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::OperationNotUndoable);
+    assert!(core.list_recent_operations().unwrap().is_empty());
+    assert_eq!(core.undo_last_operation().unwrap(), None);
+}
+
+#[test]
+fn undo_path_taken() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    core.create_at_path("/document.md").unwrap();
+
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::PathTaken);
+}
+
+#[test]
+fn undo_failure_keeps_operation() {
+    let core = test_core_with_account();
+    let document = core.create_at_path("/document.md").unwrap();
+    core.rename_file(document.id, "document2.md").unwrap();
+    let blocking = core.create_at_path("/document.md").unwrap();
+    assert_eq!(core.undo_last_operation().unwrap_err().kind, CoreError::PathTaken);
+    assert_eq!(core.list_recent_operations().unwrap().len(), 1);
+
+    core.revert_local_change(blocking.id).unwrap();
+    core.undo_last_operation().unwrap();
+    assert_eq!(core.get_by_path("/document.md").unwrap().id, document.id);
+}
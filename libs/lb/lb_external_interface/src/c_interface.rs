@@ -680,6 +680,39 @@ pub unsafe extern "C" fn suggested_docs() -> *const c_char {
     })
 }
 
+/// # Safety
+///
+/// Be sure to call `release_pointer` on the result of this function to free the data.
+#[no_mangle]
+pub unsafe extern "C" fn pin_file(id: *const c_char) -> *const c_char {
+    c_string(match static_state::get() {
+        Ok(core) => translate(core.pin_file(uuid_from_ptr(id))),
+        e => translate(e.map(|_| ())),
+    })
+}
+
+/// # Safety
+///
+/// Be sure to call `release_pointer` on the result of this function to free the data.
+#[no_mangle]
+pub unsafe extern "C" fn unpin_file(id: *const c_char) -> *const c_char {
+    c_string(match static_state::get() {
+        Ok(core) => translate(core.unpin_file(uuid_from_ptr(id))),
+        e => translate(e.map(|_| ())),
+    })
+}
+
+/// # Safety
+///
+/// Be sure to call `release_pointer` on the result of this function to free the data.
+#[no_mangle]
+pub unsafe extern "C" fn list_pinned() -> *const c_char {
+    c_string(match static_state::get() {
+        Ok(core) => translate(core.list_pinned()),
+        e => translate(e.map(|_| ())),
+    })
+}
+
 /// # Safety
 ///
 /// Be sure to call `release_pointer` on the result of this function to free the data.
@@ -764,3 +764,17 @@ impl From<LbError> for Error<FeatureFlagError> {
         }
     }
 }
+
+#[derive(Debug, Serialize, EnumIter)]
+pub enum PinFileError {
+    FileDoesNotExist,
+}
+
+impl From<LbError> for Error<PinFileError> {
+    fn from(err: LbError) -> Self {
+        match err.kind {
+            CoreError::FileNonexistent => UiError(PinFileError::FileDoesNotExist),
+            _ => unexpected!("{:#?}", err),
+        }
+    }
+}
@@ -55,6 +55,7 @@ impl_get_variants!(
     SaveDrawingError,
     ExportDrawingError,
     ExportDrawingToDiskError,
+    PinFileError,
 );
 
 #[derive(Clone)]
@@ -211,6 +212,18 @@ impl FfiCore {
         self.core.suggested_docs(settings)
     }
 
+    pub fn pin_file(&self, id: Uuid) -> Result<(), Error<PinFileError>> {
+        Ok(self.core.pin_file(id)?)
+    }
+
+    pub fn unpin_file(&self, id: Uuid) -> Result<(), UnexpectedError> {
+        self.core.unpin_file(id)
+    }
+
+    pub fn list_pinned(&self) -> Result<Vec<File>, UnexpectedError> {
+        self.core.list_pinned()
+    }
+
     pub fn get_usage(&self) -> Result<UsageMetrics, Error<GetUsageError>> {
         Ok(self.core.get_usage()?)
     }
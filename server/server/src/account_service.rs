@@ -3,7 +3,7 @@ use crate::billing::billing_model::BillingPlatform;
 use crate::billing::google_play_client::GooglePlayClient;
 use crate::billing::stripe_client::StripeClient;
 use crate::document_service::DocumentService;
-use crate::schema::{Account, ServerDb};
+use crate::schema::{Account, AccountSettings, ServerDb};
 use crate::utils::username_is_valid;
 use crate::ServerError::ClientError;
 use crate::{RequestContext, ServerError, ServerState};
@@ -15,10 +15,13 @@ use lockbook_shared::api::{
     AccountFilter, AccountIdentifier, AccountInfo, AdminDisappearAccountError,
     AdminDisappearAccountRequest, AdminGetAccountInfoError, AdminGetAccountInfoRequest,
     AdminGetAccountInfoResponse, AdminListUsersError, AdminListUsersRequest,
-    AdminListUsersResponse, DeleteAccountError, DeleteAccountRequest, FileUsage, GetPublicKeyError,
-    GetPublicKeyRequest, GetPublicKeyResponse, GetUsageError, GetUsageRequest, GetUsageResponse,
-    GetUsernameError, GetUsernameRequest, GetUsernameResponse, NewAccountError, NewAccountRequest,
-    NewAccountResponse, PaymentPlatform, METADATA_FEE,
+    AdminListUsersResponse, DeleteAccountError, DeleteAccountRequest, FileUsage,
+    GetAccountSettingsError, GetAccountSettingsRequest, GetAccountSettingsResponse,
+    GetPublicKeyError, GetPublicKeyRequest, GetPublicKeyResponse, GetUsageError, GetUsageRequest,
+    GetUsageResponse, GetUsernameError, GetUsernameRequest, GetUsernameResponse, NewAccountError,
+    NewAccountRequest, NewAccountResponse, PaymentPlatform, UpsertAccountSettingsError,
+    UpsertAccountSettingsRequest, UpsertAccountSettingsResponse, MAX_ACCOUNT_SETTINGS_SIZE,
+    METADATA_FEE,
 };
 use lockbook_shared::clock::get_time;
 use lockbook_shared::file_like::FileLike;
@@ -135,6 +138,59 @@ where
             .unwrap_or(Err(ClientError(GetUsernameError::UserNotFound)))
     }
 
+    pub async fn upsert_account_settings(
+        &self, context: RequestContext<UpsertAccountSettingsRequest>,
+    ) -> Result<UpsertAccountSettingsResponse, ServerError<UpsertAccountSettingsError>> {
+        use UpsertAccountSettingsError::*;
+
+        let request = context.request;
+        let owner = Owner(context.public_key);
+        if request.settings.value.len() > MAX_ACCOUNT_SETTINGS_SIZE {
+            return Err(ClientError(SettingsTooLarge));
+        }
+
+        let mut db = self.index_db.lock()?;
+        let handle = db.begin_transaction()?;
+
+        if !db.accounts.get().contains_key(&owner) {
+            return Err(ClientError(UserNotFound));
+        }
+        let version = db
+            .account_settings
+            .get()
+            .get(&owner)
+            .map(|settings| settings.version)
+            .unwrap_or_default();
+        if version != request.old_version {
+            return Err(ClientError(OldVersionIncorrect));
+        }
+
+        let new_version = version + 1;
+        db.account_settings
+            .insert(owner, AccountSettings { version: new_version, settings: request.settings })?;
+
+        handle.drop_safely()?;
+        Ok(UpsertAccountSettingsResponse { new_version })
+    }
+
+    pub async fn get_account_settings(
+        &self, context: RequestContext<GetAccountSettingsRequest>,
+    ) -> Result<GetAccountSettingsResponse, ServerError<GetAccountSettingsError>> {
+        let owner = Owner(context.public_key);
+        let db = self.index_db.lock()?;
+        if !db.accounts.get().contains_key(&owner) {
+            return Err(ClientError(GetAccountSettingsError::UserNotFound));
+        }
+
+        Ok(match db.account_settings.get().get(&owner) {
+            Some(settings) => GetAccountSettingsResponse {
+                version: settings.version,
+                settings: Some(settings.settings.clone()),
+            },
+            None => GetAccountSettingsResponse { version: 0, settings: None },
+        })
+    }
+
     pub async fn get_usage(
         &self, context: RequestContext<GetUsageRequest>,
     ) -> Result<GetUsageResponse, ServerError<GetUsageError>> {
@@ -437,6 +493,17 @@ where
             db.owned_files.clear_key(&Owner(*public_key))?;
             db.shared_files.clear_key(&Owner(*public_key))?;
             db.last_seen.remove(&Owner(*public_key))?;
+            db.account_settings.remove(&Owner(*public_key))?;
+            let transfers: Vec<Uuid> = db
+                .transfers
+                .get()
+                .values()
+                .filter(|transfer| &transfer.from == public_key || &transfer.to == public_key)
+                .map(|transfer| transfer.id)
+                .collect();
+            for id in transfers {
+                db.transfers.remove(&id)?;
+            }
 
             for id in metas_to_delete {
                 if let Some(meta) = db.metas.get().get(&id) {
@@ -0,0 +1,245 @@
+use crate::billing::app_store_client::AppStoreClient;
+use crate::billing::google_play_client::GooglePlayClient;
+use crate::billing::stripe_client::StripeClient;
+use crate::document_service::DocumentService;
+use crate::ServerError;
+use crate::ServerError::ClientError;
+use crate::{RequestContext, ServerState};
+use db_rs::Db;
+use lockbook_shared::access_info::UserAccessMode;
+use lockbook_shared::api::*;
+use lockbook_shared::crdt::{EncryptedCollabMessage, EncryptedPresence};
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::server_tree::ServerTree;
+use lockbook_shared::tree_like::TreeLike;
+use lockbook_shared::SharedError;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::DerefMut;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// sessions nobody has sent or received messages in for this long end
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// editors that haven't updated their presence for this long are considered to have closed the
+/// document, e.g. because they disconnected
+const PRESENCE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// sessions whose logs reach this many messages or bytes end, so that editors rejoin with a new
+/// session that starts from their text instead of replaying the whole log
+const MAX_SESSION_MESSAGES: usize = 1000;
+const MAX_SESSION_BYTES: usize = 16 * 1024 * 1024;
+
+/// Live editing sessions of documents, by document id. The server only relays messages, which are
+/// encrypted with the document's key, so sessions live in memory and end when the server restarts.
+#[derive(Clone, Default)]
+pub struct CollabSessions {
+    sessions: Arc<Mutex<HashMap<Uuid, CollabSession>>>,
+
+    /// where editors' selections are, by document id then by user and client id; client ids are
+    /// sent to everyone editing the document, so they only identify an editor along with its user
+    presences: Arc<Mutex<HashMap<Uuid, DocumentPresences>>>,
+}
+
+type DocumentPresences = HashMap<(Owner, Uuid), Presence>;
+
+struct CollabSession {
+    id: Uuid,
+    messages: Vec<EncryptedCollabMessage>,
+    /// the size of the messages' encrypted contents
+    size: usize,
+    last_active: Instant,
+}
+
+struct Presence {
+    username: String,
+    presence: EncryptedPresence,
+    last_active: Instant,
+}
+
+enum DocumentAccess {
+    NotFound,
+    Access(Option<UserAccessMode>),
+}
+
+impl<S, A, G, D> ServerState<S, A, G, D>
+where
+    S: StripeClient,
+    A: AppStoreClient,
+    G: GooglePlayClient,
+    D: DocumentService,
+{
+    pub async fn send_collab_message(
+        &self, context: RequestContext<SendCollabMessageRequest>,
+    ) -> Result<SendCollabMessageResponse, ServerError<SendCollabMessageError>> {
+        use SendCollabMessageError::*;
+
+        let request = context.request;
+        match self.document_access(Owner(context.public_key), &request.id)? {
+            DocumentAccess::NotFound => return Err(ClientError(DocumentNotFound)),
+            DocumentAccess::Access(access) if access < Some(UserAccessMode::Write) => {
+                return Err(ClientError(NotPermissioned))
+            }
+            DocumentAccess::Access(_) => {}
+        }
+
+        let mut sessions = self.collab_sessions.sessions.lock()?;
+        let now = Instant::now();
+        sessions.retain(|_, session| now - session.last_active < SESSION_TIMEOUT);
+
+        let size = request.message.value.len();
+        let session = match (sessions.get_mut(&request.id), request.session) {
+            (Some(_), None) => return Err(ClientError(SessionAlreadyStarted)),
+            (Some(session), Some(id)) if session.id == id => {
+                if session.messages.len() >= MAX_SESSION_MESSAGES
+                    || session.size + size > MAX_SESSION_BYTES
+                {
+                    sessions.remove(&request.id);
+                    return Err(ClientError(SessionEnded));
+                }
+                session
+            }
+            (_, Some(_)) => return Err(ClientError(SessionEnded)),
+            (None, None) => sessions.entry(request.id).or_insert(CollabSession {
+                id: Uuid::new_v4(),
+                messages: Vec::new(),
+                size: 0,
+                last_active: now,
+            }),
+        };
+        session.messages.push(request.message);
+        session.size += size;
+        session.last_active = now;
+
+        Ok(SendCollabMessageResponse { session: session.id })
+    }
+
+    pub async fn get_collab_messages(
+        &self, context: RequestContext<GetCollabMessagesRequest>,
+    ) -> Result<GetCollabMessagesResponse, ServerError<GetCollabMessagesError>> {
+        use GetCollabMessagesError::*;
+
+        let request = context.request;
+        match self.document_access(Owner(context.public_key), &request.id)? {
+            DocumentAccess::NotFound => return Err(ClientError(DocumentNotFound)),
+            DocumentAccess::Access(None) => return Err(ClientError(NotPermissioned)),
+            DocumentAccess::Access(Some(_)) => {}
+        }
+
+        let mut sessions = self.collab_sessions.sessions.lock()?;
+        let now = Instant::now();
+        sessions.retain(|_, session| now - session.last_active < SESSION_TIMEOUT);
+
+        let session = sessions.get_mut(&request.id);
+        if let Some(id) = request.session {
+            if session.as_ref().map(|session| session.id) != Some(id) {
+                return Err(ClientError(SessionEnded));
+            }
+        }
+
+        Ok(match session {
+            Some(session) => {
+                session.last_active = now;
+                GetCollabMessagesResponse {
+                    session: Some(session.id),
+                    messages: session
+                        .messages
+                        .iter()
+                        .skip(request.since as usize)
+                        .cloned()
+                        .collect(),
+                }
+            }
+            None => GetCollabMessagesResponse { session: None, messages: Vec::new() },
+        })
+    }
+
+    pub async fn update_presence(
+        &self, context: RequestContext<UpdatePresenceRequest>,
+    ) -> Result<UpdatePresenceResponse, ServerError<UpdatePresenceError>> {
+        use UpdatePresenceError::*;
+
+        let request = context.request;
+        let owner = Owner(context.public_key);
+        match self.document_access(owner, &request.id)? {
+            DocumentAccess::NotFound => return Err(ClientError(DocumentNotFound)),
+            DocumentAccess::Access(None) => return Err(ClientError(NotPermissioned)),
+            DocumentAccess::Access(Some(_)) => {}
+        }
+        let username = match self.index_db.lock()?.accounts.get().get(&owner) {
+            Some(account) => account.username.clone(),
+            None => return Err(internal!("no account for public key {:?}", owner)),
+        };
+
+        let mut presences = self.collab_sessions.presences.lock()?;
+        let now = Instant::now();
+        for document in presences.values_mut() {
+            document.retain(|_, presence| now - presence.last_active < PRESENCE_TIMEOUT);
+        }
+        presences.retain(|_, document| !document.is_empty());
+
+        let document = presences.entry(request.id).or_default();
+        match request.presence {
+            Some(presence) => {
+                document.insert(
+                    (owner, request.client),
+                    Presence { username, presence, last_active: now },
+                );
+            }
+            None => {
+                document.remove(&(owner, request.client));
+            }
+        }
+
+        let others = document
+            .iter()
+            .filter(|(&key, _)| key != (owner, request.client))
+            .map(|(&(_, client), presence)| RemotePresence {
+                username: presence.username.clone(),
+                client,
+                presence: presence.presence.clone(),
+            })
+            .collect();
+        if document.is_empty() {
+            presences.remove(&request.id);
+        }
+
+        Ok(UpdatePresenceResponse { others })
+    }
+
+    fn document_access<T: Debug>(
+        &self, owner: Owner, id: &Uuid,
+    ) -> Result<DocumentAccess, ServerError<T>>
+    where
+        ServerError<T>: From<SharedError>,
+    {
+        let mut lock = self.index_db.lock()?;
+        let db = lock.deref_mut();
+        let tx = db.begin_transaction()?;
+
+        let meta_exists = db.metas.get().get(id).is_some();
+
+        let mut tree = ServerTree::new(
+            owner,
+            &mut db.owned_files,
+            &mut db.shared_files,
+            &mut db.file_children,
+            &mut db.metas,
+        )?
+        .to_lazy();
+
+        let is_document = tree.maybe_find(id).map(|meta| meta.is_document());
+        let result = match is_document {
+            None if meta_exists => DocumentAccess::Access(None),
+            None | Some(false) => DocumentAccess::NotFound,
+            Some(true) if tree.calculate_deleted(id)? => DocumentAccess::NotFound,
+            Some(true) => DocumentAccess::Access(tree.access_mode(owner, id)?),
+        };
+
+        tx.drop_safely()?;
+        Ok(result)
+    }
+}
@@ -295,6 +295,12 @@ impl From<ServerError<DeleteAccountHelperError>> for ServerError<AdminDisappearA
     }
 }
 
+impl From<SharedError> for ServerError<OfferTransferError> {
+    fn from(err: SharedError) -> Self {
+        internal!("{:?}", err)
+    }
+}
+
 impl From<SharedError> for ServerError<AdminValidateAccountError> {
     fn from(err: SharedError) -> Self {
         internal!("{:?}", err)
@@ -357,6 +363,24 @@ impl From<SharedError> for ServerError<GetDocumentError> {
     }
 }
 
+impl From<SharedError> for ServerError<SendCollabMessageError> {
+    fn from(err: SharedError) -> Self {
+        internal!("{:?}", err)
+    }
+}
+
+impl From<SharedError> for ServerError<GetCollabMessagesError> {
+    fn from(err: SharedError) -> Self {
+        internal!("{:?}", err)
+    }
+}
+
+impl From<SharedError> for ServerError<UpdatePresenceError> {
+    fn from(err: SharedError) -> Self {
+        internal!("{:?}", err)
+    }
+}
+
 impl From<SharedError> for ServerError<GetFileIdsError> {
     fn from(err: SharedError) -> Self {
         internal!("{:?}", err)
@@ -47,6 +47,23 @@ where
             let usage_cap =
                 Self::get_cap(db, &context.public_key).map_err(|err| internal!("{:?}", err))?;
 
+            // files offered to the user that they take skip the usual authorization checks
+            let mut accepted_transfers = HashSet::new();
+            let mut transferred = HashSet::new();
+            for update in &request.updates {
+                if let Some(old) = &update.old {
+                    if old.owner() == update.new.owner() || update.new.owner() != req_owner {
+                        continue;
+                    }
+                    if let Some(transfer) = Self::transfer_including(db, old) {
+                        if transfer.to == context.public_key {
+                            accepted_transfers.insert(transfer.id);
+                            transferred.insert(*update.new.id());
+                        }
+                    }
+                }
+            }
+
             let mut tree = ServerTree::new(
                 req_owner,
                 &mut db.owned_files,
@@ -75,7 +92,7 @@ where
                 }
             }
 
-            tree.validate(req_owner)?;
+            tree.validate_transfer(req_owner, &transferred)?;
 
             let new_usage = Self::get_usage_helper(&mut tree, db.sizes.get())
                 .map_err(|err| internal!("{:?}", err))?
@@ -115,6 +132,11 @@ where
                 }
             }
 
+            for id in accepted_transfers {
+                debug!(?id, "Accepted transfer");
+                db.transfers.remove(&id)?;
+            }
+
             db.last_seen.insert(req_owner, get_time().0 as u64)?;
 
             tx.drop_safely()?;
@@ -199,9 +221,15 @@ where
         let owner = Owner(context.public_key);
         let id = *request.diff.id();
 
-        // Validate Diff
-        if request.diff.diff() != vec![Diff::Hmac] {
-            return Err(ClientError(DiffMalformed));
+        // Validate Diff; contents encrypted with a new key come with the same name encrypted with it
+        let renamed = match &request.diff.old {
+            Some(old) => old.secret_name().hmac != request.diff.new.secret_name().hmac,
+            None => true,
+        };
+        match request.diff.diff()[..] {
+            [Diff::Hmac] => {}
+            [Diff::Name, Diff::Hmac] if !renamed => {}
+            _ => return Err(ClientError(DiffMalformed)),
         }
         let hmac = if let Some(hmac) = request.diff.new.document_hmac() {
             base64::encode_config(hmac, base64::URL_SAFE)
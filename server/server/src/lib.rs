@@ -15,6 +15,7 @@ use serde::{Deserialize, Serialize};
 use crate::account_service::GetUsageHelperError;
 use crate::billing::billing_service::StripeWebhookError;
 use crate::billing::stripe_error::SimplifiedStripeError;
+use crate::collab_service::CollabSessions;
 use crate::schema::ServerV4;
 use crate::ServerError::ClientError;
 pub use stripe;
@@ -36,6 +37,7 @@ where
     pub google_play_client: G,
     pub app_store_client: A,
     pub document_service: D,
+    pub collab_sessions: CollabSessions,
 }
 
 #[derive(Clone)]
@@ -93,6 +95,7 @@ where
 
 pub mod account_service;
 pub mod billing;
+pub mod collab_service;
 pub mod config;
 pub mod document_service;
 pub mod error_handler;
@@ -101,4 +104,6 @@ pub mod loggers;
 pub mod metrics;
 pub mod router_service;
 pub mod schema;
+pub mod team_service;
+pub mod transfer_service;
 pub mod utils;
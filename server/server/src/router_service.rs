@@ -144,8 +144,22 @@ where
         .or(core_req!(ChangeDocRequest, ServerState::change_doc, server_state))
         .or(core_req!(UpsertRequest, ServerState::upsert_file_metadata, server_state))
         .or(core_req!(GetDocRequest, ServerState::get_document, server_state))
+        .or(core_req!(SendCollabMessageRequest, ServerState::send_collab_message, server_state))
+        .or(core_req!(GetCollabMessagesRequest, ServerState::get_collab_messages, server_state))
+        .or(core_req!(UpdatePresenceRequest, ServerState::update_presence, server_state))
         .or(core_req!(GetPublicKeyRequest, ServerState::get_public_key, server_state))
         .or(core_req!(GetUsernameRequest, ServerState::get_username, server_state))
+        .or(core_req!(UpsertTeamRequest, ServerState::upsert_team, server_state))
+        .or(core_req!(GetTeamsRequest, ServerState::get_teams, server_state))
+        .or(core_req!(OfferTransferRequest, ServerState::offer_transfer, server_state))
+        .or(core_req!(GetTransfersRequest, ServerState::get_transfers, server_state))
+        .or(core_req!(DeleteTransferRequest, ServerState::delete_transfer, server_state))
+        .or(core_req!(
+            UpsertAccountSettingsRequest,
+            ServerState::upsert_account_settings,
+            server_state
+        ))
+        .or(core_req!(GetAccountSettingsRequest, ServerState::get_account_settings, server_state))
         .or(core_req!(GetUsageRequest, ServerState::get_usage, server_state))
         .or(core_req!(GetFileIdsRequest, ServerState::get_file_ids, server_state))
         .or(core_req!(GetUpdatesRequest, ServerState::get_updates, server_state))
@@ -1,8 +1,11 @@
 use crate::billing::billing_model::SubscriptionProfile;
 use db_rs::{LookupSet, LookupTable};
 use db_rs_derive::Schema;
+use lockbook_shared::api::Transfer;
+use lockbook_shared::crypto::EncryptedDocument;
 use lockbook_shared::file_metadata::Owner;
 use lockbook_shared::server_file::ServerFile;
+use lockbook_shared::team::SignedTeam;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -15,6 +18,13 @@ pub struct Account {
     pub billing_info: SubscriptionProfile,
 }
 
+/// Settings that follow an account across devices, encrypted with the key of the account's root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSettings {
+    pub version: u64,
+    pub settings: EncryptedDocument,
+}
+
 pub type ServerDb = ServerV4;
 
 #[derive(Schema)]
@@ -31,4 +41,7 @@ pub struct ServerV4 {
     pub owned_files: LookupSet<Owner, Uuid>,
     pub shared_files: LookupSet<Owner, Uuid>,
     pub file_children: LookupSet<Uuid, Uuid>,
+    pub teams: LookupTable<Uuid, SignedTeam>,
+    pub account_settings: LookupTable<Owner, AccountSettings>,
+    pub transfers: LookupTable<Uuid, Transfer>,
 }
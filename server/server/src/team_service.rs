@@ -0,0 +1,100 @@
+use crate::billing::app_store_client::AppStoreClient;
+use crate::billing::google_play_client::GooglePlayClient;
+use crate::billing::stripe_client::StripeClient;
+use crate::document_service::DocumentService;
+use crate::ServerError;
+use crate::ServerError::ClientError;
+use crate::{RequestContext, ServerState};
+use db_rs::Db;
+use lockbook_shared::api::*;
+use lockbook_shared::clock::get_time;
+use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::pubkey;
+use tracing::debug;
+
+impl<S, A, G, D> ServerState<S, A, G, D>
+where
+    S: StripeClient,
+    A: AppStoreClient,
+    G: GooglePlayClient,
+    D: DocumentService,
+{
+    pub async fn upsert_team(
+        &self, context: RequestContext<UpsertTeamRequest>,
+    ) -> Result<(), ServerError<UpsertTeamError>> {
+        use UpsertTeamError::*;
+
+        let signed = context.request.team;
+        let team = &signed.timestamped_value.value;
+
+        // only the admin can change a team, and the membership they upload must be signed by them
+        if team.admin != context.public_key {
+            return Err(ClientError(NotPermissioned));
+        }
+        let max_delay = self.config.server.max_auth_delay as u64;
+        pubkey::verify(&team.admin, &signed, max_delay, max_delay, get_time)
+            .map_err(|_| ClientError(NotPermissioned))?;
+
+        if team.name.trim().is_empty() {
+            return Err(ClientError(TeamNameEmpty));
+        }
+        if !team.members.contains(&team.admin) {
+            return Err(ClientError(NotPermissioned));
+        }
+
+        let mut db = self.index_db.lock()?;
+        let handle = db.begin_transaction()?;
+
+        let mut existing_members = vec![];
+        if let Some(existing) = db.teams.get().get(&team.id) {
+            let existing = &existing.timestamped_value.value;
+            if existing.admin != team.admin {
+                return Err(ClientError(NotPermissioned));
+            }
+            existing_members = existing.members.clone();
+        }
+        if db.teams.get().values().any(|other| {
+            let other = &other.timestamped_value.value;
+            other.id != team.id && other.admin == team.admin && other.name == team.name
+        }) {
+            return Err(ClientError(TeamNameTaken));
+        }
+        // members who since deleted their accounts don't prevent changes to the team
+        for member in &team.members {
+            if !existing_members.contains(member)
+                && !db.accounts.get().contains_key(&Owner(*member))
+            {
+                return Err(ClientError(UserNotFound));
+            }
+        }
+
+        debug!(id = ?team.id, members = team.members.len(), shares = team.shares.len(), "Upserted team");
+        db.teams.insert(team.id, signed.clone())?;
+
+        handle.drop_safely()?;
+        Ok(())
+    }
+
+    pub async fn get_teams(
+        &self, context: RequestContext<GetTeamsRequest>,
+    ) -> Result<GetTeamsResponse, ServerError<GetTeamsError>> {
+        let db = self.index_db.lock()?;
+        if !db.accounts.get().contains_key(&Owner(context.public_key)) {
+            return Err(ClientError(GetTeamsError::UserNotFound));
+        }
+
+        let teams = db
+            .teams
+            .get()
+            .values()
+            .filter(|team| {
+                team.timestamped_value
+                    .value
+                    .members
+                    .contains(&context.public_key)
+            })
+            .cloned()
+            .collect();
+        Ok(GetTeamsResponse { teams })
+    }
+}
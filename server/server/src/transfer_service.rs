@@ -0,0 +1,126 @@
+use crate::billing::app_store_client::AppStoreClient;
+use crate::billing::google_play_client::GooglePlayClient;
+use crate::billing::stripe_client::StripeClient;
+use crate::document_service::DocumentService;
+use crate::schema::ServerDb;
+use crate::ServerError;
+use crate::ServerError::ClientError;
+use crate::{RequestContext, ServerState};
+use db_rs::Db;
+use lockbook_shared::api::*;
+use lockbook_shared::file_like::FileLike;
+use lockbook_shared::file_metadata::Owner;
+use lockbook_shared::server_tree::ServerTree;
+use lockbook_shared::signed_file::SignedFile;
+use lockbook_shared::tree_like::TreeLike;
+use std::ops::DerefMut;
+use tracing::debug;
+
+impl<S, A, G, D> ServerState<S, A, G, D>
+where
+    S: StripeClient,
+    A: AppStoreClient,
+    G: GooglePlayClient,
+    D: DocumentService,
+{
+    pub async fn offer_transfer(
+        &self, context: RequestContext<OfferTransferRequest>,
+    ) -> Result<(), ServerError<OfferTransferError>> {
+        use OfferTransferError::*;
+
+        let request = context.request;
+        let owner = Owner(context.public_key);
+
+        let mut lock = self.index_db.lock()?;
+        let db = lock.deref_mut();
+        let handle = db.begin_transaction()?;
+
+        if request.to == context.public_key || !db.accounts.get().contains_key(&Owner(request.to)) {
+            return Err(ClientError(UserNotFound));
+        }
+
+        let mut tree = ServerTree::new(
+            owner,
+            &mut db.owned_files,
+            &mut db.shared_files,
+            &mut db.file_children,
+            &mut db.metas,
+        )?
+        .to_lazy();
+        let file = tree
+            .maybe_find(&request.id)
+            .ok_or(ClientError(FileNotFound))?;
+        if file.owner() != owner || file.is_root() {
+            return Err(ClientError(NotPermissioned));
+        }
+        if tree.calculate_deleted(&request.id)? {
+            return Err(ClientError(FileNotFound));
+        }
+
+        debug!(id = ?request.id, "Offered transfer");
+        db.transfers.insert(
+            request.id,
+            Transfer { id: request.id, from: context.public_key, to: request.to },
+        )?;
+
+        handle.drop_safely()?;
+        Ok(())
+    }
+
+    pub async fn get_transfers(
+        &self, context: RequestContext<GetTransfersRequest>,
+    ) -> Result<GetTransfersResponse, ServerError<GetTransfersError>> {
+        let db = self.index_db.lock()?;
+        if !db.accounts.get().contains_key(&Owner(context.public_key)) {
+            return Err(ClientError(GetTransfersError::UserNotFound));
+        }
+
+        let transfers = db
+            .transfers
+            .get()
+            .values()
+            .filter(|transfer| {
+                transfer.from == context.public_key || transfer.to == context.public_key
+            })
+            .cloned()
+            .collect();
+        Ok(GetTransfersResponse { transfers })
+    }
+
+    pub async fn delete_transfer(
+        &self, context: RequestContext<DeleteTransferRequest>,
+    ) -> Result<(), ServerError<DeleteTransferError>> {
+        let mut db = self.index_db.lock()?;
+        let handle = db.begin_transaction()?;
+
+        match db.transfers.get().get(&context.request.id) {
+            Some(transfer)
+                if transfer.from == context.public_key || transfer.to == context.public_key => {}
+            _ => return Err(ClientError(DeleteTransferError::TransferNotFound)),
+        }
+
+        debug!(id = ?context.request.id, "Deleted transfer");
+        db.transfers.remove(&context.request.id)?;
+
+        handle.drop_safely()?;
+        Ok(())
+    }
+
+    /// The transfer offered by the owner of `file` that includes it: a transfer of the file or of
+    /// one of its ancestors
+    pub fn transfer_including(db: &ServerDb, file: &SignedFile) -> Option<Transfer> {
+        let mut id = *file.id();
+        loop {
+            if let Some(transfer) = db.transfers.get().get(&id) {
+                if transfer.from == file.owner().0 {
+                    return Some(transfer.clone());
+                }
+            }
+            let meta = db.metas.get().get(&id)?;
+            if meta.is_root() {
+                return None;
+            }
+            id = *meta.parent();
+        }
+    }
+}